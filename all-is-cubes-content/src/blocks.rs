@@ -11,7 +11,7 @@ use noise::Seedable as _;
 
 use all_is_cubes::block::{
     AnimationHint, Block, BlockCollision, BlockDefTransaction, Primitive, Resolution,
-    RotationPlacementRule, AIR,
+    RotationPlacementRule, TickAction, AIR,
 };
 use all_is_cubes::cgmath::{ElementWise as _, EuclideanSpace as _, InnerSpace, Vector3};
 use all_is_cubes::drawing::embedded_graphics::{
@@ -300,20 +300,16 @@ pub async fn install_demo_blocks(
                 .display_name("Curb Corner")
                 .collision(BlockCollision::Recur)
                 .rotation_rule(RotationPlacementRule::Attach { by: Face6::NY })
-                .voxels_fn(universe, resolution, |cube| {
-                    // TODO: rework so this isn't redoing the rotation calculations for every single voxel
-                    // We should have tools for composing blocks instead...
-                    for rot in GridRotation::CLOCKWISE.iterate() {
-                        let block = curb_fn(
-                            rot.to_positive_octant_matrix(resolution.into())
-                                .transform_cube(cube),
-                        );
-                        if block != AIR {
-                            return block;
-                        }
-                    }
-                    AIR
-                })?
+                .voxels_fn(
+                    universe,
+                    resolution,
+                    GridRotation::symmetrize(
+                        resolution.into(),
+                        GridRotation::CLOCKWISE.iterate(),
+                        AIR,
+                        &curb_fn,
+                    ),
+                )?
                 .build(),
 
             ExhibitBackground => {
@@ -421,7 +417,7 @@ pub async fn install_demo_blocks(
             let mut block: Block = (*block_def_ref.borrow()).clone();
 
             if let Primitive::Atom(attributes, _) = block.primitive_mut() {
-                attributes.tick_action = if i > 30 {
+                let brush = if i > 30 {
                     // Expire
                     Some(VoxelBrush::single(AIR))
                 } else {
@@ -464,6 +460,7 @@ pub async fn install_demo_blocks(
                         Some(VoxelBrush::new(vec![([0, 0, 0], next.clone())]))
                     }
                 };
+                attributes.tick_action = brush.map(TickAction::from);
             } else {
                 panic!("not atom");
             }
@@ -538,6 +535,37 @@ mod tests {
         // TODO: assert what entries were created, once Universe has iteration
     }
 
+    /// The curb corner is made of a curb along each of its sides, and so should have
+    /// the same shape after a quarter turn.
+    #[test]
+    fn curb_corner_is_symmetric() {
+        let mut universe = Universe::new();
+        futures_executor::block_on(async {
+            install_demo_blocks(&mut universe, YieldProgress::noop())
+                .await
+                .unwrap()
+        });
+        let curb_corner = BlockProvider::<DemoBlocks>::using(&universe).unwrap()
+            [DemoBlocks::CurbCorner]
+            .evaluate()
+            .unwrap();
+        let voxels = curb_corner.voxels.as_ref().unwrap();
+        let matrix =
+            GridRotation::CLOCKWISE.to_positive_octant_matrix(curb_corner.resolution.into());
+
+        let mut count = 0;
+        for cube in voxels.grid().interior_iter() {
+            let visible = |cube| !voxels[cube].color.fully_transparent();
+            assert_eq!(
+                visible(cube),
+                visible(matrix.transform_cube(cube)),
+                "{cube:?}"
+            );
+            count += usize::from(visible(cube));
+        }
+        assert_ne!(count, 0);
+    }
+
     #[test]
     fn gradient_lookup_cases() {
         let blocks = make_some_blocks::<4>();
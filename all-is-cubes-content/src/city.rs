@@ -130,9 +130,9 @@ pub(crate) async fn demo_city(
                 if cube.x.abs() <= road_radius || cube.z.abs() <= road_radius {
                     return None;
                 }
-                if grass_noise.at_cube(cube) > grass_threshold * 2. {
+                if grass_noise.at_cube_center(cube) > grass_threshold * 2. {
                     Some(&landscape_blocks[GrassBlades { variant: true }])
-                } else if grass_noise.at_cube(cube) > grass_threshold {
+                } else if grass_noise.at_cube_center(cube) > grass_threshold {
                     Some(&landscape_blocks[GrassBlades { variant: false }])
                 } else {
                     None
@@ -16,13 +16,10 @@ use crate::noise::NoiseFnExt;
 ///
 /// TODO: Use named block definitions or not?
 pub fn clouds(region: Grid, space: &mut Space, density: f32) -> Result<(), SetCubeError> {
-    let large_noise = noise::Perlin::default();
-    let large_noise = noise::ScalePoint::new(&large_noise).set_scale(0.02);
-    let large_noise = noise::ScaleBias::new(&large_noise).set_scale(10.0);
-    let small_noise = noise::Perlin::default();
-    let small_noise = noise::ScalePoint::new(&small_noise).set_scale(0.15);
-    let small_noise = noise::ScaleBias::new(&small_noise).set_scale(4.0);
-    let combined_noise = noise::Add::new(&large_noise, &small_noise);
+    let noise_fn = noise::Perlin::default();
+    let combined_noise = |point: GridPoint| -> f64 {
+        noise_fn.at_grid_scaled(point, 0.02) * 10.0 + noise_fn.at_grid_scaled(point, 0.15) * 4.0
+    };
 
     fn cloud_block(alpha: f32) -> Block {
         Block::builder()
@@ -45,7 +42,7 @@ pub fn clouds(region: Grid, space: &mut Space, density: f32) -> Result<(), SetCu
 
     for x in region.x_range() {
         for z in region.z_range() {
-            let noise_value = combined_noise.at_grid(GridPoint::new(x, 0, z));
+            let noise_value = combined_noise(GridPoint::new(x, 0, z));
             let surface = noise_value as GridCoordinate;
             for y in region.y_range() {
                 let cube = GridPoint::new(x, y, z);
@@ -190,9 +190,11 @@ fn cornell_box() -> Result<Space, InGenError> {
     let mut space = Space::builder(grid)
         // There shall be no light but that which we make for ourselves!
         .sky_color(Rgb::ZERO)
-        .light_physics(LightPhysics::Rays {
-            maximum_distance: (box_size * 2).try_into().unwrap_or(u16::MAX),
-        })
+        .light_physics(
+            LightPhysics::rays()
+                .maximum_distance((box_size * 2).try_into().unwrap_or(u16::MAX))
+                .build(),
+        )
         .spawn({
             let mut spawn = Spawn::default_for_new_space(grid);
             spawn.set_inventory(free_editing_starter_inventory(true));
@@ -321,6 +323,7 @@ async fn arbitrary_space(
 ) -> Result<Space, InGenError> {
     use all_is_cubes::cgmath::{Vector3, Zero};
     use all_is_cubes::math::FaceMap;
+    use all_is_cubes::space::{Gravity, Sky};
     use arbitrary::{Arbitrary, Error, Unstructured};
     use rand::{RngCore, SeedableRng};
 
@@ -342,8 +345,9 @@ async fn arbitrary_space(
 
                 // Patch physics to be reasonable
                 let mut p = space.physics().clone();
-                p.gravity = Vector3::zero(); // won't be a floor
-                p.sky_color = p.sky_color * (0.5 / p.sky_color.luminance());
+                p.gravity = Gravity::Uniform(Vector3::zero()); // won't be a floor
+                let sky_color = p.sky.overall_color();
+                p.sky = Sky::Uniform(sky_color * (0.5 / sky_color.luminance()));
                 space.set_physics(p);
 
                 // TODO: These patches are still not enough to get a good result.
@@ -8,15 +8,17 @@ use maze_generator::prelude::{Direction, FieldType, Generator};
 use rand::prelude::SliceRandom;
 use rand::{Rng, SeedableRng};
 
-use all_is_cubes::block::{Block, BlockCollision, RotationPlacementRule, AIR};
+use all_is_cubes::block::{
+    Block, BlockCollision, BlockDefTransaction, Primitive, RotationPlacementRule, TickAction, AIR,
+};
 use all_is_cubes::cgmath::{EuclideanSpace as _, InnerSpace as _, Vector3};
 use all_is_cubes::character::Spawn;
 use all_is_cubes::content::palette;
 use all_is_cubes::inv::Tool;
 use all_is_cubes::linking::{BlockModule, BlockProvider, GenError, InGenError};
 use all_is_cubes::math::{
-    point_to_enclosing_cube, Face6, Face7, FaceMap, GridCoordinate, GridPoint, GridRotation,
-    GridVector, Rgb,
+    point_to_enclosing_cube, Face6, FaceMap, GridCoordinate, GridPoint, GridRotation, GridVector,
+    Rgb,
 };
 use all_is_cubes::rgb_const;
 use all_is_cubes::space::{Grid, GridArray, Space};
@@ -53,6 +55,16 @@ impl DemoRoom {
     fn extended_map_bounds(&self) -> Grid {
         self.extended_bounds
     }
+
+    /// Relative room Y coordinate of the level this room's floor (and doors) are at.
+    /// This is 0 except for rooms sunken below the rest of the dungeon.
+    fn floor_level(&self) -> GridCoordinate {
+        match self.floor {
+            FloorKind::Solid => self.extended_bounds.lower_bounds().y,
+            // These extend downward but have their floor at the normal level.
+            FloorKind::Chasm | FloorKind::Bridge | FloorKind::Stairs { .. } => 0,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -60,6 +72,11 @@ enum FloorKind {
     Solid,
     Chasm,
     Bridge,
+    /// Stairs going down one level, ending at the door on the `direction` face,
+    /// which leads to a sunken room.
+    Stairs {
+        direction: Face6,
+    },
 }
 
 /// Data to use to construct specific dungeon rooms.
@@ -91,7 +108,7 @@ impl DemoTheme {
         space.fill_uniform(interior.abut(Face6::PY, 1).unwrap(), wall_block)?;
 
         four_walls(
-            interior.expand(FaceMap::repeat(1)),
+            interior.expand_clamped(FaceMap::repeat(1), space.grid()),
             |_, _, _, wall_excluding_corners| {
                 space.fill_uniform(wall_excluding_corners, wall_block)?;
 
@@ -104,13 +121,14 @@ impl DemoTheme {
         Ok(())
     }
 
-    fn inside_doorway(
+    /// Returns the passage between the room at `room_position` and its neighbor in the
+    /// direction of `face`, and the one-cube-thick slice of it where the door goes.
+    fn doorway_boxes(
         &self,
-        space: &mut Space,
         map: &GridArray<Option<DemoRoom>>,
         room_position: GridPoint,
         face: Face6,
-    ) -> Result<(), InGenError> {
+    ) -> (Grid, Grid) {
         let passage_axis = face.axis_number();
 
         let mut room_1_box = self.actual_room_box(
@@ -129,14 +147,20 @@ impl DemoTheme {
             std::mem::swap(&mut room_1_box, &mut room_2_box);
         }
 
-        let wall_parallel = GridRotation::CLOCKWISE.transform(face);
-        let parallel_axis = wall_parallel.axis_number();
-        assert!(parallel_axis != 1);
+        // If either room is sunken, the doorway is at its level, and the other room
+        // must have stairs reaching it.
+        let door_level = map[room_position].as_ref().unwrap().floor_level().min(
+            map[room_position + face.normal_vector()]
+                .as_ref()
+                .unwrap()
+                .floor_level(),
+        );
 
         let doorway_box = {
-            let corridor_box = self
-                .corridor_box
-                .translate(self.dungeon_grid.room_translation(room_position));
+            let corridor_box = self.corridor_box.translate(
+                self.dungeon_grid
+                    .room_translation(room_position + GridVector::new(0, door_level, 0)),
+            );
             // TODO: Add Grid operations to make this easier
             let mut lower = corridor_box.lower_bounds();
             let mut upper = corridor_box.upper_bounds();
@@ -145,8 +169,42 @@ impl DemoTheme {
             Grid::from_lower_upper(lower, upper)
         };
 
-        // Cut doorway
+        let door_box = {
+            let mut lower = doorway_box.lower_bounds();
+            let mut upper = doorway_box.upper_bounds();
+            lower[passage_axis] = (lower[passage_axis] + upper[passage_axis]) / 2;
+            upper[passage_axis] = lower[passage_axis] + 1;
+            Grid::from_lower_upper(lower, upper)
+        };
+
+        (doorway_box, door_box)
+    }
+
+    fn inside_doorway(
+        &self,
+        space: &mut Space,
+        map: &GridArray<Option<DemoRoom>>,
+        room_position: GridPoint,
+        face: Face6,
+    ) -> Result<(), InGenError> {
+        let (doorway_box, door_box) = self.doorway_boxes(map, room_position, face);
+
+        let wall_parallel = GridRotation::CLOCKWISE.transform(face);
+        let parallel_axis = wall_parallel.axis_number();
+        assert!(parallel_axis != 1);
+
+        // Cut doorway and hang the door. Doors out of the starting room open on their
+        // own, to show the player that doors exist.
+        let leaves_start = [room_position, room_position + face.normal_vector()]
+            .into_iter()
+            .any(|p| matches!(map[p].as_ref().unwrap().maze_field_type, FieldType::Start));
+        let door = if leaves_start {
+            self.blocks[OpeningDoor].clone()
+        } else {
+            self.blocks[Door].clone()
+        };
         space.fill_uniform(doorway_box, &AIR)?;
+        space.fill_uniform(door_box, &door)?;
 
         // Add floor and walls
         space.fill_uniform(
@@ -166,13 +224,66 @@ impl DemoTheme {
         Ok(())
     }
 
+    /// Builds the floor of a [`FloorKind::Stairs`] room: a normal floor at `floor_layer`
+    /// with a doorway-wide staircase cut into it, descending one cube per cube toward
+    /// `direction` until it reaches the floor of the level below.
+    fn stairs(
+        &self,
+        space: &mut Space,
+        room_position: GridPoint,
+        floor_layer: Grid,
+        direction: Face6,
+    ) -> Result<(), InGenError> {
+        let axis = direction.axis_number();
+        let drop_per_level = self.dungeon_grid.room_spacing().y;
+        let length = floor_layer.size()[axis];
+        assert!(
+            length > drop_per_level,
+            "room too short for stairs: {length} <= {drop_per_level}"
+        );
+
+        space.fill_uniform(floor_layer, &self.blocks[FloorTile])?;
+
+        // The stairwell is as wide as the doorway it leads to.
+        let stairwell = {
+            let corridor_box = self
+                .corridor_box
+                .translate(self.dungeon_grid.room_translation(room_position));
+            let mut lower = floor_layer.lower_bounds();
+            let mut upper = floor_layer.upper_bounds();
+            for other_axis in [0, 2] {
+                if other_axis != axis {
+                    lower[other_axis] = corridor_box.lower_bounds()[other_axis];
+                    upper[other_axis] = corridor_box.upper_bounds()[other_axis];
+                }
+            }
+            Grid::from_lower_upper(lower, upper)
+        };
+        space.fill_uniform(stairwell, &AIR)?;
+
+        // Place each step, starting from the end opposite `direction`.
+        let start = stairwell.face_slab(direction.opposite(), 1).unwrap();
+        for i in 0..length {
+            let drop = i.min(drop_per_level);
+            space.fill_uniform(
+                start.translate(direction.normal_vector() * i + GridVector::new(0, -drop, 0)),
+                &self.blocks[FloorTile],
+            )?;
+        }
+
+        Ok(())
+    }
+
     /// Box of the room, in space coordinates, that might be smaller or bigger than the
     /// DungeonGrid's box.
     /// TODO: Should we teach DungeonGrid to help with this?
     fn actual_room_box(&self, room_position: GridPoint, room_data: &DemoRoom) -> Grid {
         if room_data.corridor_only {
-            self.corridor_box
-                .translate(self.dungeon_grid.room_translation(room_position))
+            self.corridor_box.translate(
+                self.dungeon_grid.room_translation(
+                    room_position + GridVector::new(0, room_data.floor_level(), 0),
+                ),
+            )
         } else {
             let eb = room_data.extended_map_bounds();
             self.dungeon_grid
@@ -219,7 +330,7 @@ impl Theme<Option<DemoRoom>> for DemoTheme {
         };
         let floor_layer = self
             .dungeon_grid
-            .room_box_at(room_position)
+            .room_box_at(room_position + GridVector::new(0, room_data.floor_level(), 0))
             .abut(Face6::NY, 1)
             .unwrap();
 
@@ -228,11 +339,10 @@ impl Theme<Option<DemoRoom>> for DemoTheme {
                 self.plain_room(wall_type, space, interior)?;
 
                 // Spikes on the bottom of the pit
-                // (TODO: revise this condition when staircase-ish rooms exist)
-                if room_data.extended_map_bounds().lower_bounds().y < 0 {
+                if matches!(room_data.floor, FloorKind::Chasm | FloorKind::Bridge) {
                     assert!(!room_data.corridor_only, "{:?}", room_data);
                     space.fill_uniform(
-                        interior.abut(Face6::NY, -1).unwrap(),
+                        interior.face_slab(Face6::NY, 1).unwrap(),
                         &self.blocks[DungeonBlocks::Spikes],
                     )?;
                 }
@@ -245,9 +355,9 @@ impl Theme<Option<DemoRoom>> for DemoTheme {
                     FloorKind::Bridge => {
                         let midpoint = point_to_enclosing_cube(floor_layer.center()).unwrap();
                         for direction in [Face6::NX, Face6::NZ, Face6::PX, Face6::PZ] {
-                            if room_data.door_faces[direction.into()] {
+                            if room_data.door_faces[direction] {
                                 let wall_cube = point_to_enclosing_cube(
-                                    floor_layer.abut(direction, -1).unwrap().center(),
+                                    floor_layer.face_slab(direction, 1).unwrap().center(),
                                 )
                                 .unwrap();
                                 let bridge_box = Grid::single_cube(midpoint)
@@ -260,11 +370,14 @@ impl Theme<Option<DemoRoom>> for DemoTheme {
                             }
                         }
                     }
+                    FloorKind::Stairs { direction } => {
+                        self.stairs(space, room_position, floor_layer, direction)?;
+                    }
                 }
 
                 if room_data.lit {
                     let top_middle =
-                        point_to_enclosing_cube(interior.abut(Face6::PY, -1).unwrap().center())
+                        point_to_enclosing_cube(interior.face_slab(Face6::PY, 1).unwrap().center())
                             .unwrap();
                     space.set(
                         top_middle,
@@ -284,20 +397,20 @@ impl Theme<Option<DemoRoom>> for DemoTheme {
                     .y
                     + 1;
                 four_walls(
-                    interior.expand(FaceMap::repeat(1)),
+                    interior.expand_clamped(FaceMap::repeat(1), space.grid()),
                     |origin, along_wall, length, wall_excluding_corners_box| {
                         let wall = GridRotation::CLOCKWISE.transform(along_wall); // TODO: make four_walls provide this in a nice name
-                        if room_data.windowed_faces[wall.into()] {
+                        if room_data.windowed_faces[wall] {
                             let midpoint = length / 2;
                             for step in WINDOW_PATTERN {
                                 let mut window_pos =
                                     origin + along_wall.normal_vector() * (midpoint + step);
                                 window_pos.y = window_y;
-                                if let Some(window_box) = Grid::new(window_pos, [1, 3, 1])
-                                    .intersection(wall_excluding_corners_box)
-                                {
-                                    space.fill_uniform(window_box, &self.window_glass_block)?;
-                                }
+                                space.fill_uniform(
+                                    Grid::new(window_pos, [1, 3, 1])
+                                        .intersection_or_empty(wall_excluding_corners_box),
+                                    &self.window_glass_block,
+                                )?;
                             }
                         }
                         Ok::<(), InGenError>(())
@@ -305,7 +418,7 @@ impl Theme<Option<DemoRoom>> for DemoTheme {
                 )?;
 
                 // Ceiling light port (not handled by four_walls above)
-                if room_data.windowed_faces[Face7::PY] {
+                if room_data.windowed_faces[Face6::PY] {
                     let midpoint =
                         point_to_enclosing_cube(interior.abut(Face6::PY, 1).unwrap().center())
                             .unwrap();
@@ -321,7 +434,7 @@ impl Theme<Option<DemoRoom>> for DemoTheme {
             }
             1 => {
                 for face in [Face6::PX, Face6::PZ] {
-                    if room_data.door_faces[face.into()] {
+                    if room_data.door_faces[face] {
                         self.inside_doorway(space, map, room_position, face)?;
                     }
                 }
@@ -329,22 +442,20 @@ impl Theme<Option<DemoRoom>> for DemoTheme {
                 // Set spawn.
                 // TODO: Don't unconditionally override spawn; instead communicate this out.
                 if matches!(room_data.maze_field_type, FieldType::Start) {
-                    let mut spawn = Spawn::default_for_new_space(space.grid());
-                    spawn.set_bounds(interior);
-                    spawn.set_inventory(vec![
+                    let mut spawn = Spawn::builder(interior).inventory(vec![
                         Tool::RemoveBlock { keep: true }.into(),
                         Tool::Jetpack { active: false }.into(),
                     ]);
 
                     // Orient towards the first room's exit.
-                    for face in Face6::ALL {
-                        if room_data.door_faces[face.into()] {
-                            spawn.set_look_direction(face.normal_vector());
-                            break;
-                        }
+                    if let Some(exit) = Face6::ALL
+                        .into_iter()
+                        .find(|&face| room_data.door_faces[face])
+                    {
+                        spawn = spawn.look_direction(exit.normal_vector());
                     }
 
-                    space.set_spawn(spawn);
+                    space.set_spawn(spawn.build());
                 }
             }
             _ => unreachable!(),
@@ -362,25 +473,69 @@ pub(crate) async fn demo_dungeon(
     let [blocks_progress, progress] = progress.split(0.2);
     install_dungeon_blocks(universe, blocks_progress).await?;
 
-    let mut rng = rand_xoshiro::Xoshiro256Plus::seed_from_u64(seed);
+    let dungeon_grid = demo_dungeon_grid();
+    let landscape_blocks = BlockProvider::<LandscapeBlocks>::using(universe)?;
+    let theme = demo_theme(universe, dungeon_grid.clone())?;
 
-    let dungeon_grid = DungeonGrid {
-        room_box: Grid::new([0, 0, 0], [9, 5, 9]),
-        room_wall_thickness: FaceMap::repeat(1),
-        gap_between_walls: Vector3::new(1, 1, 1),
-    };
+    let dungeon_map = generate_dungeon_map(seed)?;
+
+    let space_bounds = dungeon_grid
+        .minimum_space_for_rooms(dungeon_map.grid())
+        .expand(FaceMap::symmetric([30, 1, 30]));
+    let mut space = Space::builder(space_bounds)
+        .sky_color(palette::DAY_SKY_COLOR * 2.0)
+        .build_empty();
+
+    // Fill in (under)ground areas
+    space.fill_uniform(
+        {
+            let mut l = space_bounds.lower_bounds();
+            let mut u = space_bounds.upper_bounds();
+            l.y = -1;
+            u.y = 0;
+            Grid::from_lower_upper(l, u)
+        },
+        &landscape_blocks[LandscapeBlocks::Grass],
+    )?;
+    space.fill_uniform(
+        {
+            let mut u = space_bounds.upper_bounds();
+            u.y = -1;
+            Grid::from_lower_upper(space_bounds.lower_bounds(), u)
+        },
+        &landscape_blocks[LandscapeBlocks::Dirt],
+    )?;
+
+    build_dungeon(&mut space, &theme, &dungeon_map, progress).await?;
+
+    Ok(space)
+}
 
+fn demo_theme(universe: &Universe, dungeon_grid: DungeonGrid) -> Result<DemoTheme, InGenError> {
     let landscape_blocks = BlockProvider::<LandscapeBlocks>::using(universe)?;
     let demo_blocks = BlockProvider::<DemoBlocks>::using(universe)?;
-    let theme = DemoTheme {
-        dungeon_grid: dungeon_grid.clone(),
+    Ok(DemoTheme {
+        dungeon_grid,
         corridor_box: Grid::new([3, 0, 3], [3, 3, 3]),
         blocks: BlockProvider::using(universe)?,
         // TODO: use more appropriate blocks
         wall_block: landscape_blocks[LandscapeBlocks::Stone].clone(),
         lamp_block: demo_blocks[DemoBlocks::Lamp].clone(),
         window_glass_block: demo_blocks[DemoBlocks::GlassBlock].clone(),
-    };
+    })
+}
+
+fn demo_dungeon_grid() -> DungeonGrid {
+    DungeonGrid {
+        room_box: Grid::new([0, 0, 0], [9, 5, 9]),
+        room_wall_thickness: FaceMap::repeat(1),
+        gap_between_walls: Vector3::new(1, 1, 1),
+    }
+}
+
+/// Choose the layout and features of the rooms of the dungeon.
+fn generate_dungeon_map(seed: u64) -> Result<GridArray<Option<DemoRoom>>, InGenError> {
+    let mut rng = rand_xoshiro::Xoshiro256Plus::seed_from_u64(seed);
 
     let mut maze_seed = [0; 32];
     maze_seed[0..8].copy_from_slice(&seed.to_le_bytes());
@@ -393,7 +548,7 @@ pub(crate) async fn demo_dungeon(
     // Expand bounds to allow for extra-tall rooms.
     let expanded_bounds = maze.grid().expand(FaceMap::symmetric([0, 1, 0]));
 
-    let dungeon_map = GridArray::from_fn(expanded_bounds, |room_position| {
+    let mut dungeon_map = GridArray::from_fn(expanded_bounds, |room_position| {
         let maze_field = maze.get(room_position)?;
 
         let corridor_only = rng.gen_bool(0.5);
@@ -401,14 +556,14 @@ pub(crate) async fn demo_dungeon(
         let mut extended_bounds = Grid::for_block(1);
         // Optional high ceiling
         if !corridor_only && rng.gen_bool(0.25) {
-            extended_bounds = extended_bounds.expand(FaceMap::default().with(Face7::PY, 1));
+            extended_bounds = extended_bounds.expand(FaceMap::default().with(Face6::PY, 1));
         };
         // Floor pit
         let floor = if !corridor_only
             && matches!(maze_field.field_type, FieldType::Normal)
             && rng.gen_bool(0.5)
         {
-            extended_bounds = extended_bounds.expand(FaceMap::default().with(Face7::NY, 1));
+            extended_bounds = extended_bounds.expand(FaceMap::default().with(Face6::NY, 1));
             *[FloorKind::Chasm, FloorKind::Bridge]
                 .choose(&mut rng)
                 .unwrap()
@@ -417,12 +572,12 @@ pub(crate) async fn demo_dungeon(
         };
 
         let windowed_faces = {
-            FaceMap::from_fn(|face| {
+            FaceMap::from_fn6(|face| {
                 // Create windows only if they look into space outside the maze
                 let adjacent = m2gp(maze_field.coordinates) + face.normal_vector();
-                if maze.grid().contains_cube(adjacent) || corridor_only || face == Face7::NY {
+                if maze.grid().contains_cube(adjacent) || corridor_only || face == Face6::NY {
                     false
-                } else if face == Face7::PY {
+                } else if face == Face6::PY {
                     // ceilings are more common overall and we want more internally-lit ones
                     rng.gen_bool(0.25)
                 } else {
@@ -448,40 +603,48 @@ pub(crate) async fn demo_dungeon(
             windowed_faces,
             floor,
             corridor_only,
-            lit: !windowed_faces[Face7::PY] && rng.gen_bool(0.75),
+            lit: !windowed_faces[Face6::PY] && rng.gen_bool(0.75),
         })
     });
 
-    let space_bounds = dungeon_grid
-        .minimum_space_for_rooms(dungeon_map.grid())
-        .expand(FaceMap::symmetric([30, 1, 30]));
-    let mut space = Space::builder(space_bounds)
-        .sky_color(palette::DAY_SKY_COLOR * 2.0)
-        .build_empty();
-
-    // Fill in (under)ground areas
-    space.fill_uniform(
-        {
-            let mut l = space_bounds.lower_bounds();
-            let mut u = space_bounds.upper_bounds();
-            l.y = -1;
-            u.y = 0;
-            Grid::from_lower_upper(l, u)
-        },
-        &landscape_blocks[LandscapeBlocks::Grass],
-    )?;
-    space.fill_uniform(
-        {
-            let mut u = space_bounds.upper_bounds();
-            u.y = -1;
-            Grid::from_lower_upper(space_bounds.lower_bounds(), u)
-        },
-        &landscape_blocks[LandscapeBlocks::Dirt],
-    )?;
+    // Sink some dead-end rooms one level down, and put stairs in the room leading
+    // to them.
+    for room_position in maze.grid().interior_iter() {
+        let room = dungeon_map[room_position].as_ref().unwrap();
+        let mut door_faces = Face6::ALL.into_iter().filter(|&face| room.door_faces[face]);
+        let door_face = match (door_faces.next(), door_faces.next()) {
+            (Some(face), None) => face,
+            _ => continue,
+        };
+        let neighbor_position = room_position + door_face.normal_vector();
+        let neighbor = dungeon_map[neighbor_position].as_ref().unwrap();
+        let eligible = matches!(room.maze_field_type, FieldType::Normal)
+            && room.floor == FloorKind::Solid
+            && room.extended_bounds == Grid::for_block(1)
+            && matches!(neighbor.maze_field_type, FieldType::Normal)
+            && neighbor.floor == FloorKind::Solid
+            && neighbor.extended_bounds.lower_bounds().y == 0
+            && !neighbor.corridor_only;
+        if !eligible || !rng.gen_bool(0.4) {
+            continue;
+        }
 
-    build_dungeon(&mut space, &theme, &dungeon_map, progress).await?;
+        let neighbor = dungeon_map[neighbor_position].as_mut().unwrap();
+        neighbor.floor = FloorKind::Stairs {
+            direction: door_face.opposite(),
+        };
+        neighbor.extended_bounds = neighbor
+            .extended_bounds
+            .expand(FaceMap::default().with(Face6::NY, 1));
+
+        let room = dungeon_map[room_position].as_mut().unwrap();
+        room.extended_bounds = Grid::new([0, -1, 0], [1, 1, 1]);
+        // Underground, so no windows.
+        room.windowed_faces = FaceMap::default();
+        room.lit = true;
+    }
 
-    Ok(space)
+    Ok(dungeon_map)
 }
 
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, strum::Display, Exhaust)]
@@ -494,6 +657,14 @@ pub(crate) enum DungeonBlocks {
     FloorTile,
     /// Spikes for pit traps, facing upward.
     Spikes,
+    /// A closed door, filling a doorway. Activating it replaces it with [`OpenDoor`].
+    Door,
+    /// A door which slides up on its own, then replaces itself with [`OpenDoor`].
+    /// Activating it while it is moving replaces it with [`Door`].
+    OpeningDoor,
+    /// A door which has been slid up out of the doorway, leaving it passable.
+    /// Activating it replaces it with [`Door`].
+    OpenDoor,
 }
 impl BlockModule for DungeonBlocks {
     fn namespace() -> &'static str {
@@ -502,6 +673,10 @@ impl BlockModule for DungeonBlocks {
 }
 use DungeonBlocks::*;
 
+/// How slowly [`DungeonBlocks::OpeningDoor`] slides: the number of ticks it takes
+/// to move by one voxel.
+const DOOR_OPENING_TICKS_PER_VOXEL: usize = 2;
+
 /// Add [`DungeonBlocks`] to the universe.
 pub async fn install_dungeon_blocks(
     universe: &mut Universe,
@@ -518,6 +693,22 @@ pub async fn install_dungeon_blocks(
     let stone_grout_1 = Block::from(stone_color * 0.8);
     let stone_grout_2 = Block::from(stone_color * 0.9);
     let spike_metal = Block::from(palette::STEEL);
+    let door_plank_1 = Block::from(palette::PLANK);
+    let door_plank_2 = Block::from(palette::PLANK * 0.8);
+
+    // Horizontal planks, so that the block looks the same whichever way the doorway
+    // faces.
+    let door_voxel = |y: GridCoordinate| {
+        if y.rem_euclid(4) == 0 {
+            &door_plank_2
+        } else if y == 1 || y == resolution_g - 3 {
+            &spike_metal
+        } else {
+            &door_plank_1
+        }
+    };
+    // How many voxels of an open door still show at the top of the doorway.
+    let open_door_lip = 2;
 
     use DungeonBlocks::*;
     BlockProvider::<DungeonBlocks>::new(progress, |key| {
@@ -569,10 +760,245 @@ pub async fn install_dungeon_blocks(
                     }
                 })?
                 .build(),
+            Door => Block::builder()
+                .display_name("Door")
+                .voxels_fn(universe, resolution, |GridPoint { y, .. }| door_voxel(y))?
+                .build(),
+            // Set up below, since it refers to OpenDoor.
+            OpeningDoor => AIR,
+            OpenDoor => Block::builder()
+                .display_name("Open Door")
+                .collision(BlockCollision::None)
+                .voxels_fn(universe, resolution, |GridPoint { y, .. }| {
+                    let slid_y = y - (resolution_g - open_door_lip);
+                    if slid_y >= 0 {
+                        door_voxel(slid_y)
+                    } else {
+                        &AIR
+                    }
+                })?
+                .build(),
         })
     })
     .await?
     .install(universe)?;
 
+    // Patch in the activation actions, which refer to each other and so cannot be
+    // set up in the provider function.
+    let provider_for_patch = BlockProvider::<DungeonBlocks>::using(universe)
+        .map_err(|e| GenError::failure(e, "TODO: dummy name".into()))?;
+    for (key, other) in [(Door, OpenDoor), (OpenDoor, Door)] {
+        if let Primitive::Indirect(block_def_ref) = provider_for_patch[key].primitive() {
+            let mut block: Block = (*block_def_ref.borrow()).clone();
+            if let Primitive::Recur { attributes, .. } = block.primitive_mut() {
+                attributes.activation_action =
+                    Some(TickAction::Replace(provider_for_patch[other].clone()));
+            }
+            block_def_ref
+                .execute(&BlockDefTransaction::overwrite(block))
+                .unwrap();
+        } else {
+            panic!("not indirect");
+        }
+    }
+
+    // The opening door is a chain of blocks, each showing the door one voxel higher
+    // than the last and replacing itself with the next when ticked, which finally
+    // replaces itself with OpenDoor. They all show windows onto one space holding
+    // the door with room below it.
+    let slide_distance = resolution_g - open_door_lip;
+    let mut door_track = Space::builder(Grid::new(
+        [0, -slide_distance, 0],
+        [resolution_g, resolution_g + slide_distance, resolution_g],
+    ))
+    .build_empty();
+    door_track
+        .fill(door_track.grid(), |GridPoint { y, .. }| {
+            Some(if y >= 0 { door_voxel(y) } else { &AIR })
+        })
+        .map_err(|e| GenError::failure(e, "TODO: dummy name".into()))?;
+    let door_track = universe.insert_anonymous(door_track);
+    let mut opening_door = provider_for_patch[OpenDoor].clone();
+    for slid in (0..slide_distance).rev() {
+        for _ in 0..DOOR_OPENING_TICKS_PER_VOXEL {
+            opening_door = Block::builder()
+                .display_name("Door")
+                .collision(BlockCollision::Recur)
+                .activation_action(Some(TickAction::Replace(provider_for_patch[Door].clone())))
+                .tick_action(Some(TickAction::Replace(opening_door)))
+                .voxels_ref(resolution, door_track.clone())
+                .offset(GridPoint::new(0, -slid, 0))
+                .build();
+        }
+    }
+    if let Primitive::Indirect(block_def_ref) = provider_for_patch[OpeningDoor].primitive() {
+        block_def_ref
+            .execute(&BlockDefTransaction::overwrite(opening_door))
+            .unwrap();
+    } else {
+        panic!("not indirect");
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::install_demo_blocks;
+    use all_is_cubes::time::Tick;
+    use futures_executor::block_on;
+    use std::collections::{HashSet, VecDeque};
+
+    /// There is a continuous path, climbing at most one cube at a time, from the
+    /// floor of a stairs room to the sunken room it leads to.
+    #[test]
+    fn stairs_connect_levels() {
+        // Find a seed that produces stairs.
+        let (seed, stairs_position, direction) = (0..100)
+            .find_map(|seed| {
+                let map = generate_dungeon_map(seed).unwrap();
+                map.grid().interior_iter().find_map(|position| {
+                    match map[position].as_ref()?.floor {
+                        FloorKind::Stairs { direction } => Some((seed, position, direction)),
+                        _ => None,
+                    }
+                })
+            })
+            .expect("no seed produced stairs");
+
+        let mut universe = Universe::new();
+        block_on(install_demo_blocks(&mut universe, YieldProgress::noop())).unwrap();
+        let space = block_on(demo_dungeon(&mut universe, YieldProgress::noop(), seed)).unwrap();
+
+        let dungeon_grid = demo_dungeon_grid();
+        let upper_room = dungeon_grid.room_box_at(stairs_position);
+        let lower_room = dungeon_grid
+            .room_box_at(stairs_position + direction.normal_vector() + GridVector::new(0, -1, 0));
+        let search_bounds = upper_room.union(lower_room).unwrap();
+
+        // Doors can be opened, so count them as empty.
+        let door = BlockProvider::<DungeonBlocks>::using(&universe).unwrap()[Door].clone();
+        let is_open = |cube: GridPoint| space[cube] == AIR || space[cube] == door;
+        let up = GridVector::new(0, 1, 0);
+        let standable = |cube: GridPoint| {
+            search_bounds.contains_cube(cube)
+                && is_open(cube)
+                && is_open(cube + up)
+                && !is_open(cube - up)
+        };
+
+        let start = upper_room
+            .interior_iter()
+            .find(|&cube| cube.y == upper_room.lower_bounds().y && standable(cube))
+            .expect("no floor in upper room");
+        let mut visited = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(cube) = queue.pop_front() {
+            if lower_room.contains_cube(cube) {
+                return;
+            }
+            for face in [Face6::NX, Face6::NZ, Face6::PX, Face6::PZ] {
+                for dy in -1..=1 {
+                    let next = cube + face.normal_vector() + up * dy;
+                    // Climbing or descending requires headroom above the lower cube.
+                    let headroom = match dy {
+                        1 => is_open(cube + up * 2),
+                        -1 => is_open(next + up * 2),
+                        _ => true,
+                    };
+                    if headroom && standable(next) && visited.insert(next) {
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        panic!(
+            "no path from {start:?} to {lower_room:?}; reached {} cubes",
+            visited.len()
+        );
+    }
+
+    #[test]
+    fn doors_in_doorways() {
+        let seed = 0;
+        let mut universe = Universe::new();
+        block_on(install_demo_blocks(&mut universe, YieldProgress::noop())).unwrap();
+        let space = block_on(demo_dungeon(&mut universe, YieldProgress::noop(), seed)).unwrap();
+        let theme = demo_theme(&universe, demo_dungeon_grid()).unwrap();
+        let map = generate_dungeon_map(seed).unwrap();
+        let door = theme.blocks[Door].clone();
+
+        let mut door_count = 0;
+        for room_position in map.grid().interior_iter() {
+            let room = match &map[room_position] {
+                Some(room) => room,
+                None => continue,
+            };
+            for face in [Face6::PX, Face6::PZ] {
+                if !room.door_faces[face] {
+                    continue;
+                }
+                let (_, door_box) = theme.doorway_boxes(&map, room_position, face);
+                for cube in door_box.interior_iter() {
+                    let placed = &space[cube];
+                    assert!(
+                        *placed == door || *placed == theme.blocks[OpeningDoor],
+                        "expected door at {cube:?}, found {placed:?}"
+                    );
+                }
+                door_count += 1;
+            }
+        }
+        assert!(door_count > 0);
+
+        assert_eq!(
+            door.evaluate().unwrap().attributes.collision,
+            BlockCollision::Hard
+        );
+    }
+
+    /// An opening door slides up out of the way, and then is an ordinary open door.
+    #[test]
+    fn opening_door_becomes_open_door() {
+        let mut universe = Universe::new();
+        block_on(install_dungeon_blocks(&mut universe, YieldProgress::noop())).unwrap();
+        let blocks = BlockProvider::<DungeonBlocks>::using(&universe).unwrap();
+        let mut space = Space::empty_positive(1, 1, 1);
+        space.set([0, 0, 0], &blocks[OpeningDoor]).unwrap();
+
+        let mut ticks = 0;
+        while space[[0, 0, 0]] != blocks[OpenDoor] {
+            assert_ne!(space[[0, 0, 0]], AIR);
+            assert!(ticks < 100, "door did not finish opening");
+            let (_, _) = space.step(None, Tick::arbitrary());
+            ticks += 1;
+        }
+        assert_eq!(ticks, 14 * DOOR_OPENING_TICKS_PER_VOXEL);
+        assert_eq!(
+            space.get_evaluated([0, 0, 0]).attributes.activation_action,
+            Some(TickAction::Replace(blocks[Door].clone()))
+        );
+    }
+
+    /// Activating a closed door opens it, and activating it again closes it.
+    #[test]
+    fn door_activation_toggles() {
+        let mut universe = Universe::new();
+        block_on(install_dungeon_blocks(&mut universe, YieldProgress::noop())).unwrap();
+        let blocks = BlockProvider::<DungeonBlocks>::using(&universe).unwrap();
+        let door = blocks[Door].evaluate().unwrap();
+        let open = blocks[OpenDoor].evaluate().unwrap();
+
+        assert_eq!(door.attributes.collision, BlockCollision::Hard);
+        assert_eq!(
+            door.attributes.activation_action,
+            Some(TickAction::Replace(blocks[OpenDoor].clone()))
+        );
+        assert_eq!(open.attributes.collision, BlockCollision::None);
+        assert_eq!(
+            open.attributes.activation_action,
+            Some(TickAction::Replace(blocks[Door].clone()))
+        );
+    }
+}
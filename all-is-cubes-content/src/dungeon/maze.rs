@@ -3,7 +3,7 @@
 
 use maze_generator::prelude::{Direction, Field, Maze};
 
-use all_is_cubes::math::{Face7, GridPoint};
+use all_is_cubes::math::{Face6, GridPoint};
 use all_is_cubes::space::{Grid, GridArray};
 
 pub fn maze_to_array(maze: &Maze) -> GridArray<Field> {
@@ -20,11 +20,11 @@ pub fn gp2m(p: GridPoint) -> maze_generator::prelude::Coordinates {
     maze_generator::prelude::Coordinates { x: p.x, y: p.z }
 }
 
-pub fn d2f(direction: Direction) -> Face7 {
+pub fn d2f(direction: Direction) -> Face6 {
     match direction {
-        Direction::North => Face7::NZ,
-        Direction::East => Face7::PX,
-        Direction::South => Face7::PZ,
-        Direction::West => Face7::NX,
+        Direction::North => Face6::NZ,
+        Direction::East => Face6::PX,
+        Direction::South => Face6::PZ,
+        Direction::West => Face6::NX,
     }
 }
@@ -644,8 +644,9 @@ exhibit! {
         let wall_block = {
             let colors_as_blocks: Vec<Block> =
                 surface_colors.iter().copied().map(Block::from).collect();
-            let mut wall_block_space = Space::for_block(wall_resolution).build_empty();
-            wall_block_space.fill_uniform(wall_block_space.grid(), &wall_color_block)?;
+            let mut wall_block_space = Space::for_block(wall_resolution)
+                .filled_with(wall_color_block.clone())
+                .build()?;
             for rotation in [
                 GridRotation::IDENTITY,
                 GridRotation::CLOCKWISE,
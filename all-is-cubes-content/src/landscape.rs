@@ -292,9 +292,9 @@ pub fn wavy_landscape(
                 let block: &Block = if altitude > 1 {
                     continue;
                 } else if altitude == 1 {
-                    if placement_noise.at_cube(cube) > grass_threshold * 2. {
+                    if placement_noise.at_cube_center(cube) > grass_threshold * 2. {
                         &blocks[GrassBlades { variant: true }]
-                    } else if placement_noise.at_cube(cube) > grass_threshold {
+                    } else if placement_noise.at_cube_center(cube) > grass_threshold {
                         &blocks[GrassBlades { variant: false }]
                     } else {
                         &AIR
@@ -172,6 +172,38 @@ pub(crate) fn voronoi_pattern<'a>(
     move |cube| pattern[cube.map(|component| component.rem_euclid(resolution.into()))].1
 }
 
+/// Divides the one-cube-thick outer layer of `grid` into slabs, one for each face of
+/// `grid` for which `include_faces` is true, and calls `f` with each face and its slab.
+///
+/// No cube belongs to more than one slab: where two included faces meet, the edge
+/// belongs to the face on the earlier axis (X, then Y, then Z). A cube is left out only
+/// if every face it lies on is excluded. Faces whose slab would be empty, because
+/// `grid` is too thin, are skipped.
+pub(crate) fn box_shell(grid: Grid, include_faces: FaceMap<bool>, mut f: impl FnMut(Face6, Grid)) {
+    let mut remaining = grid;
+    for face in [
+        Face6::NX,
+        Face6::PX,
+        Face6::NY,
+        Face6::PY,
+        Face6::NZ,
+        Face6::PZ,
+    ] {
+        if !include_faces[face] {
+            continue;
+        }
+        let axis = face.axis_number();
+        let slab = remaining.face_slab(face, 1).unwrap();
+        // Later faces get only what this slab didn't take.
+        remaining = remaining
+            .face_slab(face.opposite(), remaining.size()[axis] - slab.size()[axis])
+            .unwrap();
+        if !slab.is_empty() {
+            f(face, slab);
+        }
+    }
+}
+
 /// Given a room's exterior bounding box, act on its four walls.
 ///
 /// The function is given the bottom-left (from an exterior perspective) corner cube
@@ -186,28 +218,42 @@ pub(crate) fn four_walls<F, E>(bounding_box: Grid, mut f: F) -> Result<(), E>
 where
     F: FnMut(GridPoint, Face6, GridCoordinate, Grid) -> Result<(), E>,
 {
-    let interior = bounding_box.expand(FaceMap::symmetric([-1, 0, -1]));
+    let mut walls: FaceMap<Option<Grid>> = FaceMap::default();
+    box_shell(
+        bounding_box,
+        FaceMap::symmetric([true, false, true]),
+        |face, slab| {
+            // box_shell() gives the corners to the X walls; trim them off.
+            walls[face] = Some(if face.axis_number() == 0 {
+                slab.expand(FaceMap::symmetric([0, 0, -1]))
+            } else {
+                slab
+            });
+        },
+    );
+    let wall = |face: Face6| walls[face].expect("four_walls() box too small");
+
     let low = bounding_box.lower_bounds();
     let high = bounding_box.upper_bounds() - GridVector::new(1, 1, 1);
     let size = bounding_box.size();
-    f(low, Face6::PZ, size.z, interior.abut(Face6::NX, 1).unwrap())?;
+    f(low, Face6::PZ, size.z, wall(Face6::NX))?;
     f(
         GridPoint::new(low.x, low.y, high.z),
         Face6::PX,
         size.x,
-        interior.abut(Face6::PZ, 1).unwrap(),
+        wall(Face6::PZ),
     )?;
     f(
         GridPoint::new(high.x, low.y, high.z),
         Face6::NZ,
         size.z,
-        interior.abut(Face6::PX, 1).unwrap(),
+        wall(Face6::PX),
     )?;
     f(
         GridPoint::new(high.x, low.y, low.z),
         Face6::NX,
         size.x,
-        interior.abut(Face6::NZ, 1).unwrap(),
+        wall(Face6::NZ),
     )?;
     Ok(())
 }
@@ -264,3 +310,103 @@ pub(crate) fn space_to_transaction_copy(
 pub(crate) fn int_magnitude_squared(v: GridVector) -> GridCoordinate {
     v.x * v.x + v.y * v.y + v.z * v.z
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn box_shell_encloses_without_overlap() {
+        let grid = Grid::new([1, 2, 3], [4, 5, 6]);
+        let mut covered = GridArray::from_fn(grid, |_| 0);
+        let mut faces = Vec::new();
+        box_shell(grid, FaceMap::repeat(true), |face, slab| {
+            faces.push(face);
+            assert_eq!(slab.size()[face.axis_number()], 1, "{face:?} {slab:?}");
+            for cube in slab.interior_iter() {
+                covered[cube] += 1;
+            }
+        });
+
+        assert_eq!(faces.len(), 6);
+        let interior = grid.expand(FaceMap::repeat(-1));
+        for cube in grid.interior_iter() {
+            let expected = if interior.contains_cube(cube) { 0 } else { 1 };
+            assert_eq!(covered[cube], expected, "{cube:?}");
+        }
+    }
+
+    #[test]
+    fn box_shell_subset_of_faces() {
+        let grid = Grid::new([0, 0, 0], [3, 3, 3]);
+        let mut slabs = Vec::new();
+        box_shell(
+            grid,
+            FaceMap {
+                ny: true,
+                pz: true,
+                ..FaceMap::default()
+            },
+            |face, slab| slabs.push((face, slab)),
+        );
+        assert_eq!(
+            slabs,
+            vec![
+                (Face6::NY, Grid::new([0, 0, 0], [3, 1, 3])),
+                (Face6::PZ, Grid::new([0, 1, 2], [3, 2, 1])),
+            ]
+        );
+    }
+
+    #[test]
+    fn box_shell_thin() {
+        let mut slabs = Vec::new();
+        box_shell(
+            Grid::new([0, 0, 0], [1, 2, 2]),
+            FaceMap::repeat(true),
+            |face, slab| slabs.push((face, slab)),
+        );
+        // The single X layer is taken by NX, and nothing is left for any other face.
+        assert_eq!(slabs, vec![(Face6::NX, Grid::new([0, 0, 0], [1, 2, 2]))]);
+    }
+
+    #[test]
+    fn four_walls_excludes_corners() {
+        let bounding_box = Grid::new([0, 0, 0], [4, 2, 5]);
+        let mut walls = Vec::new();
+        four_walls(bounding_box, |origin, direction, length, wall| {
+            walls.push((origin, direction, length, wall));
+            Ok::<(), ()>(())
+        })
+        .unwrap();
+        assert_eq!(
+            walls,
+            vec![
+                (
+                    GridPoint::new(0, 0, 0),
+                    Face6::PZ,
+                    5,
+                    Grid::new([0, 0, 1], [1, 2, 3])
+                ),
+                (
+                    GridPoint::new(0, 0, 4),
+                    Face6::PX,
+                    4,
+                    Grid::new([1, 0, 4], [2, 2, 1])
+                ),
+                (
+                    GridPoint::new(3, 0, 4),
+                    Face6::NZ,
+                    5,
+                    Grid::new([3, 0, 1], [1, 2, 3])
+                ),
+                (
+                    GridPoint::new(3, 0, 0),
+                    Face6::NX,
+                    4,
+                    Grid::new([1, 0, 0], [2, 2, 1])
+                ),
+            ]
+        );
+    }
+}
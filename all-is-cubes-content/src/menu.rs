@@ -21,7 +21,7 @@ use all_is_cubes::{
     inv::Tool,
     linking::InGenError,
     math::{Face6, GridMatrix, GridVector},
-    space::{Grid, Space, SpacePhysics, SpaceTransaction},
+    space::{Gravity, Grid, Sky, Space, SpacePhysics, SpaceTransaction},
     transaction::{Merge, Transaction as _},
     universe::Universe,
     vui::{
@@ -156,8 +156,8 @@ pub(crate) fn template_menu(universe: &mut Universe) -> Result<Space, InGenError
     let mut space = Space::builder(bounds)
         .physics({
             let mut p = SpacePhysics::default();
-            p.sky_color = palette::MENU_BACK.to_rgb();
-            p.gravity = Vector3::zero();
+            p.sky = Sky::Uniform(palette::MENU_BACK.to_rgb());
+            p.gravity = Gravity::Uniform(Vector3::zero());
             p
         })
         .spawn({
@@ -1,7 +1,6 @@
 // Copyright 2020-2022 Kevin Reid under the terms of the MIT License as detailed
 // in the accompanying file README.md or <https://opensource.org/licenses/MIT>.
 
-use all_is_cubes::cgmath::Vector3;
 use noise::NoiseFn;
 
 use all_is_cubes::block::Resolution;
@@ -37,9 +36,18 @@ pub trait NoiseFnExt: NoiseFn<[f64; 3]> {
     /// symmetric shapes with even-numbered widths.
     fn at_cube(&self, cube: GridPoint) -> f64;
 
+    /// Alias for [`NoiseFnExt::at_cube`], for call sites where it should be clear
+    /// that the sample is taken at the center of the cube rather than its corner.
+    fn at_cube_center(&self, cube: GridPoint) -> f64;
+
     /// As [`NoiseFn::get`], but converting from integer. Unlike [`NoiseFnExt::at_cube`],
     /// does not apply any offset.
     fn at_grid(&self, point: GridPoint) -> f64;
+
+    /// As [`NoiseFnExt::at_grid`], but multiplies the point by `scale` before sampling.
+    /// This is equivalent to wrapping the function in [`noise::ScalePoint`], without
+    /// needing to construct one.
+    fn at_grid_scaled(&self, point: GridPoint, scale: f64) -> f64;
 }
 impl<T> NoiseFnExt for T
 where
@@ -49,8 +57,14 @@ where
     where
         Self: Sized,
     {
-        let point = cube.map(f64::from) + Vector3::new(0.5, 0.5, 0.5);
-        NoiseFn::get(&self, point.into())
+        NoiseFn::get(&self, cube_to_midpoint(cube).into())
+    }
+
+    fn at_cube_center(&self, cube: GridPoint) -> f64
+    where
+        Self: Sized,
+    {
+        self.at_cube(cube)
     }
 
     fn at_grid(&self, point: GridPoint) -> f64
@@ -60,4 +74,56 @@ where
         let point = point.map(f64::from);
         NoiseFn::get(&self, point.into())
     }
+
+    fn at_grid_scaled(&self, point: GridPoint, scale: f64) -> f64
+    where
+        Self: Sized,
+    {
+        let point = point.map(f64::from) * scale;
+        NoiseFn::get(&self, point.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use noise::Seedable as _;
+
+    #[test]
+    fn at_cube_is_midpoint() {
+        let noise = noise::OpenSimplex::new().set_seed(0x1234);
+        for cube in Grid::new([-2, -2, -2], [4, 4, 4]).interior_iter() {
+            assert_eq!(
+                noise.at_cube(cube),
+                NoiseFn::<[f64; 3]>::get(&noise, cube_to_midpoint(cube).into()),
+                "{cube:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn at_cube_center_is_midpoint() {
+        let noise = noise::OpenSimplex::new().set_seed(0x1234);
+        for cube in Grid::new([-2, -2, -2], [4, 4, 4]).interior_iter() {
+            assert_eq!(
+                noise.at_cube_center(cube),
+                NoiseFn::<[f64; 3]>::get(&noise, cube_to_midpoint(cube).into()),
+                "{cube:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn at_grid_scaled_matches_scale_point() {
+        let noise = noise::OpenSimplex::new().set_seed(0x1234);
+        let scaled = noise::ScalePoint::new(&noise).set_scale(0.3);
+        for cube in Grid::new([-2, -2, -2], [4, 4, 4]).interior_iter() {
+            assert_eq!(
+                noise.at_grid_scaled(cube, 0.3),
+                scaled.at_grid(cube),
+                "{cube:?}"
+            );
+            assert_eq!(noise.at_grid_scaled(cube, 1.0), noise.at_grid(cube));
+        }
+    }
 }
@@ -115,9 +115,11 @@ fn convert_dot_vox_model(
             spawn.set_inventory(free_editing_starter_inventory(true));
             spawn
         })
-        .light_physics(LightPhysics::Rays {
-            maximum_distance: extent.y_range().len() as u16,
-        })
+        .light_physics(
+            LightPhysics::rays()
+                .maximum_distance(extent.y_range().len() as u16)
+                .build(),
+        )
         .sky_color(Rgb::ONE)
         .build_empty();
     for v in model.voxels {
@@ -20,6 +20,8 @@ pub use info::*;
 pub mod octree_alloc;
 
 pub(crate) mod reloadable;
+mod wrap;
+pub(crate) use wrap::*;
 
 /// Error arising when GPU/platform resources could not be obtained, or there is a bug
 /// or incompatibility, and the requested graphics initialization or drawing could not be
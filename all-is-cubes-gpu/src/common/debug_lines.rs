@@ -6,6 +6,7 @@ use all_is_cubes::cgmath::Point3;
 use all_is_cubes::character::{Character, Cursor};
 use all_is_cubes::content::palette;
 use all_is_cubes::math::{Aab, FreeCoordinate, Geometry, Rgba};
+use all_is_cubes::mesh::{DepthOrdering, SpaceMesh};
 use all_is_cubes::util::MapExtend;
 
 /// TODO: give this trait a better name
@@ -77,3 +78,63 @@ where
         },
     ))
 }
+
+/// What a chunk's mesh contains, as displayed by
+/// [`GraphicsOptions::debug_chunk_boxes`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum ChunkDebugState {
+    /// The chunk has no mesh yet, or has nothing to draw.
+    Empty,
+    /// The chunk has only opaque triangles.
+    Opaque,
+    /// The chunk has some triangles that need depth sorting.
+    Transparent,
+}
+
+impl ChunkDebugState {
+    pub(crate) fn of<V, T>(mesh: &SpaceMesh<V, T>) -> Self {
+        if mesh.is_empty() {
+            Self::Empty
+        } else if mesh.transparent_range(DepthOrdering::Any).is_empty() {
+            Self::Opaque
+        } else {
+            Self::Transparent
+        }
+    }
+
+    /// Color for the chunk's wireframe box. Chunks which were retriangulated in the
+    /// current frame are highlighted regardless of their contents, so that they flash.
+    pub(crate) fn color(self, updated_this_frame: bool) -> Rgba {
+        if updated_this_frame {
+            return palette::DEBUG_CHUNK_UPDATED;
+        }
+        match self {
+            Self::Empty => palette::DEBUG_CHUNK_EMPTY,
+            Self::Opaque => palette::DEBUG_CHUNK_OPAQUE,
+            Self::Transparent => palette::DEBUG_CHUNK_TRANSPARENT,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use all_is_cubes::mesh::{BlockVertex, TestTextureTile};
+
+    #[test]
+    fn chunk_debug_state_of_empty_mesh() {
+        let mesh = SpaceMesh::<BlockVertex, TestTextureTile>::default();
+        assert_eq!(ChunkDebugState::of(&mesh), ChunkDebugState::Empty);
+    }
+
+    #[test]
+    fn chunk_debug_color() {
+        use ChunkDebugState::*;
+        assert_eq!(Empty.color(false), palette::DEBUG_CHUNK_EMPTY);
+        assert_eq!(Opaque.color(false), palette::DEBUG_CHUNK_OPAQUE);
+        assert_eq!(Transparent.color(false), palette::DEBUG_CHUNK_TRANSPARENT);
+        for state in [Empty, Opaque, Transparent] {
+            assert_eq!(state.color(true), palette::DEBUG_CHUNK_UPDATED);
+        }
+    }
+}
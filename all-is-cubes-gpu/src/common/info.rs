@@ -50,6 +50,19 @@ pub struct DrawInfo {
     /// Time taken on submitting accumulated information to the GPU.
     /// `None` if the graphics API does not expose this as a step.
     pub(crate) submit_time: Option<Duration>,
+    /// Whether drawing was skipped because the viewport was not
+    /// [valid](all_is_cubes::camera::Viewport::is_valid).
+    pub(crate) skipped_invalid_viewport: bool,
+}
+
+impl DrawInfo {
+    /// Info for a frame which was not drawn because the viewport was not valid.
+    pub(crate) fn skipped_invalid_viewport() -> Self {
+        Self {
+            skipped_invalid_viewport: true,
+            ..Self::default()
+        }
+    }
 }
 
 impl CustomFormat<StatusText> for RenderInfo {
@@ -68,6 +81,7 @@ impl CustomFormat<StatusText> for RenderInfo {
                     times: draw_time,
                     space_info: ref draw_spaces,
                     submit_time,
+                    skipped_invalid_viewport,
                 },
         } = self;
 
@@ -89,6 +103,9 @@ impl CustomFormat<StatusText> for RenderInfo {
             write!(fmt, ", submit {}", t.custom_format(StatusText))?;
         }
         writeln!(fmt, ")")?;
+        if skipped_invalid_viewport {
+            writeln!(fmt, "Drawing skipped: viewport is empty")?;
+        }
 
         // UpdateInfo details
         write!(
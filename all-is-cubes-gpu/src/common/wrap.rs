@@ -0,0 +1,123 @@
+// Copyright 2020-2022 Kevin Reid under the terms of the MIT License as detailed
+// in the accompanying file README.md or <https://opensource.org/licenses/MIT>.
+
+//! Coordinate math for textures which are addressed toroidally, i.e. with each cube
+//! stored at its coordinates modulo the texture size.
+
+use all_is_cubes::math::{GridCoordinate, GridPoint, GridVector};
+use all_is_cubes::space::Grid;
+
+/// Returns the texel position at which `cube` is stored in a toroidally addressed
+/// texture of the given size.
+pub(crate) fn wrap_point(cube: GridPoint, texture_size: GridVector) -> GridPoint {
+    GridPoint::new(
+        cube.x.rem_euclid(texture_size.x),
+        cube.y.rem_euclid(texture_size.y),
+        cube.z.rem_euclid(texture_size.z),
+    )
+}
+
+/// Splits `region` into the pieces (at most 8) which, when stored in a toroidally
+/// addressed texture of the given size, are each contiguous in the texture;
+/// that is, none of them straddles a multiple of `texture_size` on any axis.
+///
+/// Each piece's location in the texture is [`wrap_point`] of its lower bounds.
+///
+/// Panics if `region` is larger than `texture_size` on any axis, since that would
+/// make the pieces overlap.
+pub(crate) fn wrap_region(region: Grid, texture_size: GridVector) -> Vec<Grid> {
+    let mut axis_ranges: [Vec<(GridCoordinate, GridCoordinate)>; 3] = Default::default();
+    for (axis, ranges) in axis_ranges.iter_mut().enumerate() {
+        let range = region.axis_range(axis);
+        let modulus = texture_size[axis];
+        assert!(
+            range.end - range.start <= modulus,
+            "region {region:?} does not fit in texture of size {texture_size:?}"
+        );
+        // The first multiple of the modulus strictly above the start.
+        let boundary = range.start - range.start.rem_euclid(modulus) + modulus;
+        if boundary < range.end {
+            ranges.push((range.start, boundary));
+            ranges.push((boundary, range.end));
+        } else {
+            ranges.push((range.start, range.end));
+        }
+    }
+
+    let [xs, ys, zs] = axis_ranges;
+    let mut pieces = Vec::with_capacity(xs.len() * ys.len() * zs.len());
+    for &(x0, x1) in &xs {
+        for &(y0, y1) in &ys {
+            for &(z0, z1) in &zs {
+                pieces.push(Grid::from_lower_upper([x0, y0, z0], [x1, y1, z1]));
+            }
+        }
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: GridVector = GridVector::new(10, 10, 10);
+
+    #[test]
+    fn wrap_point_negative() {
+        assert_eq!(
+            wrap_point(GridPoint::new(-1, 10, 25), SIZE),
+            GridPoint::new(9, 0, 5)
+        );
+    }
+
+    #[test]
+    fn wrap_region_not_straddling() {
+        let region = Grid::new([12, 3, -10], [8, 7, 10]);
+        assert_eq!(wrap_region(region, SIZE), vec![region]);
+    }
+
+    #[test]
+    fn wrap_region_straddling_two_axes() {
+        let region = Grid::from_lower_upper([8, -2, 0], [13, 3, 5]);
+        assert_eq!(
+            wrap_region(region, SIZE),
+            vec![
+                Grid::from_lower_upper([8, -2, 0], [10, 0, 5]),
+                Grid::from_lower_upper([8, 0, 0], [10, 3, 5]),
+                Grid::from_lower_upper([10, -2, 0], [13, 0, 5]),
+                Grid::from_lower_upper([10, 0, 0], [13, 3, 5]),
+            ]
+        );
+        // The pieces land in disjoint parts of the texture.
+        let texel_regions: Vec<Grid> = wrap_region(region, SIZE)
+            .into_iter()
+            .map(|piece| Grid::new(wrap_point(piece.lower_bounds(), SIZE), piece.size()))
+            .collect();
+        assert_eq!(
+            texel_regions,
+            vec![
+                Grid::from_lower_upper([8, 8, 0], [10, 10, 5]),
+                Grid::from_lower_upper([8, 0, 0], [10, 3, 5]),
+                Grid::from_lower_upper([0, 8, 0], [3, 10, 5]),
+                Grid::from_lower_upper([0, 0, 0], [3, 3, 5]),
+            ]
+        );
+    }
+
+    #[test]
+    fn wrap_region_straddling_all_axes() {
+        let region = Grid::new([5, 5, 5], [10, 10, 10]);
+        let pieces = wrap_region(region, SIZE);
+        assert_eq!(pieces.len(), 8);
+        assert_eq!(
+            pieces.iter().map(Grid::volume).sum::<usize>(),
+            region.volume()
+        );
+    }
+
+    #[test]
+    #[should_panic = "does not fit"]
+    fn wrap_region_too_large() {
+        wrap_region(Grid::new([0, 0, 0], [11, 1, 1]), SIZE);
+    }
+}
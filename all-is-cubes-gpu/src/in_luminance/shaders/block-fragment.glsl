@@ -10,6 +10,7 @@ in mediump vec4 v_color_or_texture;
 in mediump vec3 v_normal;
 in mediump vec3 v_clamp_min;
 in mediump vec3 v_clamp_max;
+in lowp float v_ambient_occlusion;
 
 #ifdef SMOOTH_LIGHTING
   // Two positive unit vectors perpendicular to the normal vector.
@@ -240,7 +241,7 @@ void main(void) {
 
   // Lighting
   // TODO: What's a better name for this variable?
-  mediump vec4 color = diffuse_color * vec4(lighting(), 1.0);
+  mediump vec4 color = diffuse_color * vec4(lighting() * v_ambient_occlusion, 1.0);
 
   // Fog
   color.rgb = mix(color.rgb, fog_color, fog_mix);
@@ -7,6 +7,7 @@ in lowp vec3 a_normal;
 in mediump vec4 a_color_or_texture;
 in mediump vec3 a_clamp_min;
 in mediump vec3 a_clamp_max;
+in lowp float a_ambient_occlusion;
 
 out highp vec3 v_position;
 out highp vec3 v_position_in_cube;
@@ -14,6 +15,7 @@ out mediump vec4 v_color_or_texture;
 out mediump vec3 v_clamp_min;
 out mediump vec3 v_clamp_max;
 out lowp vec3 v_normal;
+out lowp float v_ambient_occlusion;
 
 #ifdef LIGHTING
   #ifdef SMOOTH_LIGHTING
@@ -36,6 +38,7 @@ void main(void) {
   v_color_or_texture = a_color_or_texture;
   v_clamp_min = a_clamp_min;
   v_clamp_max = a_clamp_max;
+  v_ambient_occlusion = a_ambient_occlusion;
 
   v_position_in_cube = a_position - a_cube;
 
@@ -11,7 +11,6 @@ uniform highp vec3 view_position;
 uniform lowp sampler3D block_texture;
 
 uniform lowp sampler3D light_texture;
-uniform highp ivec3 light_offset;
 
 // Fog equation blending: 0 is realistic fog and 1 is distant more abrupt fog.
 // TODO: Replace this uniform with a compiled-in flag since it doesn't need to be continuously changing.
@@ -38,10 +37,9 @@ uniform mediump vec3 fog_color;
 // truly ignored.
 lowp vec4 light_texture_fetch(mediump vec3 p) {
   ivec3 lookup_position = ivec3(floor(p));
-  lookup_position += light_offset;
   // Implement wrapping (not automatic since we're using texelFetch).
-  // Wrapping is used to handle sky light and in the future will be used for
-  // circular buffering of the local light in an unbounded world.
+  // The texture stores each cube at its coordinates modulo the texture size,
+  // and wrapping also provides sky light for cubes just outside the space.
   ivec3 size = textureSize(light_texture, 0);
   lookup_position = (lookup_position % size + size) % size;
 
@@ -295,9 +295,6 @@ pub struct BlockUniformInterface {
     /// Texture containing light map.
     #[uniform(unbound)] // unbound if LightingOption::None
     light_texture: Uniform<TextureBinding<Dim3, NormUnsigned>>,
-    /// Offset applied to vertex coordinates to get light map coordinates.
-    #[uniform(unbound)] // unbound if LightingOption::None
-    light_offset: Uniform<Vec3<i32>>,
 
     /// Fog equation blending: 0 is realistic fog and 1 is distant more abrupt fog.
     /// TODO: Replace this uniform with a compiled-in flag since it doesn't need to be continuously changing.
@@ -331,12 +328,8 @@ impl BlockUniformInterface {
             &self.light_texture,
             space.bound_light_texture.texture.binding(),
         );
-        program_iface.set(
-            &self.light_offset,
-            Vec3(space.bound_light_texture.offset.into()),
-        );
 
-        let view_distance = camera.view_distance() as f32;
+        let view_distance = (camera.view_distance() * options.fog_distance.into_inner()) as f32;
         let (fog_mode_blend, fog_distance) = match options.fog {
             FogOption::Abrupt => (1.0, view_distance),
             FogOption::Compromise => (0.5, view_distance),
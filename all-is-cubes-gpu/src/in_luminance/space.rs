@@ -21,7 +21,9 @@ use luminance::tess_gate::TessGate;
 use luminance::texture::{Dim3, Sampler, TexelUpload, Texture, TextureError};
 
 use all_is_cubes::camera::Camera;
-use all_is_cubes::cgmath::{EuclideanSpace as _, Matrix4, Point3, Transform as _, Vector3};
+use all_is_cubes::cgmath::{
+    EuclideanSpace as _, Matrix4, Point3, SquareMatrix as _, Transform as _,
+};
 use all_is_cubes::chunking::ChunkPos;
 use all_is_cubes::content::palette;
 use all_is_cubes::listen::Listener;
@@ -39,11 +41,28 @@ use crate::in_luminance::{
     types::{AicLumBackend, LinesVertex, LumBlockVertex},
     wireframe_vertices,
 };
-use crate::{GraphicsResourceError, SpaceDrawInfo, SpaceUpdateInfo};
+use crate::{
+    wrap_point, wrap_region, ChunkDebugState, GraphicsResourceError, SpaceDrawInfo, SpaceUpdateInfo,
+};
 
 const CHUNK_SIZE: GridCoordinate = 16;
 
-type ChunkData<Backend> = Option<Tess<Backend, LumBlockVertex, u32>>;
+/// Per-chunk data stored in the [`ChunkedSpaceMesh`].
+struct ChunkData<Backend: AicLumBackend> {
+    tess: Option<Tess<Backend, LumBlockVertex, u32>>,
+    /// Value of [`SpaceRenderer::frame_number`] when the mesh was last updated,
+    /// for debug display.
+    updated_frame: u64,
+}
+
+impl<Backend: AicLumBackend> Default for ChunkData<Backend> {
+    fn default() -> Self {
+        Self {
+            tess: None,
+            updated_frame: 0,
+        }
+    }
+}
 
 /// Manages cached data and GPU resources for drawing a single [`Space`] and
 /// following its changes.
@@ -58,7 +77,12 @@ pub struct SpaceRenderer<Backend: AicLumBackend> {
         LumAtlasAllocator<Backend>,
         CHUNK_SIZE,
     >,
+    /// Lines framing the chunk containing the camera; static except for translation.
     debug_chunk_boxes_tess: Option<Tess<Backend, LinesVertex>>,
+    /// Lines framing every chunk, colored by its [`ChunkDebugState`]; rebuilt every frame.
+    debug_chunk_state_tess: Option<Tess<Backend, LinesVertex>>,
+    /// Incremented on every [`Self::prepare_frame`].
+    frame_number: u64,
 }
 
 impl<Backend: AicLumBackend> SpaceRenderer<Backend> {
@@ -80,6 +104,8 @@ impl<Backend: AicLumBackend> SpaceRenderer<Backend> {
             light_texture: None,
             csm: ChunkedSpaceMesh::new(space),
             debug_chunk_boxes_tess: None,
+            debug_chunk_state_tess: None,
+            frame_number: 0,
         }
     }
 
@@ -101,6 +127,8 @@ impl<Backend: AicLumBackend> SpaceRenderer<Backend> {
         C: GraphicsContext<Backend = Backend>,
     {
         let start_time = Instant::now();
+        self.frame_number += 1;
+        let frame_number = self.frame_number;
 
         let graphics_options = camera.options();
         let mut todo = self.todo.lock().unwrap();
@@ -125,15 +153,20 @@ impl<Backend: AicLumBackend> SpaceRenderer<Backend> {
         // Update light texture
         let start_light_update = Instant::now();
         let mut light_update_count = 0;
+        let light_visible_region = if graphics_options.use_frustum_culling {
+            visible_light_region(light_texture.texture_grid, camera.view_frustum_aab())
+        } else {
+            light_texture.texture_grid
+        };
         if let Some(set) = &mut todo.light {
             // TODO: work in larger, ahem, chunks
             for cube in set.drain() {
                 light_texture.update(space, Grid::new(cube, [1, 1, 1]))?;
                 light_update_count += 1;
             }
+            light_update_count += light_texture.update_stale(space, light_visible_region)?;
         } else {
-            light_texture.update_all(space)?;
-            light_update_count += space.grid().volume();
+            light_update_count += light_texture.update_all(space, light_visible_region)?;
             todo.light = Some(HashSet::new());
         }
         let end_light_update = Instant::now();
@@ -144,7 +177,8 @@ impl<Backend: AicLumBackend> SpaceRenderer<Backend> {
             block_texture_allocator,
             deadline, // TODO: decrease deadline by some guess at texture writing time
             |mesh, render_data| {
-                update_chunk_tess(context, mesh, render_data);
+                update_chunk_tess(context, mesh, &mut render_data.tess);
+                render_data.updated_frame = frame_number;
             },
             |mesh, render_data| {
                 // Disable dynamic depth sorting because luminance bug
@@ -152,7 +186,7 @@ impl<Backend: AicLumBackend> SpaceRenderer<Backend> {
                 // means indices_mut() can fail and corrupt other buffers.
                 // TODO: Reenble this and also in-place chunk updating when bug is fixed
                 if !cfg!(target_family = "wasm") {
-                    if let Some(tess) = render_data {
+                    if let Some(tess) = &mut render_data.tess {
                         let range = mesh.transparent_range(DepthOrdering::Within);
                         tess.indices_mut()
                             .expect("failed to map indices for depth sorting")[range.clone()]
@@ -172,17 +206,6 @@ impl<Backend: AicLumBackend> SpaceRenderer<Backend> {
         if graphics_options.debug_chunk_boxes {
             if self.debug_chunk_boxes_tess.is_none() {
                 let mut v = Vec::new();
-                for chunk in self
-                    .csm
-                    .chunk_chart()
-                    .chunks(view_chunk, view_direction_mask)
-                {
-                    wireframe_vertices::<LinesVertex, _, _>(
-                        &mut v,
-                        palette::DEBUG_CHUNK_MAJOR,
-                        &Aab::from(chunk.grid()),
-                    );
-                }
 
                 // Frame the nearest chunk in detail
                 for face in Face6::ALL {
@@ -201,11 +224,6 @@ impl<Backend: AicLumBackend> SpaceRenderer<Backend> {
                     }
                 }
 
-                // TODO: Allocate some vertices for dynamic debug info
-                // (or maybe use separate Tesses per chunk)
-                // * Signal chunk updates
-                // * Mark which chunks are nonempty, opaque, transparent, view angle
-
                 self.debug_chunk_boxes_tess = Some(
                     context
                         .new_tess()
@@ -214,8 +232,35 @@ impl<Backend: AicLumBackend> SpaceRenderer<Backend> {
                         .build()?,
                 );
             }
+
+            // Frame every chunk, colored according to its current state.
+            let mut v = Vec::new();
+            for chunk_pos in self
+                .csm
+                .chunk_chart()
+                .chunks(view_chunk, view_direction_mask)
+            {
+                let color = match self.csm.chunk(chunk_pos) {
+                    Some(chunk) => ChunkDebugState::of(chunk.mesh())
+                        .color(chunk.render_data.updated_frame == frame_number),
+                    None => palette::DEBUG_CHUNK_MAJOR,
+                };
+                wireframe_vertices::<LinesVertex, _, _>(
+                    &mut v,
+                    color,
+                    &Aab::from(chunk_pos.grid()),
+                );
+            }
+            self.debug_chunk_state_tess = Some(
+                context
+                    .new_tess()
+                    .set_vertices(v)
+                    .set_mode(Mode::Line)
+                    .build()?,
+            );
         } else {
             self.debug_chunk_boxes_tess = None;
+            self.debug_chunk_state_tess = None;
         }
 
         let end_time = Instant::now();
@@ -225,6 +270,7 @@ impl<Backend: AicLumBackend> SpaceRenderer<Backend> {
                 camera: camera.clone(),
                 csm: &self.csm,
                 debug_chunk_boxes_tess: &self.debug_chunk_boxes_tess,
+                debug_chunk_state_tess: &self.debug_chunk_state_tess,
                 view_chunk,
                 update_info: SpaceUpdateInfo {
                     total_time: end_time.duration_since(start_time),
@@ -233,7 +279,7 @@ impl<Backend: AicLumBackend> SpaceRenderer<Backend> {
                     chunk_info: csm_info,
                     texture_info,
                 },
-                sky_color: space.physics().sky_color,
+                sky_color: space.physics().sky.overall_color(),
             },
             block_texture: &mut block_texture_allocator.texture,
             light_texture,
@@ -259,6 +305,7 @@ pub(super) struct SpaceRendererOutputData<'a, Backend: AicLumBackend> {
         CHUNK_SIZE,
     >,
     debug_chunk_boxes_tess: &'a Option<Tess<Backend, LinesVertex>>,
+    debug_chunk_state_tess: &'a Option<Tess<Backend, LinesVertex>>,
     view_chunk: ChunkPos<CHUNK_SIZE>,
     pub(super) update_info: SpaceUpdateInfo,
 
@@ -372,6 +419,18 @@ impl<'a, Backend: AicLumBackend> SpaceRendererBound<'a, Backend> {
                 },
             )?;
         }
+        if let Some(debug_tess) = self.data.debug_chunk_state_tess {
+            shading_gate.shade(
+                lines_program,
+                |ref mut program_iface, u, mut render_gate| {
+                    u.initialize(program_iface, self, Matrix4::identity());
+                    render_gate.render(&RenderState::default(), |mut tess_gate| {
+                        tess_gate.render(debug_tess)
+                    })?;
+                    Ok(())
+                },
+            )?;
+        }
 
         let start_transparent_draw_time = Instant::now();
         if self.data.camera.options().transparency.will_output_alpha() {
@@ -459,7 +518,7 @@ fn render_chunk_tess<Backend: AicLumBackend, E>(
     ordering: DepthOrdering,
 ) -> Result<usize, E> {
     let mut count = 0;
-    if let Some(tess) = &chunk.render_data {
+    if let Some(tess) = &chunk.render_data.tess {
         let range = match pass {
             SpaceRendererPass::Opaque => chunk.mesh().opaque_range(),
             SpaceRendererPass::Transparent => chunk.mesh().transparent_range(ordering),
@@ -479,7 +538,7 @@ fn render_chunk_tess<Backend: AicLumBackend, E>(
 fn update_chunk_tess<C>(
     context: &mut C,
     new_mesh: &SpaceMesh<LumBlockVertex, LumAtlasTile>,
-    tess_option: &mut ChunkData<C::Backend>,
+    tess_option: &mut Option<Tess<C::Backend, LumBlockVertex, u32>>,
 ) where
     C: GraphicsContext,
     C::Backend: AicLumBackend,
@@ -555,6 +614,13 @@ impl Listener<SpaceChange> for TodoListener {
                     SpaceChange::Block(..) => {}
                     SpaceChange::Number(..) => {}
                     SpaceChange::BlockValue(..) => {}
+                    SpaceChange::Physics => {
+                        // The sky color is fetched every frame, but the light physics
+                        // may have changed, invalidating all light data.
+                        todo.light = None;
+                    }
+                    SpaceChange::Spawn => {}
+                    SpaceChange::MoveCompleted(..) => {}
                 }
             }
         }
@@ -567,11 +633,17 @@ impl Listener<SpaceChange> for TodoListener {
 
 /// Keeps a 3D [`Texture`] up to date with the light data from a [`Space`].
 ///
-/// The texels are in [`PackedLight`] form.
+/// The texels are in [`PackedLight`] form. Each cube's light is stored at its
+/// coordinates modulo the texture size (see [`wrap_point`]), so the shader needs
+/// no offset and a region of the space may be updated in place without disturbing
+/// the rest of the texture.
 struct SpaceLightTexture<Backend: AicLumBackend> {
     texture: Texture<Backend, Dim3, NormRGBA8UI>,
     /// The region of cube coordinates for which there are valid texels.
     texture_grid: Grid,
+    /// Regions of `texture_grid` whose texels are out of date and have not yet been
+    /// uploaded because they were not visible; see [`Self::update_stale`].
+    stale: Vec<Grid>,
 }
 
 impl<Backend: AicLumBackend> SpaceLightTexture<Backend> {
@@ -599,35 +671,71 @@ impl<Backend: AicLumBackend> SpaceLightTexture<Backend> {
         Ok(Self {
             texture,
             texture_grid,
+            stale: Vec::new(),
         })
     }
 
     /// Copy the specified region of light data.
     pub fn update(&mut self, space: &Space, region: Grid) -> Result<(), TextureError> {
-        let mut data = Vec::with_capacity(region.volume());
-        // TODO: Enable circular operation and eliminate the need for the offset of the
-        // coordinates (texture_grid.lower_bounds() and light_offset in the shader)
-        // by doing a coordinate wrap-around -- the shader and the Space will agree
-        // on coordinates modulo the texture size, and this upload will need to be broken
-        // into up to 8 pieces.
-        for z in region.z_range() {
-            for y in region.y_range() {
-                for x in region.x_range() {
-                    data.push(space.get_lighting([x, y, z]).as_texel());
+        let texture_size = self.texture_grid.size();
+        let mut data = Vec::new();
+        for piece in wrap_region(region, texture_size) {
+            data.clear();
+            data.reserve(piece.volume());
+            for z in piece.z_range() {
+                for y in piece.y_range() {
+                    for x in piece.x_range() {
+                        data.push(space.get_lighting([x, y, z]).as_texel());
+                    }
                 }
             }
+            self.texture.upload_part(
+                wrap_point(piece.lower_bounds(), texture_size)
+                    .map(|s| s as u32)
+                    .into(),
+                piece.unsigned_size().into(),
+                TexelUpload::base_level(&data, 0),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Copy all light data, except that only chunks intersecting `visible_region` are
+    /// copied now and the rest are left for [`Self::update_stale`] to copy when they
+    /// become visible.
+    ///
+    /// Returns the number of cubes copied.
+    pub fn update_all(
+        &mut self,
+        space: &Space,
+        visible_region: Grid,
+    ) -> Result<usize, TextureError> {
+        if visible_region.contains_grid(self.texture_grid) {
+            // Everything is visible, so upload it all at once.
+            self.stale.clear();
+            self.update(space, self.texture_grid)?;
+            Ok(self.texture_grid.volume())
+        } else {
+            self.stale = self.texture_grid.chunks(CHUNK_SIZE).collect();
+            self.update_stale(space, visible_region)
         }
-        self.texture.upload_part(
-            (region.lower_bounds() - self.texture_grid.lower_bounds())
-                .map(|s| s as u32)
-                .into(),
-            region.unsigned_size().into(),
-            TexelUpload::base_level(&data, 0),
-        )
     }
 
-    pub fn update_all(&mut self, space: &Space) -> Result<(), TextureError> {
-        self.update(space, self.texture_grid)
+    /// Copy the light data of any chunks skipped by [`Self::update_all`] which
+    /// intersect `visible_region`.
+    ///
+    /// Returns the number of cubes copied.
+    pub fn update_stale(
+        &mut self,
+        space: &Space,
+        visible_region: Grid,
+    ) -> Result<usize, TextureError> {
+        let mut count = 0;
+        for chunk in take_visible_chunks(&mut self.stale, visible_region) {
+            self.update(space, chunk)?;
+            count += chunk.volume();
+        }
+        Ok(count)
     }
 
     fn bind<'a>(
@@ -636,17 +744,84 @@ impl<Backend: AicLumBackend> SpaceLightTexture<Backend> {
     ) -> Result<SpaceLightTextureBound<'a, Backend>, PipelineError> {
         Ok(SpaceLightTextureBound {
             texture: pipeline.bind_texture(&mut self.texture)?,
-            offset: -self.texture_grid.lower_bounds().to_vec(),
         })
     }
 }
 
 pub(crate) struct SpaceLightTextureBound<'a, Backend: AicLumBackend> {
     pub(crate) texture: BoundTexture<'a, Backend, Dim3, NormRGBA8UI>,
-    pub(crate) offset: Vector3<GridCoordinate>,
+}
+
+/// Computes the region of the light texture which must be up to date to draw what the
+/// camera can see, given the camera's [`Camera::view_frustum_aab`].
+///
+/// This includes one cube of margin, since the light of a block's neighbors is used to
+/// light its faces.
+fn visible_light_region(texture_grid: Grid, view_frustum_aab: Aab) -> Grid {
+    match view_frustum_aab.intersection(texture_grid.to_aab()) {
+        Some(aab) => aab
+            .round_up_to_grid()
+            .expand_clamped(FaceMap::repeat(1), texture_grid),
+        None => Grid::EMPTY,
+    }
+}
+
+/// Removes and returns the elements of `stale` which intersect `visible_region`.
+fn take_visible_chunks(stale: &mut Vec<Grid>, visible_region: Grid) -> Vec<Grid> {
+    let mut visible = Vec::new();
+    stale.retain(|&chunk| {
+        if chunk.intersection(visible_region).is_some() {
+            visible.push(chunk);
+            false
+        } else {
+            true
+        }
+    });
+    visible
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     // TODO: Arrange, somehow, to test the parts that need a GraphicsContext
+
+    #[test]
+    fn visible_light_region_clipped_and_expanded() {
+        let texture_grid = Grid::new([0, 0, 0], [64, 16, 64]);
+        assert_eq!(
+            visible_light_region(texture_grid, Aab::new(-10.0, 20.5, 2.5, 3.5, 4.0, 100.0)),
+            Grid::from_lower_upper([0, 1, 3], [22, 5, 64])
+        );
+        assert_eq!(
+            visible_light_region(texture_grid, Aab::new(100., 200., 0., 1., 0., 1.)),
+            Grid::EMPTY
+        );
+    }
+
+    #[test]
+    fn take_visible_chunks_only_takes_visible() {
+        let texture_grid = Grid::new([0, 0, 0], [48, 16, 16]);
+        let mut stale: Vec<Grid> = texture_grid.chunks(CHUNK_SIZE).collect();
+        let visible_region =
+            visible_light_region(texture_grid, Aab::new(0.0, 15.5, 0.0, 16.0, 0.0, 16.0));
+
+        // The margin reaches into the second chunk but not the third.
+        assert_eq!(
+            take_visible_chunks(&mut stale, visible_region),
+            vec![
+                Grid::new([0, 0, 0], [16, 16, 16]),
+                Grid::new([16, 0, 0], [16, 16, 16]),
+            ]
+        );
+        assert_eq!(stale, vec![Grid::new([32, 0, 0], [16, 16, 16])]);
+
+        // Chunks are only taken once.
+        assert_eq!(take_visible_chunks(&mut stale, visible_region), vec![]);
+        assert_eq!(
+            take_visible_chunks(&mut stale, texture_grid),
+            vec![Grid::new([32, 0, 0], [16, 16, 16])]
+        );
+        assert_eq!(stale, vec![]);
+    }
 }
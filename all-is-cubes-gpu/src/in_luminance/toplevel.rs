@@ -202,6 +202,12 @@ impl<Backend: AicLumBackend> EverythingRenderer<Backend> {
 
         // This updates camera matrices and graphics options
         self.cameras.update();
+        if !self.cameras.viewport_is_valid() {
+            return Ok(RenderInfo {
+                draw: DrawInfo::skipped_invalid_viewport(),
+                ..RenderInfo::default()
+            });
+        }
         let graphics_options = self.cameras.graphics_options();
 
         // Update full frame texture. (resize() implements do-nothing-if-equal so it's
@@ -400,6 +406,7 @@ impl<Backend: AicLumBackend> EverythingRenderer<Backend> {
                     ui: ui_draw_info,
                 },
                 submit_time: None,
+                skipped_invalid_viewport: false,
             },
         })
     }
@@ -125,6 +125,10 @@ pub enum VertexSemantics {
     /// Interpolated texture coordinates are clamped to be ≤ this value, to avoid bleeding.
     #[sem(name = "a_clamp_max", repr = "[f32; 3]", wrapper = "VertexClampHigh")]
     ClampHigh,
+    /// Factor by which the lighting is multiplied, from
+    /// [`BlockVertex::ambient_occlusion`].
+    #[sem(name = "a_ambient_occlusion", repr = "f32", wrapper = "VertexAmbientOcclusion")]
+    AmbientOcclusion,
 }
 
 /// Vertex type sent to shader for rendering blocks (and, for the moment, other geometry,
@@ -142,6 +146,7 @@ pub struct LumBlockVertex {
     color_or_texture: VertexColorOrTexture,
     clamp_min: VertexClampLow,
     clamp_max: VertexClampHigh,
+    ambient_occlusion: VertexAmbientOcclusion,
 }
 
 impl LumBlockVertex {
@@ -153,6 +158,7 @@ impl LumBlockVertex {
         color_or_texture: VertexColorOrTexture::new([0., 0., 0., 0.]),
         clamp_min: VertexClampLow::new([0., 0., 0.]),
         clamp_max: VertexClampHigh::new([0., 0., 0.]),
+        ambient_occlusion: VertexAmbientOcclusion::new(1.0),
     };
 
     /// Constructor taking our natural types instead of luminance specialized types.
@@ -169,6 +175,7 @@ impl LumBlockVertex {
             color_or_texture: VertexColorOrTexture::new(color.into()),
             clamp_min: VertexClampLow::new([0., 0., 0.]),
             clamp_max: VertexClampHigh::new([0., 0., 0.]),
+            ambient_occlusion: VertexAmbientOcclusion::new(1.0),
         }
     }
 
@@ -193,6 +200,7 @@ impl LumBlockVertex {
             color_or_texture: VertexColorOrTexture::new(t.extend(-1.0).into()),
             clamp_min: VertexClampLow::new([0., 0., 0.]),
             clamp_max: VertexClampHigh::new([0., 0., 0.]),
+            ambient_occlusion: VertexAmbientOcclusion::new(1.0),
         };
         Box::new([
             v(origin, tex_origin),
@@ -211,6 +219,7 @@ impl From<BlockVertex> for LumBlockVertex {
         let position = vertex.position.cast::<f32>().unwrap().to_vec();
         let cube = VertexCube::new([0., 0., 0.]);
         let normal = VertexNormal::new(vertex.face.normal_vector::<f32>().into());
+        let ambient_occlusion = VertexAmbientOcclusion::new(vertex.ambient_occlusion);
         match vertex.coloring {
             Coloring::Solid(color) => {
                 let mut color_attribute = VertexColorOrTexture::new(color.into());
@@ -224,6 +233,7 @@ impl From<BlockVertex> for LumBlockVertex {
                     color_or_texture: color_attribute,
                     clamp_min: VertexClampLow::new([0., 0., 0.]),
                     clamp_max: VertexClampHigh::new([0., 0., 0.]),
+                    ambient_occlusion,
                 }
             }
             Coloring::Texture {
@@ -237,6 +247,7 @@ impl From<BlockVertex> for LumBlockVertex {
                 color_or_texture: VertexColorOrTexture::new([tc[0], tc[1], tc[2], -1.0]),
                 clamp_min: VertexClampLow::new(clamp_min.into()),
                 clamp_max: VertexClampHigh::new(clamp_max.into()),
+                ambient_occlusion,
             },
         }
     }
@@ -253,11 +264,17 @@ impl GfxVertex for LumBlockVertex {
     }
 
     #[inline]
-    fn instantiate_vertex(&mut self, cube: Self::BlockInst, _lighting: PackedLight) {
+    fn instantiate_vertex(
+        &mut self,
+        cube: Self::BlockInst,
+        _lighting: PackedLight,
+        ambient_occlusion: f32,
+    ) {
         self.position.repr[0] += cube.x;
         self.position.repr[1] += cube.y;
         self.position.repr[2] += cube.z;
         self.cube.repr = cube.into();
+        self.ambient_occlusion.repr = ambient_occlusion;
     }
 
     #[inline]
@@ -330,15 +347,18 @@ mod tests {
             position: Point3::new(1.0, 2.1, 3.0),
             face: Face6::PX,
             coloring: Coloring::Solid(Rgba::new(7.0, 8.0, 9.0, 0.5)),
+            ambient_occlusion: 1.0,
         };
         let mut vertex = LumBlockVertex::from(block_vertex);
         vertex.instantiate_vertex(
             LumBlockVertex::instantiate_block(Point3::new(10, 20, 30)),
             Rgb::new(1.0, 0.0, 2.0).into(),
+            0.5,
         );
         assert_eq!(vertex.position.repr, [11., 22.1, 33.]);
         assert_eq!(vertex.cube.repr, [10., 20., 30.]);
         assert_eq!(vertex.normal.repr, [1.0, 0.0, 0.0]);
         assert_eq!(vertex.color_or_texture.repr, [7.0, 8.0, 9.0, 0.5]);
+        assert_eq!(vertex.ambient_occlusion.repr, 0.5);
     }
 }
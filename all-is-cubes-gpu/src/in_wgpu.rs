@@ -13,7 +13,7 @@ use instant::Instant;
 use once_cell::sync::Lazy;
 
 use all_is_cubes::apps::{Layers, StandardCameras};
-use all_is_cubes::camera::info_text_drawable;
+use all_is_cubes::camera::{info_text_drawable, AntialiasingOption, GraphicsOptions};
 use all_is_cubes::cgmath::Vector2;
 use all_is_cubes::character::Cursor;
 use all_is_cubes::content::palette;
@@ -41,6 +41,7 @@ mod camera;
 mod frame_texture;
 use frame_texture::DrawableTexture;
 mod glue;
+pub mod headless;
 mod pipelines;
 mod space;
 use space::SpaceRenderer;
@@ -121,6 +122,14 @@ impl SurfaceRenderer {
             &FrameBudget::SIXTY_FPS, // TODO: figure out what we're vsyncing to, instead
         )?;
 
+        if !self.everything.cameras.viewport_is_valid() {
+            // Nothing can be drawn, and wgpu would reject a zero-sized surface.
+            return Ok(RenderInfo {
+                update: update_info,
+                draw: DrawInfo::skipped_invalid_viewport(),
+            });
+        }
+
         if self.viewport_dirty.get_and_clear() {
             // Test because wgpu insists on nonzero values -- we'd rather be inconsistent
             // than crash.
@@ -169,10 +178,20 @@ pub struct EverythingRenderer {
     linear_scene_texture: wgpu::Texture,
     linear_scene_texture_view: wgpu::TextureView,
     linear_scene_texture_format: wgpu::TextureFormat,
-    /// Depth texture to pair with `linear_scene_texture`.
+    /// If multisampling is enabled, the texture which geometry is actually drawn into,
+    /// which is then resolved into `linear_scene_texture`.
+    multisampled_scene_texture: Option<wgpu::Texture>,
+    multisampled_scene_texture_view: Option<wgpu::TextureView>,
+    /// Depth texture to pair with `linear_scene_texture` (or
+    /// `multisampled_scene_texture`, if present).
     depth_texture: wgpu::Texture,
     depth_texture_view: wgpu::TextureView,
 
+    /// Whether the adapter supports multisampling our scene and depth texture formats.
+    msaa_supported: bool,
+    /// Multisample count of the current scene textures and pipelines.
+    sample_count: u32,
+
     /// Pipelines and layouts for rendering Space content
     pipelines: Pipelines,
 
@@ -246,8 +265,19 @@ impl EverythingRenderer {
             wgpu::TextureFormat::Rgba8UnormSrgb
         };
 
-        let (linear_scene_texture, depth_texture) =
-            create_fb_textures(&device, &config, linear_scene_texture_format);
+        let msaa_supported =
+            [linear_scene_texture_format, DEPTH_FORMAT]
+                .into_iter()
+                .all(|format| {
+                    adapter.get_texture_format_features(format).flags.contains(
+                        wgpu::TextureFormatFeatureFlags::MULTISAMPLE
+                            | wgpu::TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE,
+                    )
+                });
+        let sample_count = msaa_sample_count(cameras.graphics_options(), msaa_supported);
+
+        let (linear_scene_texture, multisampled_scene_texture, depth_texture) =
+            create_fb_textures(&device, &config, linear_scene_texture_format, sample_count);
 
         let postprocess_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -296,7 +326,7 @@ impl EverythingRenderer {
                 label: Some("EverythingRenderer::postprocess_bind_group_layout"),
             });
 
-        let pipelines = Pipelines::new(&device, linear_scene_texture_format);
+        let pipelines = Pipelines::new(&device, linear_scene_texture_format, sample_count);
 
         let mut new_self = EverythingRenderer {
             staging_belt: wgpu::util::StagingBelt::new(
@@ -308,8 +338,14 @@ impl EverythingRenderer {
             linear_scene_texture_view: linear_scene_texture
                 .create_view(&wgpu::TextureViewDescriptor::default()),
             linear_scene_texture,
+            multisampled_scene_texture_view: multisampled_scene_texture
+                .as_ref()
+                .map(|texture| texture.create_view(&Default::default())),
+            multisampled_scene_texture,
             depth_texture_view: depth_texture.create_view(&Default::default()),
             depth_texture,
+            msaa_supported,
+            sample_count,
 
             space_renderers: Default::default(),
 
@@ -427,25 +463,42 @@ impl EverythingRenderer {
         // or copy to the GPU.
         self.cameras.update();
 
-        // Update viewport-sized resources from viewport.
+        // Update viewport-sized resources from viewport and graphics options.
         {
             let viewport = self.cameras.viewport();
             let size = viewport.framebuffer_size;
             let previous_size = Vector2::new(self.config.width, self.config.height);
+            let sample_count =
+                msaa_sample_count(self.cameras.graphics_options(), self.msaa_supported);
             // wgpu insists on nonzero values, so if we get a zero, ignore it
-            if size != previous_size && size.x != 0 && size.y != 0 {
+            let resized = size != previous_size && size.x != 0 && size.y != 0;
+            if resized {
                 self.config.width = size.x;
                 self.config.height = size.y;
-
-                (self.linear_scene_texture, self.depth_texture) = create_fb_textures(
+            }
+            if resized || sample_count != self.sample_count {
+                self.sample_count = sample_count;
+
+                (
+                    self.linear_scene_texture,
+                    self.multisampled_scene_texture,
+                    self.depth_texture,
+                ) = create_fb_textures(
                     &self.device,
                     &self.config,
                     self.linear_scene_texture_format,
+                    sample_count,
                 );
                 self.linear_scene_texture_view =
                     self.linear_scene_texture.create_view(&Default::default());
+                self.multisampled_scene_texture_view = self
+                    .multisampled_scene_texture
+                    .as_ref()
+                    .map(|texture| texture.create_view(&Default::default()));
                 self.depth_texture_view = self.depth_texture.create_view(&Default::default());
-
+                self.postprocess_bind_group = None;
+            }
+            if resized {
                 self.info_text_texture.resize(
                     &self.device,
                     Some("info_text_texture"),
@@ -465,8 +518,11 @@ impl EverythingRenderer {
                 self.config.format,
             );
         }
-        self.pipelines
-            .recompile_if_changed(&self.device, self.config.format);
+        self.pipelines.recompile_if_changed(
+            &self.device,
+            self.linear_scene_texture_format,
+            self.sample_count,
+        );
 
         // Identify spaces to be rendered
         let ws = self.cameras.world_space().snapshot(); // TODO: ugly
@@ -610,7 +666,12 @@ impl EverythingRenderer {
         &mut self,
         queue: &wgpu::Queue,
     ) -> Result<DrawInfo, GraphicsResourceError> {
-        let output_view = &self.linear_scene_texture_view;
+        // If multisampling, draw into the multisampled texture and resolve into
+        // linear_scene_texture at the end of each pass.
+        let (output_view, resolve_target) = match &self.multisampled_scene_texture_view {
+            Some(view) => (view, Some(&self.linear_scene_texture_view)),
+            None => (&self.linear_scene_texture_view, None),
+        };
         let depth_texture_view = &self.depth_texture_view;
         let mut encoder = self
             .device
@@ -628,6 +689,7 @@ impl EverythingRenderer {
             let camera = &self.cameras.cameras().world;
             sr.draw(
                 output_view,
+                resolve_target,
                 depth_texture_view,
                 queue,
                 &mut encoder,
@@ -652,7 +714,7 @@ impl EverythingRenderer {
                 label: Some("debug lines"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                     view: output_view,
-                    resolve_target: None,
+                    resolve_target,
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Load,
                         store: true,
@@ -683,6 +745,7 @@ impl EverythingRenderer {
         let ui_draw_info = if let Some(sr) = &self.space_renderers.ui {
             sr.draw(
                 output_view,
+                resolve_target,
                 depth_texture_view,
                 queue,
                 &mut encoder,
@@ -723,6 +786,7 @@ impl EverythingRenderer {
                 ui: ui_draw_info,
             },
             submit_time: Some(end_time.duration_since(ui_to_submit_time)), // also counting recall()
+            skipped_invalid_viewport: false,
         })
     }
 
@@ -813,37 +877,58 @@ impl EverythingRenderer {
 const LINEAR_SCENE_TEXTURE_USAGES: wgpu::TextureUsages =
     wgpu::TextureUsages::RENDER_ATTACHMENT.union(wgpu::TextureUsages::TEXTURE_BINDING);
 
-/// Create linear color texture and depth texture.
+/// Choose the multisample count to use for the given options.
+///
+/// wgpu only guarantees support for a count of 4 (if the format supports multisampling
+/// at all), so any request for multisampling gets that rather than the exact count.
+fn msaa_sample_count(options: &GraphicsOptions, msaa_supported: bool) -> u32 {
+    match options.antialiasing {
+        AntialiasingOption::Msaa { samples } if samples > 1 && msaa_supported => 4,
+        _ => 1,
+    }
+}
+
+/// Create linear color texture, multisampled color texture if `sample_count` is
+/// greater than 1, and depth texture.
 ///
 /// `config` must be valid (in particular, not zero sized).
 fn create_fb_textures(
     device: &wgpu::Device,
     config: &wgpu::SurfaceConfiguration,
     scene_texture_format: wgpu::TextureFormat,
-) -> (wgpu::Texture, wgpu::Texture) {
+    sample_count: u32,
+) -> (wgpu::Texture, Option<wgpu::Texture>, wgpu::Texture) {
+    let size = wgpu::Extent3d {
+        width: config.width,
+        height: config.height,
+        depth_or_array_layers: 1,
+    };
     (
         device.create_texture(&wgpu::TextureDescriptor {
             label: Some("EverythingRenderer::linear_scene_texture"),
-            size: wgpu::Extent3d {
-                width: config.width,
-                height: config.height,
-                depth_or_array_layers: 1,
-            },
+            size,
             mip_level_count: 1,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: scene_texture_format,
             usage: LINEAR_SCENE_TEXTURE_USAGES,
         }),
+        (sample_count > 1).then(|| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("EverythingRenderer::multisampled_scene_texture"),
+                size,
+                mip_level_count: 1,
+                sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format: scene_texture_format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            })
+        }),
         device.create_texture(&wgpu::TextureDescriptor {
             label: Some("EverythingRenderer::depth_texture"),
-            size: wgpu::Extent3d {
-                width: config.width,
-                height: config.height,
-                depth_or_array_layers: 1,
-            },
+            size,
             mip_level_count: 1,
-            sample_count: 1,
+            sample_count,
             dimension: wgpu::TextureDimension::D2,
             format: DEPTH_FORMAT,
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
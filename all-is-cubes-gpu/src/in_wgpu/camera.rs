@@ -22,14 +22,10 @@ pub(crate) struct ShaderSpaceCamera {
     /// in volumetric rendering.
     view_position: PaddedVec3,
 
-    /// Translation which converts mesh cube coordinates to `light_texture` texel
-    /// coordinates.
-    ///
-    /// This is not strictly part of the [`Camera`], but it is expected to change under
-    /// the same sort of conditions.
-    light_lookup_offset: [i32; 3], // next field functions as the required 4th component/padding
     /// Light rendering style to use; a copy of [`GraphicsOptions::lighting_display`].
     light_option: i32,
+    /// pad out to vec4<i32>
+    _padding_light: [i32; 3],
 
     /// Color for the fog.
     fog_color: [f32; 3],
@@ -46,9 +42,9 @@ pub(crate) struct ShaderSpaceCamera {
 }
 
 impl ShaderSpaceCamera {
-    pub fn new(camera: &Camera, sky_color: Rgb, light_lookup_offset: Vector3<i32>) -> Self {
+    pub fn new(camera: &Camera, sky_color: Rgb) -> Self {
         let options = camera.options();
-        let view_distance = camera.view_distance() as f32;
+        let view_distance = (camera.view_distance() * options.fog_distance.into_inner()) as f32;
         let (fog_mode_blend, fog_distance) = match options.fog {
             FogOption::Abrupt => (1.0, view_distance),
             FogOption::Compromise => (0.5, view_distance),
@@ -61,7 +57,6 @@ impl ShaderSpaceCamera {
             view_matrix: convert_matrix(camera.view_matrix()),
             view_position: camera.view_position().map(|s| s as f32).to_vec().into(),
 
-            light_lookup_offset: light_lookup_offset.into(),
             light_option: match options.lighting_display {
                 LightingOption::None => 0,
                 LightingOption::Flat => 1,
@@ -71,6 +66,7 @@ impl ShaderSpaceCamera {
                     options.lighting_display
                 ),
             },
+            _padding_light: Default::default(),
 
             fog_color: Vector3::<f32>::from(sky_color).into(),
             fog_mode_blend,
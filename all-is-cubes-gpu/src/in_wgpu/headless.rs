@@ -0,0 +1,270 @@
+// Copyright 2020-2022 Kevin Reid under the terms of the MIT License as detailed
+// in the accompanying file README.md or <https://opensource.org/licenses/MIT>.
+
+//! Rendering to in-memory images, as an implementation of [`HeadlessRenderer`], using
+//! the same [`EverythingRenderer`] as is used for drawing to a window.
+
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+
+use futures_core::future::BoxFuture;
+use image::RgbaImage;
+
+use all_is_cubes::apps::StandardCameras;
+use all_is_cubes::camera::{HeadlessRenderer, RenderError, Viewport};
+use all_is_cubes::character::Cursor;
+use all_is_cubes::listen::{DirtyFlag, ListenableSource};
+
+use crate::in_wgpu::EverythingRenderer;
+use crate::FrameBudget;
+
+/// Format of the texture that is drawn to and then copied out as the image.
+const COLOR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// Creates [`Renderer`]s, which all share one [`wgpu::Device`].
+#[derive(Clone, Debug)]
+pub struct Builder {
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    adapter: Arc<wgpu::Adapter>,
+}
+
+impl Builder {
+    /// Creates a [`Builder`] using a new [`wgpu::Device`] obtained from `adapter`.
+    pub async fn from_adapter(
+        adapter: Arc<wgpu::Adapter>,
+    ) -> Result<Self, wgpu::RequestDeviceError> {
+        let (device, queue) = adapter
+            .request_device(&EverythingRenderer::device_descriptor(), None)
+            .await?;
+        Ok(Self {
+            device: Arc::new(device),
+            queue: Arc::new(queue),
+            adapter,
+        })
+    }
+
+    /// Creates a [`Renderer`] which draws the scene specified by `cameras`, at the size
+    /// given by its viewport.
+    pub fn build(&self, cameras: StandardCameras) -> Renderer {
+        let viewport_source = cameras.viewport_source();
+        let everything =
+            EverythingRenderer::new(self.device.clone(), cameras, COLOR_FORMAT, &self.adapter);
+
+        let viewport_dirty = DirtyFlag::listening(false, |l| viewport_source.listen(l));
+        let viewport = viewport_source.snapshot();
+        let color_texture = create_color_texture(&self.device, viewport);
+
+        Renderer {
+            device: self.device.clone(),
+            queue: self.queue.clone(),
+            color_texture,
+            everything,
+            viewport_source,
+            viewport_dirty,
+        }
+    }
+}
+
+/// A [`HeadlessRenderer`] which uses the GPU, producing the same images as would be
+/// displayed in a window.
+///
+/// To create one, use a [`Builder`].
+///
+/// TODO: Reading back the image blocks on [`wgpu::Device::poll()`], which is not
+/// possible on the web.
+#[allow(missing_debug_implementations)] // EverythingRenderer isn't Debug
+pub struct Renderer {
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    color_texture: wgpu::Texture,
+    everything: EverythingRenderer,
+    viewport_source: ListenableSource<Viewport>,
+    viewport_dirty: DirtyFlag,
+}
+
+impl HeadlessRenderer for Renderer {
+    fn update<'a>(
+        &'a mut self,
+        cursor: Option<&'a Cursor>,
+    ) -> BoxFuture<'a, Result<(), RenderError>> {
+        Box::pin(async move {
+            let _uinfo = self
+                .everything
+                .update(&self.queue, cursor, &FrameBudget::PRACTICALLY_INFINITE)
+                .unwrap();
+            // TODO: report RenderError::Read rather than panicking, when applicable
+            Ok(())
+        })
+    }
+
+    fn draw<'a>(&'a mut self, info_text: &'a str) -> BoxFuture<'a, Result<RgbaImage, RenderError>> {
+        let viewport = self.viewport_source.snapshot();
+        if self.viewport_dirty.get_and_clear() {
+            self.color_texture = create_color_texture(&self.device, viewport);
+        }
+
+        Box::pin(async move {
+            let _dinfo = self.everything.draw_frame_linear(&self.queue).unwrap();
+            self.everything.add_info_text_and_postprocess(
+                &self.queue,
+                &self.color_texture,
+                info_text,
+            );
+            Ok(get_pixels_from_gpu(
+                &self.device,
+                &self.queue,
+                &self.color_texture,
+                viewport,
+            ))
+        })
+    }
+}
+
+fn create_color_texture(device: &wgpu::Device, viewport: Viewport) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("headless::Renderer::color_texture"),
+        size: wgpu::Extent3d {
+            width: viewport.framebuffer_size.x.max(1),
+            height: viewport.framebuffer_size.y.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: COLOR_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+    })
+}
+
+/// Copies the contents of `fb_texture`, which must be in [`COLOR_FORMAT`] and at least
+/// as large as the viewport, into an image.
+fn get_pixels_from_gpu(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    fb_texture: &wgpu::Texture,
+    viewport: Viewport,
+) -> RgbaImage {
+    let size = viewport.framebuffer_size;
+    if size.x == 0 || size.y == 0 {
+        return RgbaImage::new(size.x, size.y);
+    }
+
+    // Texture-to-buffer copies must have rows aligned to COPY_BYTES_PER_ROW_ALIGNMENT,
+    // so the buffer may have padding we need to remove.
+    let unpadded_bytes_per_row = size.x * 4;
+    let padded_bytes_per_row = {
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        (unpadded_bytes_per_row + align - 1) / align * align
+    };
+
+    let temp_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("headless::Renderer image copy buffer"),
+        size: u64::from(padded_bytes_per_row) * u64::from(size.y),
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: fb_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &temp_buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(padded_bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+        );
+        queue.submit(Some(encoder.finish()));
+    }
+
+    let map_result = Arc::new(Mutex::new(None));
+    {
+        let map_result = map_result.clone();
+        temp_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                *map_result.lock().unwrap() = Some(result);
+            });
+    }
+    device.poll(wgpu::Maintain::Wait); // TODO: poll in the background instead of blocking
+    map_result
+        .lock()
+        .unwrap()
+        .take()
+        .expect("buffer mapping did not complete")
+        .expect("buffer reading failed");
+
+    let bytes: Vec<u8> = temp_buffer
+        .slice(..)
+        .get_mapped_range()
+        .chunks_exact(padded_bytes_per_row as usize)
+        .flat_map(|row| &row[..unpadded_bytes_per_row as usize])
+        .copied()
+        .collect();
+
+    RgbaImage::from_raw(size.x, size.y, bytes).expect("image copy buffer was incorrectly sized")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use all_is_cubes::apps::Layers;
+    use all_is_cubes::camera::GraphicsOptions;
+    use all_is_cubes::cgmath::Vector2;
+    use futures_executor::block_on;
+
+    /// Renders a frame, if a GPU (or software renderer) is available.
+    #[test]
+    fn draw_produces_image_of_viewport_size() {
+        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let adapter = match block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            compatible_surface: None,
+            force_fallback_adapter: false,
+        })) {
+            Some(adapter) => adapter,
+            None => {
+                eprintln!("Skipping headless rendering test due to lack of wgpu::Adapter.");
+                return;
+            }
+        };
+        let builder = block_on(Builder::from_adapter(Arc::new(adapter))).unwrap();
+
+        // Deliberately not a multiple of the row alignment.
+        let viewport = Viewport {
+            nominal_size: Vector2::new(100.0, 30.0),
+            framebuffer_size: Vector2::new(100, 30),
+        };
+        let cameras = StandardCameras::new(
+            ListenableSource::constant(GraphicsOptions::default()),
+            ListenableSource::constant(viewport),
+            ListenableSource::constant(None),
+            ListenableSource::constant(None),
+            Layers::default(),
+        )
+        .unwrap();
+        let mut renderer = builder.build(cameras);
+
+        block_on(renderer.update(None)).unwrap();
+        let image = block_on(renderer.draw("")).unwrap();
+        assert_eq!(image.dimensions(), (100, 30));
+        assert!(
+            image.pixels().any(|pixel| pixel.0 != [0, 0, 0, 0]),
+            "image is blank"
+        );
+    }
+}
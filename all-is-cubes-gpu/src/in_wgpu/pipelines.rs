@@ -13,8 +13,8 @@ use crate::reloadable::{reloadable_str, Reloadable};
 /// Resources needed for rendering that aren't actually specific to any content and so
 /// don't need to be modified under normal circumstances.
 ///
-/// (They are, however, replaced in the event of a shader edit and hot-reload, and in
-/// the future we might decide that some graphics options changes affect this too.)
+/// (They are, however, replaced in the event of a shader edit and hot-reload, or a
+/// change of the multisample count.)
 ///
 /// Design note: This was originally intended as a shared component for SpaceRenderer,
 /// but it turned out that we want to reuse the same shader module and camera buffer for
@@ -25,6 +25,10 @@ pub(crate) struct Pipelines {
     /// Tracks whether we need to reload shaders from disk.
     shader_dirty: DirtyFlag,
 
+    /// Multisample count the pipelines were created with, which must match the
+    /// render targets they are used with.
+    sample_count: u32,
+
     /// Layout for the camera buffer.
     pub(crate) camera_bind_group_layout: wgpu::BindGroupLayout,
 
@@ -46,7 +50,11 @@ static BLOCKS_AND_LINES_SHADER: Lazy<Reloadable> =
 
 impl Pipelines {
     // TODO: wants graphics options to configure shader?
-    pub fn new(device: &wgpu::Device, surface_format: wgpu::TextureFormat) -> Self {
+    pub fn new(
+        device: &wgpu::Device,
+        surface_format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Self {
         let shader = create_wgsl_module_from_reloadable(
             device,
             "blocks-and-lines",
@@ -108,6 +116,11 @@ impl Pipelines {
                 push_constant_ranges: &[],
             });
 
+        let multisample = wgpu::MultisampleState {
+            count: sample_count,
+            ..wgpu::MultisampleState::default()
+        };
+
         // Parts of the render pipeline shared between opaque and transparent passes
         let block_primitive_state = wgpu::PrimitiveState {
             topology: wgpu::PrimitiveTopology::TriangleList,
@@ -145,7 +158,7 @@ impl Pipelines {
                     stencil: wgpu::StencilState::default(),
                     bias: wgpu::DepthBiasState::default(),
                 }),
-                multisample: wgpu::MultisampleState::default(), // default = off
+                multisample,
                 multiview: None,
             });
 
@@ -189,7 +202,7 @@ impl Pipelines {
                     stencil: wgpu::StencilState::default(),
                     bias: wgpu::DepthBiasState::default(),
                 }),
-                multisample: wgpu::MultisampleState::default(), // default = off
+                multisample,
                 multiview: None,
             });
 
@@ -229,7 +242,7 @@ impl Pipelines {
                     stencil: wgpu::StencilState::default(),
                     bias: wgpu::DepthBiasState::default(),
                 }),
-                multisample: wgpu::MultisampleState::default(), // default = off
+                multisample,
                 multiview: None,
             });
 
@@ -237,6 +250,7 @@ impl Pipelines {
             shader_dirty: DirtyFlag::listening(false, |l| {
                 BLOCKS_AND_LINES_SHADER.as_source().listen(l)
             }),
+            sample_count,
             camera_bind_group_layout,
             space_texture_bind_group_layout,
             opaque_render_pipeline,
@@ -250,10 +264,11 @@ impl Pipelines {
         &mut self,
         device: &wgpu::Device,
         surface_format: wgpu::TextureFormat,
+        sample_count: u32,
     ) {
-        if self.shader_dirty.get_and_clear() {
+        if self.shader_dirty.get_and_clear() || sample_count != self.sample_count {
             // TODO: slightly less efficient than it could be since it rebuilds the layouts too
-            *self = Self::new(device, surface_format);
+            *self = Self::new(device, surface_format, sample_count);
         }
     }
 }
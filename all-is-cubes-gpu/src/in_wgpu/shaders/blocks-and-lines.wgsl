@@ -11,7 +11,7 @@ struct ShaderSpaceCamera {
     @location(0) projection: mat4x4<f32>,
     @location(1) view_matrix: mat4x4<f32>,
     @location(2) view_position: vec3<f32>,
-    @location(3) light_lookup_offset_and_option: vec4<i32>, // vec3 + 1
+    @location(3) light_option: vec4<i32>, // 1 + unused padding
     @location(4) fog_color_and_fog_mode_blend: vec4<f32>, // vec3 + 1
     @location(5) fog_distance_and_exposure: vec4<f32>, // last two components unused
 };
@@ -20,7 +20,7 @@ struct ShaderSpaceCamera {
 struct WgpuBlockVertex {
     @location(0) position: vec3<f32>,
     @location(1) cube: vec3<f32>,
-    @location(2) normal_face: u32,
+    @location(2) normal_face_and_ao: u32, // face in low 8 bits, then ambient occlusion
     @location(3) color_or_texture: vec4<f32>,
     @location(4) clamp_min: vec3<f32>,
     @location(5) clamp_max: vec3<f32>,
@@ -94,6 +94,7 @@ struct BlockFragmentInput {
     @location(4) clamp_min: vec3<f32>,
     @location(5) clamp_max: vec3<f32>,
     @location(6) fog_mix: f32,
+    @location(7) ambient_occlusion: f32,
 };
 
 @vertex
@@ -101,7 +102,7 @@ fn block_vertex_main(
     input: WgpuBlockVertex,
 ) -> BlockFragmentInput {
     var normal = vec3<f32>(1.0);
-    switch input.normal_face {
+    switch input.normal_face_and_ao & 0xFFu {
         case 1u { normal = vec3<f32>(-1.0, 0.0, 0.0); }
         case 2u { normal = vec3<f32>(0.0, -1.0, 0.0); }
         case 3u { normal = vec3<f32>(0.0, 0.0, -1.0); }
@@ -120,6 +121,7 @@ fn block_vertex_main(
         input.clamp_min,
         input.clamp_max,
         compute_fog(input.position),
+        f32((input.normal_face_and_ao >> 8u) & 0xFFu) / 255.0,
     );
 }
 
@@ -143,11 +145,11 @@ fn modulo(a: f32, b: f32) -> f32 {
 // excluding opaque blocks, while the -1 value indicates values that should be
 // truly ignored.
 fn light_texture_fetch(fragment_position: vec3<f32>) -> vec4<f32> {
-    var lookup_position = vec3<i32>(floor(fragment_position)) + camera.light_lookup_offset_and_option.xyz;
+    var lookup_position = vec3<i32>(floor(fragment_position));
     
     // Implement wrapping (not automatic since we're not using a sampler).
-    // Wrapping is used to handle sky light and in the future will be used for
-    // circular buffering of the local light in an unbounded world.
+    // The texture stores each cube at its coordinates modulo the texture size,
+    // and wrapping also provides sky light for cubes just outside the space.
     let size: vec3<i32> = textureDimensions(light_texture, 0);
     lookup_position = (lookup_position % size + size) % size;
 
@@ -279,7 +281,7 @@ fn interpolated_space_light(in: BlockFragmentInput) -> vec3<f32> {
 
 // Compute light intensity applying to the fragment.
 fn lighting(in: BlockFragmentInput) -> vec3<f32> {
-    switch camera.light_lookup_offset_and_option.w {
+    switch camera.light_option.x {
         // LightingOption::None or fallback: no lighting
         default {
             return vec3<f32>(1.0);
@@ -319,7 +321,7 @@ fn block_fragment_opaque(in: BlockFragmentInput) -> @location(0) vec4<f32> {
     let diffuse_color: vec4<f32> = get_diffuse_color(in);
     
     // Lighting
-    let lit_color = diffuse_color * vec4<f32>(lighting(in), 1.0);
+    let lit_color = diffuse_color * vec4<f32>(lighting(in) * in.ambient_occlusion, 1.0);
 
     // Fog
     let fogged_color = vec4<f32>(mix(lit_color.rgb, camera.fog_color_and_fog_mode_blend.rgb, in.fog_mix), lit_color.a);
@@ -335,7 +337,7 @@ fn block_fragment_transparent(in: BlockFragmentInput) -> @location(0) vec4<f32>
     let diffuse_color: vec4<f32> = get_diffuse_color(in);
     
     // Lighting
-    let lit_color = diffuse_color * vec4<f32>(lighting(in), 1.0);
+    let lit_color = diffuse_color * vec4<f32>(lighting(in) * in.ambient_occlusion, 1.0);
 
     // Fog
     let fogged_color = vec4<f32>(mix(lit_color.rgb, camera.fog_color_and_fog_mode_blend.rgb, in.fog_mix), lit_color.a);
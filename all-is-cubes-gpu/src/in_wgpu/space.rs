@@ -7,7 +7,6 @@ use std::cell::RefCell;
 use std::collections::HashSet;
 use std::sync::{Arc, Mutex, Weak};
 
-use all_is_cubes::cgmath::{EuclideanSpace, Vector3};
 use instant::Instant;
 
 use all_is_cubes::camera::Camera;
@@ -27,7 +26,7 @@ use crate::in_wgpu::{
     glue::{to_wgpu_index_range, BeltWritingParts, ResizingBuffer},
     vertex::WgpuBlockVertex,
 };
-use crate::{GraphicsResourceError, SpaceDrawInfo, SpaceUpdateInfo};
+use crate::{wrap_point, wrap_region, GraphicsResourceError, SpaceDrawInfo, SpaceUpdateInfo};
 
 const CHUNK_SIZE: GridCoordinate = 16;
 
@@ -43,7 +42,7 @@ pub(crate) struct SpaceRenderer {
     /// Note that `self.csm` has its own todo listener too.
     todo: Arc<Mutex<SpaceRendererTodo>>,
 
-    /// Cached copy of `space.physics.sky_color`.
+    /// Cached copy of `space.physics.sky.overall_color()`.
     pub(crate) sky_color: Rgb,
 
     block_texture: AtlasAllocator,
@@ -123,7 +122,7 @@ impl SpaceRenderer {
         Ok(SpaceRenderer {
             todo: todo_rc,
             render_pass_label: format!("{space_label} render_pass"),
-            sky_color: space_borrowed.physics().sky_color,
+            sky_color: space_borrowed.physics().sky.overall_color(),
             block_texture,
             light_texture,
             space_bind_group,
@@ -158,7 +157,7 @@ impl SpaceRenderer {
             .expect("TODO: return a trivial result instead of panic.");
 
         // Update sky color (cheap so we don't bother todo-tracking it)
-        self.sky_color = space.physics().sky_color;
+        self.sky_color = space.physics().sky.overall_color();
 
         // Update light texture
         let start_light_update = Instant::now();
@@ -232,6 +231,7 @@ impl SpaceRenderer {
     pub fn draw(
         &self,
         output_view: &wgpu::TextureView,
+        resolve_target: Option<&wgpu::TextureView>,
         depth_texture_view: &wgpu::TextureView,
         queue: &wgpu::Queue,
         encoder: &mut wgpu::CommandEncoder,
@@ -253,7 +253,6 @@ impl SpaceRenderer {
             bytemuck::cast_slice::<ShaderSpaceCamera, u8>(&[ShaderSpaceCamera::new(
                 camera,
                 self.sky_color,
-                self.light_texture.light_lookup_offset(),
             )]),
         );
 
@@ -261,7 +260,7 @@ impl SpaceRenderer {
             label: Some(&self.render_pass_label),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                 view: output_view,
-                resolve_target: None,
+                resolve_target,
                 ops: wgpu::Operations {
                     load: color_load_op,
                     store: true,
@@ -441,6 +440,13 @@ impl Listener<SpaceChange> for TodoListener {
                     SpaceChange::Block(..) => {}
                     SpaceChange::Number(..) => {}
                     SpaceChange::BlockValue(..) => {}
+                    SpaceChange::Physics => {
+                        // The sky color is fetched every frame, but the light physics
+                        // may have changed, invalidating all light data.
+                        todo.light = None;
+                    }
+                    SpaceChange::Spawn => {}
+                    SpaceChange::MoveCompleted(..) => {}
                 }
             }
         }
@@ -453,7 +459,10 @@ impl Listener<SpaceChange> for TodoListener {
 
 /// Keeps a 3D [`Texture`] up to date with the light data from a [`Space`].
 ///
-/// The texels are in [`PackedLight`] form.
+/// The texels are in [`PackedLight`] form. Each cube's light is stored at its
+/// coordinates modulo the texture size (see [`wrap_point`]), so the shader needs
+/// no offset and a region of the space may be updated in place without disturbing
+/// the rest of the texture.
 #[derive(Debug)]
 struct SpaceLightTexture {
     texture: wgpu::Texture,
@@ -494,26 +503,26 @@ impl SpaceLightTexture {
 
     /// Copy the specified region of light data.
     pub fn update(&mut self, queue: &wgpu::Queue, space: &Space, region: Grid) -> usize {
-        let mut data: Vec<[u8; 4]> = Vec::with_capacity(region.volume());
-        // TODO: Enable circular operation and eliminate the need for the offset of the
-        // coordinates (texture_grid.lower_bounds() and light_offset in the shader)
-        // by doing a coordinate wrap-around -- the shader and the Space will agree
-        // on coordinates modulo the texture size, and this upload will need to be broken
-        // into up to 8 pieces.
-        for z in region.z_range() {
-            for y in region.y_range() {
-                for x in region.x_range() {
-                    data.push(space.get_lighting([x, y, z]).as_texel());
+        let texture_size = self.texture_grid.size();
+        let mut data: Vec<[u8; 4]> = Vec::new();
+        for piece in wrap_region(region, texture_size) {
+            data.clear();
+            data.reserve(piece.volume());
+            for z in piece.z_range() {
+                for y in piece.y_range() {
+                    for x in piece.x_range() {
+                        data.push(space.get_lighting([x, y, z]).as_texel());
+                    }
                 }
             }
-        }
 
-        write_texture_by_grid(
-            queue,
-            &self.texture,
-            region.translate(self.light_lookup_offset()),
-            &data,
-        );
+            write_texture_by_grid(
+                queue,
+                &self.texture,
+                Grid::new(wrap_point(piece.lower_bounds(), texture_size), piece.size()),
+                &data,
+            );
+        }
 
         region.volume()
     }
@@ -522,8 +531,4 @@ impl SpaceLightTexture {
         self.update(queue, space, self.texture_grid);
         self.texture_grid.volume()
     }
-
-    fn light_lookup_offset(&self) -> Vector3<i32> {
-        -self.texture_grid.lower_bounds().to_vec()
-    }
 }
@@ -22,8 +22,11 @@ pub(crate) struct WgpuBlockVertex {
     /// TODO: Once we implement storing chunks in relative coordinates for better
     /// precision, we can reduce this representation size down to i8 or u8.
     cube: [f32; 3],
-    /// Vertex normal in [`Face7`] format.
-    /// TODO: Make use of all the spare bits here for something.
+    /// Packed format:
+    /// * Bits 0-7 are the vertex normal in [`Face7`] format.
+    /// * Bits 8-15 are the ambient occlusion factor, scaled to 0-255.
+    ///
+    /// TODO: Make use of the remaining spare bits here for something.
     normal: u32,
     /// Packed format:
     /// * If `[3]` is in the range 0.0 to 1.0, then the attribute is a linear RGBA color.
@@ -36,6 +39,11 @@ pub(crate) struct WgpuBlockVertex {
 }
 
 impl WgpuBlockVertex {
+    fn pack_normal(face: Face7, ambient_occlusion: f32) -> u32 {
+        let ao_bits = (ambient_occlusion.clamp(0.0, 1.0) * 255.0).round() as u32;
+        face as u32 | ao_bits << 8
+    }
+
     const ATTRIBUTE_LAYOUT: &'static [wgpu::VertexAttribute] = &wgpu::vertex_attr_array![
         0 => Float32x3, // position
         1 => Float32x3, // cube
@@ -59,7 +67,7 @@ impl From<BlockVertex> for WgpuBlockVertex {
     fn from(vertex: BlockVertex) -> Self {
         let position = vertex.position.cast::<f32>().unwrap().to_vec();
         let cube = [0., 0., 0.];
-        let normal = vertex.face as u32;
+        let normal = Self::pack_normal(vertex.face.into(), vertex.ambient_occlusion);
         match vertex.coloring {
             Coloring::Solid(color) => {
                 let mut color_attribute: [f32; 4] = color.into();
@@ -102,11 +110,17 @@ impl GfxVertex for WgpuBlockVertex {
     }
 
     #[inline]
-    fn instantiate_vertex(&mut self, cube: Self::BlockInst, _lighting: PackedLight) {
+    fn instantiate_vertex(
+        &mut self,
+        cube: Self::BlockInst,
+        _lighting: PackedLight,
+        ambient_occlusion: f32,
+    ) {
         self.position[0] += cube.x;
         self.position[1] += cube.y;
         self.position[2] += cube.z;
         self.cube = cube.into();
+        self.normal = Self::pack_normal(self.face(), ambient_occlusion);
     }
 
     #[inline]
@@ -116,7 +130,7 @@ impl GfxVertex for WgpuBlockVertex {
 
     #[inline]
     fn face(&self) -> Face7 {
-        Face7::from_discriminant(self.normal as u8).unwrap_or(Face7::Within)
+        Face7::from_discriminant((self.normal & 0xFF) as u8).unwrap_or(Face7::Within)
     }
 }
 
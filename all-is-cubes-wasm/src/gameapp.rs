@@ -12,15 +12,19 @@ use send_wrapper::SendWrapper;
 use wasm_bindgen::prelude::{wasm_bindgen, Closure, JsValue};
 use wasm_bindgen::JsCast; // dyn_into()
 use web_sys::{
-    console, AddEventListenerOptions, Document, Element, Event, FocusEvent, HtmlElement,
-    HtmlProgressElement, KeyboardEvent, MouseEvent, Text, WebGlContextAttributes,
+    console, AddEventListenerOptions, Document, Element, Event, FocusEvent, Gamepad, GamepadButton,
+    HtmlElement, HtmlProgressElement, KeyboardEvent, MouseEvent, Text, TouchEvent,
+    WebGlContextAttributes, WheelEvent,
 };
 use winit::platform::web::WindowBuilderExtWebSys as _;
 
 use all_is_cubes::apps::{Key, Session, StandardCameras};
-use all_is_cubes::camera::Viewport;
-use all_is_cubes::cgmath::{Point2, Vector2};
+use all_is_cubes::camera::{AntialiasingOption, Viewport};
+use all_is_cubes::cgmath::{
+    EuclideanSpace as _, InnerSpace as _, MetricSpace as _, Point2, Vector2, Vector3, Zero as _,
+};
 use all_is_cubes::listen::ListenableCell;
+use all_is_cubes::math::FreeCoordinate;
 use all_is_cubes::universe::UniverseStepInfo;
 use all_is_cubes::util::YieldProgress;
 use all_is_cubes_gpu::in_luminance;
@@ -126,9 +130,12 @@ pub async fn start_game(gui_helpers: GuiHelpers) -> Result<(), JsValue> {
                 web_sys::window().unwrap(), // TODO messy
                 document,
                 gui_helpers.canvas_helper().canvas(),
-                // This is set for parity with the all-is-cubes-desktop defaults.
-                // TODO: Probably `GraphicsOptions` should get an antialias/MSAA field.
-                WebGlContextAttributes::new().antialias(false),
+                // The luminance renderer draws directly to the canvas, so its
+                // multisampling is controlled by the context attributes.
+                WebGlContextAttributes::new().antialias(matches!(
+                    cameras.graphics_options().antialiasing,
+                    AntialiasingOption::Msaa { samples } if samples > 1
+                )),
             )
             .map_err(|e| Error::new(&format!("did not initialize WebGL: {}", e)))?;
 
@@ -154,7 +161,8 @@ pub async fn start_game(gui_helpers: GuiHelpers) -> Result<(), JsValue> {
             // as long as the surface is. In this case, that's the canvas element, which we
             // will never remove (and in any case, almost certainly isn't going to cause UB).
             let surface = unsafe { wgpu_instance.create_surface(&winit_window) };
-            // TODO: we lost the 'request no MSAA' feature
+            // wgpu creates the WebGL context without antialiasing; EverythingRenderer
+            // does its own multisampling according to the graphics options.
             let adapter = wgpu_instance
                 .request_adapter(&wgpu::RequestAdapterOptions {
                     power_preference: wgpu::PowerPreference::HighPerformance,
@@ -230,6 +238,14 @@ struct WebGameRoot {
     step_callback_scheduled: bool,
     last_raf_timestamp: f64,
     last_step_info: UniverseStepInfo,
+    /// Fractional slot-selection steps from pixel-mode wheel events not yet applied.
+    wheel_accumulator: f64,
+    touch_tracker: TouchTracker,
+    /// Whether the canvas has keyboard focus; gamepad input is ignored when it does not,
+    /// as keyboard input is.
+    has_focus: bool,
+    /// Which of [`GAMEPAD_CLICK_BUTTONS`] were pressed when the gamepad was last polled.
+    gamepad_buttons_pressed: [bool; GAMEPAD_CLICK_BUTTONS.len()],
 }
 
 impl WebGameRoot {
@@ -240,6 +256,17 @@ impl WebGameRoot {
         renderer: WebRenderer,
         viewport_cell: ListenableCell<Viewport>,
     ) -> Rc<RefCell<WebGameRoot>> {
+        // The canvas may have been focused before we started listening for focus events.
+        let has_focus = {
+            let canvas = gui_helpers.canvas_helper().canvas();
+            let canvas: &Element = canvas.as_ref();
+            canvas
+                .owner_document()
+                .and_then(|document| document.active_element())
+                .as_ref()
+                == Some(canvas)
+        };
+
         // Construct a non-self-referential initial mutable object.
         let self_cell_ref = Rc::new(RefCell::new(Self {
             self_ref: Weak::new(),
@@ -254,6 +281,10 @@ impl WebGameRoot {
             step_callback_scheduled: false,
             last_raf_timestamp: 0.0, // TODO better initial value or special case
             last_step_info: UniverseStepInfo::default(),
+            wheel_accumulator: 0.0,
+            touch_tracker: TouchTracker::default(),
+            has_focus,
+            gamepad_buttons_pressed: [false; GAMEPAD_CLICK_BUTTONS.len()],
         }));
 
         // Add the self-references.
@@ -312,10 +343,12 @@ impl WebGameRoot {
         );
 
         self.add_canvas_to_self_event_listener("focus", true, move |this, _: FocusEvent| {
+            this.has_focus = true;
             this.session.input_processor.key_focus(true);
         });
 
         self.add_canvas_to_self_event_listener("blur", true, move |this, _: FocusEvent| {
+            this.has_focus = false;
             this.session.input_processor.key_focus(false);
             this.session.input_processor.mouse_ndc_position(None);
         });
@@ -357,6 +390,37 @@ impl WebGameRoot {
             },
         );
 
+        // Not passive, because we want to prevent the page from scrolling.
+        self.add_canvas_to_self_event_listener("wheel", false, move |this, event: WheelEvent| {
+            let steps = wheel_steps(
+                &mut this.wheel_accumulator,
+                event.delta_y(),
+                event.delta_mode(),
+            );
+            if steps != 0 {
+                this.session.input_processor.scroll_slot_selection(steps);
+            }
+            event.prevent_default();
+        });
+
+        // Not passive, because we want to prevent scrolling and zooming, and the
+        // mouse events the browser would otherwise synthesize.
+        for (event_name, phase) in [
+            ("touchstart", TouchPhase::Start),
+            ("touchmove", TouchPhase::Move),
+            ("touchend", TouchPhase::End),
+            ("touchcancel", TouchPhase::Cancel),
+        ] {
+            self.add_canvas_to_self_event_listener(
+                event_name,
+                false,
+                move |this, event: TouchEvent| {
+                    this.handle_touch_event(&event, phase);
+                    event.prevent_default();
+                },
+            );
+        }
+
         add_event_listener(
             &self.gui_helpers.canvas_helper().canvas(),
             "contextmenu",
@@ -419,6 +483,8 @@ impl WebGameRoot {
         self.last_raf_timestamp = dom_timestamp;
         let should_draw = self.session.frame_clock.request_frame(delta);
 
+        self.poll_gamepad();
+
         if should_draw {
             let viewport = self.gui_helpers.canvas_helper().viewport();
             if viewport != *self.viewport_cell.get() {
@@ -516,6 +582,105 @@ impl WebGameRoot {
         );
     }
 
+    /// Reads the state of the first connected gamepad, if any, and applies it to
+    /// the input processor.
+    ///
+    /// Does nothing while the canvas does not have focus, so that a gamepad being used
+    /// for something else does not also control the game. (Losing focus already stops
+    /// any analog movement, via [`InputProcessor::key_focus()`].)
+    ///
+    /// [`InputProcessor::key_focus()`]: all_is_cubes::apps::InputProcessor::key_focus
+    fn poll_gamepad(&mut self) {
+        if !self.has_focus {
+            return;
+        }
+
+        let gamepad: Option<Gamepad> = web_sys::window()
+            .and_then(|window| window.navigator().get_gamepads().ok())
+            .and_then(|gamepads| {
+                gamepads
+                    .iter()
+                    .find_map(|value| value.dyn_into::<Gamepad>().ok())
+                    .filter(Gamepad::connected)
+            });
+        let gamepad = match gamepad {
+            Some(gamepad) => gamepad,
+            None => {
+                // Don't keep moving if the gamepad was disconnected while in use.
+                self.gamepad_buttons_pressed = [false; GAMEPAD_CLICK_BUTTONS.len()];
+                self.session
+                    .input_processor
+                    .analog_movement(Vector3::zero());
+                self.session.input_processor.analog_turning(Vector2::zero());
+                return;
+            }
+        };
+
+        // Axes and buttons per the "standard" gamepad mapping:
+        // <https://w3c.github.io/gamepad/#remapping>
+        let axes = gamepad.axes();
+        let axis = |i: u32| axes.get(i).as_f64().unwrap_or(0.0);
+        let left = stick_input(Vector2::new(axis(0), axis(1)));
+        let right = stick_input(Vector2::new(axis(2), axis(3)));
+        let i = &mut self.session.input_processor;
+        // Stick down is positive y, which is backward (+z) movement and looking down.
+        i.analog_movement(Vector3::new(left.x, 0.0, left.y));
+        i.analog_turning(right);
+
+        let buttons = gamepad.buttons();
+        for (&(button_index, click_button), was_pressed) in GAMEPAD_CLICK_BUTTONS
+            .iter()
+            .zip(self.gamepad_buttons_pressed.iter_mut())
+        {
+            let pressed = buttons
+                .get(button_index)
+                .dyn_into::<GamepadButton>()
+                .map(|b| b.pressed())
+                .unwrap_or(false);
+            if pressed && !*was_pressed {
+                self.session.click(click_button);
+            }
+            *was_pressed = pressed;
+        }
+    }
+
+    fn handle_touch_event(&mut self, event: &TouchEvent, phase: TouchPhase) {
+        let touches = event.changed_touches();
+        for touch in (0..touches.length()).filter_map(|i| touches.item(i)) {
+            let position = Point2::new(touch.client_x().into(), touch.client_y().into());
+            let gesture = match self
+                .touch_tracker
+                .update(touch.identifier(), position, phase)
+            {
+                Some(gesture) => gesture,
+                None => continue,
+            };
+
+            let viewport = *self.viewport_cell.get();
+            let i = &mut self.session.input_processor;
+            match gesture {
+                TouchGesture::Hover(position) => {
+                    i.mouse_ndc_position(Some(touch_ndc_position(viewport, position)));
+                }
+                TouchGesture::Drag { position, delta } => {
+                    // Touch input never has pointer lock, but a drag is the same kind of
+                    // relative movement, so let it act as mouselook.
+                    i.has_pointer_lock(true);
+                    i.mouselook_delta(delta);
+                    i.mouse_ndc_position(Some(touch_ndc_position(viewport, position)));
+                }
+                TouchGesture::Tap(position) => {
+                    i.mouse_ndc_position(Some(touch_ndc_position(viewport, position)));
+                    self.session.click(0);
+                }
+                TouchGesture::Release => {
+                    let lock = self.check_pointer_lock();
+                    self.session.input_processor.has_pointer_lock(lock);
+                }
+            }
+        }
+    }
+
     fn check_pointer_lock(&self) -> bool {
         let canvas = self.gui_helpers.canvas_helper().canvas(); // TODO: less indirection?
         canvas
@@ -552,18 +717,389 @@ impl StaticDom {
     }
 }
 
+/// Converts the vertical scroll distance of a [`WheelEvent`] into a number of
+/// slot-selection steps, where positive is “next”.
+///
+/// Pixel-mode deltas (typical of touchpads and some browsers' mouse wheels) may be
+/// small, so they are accumulated in `accumulator` until they add up to whole steps.
+/// Line- and page-mode deltas vary in magnitude per wheel notch between browsers and
+/// platforms, so each such event is one step.
+fn wheel_steps(accumulator: &mut f64, delta_y: f64, delta_mode: u32) -> i32 {
+    /// Approximately the distance that one notch of a mouse wheel reports.
+    const PIXELS_PER_STEP: f64 = 100.0;
+
+    match delta_mode {
+        WheelEvent::DOM_DELTA_PIXEL => {
+            *accumulator += delta_y / PIXELS_PER_STEP;
+            let steps = accumulator.trunc();
+            *accumulator -= steps;
+            steps as i32
+        }
+        _ => {
+            *accumulator = 0.0;
+            if delta_y > 0.0 {
+                1
+            } else if delta_y < 0.0 {
+                -1
+            } else {
+                0
+            }
+        }
+    }
+}
+
+/// Gamepad buttons which act as clicks, as pairs of
+/// (standard mapping button index, [`Session::click`] button number).
+///
+/// These are the bottom, right, and left face buttons (“A”, “B”, and “X” on an
+/// Xbox-style controller), mapped to the primary, secondary, and tertiary clicks.
+const GAMEPAD_CLICK_BUTTONS: [(u32, usize); 3] = [(0, 0), (1, 1), (2, 2)];
+
+/// Converts the position of a gamepad analog stick, with each axis ranging from -1 to 1,
+/// to an analog input value.
+///
+/// Positions within a small radius of the center are treated as zero, to ignore
+/// sticks that do not center exactly; beyond that, the output ramps up from zero so
+/// that there is no jump at the edge of the dead zone. The result's length is at
+/// most 1.
+fn stick_input(stick: Vector2<f64>) -> Vector2<f64> {
+    const DEAD_ZONE: f64 = 0.15;
+
+    let magnitude = stick.magnitude();
+    if magnitude.is_nan() || magnitude <= DEAD_ZONE {
+        return Vector2::zero();
+    }
+    let scaled_magnitude = ((magnitude - DEAD_ZONE) / (1.0 - DEAD_ZONE)).min(1.0);
+    stick * (scaled_magnitude / magnitude)
+}
+
+/// Which kind of touch event is being processed by [`TouchTracker::update`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum TouchPhase {
+    Start,
+    Move,
+    End,
+    Cancel,
+}
+
+/// What a [`TouchTracker`] has determined the user is doing.
+/// Positions are in the same client pixel coordinates as the input.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TouchGesture {
+    /// The touch is down but has not yet moved far enough to be a drag;
+    /// the cursor should be at its position.
+    Hover(Point2<f64>),
+    /// The touch is being dragged, and has moved by `delta` since the last event.
+    Drag {
+        position: Point2<f64>,
+        delta: Vector2<f64>,
+    },
+    /// The touch was lifted without having been dragged; this is a primary click.
+    Tap(Point2<f64>),
+    /// The touch was lifted, or cancelled, after a drag.
+    Release,
+}
+
+/// Interprets touch events as taps and drags.
+///
+/// Only one touch, the primary touch, is followed at a time: the first to start while
+/// no other touch is being followed. Other simultaneous touches are ignored.
+#[derive(Debug, Default)]
+struct TouchTracker {
+    primary: Option<PrimaryTouch>,
+}
+
+#[derive(Debug)]
+struct PrimaryTouch {
+    /// The `Touch.identifier` of the touch.
+    identifier: i32,
+    start: Point2<f64>,
+    last: Point2<f64>,
+    dragging: bool,
+}
+
+impl TouchTracker {
+    /// Distance in pixels that a touch may move and still count as a tap.
+    const TAP_SLOP: f64 = 10.0;
+
+    /// Processes one changed touch from a touch event, returning the resulting gesture
+    /// if it is the primary touch.
+    fn update(
+        &mut self,
+        identifier: i32,
+        position: Point2<f64>,
+        phase: TouchPhase,
+    ) -> Option<TouchGesture> {
+        if phase == TouchPhase::Start {
+            if self.primary.is_some() {
+                return None;
+            }
+            self.primary = Some(PrimaryTouch {
+                identifier,
+                start: position,
+                last: position,
+                dragging: false,
+            });
+            return Some(TouchGesture::Hover(position));
+        }
+
+        let primary = self
+            .primary
+            .as_mut()
+            .filter(|p| p.identifier == identifier)?;
+        match phase {
+            TouchPhase::Start => unreachable!(),
+            TouchPhase::Move => {
+                if !primary.dragging && position.distance(primary.start) > Self::TAP_SLOP {
+                    primary.dragging = true;
+                }
+                let gesture = if primary.dragging {
+                    TouchGesture::Drag {
+                        position,
+                        delta: position - primary.last,
+                    }
+                } else {
+                    TouchGesture::Hover(position)
+                };
+                primary.last = position;
+                Some(gesture)
+            }
+            TouchPhase::End | TouchPhase::Cancel => {
+                let was_dragging = primary.dragging;
+                self.primary = None;
+                if phase == TouchPhase::End && !was_dragging {
+                    Some(TouchGesture::Tap(position))
+                } else {
+                    Some(TouchGesture::Release)
+                }
+            }
+        }
+    }
+}
+
+/// Converts the client coordinates of a touch to normalized device coordinates
+/// within `viewport`, in the same way as mouse positions.
+fn touch_ndc_position(viewport: Viewport, client_position: Point2<f64>) -> Point2<FreeCoordinate> {
+    Point2::from_vec(viewport.normalize_nominal_point(client_position))
+}
+
 fn map_keyboard_event(event: &KeyboardEvent) -> Option<Key> {
     if event.alt_key() || event.ctrl_key() || event.meta_key() {
         return None;
     }
-    Some(match event.key_code() as u8 as char {
-        '\x25' => Key::Left,
-        '\x26' => Key::Up,
-        '\x27' => Key::Right,
-        '\x28' => Key::Down,
-        c @ '\x20'..='\x7e' => Key::Character(c.to_ascii_lowercase()),
+    map_key(&event.key(), &event.code())
+}
+
+/// Maps the [`KeyboardEvent`] `key` (logical key, or produced text) and `code` (physical
+/// key position) values to a [`Key`].
+///
+/// Movement keys are identified by `code`, so that they are in the same place regardless
+/// of keyboard layout; everything else uses `key`, so that bindings which are mnemonic
+/// (letters) or symbolic (digits) follow what is printed on the key.
+fn map_key(key: &str, code: &str) -> Option<Key> {
+    // Positional bindings. These letters match the QWERTY layout, which the codes
+    // are named after.
+    const POSITIONAL: [(&str, char); 7] = [
+        ("KeyW", 'w'),
+        ("KeyA", 'a'),
+        ("KeyS", 's'),
+        ("KeyD", 'd'),
+        ("KeyE", 'e'),
+        ("KeyC", 'c'),
+        ("Space", ' '),
+    ];
+    if let Some(&(_, c)) = POSITIONAL.iter().find(|&&(pc, _)| pc == code) {
+        return Some(Key::Character(c));
+    }
+
+    Some(match key {
+        "ArrowLeft" | "Left" => Key::Left,
+        "ArrowUp" | "Up" => Key::Up,
+        "ArrowRight" | "Right" => Key::Right,
+        "ArrowDown" | "Down" => Key::Down,
         _ => {
-            return None;
+            let mut chars = key.chars();
+            match (chars.next(), chars.next()) {
+                // Named keys such as "Shift" have multi-character names; skip those.
+                (Some(c), None) if !c.is_control() => {
+                    let c = c.to_ascii_lowercase();
+                    if POSITIONAL.iter().any(|&(_, pc)| pc == c) {
+                        // Don't let another key in a different position act as a
+                        // movement key.
+                        return None;
+                    }
+                    Key::Character(c)
+                }
+                _ => return None,
+            }
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_key_movement_is_positional() {
+        // QWERTY
+        assert_eq!(map_key("w", "KeyW"), Some(Key::Character('w')));
+        // AZERTY: the key in the W position produces "z"
+        assert_eq!(map_key("z", "KeyW"), Some(Key::Character('w')));
+        // Dvorak: the key in the W position produces ","
+        assert_eq!(map_key(",", "KeyW"), Some(Key::Character('w')));
+        // Shifted
+        assert_eq!(map_key("W", "KeyW"), Some(Key::Character('w')));
+    }
+
+    #[test]
+    fn map_key_other_keys_use_key_value() {
+        // AZERTY: the key in the Z position produces "w"; it should not also
+        // act as the forward movement key.
+        assert_eq!(map_key("w", "KeyZ"), None);
+        assert_eq!(map_key("P", "KeyP"), Some(Key::Character('p')));
+        assert_eq!(map_key("5", "Digit5"), Some(Key::Character('5')));
+        assert_eq!(map_key("ArrowLeft", "ArrowLeft"), Some(Key::Left));
+        assert_eq!(map_key("ArrowDown", "ArrowDown"), Some(Key::Down));
+        assert_eq!(map_key("Shift", "ShiftLeft"), None);
+    }
+
+    #[test]
+    fn wheel_steps_line_mode() {
+        let mut acc = 0.0;
+        assert_eq!(wheel_steps(&mut acc, 3.0, WheelEvent::DOM_DELTA_LINE), 1);
+        assert_eq!(wheel_steps(&mut acc, -3.0, WheelEvent::DOM_DELTA_LINE), -1);
+        assert_eq!(wheel_steps(&mut acc, 0.0, WheelEvent::DOM_DELTA_LINE), 0);
+        assert_eq!(wheel_steps(&mut acc, 1.0, WheelEvent::DOM_DELTA_PAGE), 1);
+    }
+
+    #[test]
+    fn wheel_steps_pixel_mode_accumulates() {
+        let mut acc = 0.0;
+        assert_eq!(wheel_steps(&mut acc, 40.0, WheelEvent::DOM_DELTA_PIXEL), 0);
+        assert_eq!(wheel_steps(&mut acc, 40.0, WheelEvent::DOM_DELTA_PIXEL), 0);
+        assert_eq!(wheel_steps(&mut acc, 40.0, WheelEvent::DOM_DELTA_PIXEL), 1);
+        assert_eq!(
+            wheel_steps(&mut acc, -250.0, WheelEvent::DOM_DELTA_PIXEL),
+            -2
+        );
+        // The leftover partial step in the other direction is carried over.
+        assert_eq!(wheel_steps(&mut acc, 100.0, WheelEvent::DOM_DELTA_PIXEL), 0);
+    }
+
+    #[test]
+    fn stick_input_dead_zone() {
+        assert_eq!(stick_input(Vector2::new(0.0, 0.0)), Vector2::zero());
+        assert_eq!(stick_input(Vector2::new(0.1, -0.1)), Vector2::zero());
+        assert_eq!(stick_input(Vector2::new(f64::NAN, 0.0)), Vector2::zero());
+        // Just past the dead zone is just past zero.
+        let barely = stick_input(Vector2::new(0.16, 0.0));
+        assert!(barely.x > 0.0 && barely.x < 0.02, "{barely:?}");
+        assert_eq!(barely.y, 0.0);
+    }
+
+    #[test]
+    fn stick_input_full_range() {
+        assert_eq!(stick_input(Vector2::new(1.0, 0.0)), Vector2::new(1.0, 0.0));
+        assert_eq!(
+            stick_input(Vector2::new(0.0, -1.0)),
+            Vector2::new(0.0, -1.0)
+        );
+        // Corners of square-gated sticks don't exceed length 1, and keep their direction.
+        let corner = stick_input(Vector2::new(1.0, 1.0));
+        assert!((corner.magnitude() - 1.0).abs() < 1e-9, "{corner:?}");
+        assert_eq!(corner.x, corner.y);
+        // Halfway through the live range.
+        let half = stick_input(Vector2::new(0.0, 0.575));
+        assert!((half.y - 0.5).abs() < 1e-9, "{half:?}");
+    }
+
+    #[test]
+    fn touch_ndc_position() {
+        let viewport = Viewport {
+            nominal_size: Vector2::new(200.0, 100.0),
+            framebuffer_size: Vector2::new(400, 200),
+        };
+        // Positions are of pixel centers, so the corner pixel is just inside ±1.
+        assert_eq!(
+            super::touch_ndc_position(viewport, Point2::new(0.0, 0.0)),
+            Point2::new(-0.995, 0.99)
+        );
+        assert_eq!(
+            super::touch_ndc_position(viewport, Point2::new(99.5, 49.5)),
+            Point2::new(0.0, 0.0)
+        );
+        assert_eq!(
+            super::touch_ndc_position(viewport, Point2::new(149.5, 74.5)),
+            Point2::new(0.5, -0.5)
+        );
+    }
+
+    #[test]
+    fn touch_tap() {
+        let mut t = TouchTracker::default();
+        let p = Point2::new(50.0, 50.0);
+        let q = Point2::new(53.0, 51.0); // within slop
+        assert_eq!(
+            t.update(7, p, TouchPhase::Start),
+            Some(TouchGesture::Hover(p))
+        );
+        assert_eq!(
+            t.update(7, q, TouchPhase::Move),
+            Some(TouchGesture::Hover(q))
+        );
+        assert_eq!(t.update(7, q, TouchPhase::End), Some(TouchGesture::Tap(q)));
+    }
+
+    #[test]
+    fn touch_drag() {
+        let mut t = TouchTracker::default();
+        t.update(1, Point2::new(50.0, 50.0), TouchPhase::Start);
+        assert_eq!(
+            t.update(1, Point2::new(70.0, 50.0), TouchPhase::Move),
+            Some(TouchGesture::Drag {
+                position: Point2::new(70.0, 50.0),
+                delta: Vector2::new(20.0, 0.0),
+            })
+        );
+        assert_eq!(
+            t.update(1, Point2::new(70.0, 45.0), TouchPhase::Move),
+            Some(TouchGesture::Drag {
+                position: Point2::new(70.0, 45.0),
+                delta: Vector2::new(0.0, -5.0),
+            })
+        );
+        assert_eq!(
+            t.update(1, Point2::new(70.0, 45.0), TouchPhase::End),
+            Some(TouchGesture::Release)
+        );
+    }
+
+    #[test]
+    fn touch_cancel_is_not_tap() {
+        let mut t = TouchTracker::default();
+        let p = Point2::new(50.0, 50.0);
+        t.update(1, p, TouchPhase::Start);
+        assert_eq!(
+            t.update(1, p, TouchPhase::Cancel),
+            Some(TouchGesture::Release)
+        );
+    }
+
+    #[test]
+    fn touch_secondary_ignored() {
+        let mut t = TouchTracker::default();
+        let p = Point2::new(50.0, 50.0);
+        let q = Point2::new(150.0, 50.0);
+        t.update(1, p, TouchPhase::Start);
+        assert_eq!(t.update(2, q, TouchPhase::Start), None);
+        assert_eq!(t.update(2, p, TouchPhase::Move), None);
+        assert_eq!(t.update(2, q, TouchPhase::End), None);
+        assert_eq!(t.update(1, p, TouchPhase::End), Some(TouchGesture::Tap(p)));
+        // Once the primary touch is gone, a new touch becomes primary.
+        assert_eq!(
+            t.update(2, q, TouchPhase::Start),
+            Some(TouchGesture::Hover(q))
+        );
+    }
+}
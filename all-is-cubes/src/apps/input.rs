@@ -52,6 +52,15 @@ pub struct InputProcessor {
     /// Mouse position used for generating mouselook deltas.
     /// [`None`] if games.
     mouse_previous_pixel_position: Option<Point2<f64>>,
+
+    /// Net number of toolbar slots to move the selection by, from
+    /// [`Self::scroll_slot_selection`], since the last [`Self::apply_input`].
+    slot_scroll_buffer: i32,
+
+    /// Movement requested by analog controls, from [`Self::analog_movement`].
+    analog_movement: Vector3<FreeCoordinate>,
+    /// Turning requested by analog controls, from [`Self::analog_turning`].
+    analog_turning: Vector2<FreeCoordinate>,
 }
 
 impl InputProcessor {
@@ -66,6 +75,9 @@ impl InputProcessor {
             mouselook_buffer: Vector2::zero(),
             mouse_ndc_position: Some(Point2::origin()),
             mouse_previous_pixel_position: None,
+            slot_scroll_buffer: 0,
+            analog_movement: Vector3::zero(),
+            analog_turning: Vector2::zero(),
         }
     }
 
@@ -147,6 +159,8 @@ impl InputProcessor {
         } else {
             self.keys_held.clear();
             self.momentary_timeout.clear();
+            self.analog_movement = Vector3::zero();
+            self.analog_turning = Vector2::zero();
 
             self.mouselook_mode.set(false);
         }
@@ -226,13 +240,43 @@ impl InputProcessor {
         }
     }
 
+    /// Handles a request to move the tool selection to the previous (negative) or next
+    /// (positive) toolbar slot, such as from a mouse scroll wheel. The selection wraps
+    /// around at the ends of the toolbar.
+    ///
+    /// The platform-specific code is responsible for converting scroll distances into
+    /// whole steps.
+    pub fn scroll_slot_selection(&mut self, steps: i32) {
+        self.slot_scroll_buffer = self.slot_scroll_buffer.saturating_add(steps);
+    }
+
+    /// Provide movement input from an analog control such as a gamepad stick.
+    ///
+    /// Each component ranges from -1 to 1, in the same directions as the result of
+    /// [`InputProcessor::movement`]. This value is a level, not an event; it stays in
+    /// effect until changed, and is combined with movement keys.
+    /// Dead zones, if wanted, are the responsibility of the caller.
+    pub fn analog_movement(&mut self, movement: Vector3<FreeCoordinate>) {
+        self.analog_movement = movement.map(|c| c.clamp(-1.0, 1.0));
+    }
+
+    /// Provide turning input from an analog control such as a gamepad stick.
+    ///
+    /// Each component ranges from -1 to 1, where 1 is the same turning rate as is
+    /// produced by holding an arrow key (right or down). Like
+    /// [`InputProcessor::analog_movement`], this stays in effect until changed.
+    pub fn analog_turning(&mut self, turning: Vector2<FreeCoordinate>) {
+        self.analog_turning = turning.map(|c| c.clamp(-1.0, 1.0));
+    }
+
     /// Returns the character movement velocity that input is currently requesting.
     pub fn movement(&self) -> Vector3<FreeCoordinate> {
-        Vector3::new(
+        let keys = Vector3::new(
             self.net_movement(Key::Character('a'), Key::Character('d')),
             self.net_movement(Key::Character('c'), Key::Character('e')),
             self.net_movement(Key::Character('w'), Key::Character('s')),
-        )
+        );
+        (keys + self.analog_movement).map(|c| c.clamp(-1.0, 1.0))
     }
 
     /// Advance time insofar as input interpretation is affected by time.
@@ -278,9 +322,13 @@ impl InputProcessor {
                     character.set_velocity_input(movement);
 
                     let turning = Vector2::new(
-                        key_turning_step * self.net_movement(Key::Left, Key::Right)
+                        key_turning_step
+                            * (self.net_movement(Key::Left, Key::Right) + self.analog_turning.x)
+                                .clamp(-1.0, 1.0)
                             + self.mouselook_buffer.x,
-                        key_turning_step * self.net_movement(Key::Up, Key::Down)
+                        key_turning_step
+                            * (self.net_movement(Key::Up, Key::Down) + self.analog_turning.y)
+                                .clamp(-1.0, 1.0)
                             + self.mouselook_buffer.y,
                     );
                     character.body.yaw = (character.body.yaw + turning.x).rem_euclid(360.0);
@@ -293,6 +341,18 @@ impl InputProcessor {
                 .expect("character was borrowed during apply_input()");
         }
 
+        let slot_scroll = std::mem::take(&mut self.slot_scroll_buffer);
+        if slot_scroll != 0 {
+            if let Some(character_ref) = character_opt {
+                character_ref
+                    .try_modify(|c| {
+                        let slot = offset_slot(c.selected_slots()[1], slot_scroll, TOOLBAR_SLOTS);
+                        c.set_selected_slot(1, slot)
+                    })
+                    .expect("character was borrowed during apply_input()");
+            }
+        }
+
         for key in self.command_buffer.drain(..) {
             match key {
                 Key::Character('i') => {
@@ -348,7 +408,7 @@ impl InputProcessor {
                 }
                 Key::Character(numeral) if numeral.is_ascii_digit() => {
                     let digit = numeral.to_digit(10).unwrap() as usize;
-                    let slot = (digit + 9).rem_euclid(10); // wrap 0 to 9
+                    let slot = (digit + TOOLBAR_SLOTS - 1).rem_euclid(TOOLBAR_SLOTS); // wrap 0 to 9
                     if let Some(character_ref) = character_opt {
                         character_ref
                             .try_modify(|c| c.set_selected_slot(1, slot))
@@ -400,6 +460,16 @@ impl InputProcessor {
     }
 }
 
+/// Number of toolbar slots which may be selected by number keys or scrolling.
+const TOOLBAR_SLOTS: usize = 10;
+
+/// Computes the slot index `offset` slots away from `current`, wrapping around within
+/// `0..count`.
+fn offset_slot(current: usize, offset: i32, count: usize) -> usize {
+    let count = count as i64;
+    (current as i64 + i64::from(offset)).rem_euclid(count) as usize
+}
+
 /// Things needed to apply input.
 ///
 /// Missing inputs will cause input to be ignored.
@@ -463,6 +533,48 @@ mod tests {
         assert_eq!(input.movement(), Vector3::new(-1.0, 0.0, 0.0));
     }
 
+    #[test]
+    fn analog_movement() {
+        let mut input = InputProcessor::new();
+        input.analog_movement(Vector3::new(0.5, 0.0, -0.25));
+        assert_eq!(input.movement(), Vector3::new(0.5, 0.0, -0.25));
+        // Combined with keys, and clamped.
+        input.key_down(Key::Character('d'));
+        input.key_down(Key::Character('s'));
+        assert_eq!(input.movement(), Vector3::new(1.0, 0.0, 0.75));
+        // Out-of-range input is clamped.
+        input.key_up(Key::Character('d'));
+        input.key_up(Key::Character('s'));
+        input.analog_movement(Vector3::new(-3.0, 0.0, 0.0));
+        assert_eq!(input.movement(), Vector3::new(-1.0, 0.0, 0.0));
+        // Focus loss stops it like keys.
+        input.key_focus(false);
+        assert_eq!(input.movement(), Vector3::zero());
+    }
+
+    #[test]
+    fn analog_turning() {
+        let u = &mut Universe::new();
+        let space = u.insert_anonymous(Space::empty_positive(1, 1, 1));
+        let character = u.insert_anonymous(Character::spawn_default(space.clone()));
+        let mut input = InputProcessor::new();
+        let yaw_before = character.borrow().body.yaw;
+
+        input.analog_turning(Vector2::new(0.5, 0.0));
+        apply_input_helper(&mut input, u, &character);
+        let half_turn = character.borrow().body.yaw - yaw_before;
+        assert!(half_turn > 0.0, "{half_turn}");
+
+        input.analog_turning(Vector2::zero());
+        input.key_down(Key::Right);
+        apply_input_helper(&mut input, u, &character);
+        let full_turn = character.borrow().body.yaw - yaw_before - half_turn;
+        assert!(
+            (full_turn - 2.0 * half_turn).abs() < 1e-9,
+            "{full_turn} {half_turn}"
+        );
+    }
+
     #[test]
     fn focus_lost_cancels_keys() {
         let mut input = InputProcessor::new();
@@ -500,5 +612,39 @@ mod tests {
         assert_eq!(character.borrow().selected_slots()[1], 9);
     }
 
+    #[test]
+    fn offset_slot_wrapping() {
+        assert_eq!(offset_slot(0, 1, 10), 1);
+        assert_eq!(offset_slot(5, -1, 10), 4);
+        assert_eq!(offset_slot(9, 1, 10), 0);
+        assert_eq!(offset_slot(0, -1, 10), 9);
+        assert_eq!(offset_slot(8, 3, 10), 1);
+        assert_eq!(offset_slot(1, -23, 10), 8);
+        // Current slot out of range (e.g. an invisible slot) is brought back in range.
+        assert_eq!(offset_slot(11, 1, 10), 2);
+    }
+
+    #[test]
+    fn slot_scrolling() {
+        let u = &mut Universe::new();
+        let space = u.insert_anonymous(Space::empty_positive(1, 1, 1));
+        let character = u.insert_anonymous(Character::spawn_default(space.clone()));
+        let mut input = InputProcessor::new();
+        character.try_modify(|c| c.set_selected_slot(1, 0)).unwrap();
+
+        input.scroll_slot_selection(-1);
+        apply_input_helper(&mut input, u, &character);
+        assert_eq!(character.borrow().selected_slots()[1], 9);
+
+        // Scrolling is consumed by apply_input and not repeated.
+        apply_input_helper(&mut input, u, &character);
+        assert_eq!(character.borrow().selected_slots()[1], 9);
+
+        input.scroll_slot_selection(1);
+        input.scroll_slot_selection(1);
+        apply_input_helper(&mut input, u, &character);
+        assert_eq!(character.borrow().selected_slots()[1], 1);
+    }
+
     // TODO: test jump and flying logic
 }
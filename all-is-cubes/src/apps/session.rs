@@ -7,17 +7,22 @@ use std::sync::mpsc::{self, TryRecvError};
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
+use cgmath::Vector2;
 use futures_core::future::BoxFuture;
 use futures_task::noop_waker_ref;
+use image::RgbaImage;
 
 use crate::apps::{FpsCounter, FrameClock, InputProcessor, InputTargets, StandardCameras};
-use crate::camera::GraphicsOptions;
+use crate::block::BlockDef;
+use crate::camera::{Camera, GraphicsOptions, Viewport};
 use crate::character::{Character, Cursor};
 use crate::inv::ToolError;
 use crate::listen::{ListenableCell, ListenableCellWithLocal, ListenableSource};
+use crate::raytracer::RtRenderer;
 use crate::space::Space;
+use crate::time::Tick;
 use crate::transaction::Transaction;
-use crate::universe::{URef, Universe, UniverseStepInfo};
+use crate::universe::{Name, URef, Universe, UniverseIndex, UniverseSnapshot, UniverseStepInfo};
 use crate::util::{CustomFormat, StatusText};
 use crate::vui::Vui;
 
@@ -242,37 +247,66 @@ impl Session {
                     base_tick
                 };
                 self.frame_clock.did_step();
+                result = Some(self.step_with_ticks(base_tick, game_tick));
+            }
+        }
+        result
+    }
 
-                if let Some(character_ref) = self.game_character.borrow() {
-                    self.input_processor.apply_input(
-                        InputTargets {
-                            universe: Some(&mut self.game_universe),
-                            character: Some(character_ref),
-                            paused: Some(&self.paused),
-                            graphics_options: Some(&self.graphics_options),
-                        },
-                        game_tick,
-                    );
-                }
-                self.input_processor.step(game_tick);
+    /// Performs one step of the game universe and UI, without consulting the
+    /// [`FrameClock`].
+    fn step_with_ticks(&mut self, base_tick: Tick, game_tick: Tick) -> UniverseStepInfo {
+        if let Some(character_ref) = self.game_character.borrow() {
+            self.input_processor.apply_input(
+                InputTargets {
+                    universe: Some(&mut self.game_universe),
+                    character: Some(character_ref),
+                    paused: Some(&self.paused),
+                    graphics_options: Some(&self.graphics_options),
+                },
+                game_tick,
+            );
+        }
+        self.input_processor.step(game_tick);
 
-                let mut info = self.game_universe.step(game_tick);
+        let mut info = self.game_universe.step(game_tick);
 
-                info += self.ui.step(base_tick);
+        info += self.ui.step(base_tick);
 
-                if LOG_FIRST_FRAMES && self.tick_counter_for_logging <= 10 {
-                    self.tick_counter_for_logging = self.tick_counter_for_logging.saturating_add(1);
-                    log::debug!(
-                        "tick={} step {}",
-                        self.tick_counter_for_logging,
-                        info.computation_time.custom_format(StatusText)
-                    );
-                }
-                self.last_step_info = info.clone();
-                result = Some(info);
-            }
+        if LOG_FIRST_FRAMES && self.tick_counter_for_logging <= 10 {
+            self.tick_counter_for_logging = self.tick_counter_for_logging.saturating_add(1);
+            log::debug!(
+                "tick={} step {}",
+                self.tick_counter_for_logging,
+                info.computation_time.custom_format(StatusText)
+            );
         }
-        result
+        self.last_step_info = info.clone();
+        info
+    }
+
+    /// Returns whether the game universe is paused; that is, whether
+    /// [`Self::maybe_step_universe`] will refrain from advancing game time.
+    /// User interface elements continue to run while paused.
+    pub fn is_paused(&self) -> bool {
+        *self.paused.get()
+    }
+
+    /// Pauses or resumes the game universe. See [`Self::is_paused`].
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.set(paused);
+    }
+
+    /// Advances the game universe by exactly one step, even though it is paused, and
+    /// returns information about that step.
+    ///
+    /// If the session is not already paused, it is paused first, so that the step is
+    /// not followed by further steps; this allows debugging behaviors and the like one
+    /// step at a time.
+    pub fn single_step(&mut self) -> UniverseStepInfo {
+        self.set_paused(true);
+        let tick = self.frame_clock.tick();
+        self.step_with_ticks(tick, tick)
     }
 
     /// Call this once per frame to update the cursor raycast.
@@ -363,6 +397,69 @@ impl Session {
     pub fn draw_fps_counter(&self) -> &FpsCounter {
         self.frame_clock.draw_fps_counter()
     }
+
+    /// Records the current state of the session in a form suitable for attaching to a
+    /// bug report. See [`SessionSnapshot`] for what is included.
+    ///
+    /// This does not step or otherwise modify the session, and the snapshot shares no
+    /// state with it, so the simulation may continue as soon as this returns. The image
+    /// is rendered with the raytracer, so no graphics context is needed.
+    pub fn capture(&self) -> SessionSnapshot {
+        let universe = &self.game_universe;
+        let mut member_names: Vec<Name> = UniverseIndex::<BlockDef>::iter_by_type(universe)
+            .map(|(name, _)| name)
+            .chain(UniverseIndex::<Character>::iter_by_type(universe).map(|(name, _)| name))
+            .chain(UniverseIndex::<Space>::iter_by_type(universe).map(|(name, _)| name))
+            .collect();
+        member_names.sort();
+
+        let cameras = StandardCameras::from_session(
+            self,
+            ListenableSource::constant(SessionSnapshot::VIEWPORT),
+        )
+        .unwrap();
+        let camera = cameras.cameras().world.clone();
+        let mut renderer =
+            RtRenderer::new(cameras, Box::new(|v| v), ListenableSource::constant(()));
+        if let Err(e) = renderer.update(None) {
+            // The image will be missing whatever could not be read; still worth having.
+            log::warn!("Error while rendering session capture: {e}");
+        }
+        let (image, _) = renderer.draw_rgba(|_| String::new());
+
+        SessionSnapshot {
+            member_names,
+            universe: UniverseSnapshot::new(universe),
+            graphics_options: GraphicsOptions::clone(&self.graphics_options.get()),
+            camera,
+            image,
+        }
+    }
+}
+
+/// The state of a [`Session`] at some moment, as returned by [`Session::capture()`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct SessionSnapshot {
+    /// Names of all the members of the game [`Universe`], in sorted order.
+    pub member_names: Vec<Name>,
+    /// The block definitions and spaces of the game [`Universe`], in serializable form.
+    pub universe: UniverseSnapshot,
+    /// The graphics options in effect.
+    pub graphics_options: GraphicsOptions,
+    /// The camera viewing the game world from the player character's viewpoint.
+    /// Its viewport is [`SessionSnapshot::VIEWPORT`].
+    pub camera: Camera,
+    /// An image of the game world and user interface, as seen by `camera`.
+    pub image: RgbaImage,
+}
+
+impl SessionSnapshot {
+    /// The size of the image captured by [`Session::capture()`].
+    pub const VIEWPORT: Viewport = Viewport {
+        nominal_size: Vector2::new(320.0, 240.0),
+        framebuffer_size: Vector2::new(320, 240),
+    };
 }
 
 /// A message sent to the [`Session`], such as from a user interface element.
@@ -407,10 +504,117 @@ impl<T: CustomFormat<StatusText>> fmt::Display for InfoText<'_, T> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::space::Space;
-    use crate::universe::{Name, Universe, UniverseIndex};
+    use crate::behavior::{Behavior, BehaviorContext};
+    use crate::math::Rgb;
+    use crate::space::{Grid, Space};
+    use crate::universe::{
+        Name, RefVisitor, Universe, UniverseIndex, UniverseTransaction, VisitRefs,
+    };
     use futures_channel::oneshot;
     use futures_executor::block_on;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn capture() {
+        let mut universe = Universe::new();
+        let space = universe
+            .insert(
+                "space".into(),
+                Space::builder(Grid::new([0, 0, 0], [1, 1, 1]))
+                    .sky_color(Rgb::new(1.0, 0.0, 0.0))
+                    .build_empty(),
+            )
+            .unwrap();
+        universe
+            .insert("character".into(), Character::spawn_default(space))
+            .unwrap();
+        let mut session = block_on(Session::new());
+        session.set_universe(universe);
+
+        let snapshot = session.capture();
+
+        assert_eq!(
+            snapshot.member_names,
+            vec![Name::from("character"), Name::from("space")]
+        );
+        assert_eq!(
+            snapshot.image.dimensions(),
+            SessionSnapshot::VIEWPORT.framebuffer_size.into()
+        );
+        assert_eq!(snapshot.camera.viewport(), SessionSnapshot::VIEWPORT);
+        // The snapshot of the universe includes the space's contents.
+        let universe_json = serde_json::to_value(&snapshot.universe).unwrap();
+        assert_eq!(universe_json["spaces"][0][0], "'space'");
+        assert_eq!(
+            universe_json["spaces"][0][1]["bounds"]["size"],
+            serde_json::json!([1, 1, 1])
+        );
+        // The image shows the space's distinctive sky.
+        let pixel = snapshot.image.get_pixel(0, 0).0;
+        assert!(
+            matches!(pixel, [200..=255, 0..=50, 0..=50, 255]),
+            "{pixel:?}"
+        );
+    }
+
+    #[test]
+    fn pause_and_single_step() {
+        /// Counts the steps its host character experiences.
+        #[derive(Debug)]
+        struct StepCounter(Arc<AtomicUsize>);
+        impl Behavior<Character> for StepCounter {
+            fn step(&self, _: &BehaviorContext<'_, Character>, _: Tick) -> UniverseTransaction {
+                self.0.fetch_add(1, Ordering::Relaxed);
+                UniverseTransaction::default()
+            }
+            fn alive(&self, _: &BehaviorContext<'_, Character>) -> bool {
+                true
+            }
+            fn ephemeral(&self) -> bool {
+                true
+            }
+        }
+        impl VisitRefs for StepCounter {
+            fn visit_refs(&self, _: &mut dyn RefVisitor) {}
+        }
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let mut universe = Universe::new();
+        let space = universe.insert_anonymous(Space::empty_positive(1, 1, 1));
+        let mut character = Character::spawn_default(space);
+        character.add_behavior(StepCounter(count.clone()));
+        universe.insert("character".into(), character).unwrap();
+        let mut session = block_on(Session::new());
+        session.set_universe(universe);
+        let step_length = session.frame_clock.tick().delta_t;
+
+        // Confirm that unpaused stepping is counted.
+        assert!(!session.is_paused());
+        session.frame_clock.advance_by(step_length);
+        assert!(session.maybe_step_universe().is_some());
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+
+        session.set_paused(true);
+        assert!(session.is_paused());
+        for _ in 0..5 {
+            session.frame_clock.advance_by(step_length);
+            assert!(session.maybe_step_universe().is_some());
+        }
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+
+        session.single_step();
+        assert_eq!(count.load(Ordering::Relaxed), 2);
+        assert!(session.is_paused());
+
+        session.frame_clock.advance_by(step_length);
+        session.maybe_step_universe();
+        assert_eq!(count.load(Ordering::Relaxed), 2);
+
+        session.set_paused(false);
+        session.frame_clock.advance_by(step_length);
+        session.maybe_step_universe();
+        assert_eq!(count.load(Ordering::Relaxed), 3);
+    }
 
     #[test]
     fn set_universe_async() {
@@ -54,7 +54,7 @@ impl<T> Layers<T> {
 ///
 /// All of its data is provided through [`ListenableSource`]s, and consists of:
 ///
-/// * [`GraphicsOptions`].
+/// * [`GraphicsOptions`], and optionally different [`GraphicsOptions`] for each layer.
 /// * A [`Viewport`] specifying the dimensions of image to render.
 /// * A [`URef`] to the [`Character`] whose eyes we look through to render the “world”
 ///   [`Space`].
@@ -68,14 +68,21 @@ impl<T> Layers<T> {
 /// Every [`StandardCameras`] which was created with the same sources will have the same
 /// results (after `update()`).
 ///
+/// Additional cameras, such as for a minimap or other overlay, may be added with
+/// [`StandardCameras::add_extra_layer()`]. Each follows the same [`GraphicsOptions`]
+/// but has its own [`Viewport`] source; its view transform is left to the application.
+///
 /// Design note: The sense in which this is “standard” is that if an application wished
 /// to, for example, have multiple views into the same [`Space`], it would need to create
 /// additional [`Camera`]s (or multiple [`StandardCameras`]) and update them itself.
 #[derive(Debug)]
 pub struct StandardCameras {
-    /// Cameras are synced with this
+    /// Cameras are synced with this, except where overridden by `layer_options`.
     graphics_options: ListenableSource<GraphicsOptions>,
-    graphics_options_dirty: DirtyFlag,
+    /// Per-layer replacements for `graphics_options`.
+    layer_options: Layers<Option<ListenableSource<GraphicsOptions>>>,
+    /// Tracks whether the options source in effect for each layer has changed.
+    graphics_options_dirty: Layers<DirtyFlag>,
 
     character_source: ListenableSource<Option<URef<Character>>>,
     /// Tracks whether the character was replaced (not whether its view changed).
@@ -93,29 +100,59 @@ pub struct StandardCameras {
     viewport_dirty: DirtyFlag,
 
     cameras: Layers<Camera>,
+
+    /// Cameras added with [`Self::add_extra_layer()`], in the order they were added.
+    extra_layers: Vec<ExtraLayer>,
+}
+
+/// A camera in [`StandardCameras`] beyond the world and UI layers.
+#[derive(Debug)]
+struct ExtraLayer {
+    name: String,
+    viewport_source: ListenableSource<Viewport>,
+    viewport_dirty: DirtyFlag,
+    camera: Camera,
 }
 
 impl StandardCameras {
     /// Most general constructor; hidden because the details needed might vary and so we
     /// want to discourage use of this directly.
+    ///
+    /// Each layer whose `layer_options` is [`Some`] uses those options as-is instead of
+    /// deriving them from `graphics_options`.
     #[doc(hidden)]
     pub fn new(
         graphics_options: ListenableSource<GraphicsOptions>,
         viewport_source: ListenableSource<Viewport>,
         character_source: ListenableSource<Option<URef<Character>>>,
         ui_space_source: ListenableSource<Option<URef<Space>>>,
+        layer_options: Layers<Option<ListenableSource<GraphicsOptions>>>,
     ) -> Result<Self, std::convert::Infallible> {
         // TODO: Add a unit test that each of these listeners works as intended.
         // TODO: This is also an awful lot of repetitive code; we should design a pattern
         // to not have it (some kind of "following cell")?
-        let graphics_options_dirty = DirtyFlag::listening(false, |l| graphics_options.listen(l));
+        let graphics_options_dirty = layer_options.as_refs().map(|layer_source| {
+            let source = layer_source.as_ref().unwrap_or(&graphics_options);
+            DirtyFlag::listening(false, |l| source.listen(l))
+        });
         let viewport_dirty = DirtyFlag::listening(false, |l| viewport_source.listen(l));
 
-        let initial_options: &GraphicsOptions = &*graphics_options.get();
         let initial_viewport: Viewport = *viewport_source.get();
 
         let mut this = Self {
+            cameras: Layers {
+                ui: Camera::new(
+                    Self::ui_options(&graphics_options, &layer_options),
+                    initial_viewport,
+                ),
+                world: Camera::new(
+                    Self::world_options(&graphics_options, &layer_options),
+                    initial_viewport,
+                ),
+            },
+
             graphics_options,
+            layer_options,
             graphics_options_dirty,
 
             character_dirty: DirtyFlag::listening(true, |l| character_source.listen(l)),
@@ -130,13 +167,7 @@ impl StandardCameras {
             viewport_dirty,
             viewport_source,
 
-            cameras: Layers {
-                ui: Camera::new(
-                    Vui::graphics_options(initial_options.clone()),
-                    initial_viewport,
-                ),
-                world: Camera::new(initial_options.clone(), initial_viewport),
-            },
+            extra_layers: Vec::new(),
         };
 
         this.update();
@@ -147,12 +178,27 @@ impl StandardCameras {
     pub fn from_session(
         session: &Session,
         viewport_source: ListenableSource<Viewport>,
+    ) -> Result<Self, std::convert::Infallible> {
+        Self::from_session_with_layer_options(session, viewport_source, Layers::default())
+    }
+
+    /// Constructs a [`StandardCameras`] that will display, and track, the current state
+    /// of the [`Session`], except that each layer whose `layer_options` is [`Some`]
+    /// uses those options instead of the session's graphics options.
+    ///
+    /// For example, this may be used to give the world a different field of view than
+    /// the options the UI is derived from.
+    pub fn from_session_with_layer_options(
+        session: &Session,
+        viewport_source: ListenableSource<Viewport>,
+        layer_options: Layers<Option<ListenableSource<GraphicsOptions>>>,
     ) -> Result<Self, std::convert::Infallible> {
         Self::new(
             session.graphics_options(),
             viewport_source,
             session.character(),
             session.ui_space(),
+            layer_options,
         )
     }
 
@@ -167,26 +213,59 @@ impl StandardCameras {
             ListenableSource::constant(viewport),
             ListenableSource::constant(universe.get_default_character()),
             ListenableSource::constant(None),
+            Layers::default(),
         )
         .unwrap()
     }
 
+    /// Options for the world camera: the world override if present, or else the
+    /// shared options.
+    fn world_options(
+        graphics_options: &ListenableSource<GraphicsOptions>,
+        layer_options: &Layers<Option<ListenableSource<GraphicsOptions>>>,
+    ) -> GraphicsOptions {
+        layer_options
+            .world
+            .as_ref()
+            .unwrap_or(graphics_options)
+            .snapshot()
+    }
+
+    /// Options for the UI camera: the UI override if present, or else derived from
+    /// the shared options.
+    fn ui_options(
+        graphics_options: &ListenableSource<GraphicsOptions>,
+        layer_options: &Layers<Option<ListenableSource<GraphicsOptions>>>,
+    ) -> GraphicsOptions {
+        match &layer_options.ui {
+            Some(source) => source.snapshot(),
+            None => Vui::graphics_options(graphics_options.snapshot()),
+        }
+    }
+
     /// Updates camera state from data sources.
     ///
     /// This should be called at the beginning of each frame or as needed when the
     /// cameras are to be used.
     pub fn update(&mut self) {
-        let options_dirty = self.graphics_options_dirty.get_and_clear();
-        if options_dirty {
-            let current_options = self.graphics_options.snapshot();
-            self.cameras.world.set_options(current_options.clone());
-            self.cameras
-                .ui
-                .set_options(Vui::graphics_options(current_options));
+        let world_options_dirty = self.graphics_options_dirty.world.get_and_clear();
+        let ui_options_dirty = self.graphics_options_dirty.ui.get_and_clear();
+        if world_options_dirty {
+            self.cameras.world.set_options(Self::world_options(
+                &self.graphics_options,
+                &self.layer_options,
+            ));
         }
+        if ui_options_dirty {
+            self.cameras.ui.set_options(Self::ui_options(
+                &self.graphics_options,
+                &self.layer_options,
+            ));
+        }
+        let options_dirty = world_options_dirty || ui_options_dirty;
 
         let ui_space_dirty = self.ui_space_dirty.get_and_clear();
-        if ui_space_dirty || options_dirty {
+        if ui_space_dirty || ui_options_dirty {
             self.ui_space = if self.cameras.ui.options().show_ui {
                 self.ui_space_source.snapshot()
             } else {
@@ -226,6 +305,17 @@ impl StandardCameras {
             }
         }
 
+        for layer in &mut self.extra_layers {
+            if world_options_dirty {
+                layer
+                    .camera
+                    .set_options(self.cameras.world.options().clone());
+            }
+            if layer.viewport_dirty.get_and_clear() {
+                layer.camera.set_viewport(layer.viewport_source.snapshot());
+            }
+        }
+
         if let Some(character_ref) = &self.character {
             match character_ref.try_borrow() {
                 Ok(character) => {
@@ -262,6 +352,70 @@ impl StandardCameras {
         &self.cameras
     }
 
+    /// Adds a camera layer named `name`, beyond the world and UI layers, whose viewport
+    /// follows `viewport_source` and whose options follow the world layer's.
+    ///
+    /// The new camera's view transform is the identity; use
+    /// [`Self::extra_camera_mut()`] to set it. If a layer with the same name already
+    /// exists, it is replaced.
+    pub fn add_extra_layer(
+        &mut self,
+        name: impl Into<String>,
+        viewport_source: ListenableSource<Viewport>,
+    ) {
+        let name = name.into();
+        let layer = ExtraLayer {
+            viewport_dirty: DirtyFlag::listening(false, |l| viewport_source.listen(l)),
+            camera: Camera::new(
+                self.cameras.world.options().clone(),
+                viewport_source.snapshot(),
+            ),
+            name,
+            viewport_source,
+        };
+        match self.extra_layers.iter_mut().find(|l| l.name == layer.name) {
+            Some(existing) => *existing = layer,
+            None => self.extra_layers.push(layer),
+        }
+    }
+
+    /// Removes the camera layer named `name` that was added with
+    /// [`Self::add_extra_layer()`]. Returns whether there was such a layer.
+    pub fn remove_extra_layer(&mut self, name: &str) -> bool {
+        let count = self.extra_layers.len();
+        self.extra_layers.retain(|l| l.name != name);
+        self.extra_layers.len() != count
+    }
+
+    /// Returns the camera for the layer named `name` that was added with
+    /// [`Self::add_extra_layer()`].
+    pub fn extra_camera(&self, name: &str) -> Option<&Camera> {
+        self.extra_layers
+            .iter()
+            .find(|l| l.name == name)
+            .map(|l| &l.camera)
+    }
+
+    /// Returns mutable access to the camera for the layer named `name`, so that its
+    /// view transform may be set.
+    ///
+    /// Changes to its options or viewport will be overwritten by [`Self::update()`]
+    /// when the corresponding sources change.
+    pub fn extra_camera_mut(&mut self, name: &str) -> Option<&mut Camera> {
+        self.extra_layers
+            .iter_mut()
+            .find(|l| l.name == name)
+            .map(|l| &mut l.camera)
+    }
+
+    /// Returns the names and cameras of all layers added with
+    /// [`Self::add_extra_layer()`], in the order they were added.
+    pub fn extra_cameras(&self) -> impl Iterator<Item = (&str, &Camera)> + '_ {
+        self.extra_layers
+            .iter()
+            .map(|l| (l.name.as_str(), &l.camera))
+    }
+
     /// Returns the character's viewpoint to draw in the world layer.
     /// May be [`None`] if there is no current character.
     pub fn character(&self) -> Option<&URef<Character>> {
@@ -295,6 +449,13 @@ impl StandardCameras {
         self.cameras.world.viewport()
     }
 
+    /// Returns whether [`Self::viewport()`] is [valid](Viewport::is_valid).
+    /// If it is not, the camera matrices are meaningless and renderers should skip
+    /// drawing the frame.
+    pub fn viewport_is_valid(&self) -> bool {
+        self.viewport().is_valid()
+    }
+
     /// Returns a clone of the viewport source this is following.
     pub fn viewport_source(&self) -> ListenableSource<Viewport> {
         self.viewport_source.clone()
@@ -328,22 +489,40 @@ impl Clone for StandardCameras {
     /// Returns a [`StandardCameras`] which tracks the same data sources (graphics
     /// options, scene sources, viewport) as `self`, but whose local state (such as
     /// the last updated camera state) is independent.
+    ///
+    /// Extra layers are also copied, with the same viewport sources and current
+    /// view transforms.
     fn clone(&self) -> Self {
-        Self::new(
+        let mut new_self = Self::new(
             self.graphics_options.clone(),
             self.viewport_source.clone(),
             self.character_source.clone(),
             self.ui_space_source.clone(),
+            self.layer_options.clone(),
         )
-        .unwrap()
+        .unwrap();
+        for layer in &self.extra_layers {
+            let mut camera = layer.camera.clone();
+            camera.set_options(new_self.cameras.world.options().clone());
+            camera.set_viewport(layer.viewport_source.snapshot());
+            new_self.extra_layers.push(ExtraLayer {
+                name: layer.name.clone(),
+                viewport_dirty: DirtyFlag::listening(false, |l| layer.viewport_source.listen(l)),
+                viewport_source: layer.viewport_source.clone(),
+                camera,
+            });
+        }
+        new_self
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::camera::ViewTransform;
     use crate::space::Space;
     use crate::universe::{Universe, UniverseIndex};
+    use cgmath::{Point3, Vector2, Vector3};
     use futures_executor::block_on;
 
     #[test]
@@ -415,4 +594,113 @@ mod tests {
         assert_eq!(cameras.cameras().world.options(), &different_o);
         assert_eq!(cameras2.cameras().world.options(), &different_o);
     }
+
+    #[test]
+    fn world_options_override() {
+        let session = block_on(Session::new());
+        let default_o = GraphicsOptions::default();
+        let world_options = ListenableCell::new(default_o.clone());
+        let mut cameras = StandardCameras::from_session_with_layer_options(
+            &session,
+            ListenableSource::constant(Viewport::ARBITRARY),
+            Layers {
+                world: Some(world_options.as_source()),
+                ui: None,
+            },
+        )
+        .unwrap();
+        let initial_ui_options = cameras.cameras().ui.options().clone();
+
+        // Changing only the world override changes only the world camera.
+        let mut wide_o = default_o.clone();
+        wide_o.fov_y = notnan!(120.0);
+        world_options.set(wide_o.clone());
+        cameras.update();
+        assert_eq!(cameras.cameras().world.options(), &wide_o);
+        assert_eq!(cameras.cameras().ui.options(), &initial_ui_options);
+
+        // Changing the session options changes only the UI camera, since the world
+        // camera is overridden.
+        let mut different_o = default_o.clone();
+        different_o.debug_chunk_boxes = true;
+        session.graphics_options_mut().set(different_o.clone());
+        cameras.update();
+        assert_eq!(cameras.cameras().world.options(), &wide_o);
+        assert_eq!(
+            cameras.cameras().ui.options(),
+            &Vui::graphics_options(different_o)
+        );
+    }
+
+    #[test]
+    fn extra_layer_updates_independently() {
+        let session = block_on(Session::new());
+        let main_viewport = ListenableCell::new(Viewport::ARBITRARY);
+        let extra_viewport_1 = Viewport::with_scale(1.0, Vector2::new(10, 10));
+        let extra_viewport_2 = Viewport::with_scale(1.0, Vector2::new(20, 30));
+        let extra_viewport = ListenableCell::new(extra_viewport_1);
+        let mut cameras =
+            StandardCameras::from_session(&session, main_viewport.as_source()).unwrap();
+        cameras.add_extra_layer("minimap", extra_viewport.as_source());
+        assert_eq!(
+            cameras.extra_camera("minimap").unwrap().viewport(),
+            extra_viewport_1
+        );
+        assert!(cameras.extra_camera("other").is_none());
+
+        // Changing the extra viewport affects only the extra camera.
+        extra_viewport.set(extra_viewport_2);
+        cameras.update();
+        assert_eq!(
+            cameras.extra_camera("minimap").unwrap().viewport(),
+            extra_viewport_2
+        );
+        assert_eq!(cameras.cameras().world.viewport(), Viewport::ARBITRARY);
+        assert_eq!(cameras.cameras().ui.viewport(), Viewport::ARBITRARY);
+
+        // Changing the main viewport does not affect the extra camera.
+        main_viewport.set(extra_viewport_1);
+        cameras.update();
+        assert_eq!(cameras.cameras().world.viewport(), extra_viewport_1);
+        assert_eq!(
+            cameras.extra_camera("minimap").unwrap().viewport(),
+            extra_viewport_2
+        );
+
+        // The extra camera's view transform is left alone by updates.
+        let transform = ViewTransform {
+            disp: Vector3::new(1., 2., 3.),
+            ..ViewTransform::one()
+        };
+        cameras
+            .extra_camera_mut("minimap")
+            .unwrap()
+            .set_view_transform(transform);
+        cameras.update();
+        assert_eq!(
+            cameras.extra_camera("minimap").unwrap().view_position(),
+            Point3::new(1., 2., 3.)
+        );
+
+        // Graphics options are followed by the extra camera too.
+        let mut different_o = GraphicsOptions::default();
+        different_o.debug_chunk_boxes = true;
+        session.graphics_options_mut().set(different_o.clone());
+        cameras.update();
+        assert_eq!(
+            cameras.extra_camera("minimap").unwrap().options(),
+            &different_o
+        );
+
+        assert_eq!(
+            cameras
+                .extra_cameras()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>(),
+            vec!["minimap"]
+        );
+        assert!(cameras.remove_extra_layer("minimap"));
+        assert!(!cameras.remove_extra_layer("minimap"));
+        assert_eq!(cameras.extra_cameras().count(), 0);
+    }
 }
@@ -4,7 +4,7 @@
 //! Dynamic add-ons to game objects; we might also have called them “components”.
 
 use std::any::TypeId;
-use std::collections::BTreeMap;
+use std::collections::{hash_map, BTreeMap, BTreeSet, HashMap};
 use std::fmt::{self, Debug};
 use std::sync::Arc;
 
@@ -12,7 +12,9 @@ use downcast_rs::{impl_downcast, Downcast};
 use ordered_float::NotNan;
 
 use crate::character::{Character, CharacterTransaction};
+use crate::math::{GridCoordinate, GridPoint};
 use crate::physics::BodyTransaction;
+use crate::space::Grid;
 use crate::time::Tick;
 use crate::transaction::{
     CommitError, Merge, PreconditionFailed, Transaction, TransactionConflict, Transactional,
@@ -43,6 +45,18 @@ pub trait Behavior<H: Transactional + 'static>:
     /// unspecified.
     fn ephemeral(&self) -> bool;
 
+    /// Returns the region of the host which this behavior is concerned with, if it is
+    /// limited to one, so that [`BehaviorSet::query_region()`] can skip it when it is
+    /// not relevant. This is only meaningful for hosts which have such a coordinate
+    /// system, i.e. [`Space`](crate::space::Space).
+    ///
+    /// The value is read when the behavior is added to a [`BehaviorSet`], so it should
+    /// not change over the behavior's lifetime. The default implementation returns
+    /// [`None`], meaning the behavior is not limited to any region.
+    fn bounds(&self) -> Option<Grid> {
+        None
+    }
+
     // TODO: serialization, quiescence, incoming events...
 }
 
@@ -81,11 +95,17 @@ pub struct BehaviorSet<H> {
     /// Behaviors are stored in [`Arc`] so that they can be used in transactions in ways
     /// that would otherwise require `Clone + PartialEq`.
     items: Vec<Arc<dyn Behavior<H>>>,
+
+    /// Index of `items` by their [`Behavior::bounds()`].
+    region_index: RegionIndex,
 }
 
 impl<H: Transactional + 'static> BehaviorSet<H> {
     pub(crate) fn new() -> Self {
-        BehaviorSet { items: Vec::new() }
+        BehaviorSet {
+            items: Vec::new(),
+            region_index: RegionIndex::default(),
+        }
     }
 
     /// Add a behavior to the set.
@@ -93,9 +113,25 @@ impl<H: Transactional + 'static> BehaviorSet<H> {
     where
         B: Behavior<H> + 'static,
     {
+        self.region_index.push(behavior.bounds());
         self.items.push(Arc::new(behavior));
     }
 
+    /// Find behaviors whose [`bounds`](Behavior::bounds) intersect `region`, as well as
+    /// all behaviors which have no bounds.
+    ///
+    /// Behaviors are returned in the order they were added.
+    pub fn query_region(&self, region: Grid) -> impl Iterator<Item = &dyn Behavior<H>> + '_ {
+        self.region_index
+            .candidates(region)
+            .into_iter()
+            .filter(move |&index| match self.region_index.bounds[index] {
+                Some(bounds) => bounds.intersection(region).is_some(),
+                None => true,
+            })
+            .map(|index| &*self.items[index])
+    }
+
     /// Find behaviors of a specified type.
     ///
     /// TODO: We probably want other filtering strategies than just type, so this might change.
@@ -161,17 +197,124 @@ impl<H> std::fmt::Debug for BehaviorSet<H> {
 
 impl<H> VisitRefs for BehaviorSet<H> {
     fn visit_refs(&self, visitor: &mut dyn RefVisitor) {
-        let Self { items } = self;
+        let Self {
+            items,
+            region_index: _,
+        } = self;
         for behavior in items {
             behavior.visit_refs(visitor);
         }
     }
 }
 
-impl<H> Transactional for BehaviorSet<H> {
+impl<H: Transactional + 'static> Transactional for BehaviorSet<H> {
     type Transaction = BehaviorSetTransaction<H>;
 }
 
+/// Spatial index of the behaviors in a [`BehaviorSet`], by [`Behavior::bounds()`].
+///
+/// Space is divided into cubical buckets of side [`Self::BUCKET_SIZE`], and each bounded
+/// behavior is listed in every bucket its bounds intersect. Behaviors which are
+/// unbounded, or whose bounds cover too many buckets, are listed in `everywhere` instead
+/// and are always candidates.
+#[derive(Debug, Default)]
+struct RegionIndex {
+    /// The bounds of each behavior, in the same order as [`BehaviorSet::items`].
+    bounds: Vec<Option<Grid>>,
+    /// Indices of behaviors which are candidates for every query.
+    everywhere: BTreeSet<usize>,
+    /// Indices of behaviors whose bounds intersect each bucket, keyed by bucket
+    /// coordinates (cube coordinates divided by [`Self::BUCKET_SIZE`]).
+    buckets: HashMap<GridPoint, BTreeSet<usize>>,
+}
+
+impl RegionIndex {
+    const BUCKET_SIZE: GridCoordinate = 16;
+    /// Behaviors whose bounds cover more buckets than this are put in `everywhere`.
+    const MAX_BUCKETS_PER_BEHAVIOR: usize = 64;
+
+    fn push(&mut self, bounds: Option<Grid>) {
+        let index = self.bounds.len();
+        self.bounds.push(bounds);
+        self.add(index);
+    }
+
+    fn replace(&mut self, index: usize, bounds: Option<Grid>) {
+        self.remove(index);
+        self.bounds[index] = bounds;
+        self.add(index);
+    }
+
+    /// Returns the buckets that `grid` intersects, or [`None`] if it is empty.
+    fn buckets_of(grid: Grid) -> Option<Grid> {
+        if grid.is_empty() {
+            None
+        } else {
+            Some(grid.divide(Self::BUCKET_SIZE))
+        }
+    }
+
+    fn add(&mut self, index: usize) {
+        match self.bounds[index] {
+            None => {
+                self.everywhere.insert(index);
+            }
+            Some(bounds) => match Self::buckets_of(bounds) {
+                Some(buckets) if buckets.volume() > Self::MAX_BUCKETS_PER_BEHAVIOR => {
+                    self.everywhere.insert(index);
+                }
+                Some(buckets) => {
+                    for bucket in buckets.interior_iter() {
+                        self.buckets.entry(bucket).or_default().insert(index);
+                    }
+                }
+                // Empty bounds never intersect anything.
+                None => {}
+            },
+        }
+    }
+
+    fn remove(&mut self, index: usize) {
+        self.everywhere.remove(&index);
+        if let Some(buckets) = self.bounds[index].and_then(Self::buckets_of) {
+            if buckets.volume() <= Self::MAX_BUCKETS_PER_BEHAVIOR {
+                for bucket in buckets.interior_iter() {
+                    if let hash_map::Entry::Occupied(mut entry) = self.buckets.entry(bucket) {
+                        entry.get_mut().remove(&index);
+                        if entry.get().is_empty() {
+                            entry.remove();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the indices of all behaviors which might intersect `region`, in
+    /// increasing order.
+    fn candidates(&self, region: Grid) -> BTreeSet<usize> {
+        let mut candidates = self.everywhere.clone();
+        if let Some(region_buckets) = Self::buckets_of(region) {
+            // Look up the region's buckets individually, unless there are more of them
+            // than there are occupied buckets.
+            if region_buckets.volume() <= self.buckets.len() {
+                for bucket in region_buckets.interior_iter() {
+                    if let Some(indices) = self.buckets.get(&bucket) {
+                        candidates.extend(indices);
+                    }
+                }
+            } else {
+                for (&bucket, indices) in &self.buckets {
+                    if region_buckets.contains_cube(bucket) {
+                        candidates.extend(indices);
+                    }
+                }
+            }
+        }
+        candidates
+    }
+}
+
 #[derive(Debug)]
 pub struct BehaviorSetTransaction<H> {
     replace: BTreeMap<usize, Arc<dyn Behavior<H>>>,
@@ -202,7 +345,7 @@ impl<H> BehaviorSetTransaction<H> {
     }
 }
 
-impl<H> Transaction<BehaviorSet<H>> for BehaviorSetTransaction<H> {
+impl<H: Transactional + 'static> Transaction<BehaviorSet<H>> for BehaviorSetTransaction<H> {
     type CommitCheck = ();
     type Output = ();
 
@@ -224,9 +367,13 @@ impl<H> Transaction<BehaviorSet<H>> for BehaviorSetTransaction<H> {
         (): Self::CommitCheck,
     ) -> Result<(), CommitError> {
         for (index, new) in &self.replace {
+            target.region_index.replace(*index, new.bounds());
             target.items[*index] = new.clone();
         }
-        target.items.extend(self.insert.iter().cloned());
+        for new in &self.insert {
+            target.region_index.push(new.bounds());
+            target.items.push(new.clone());
+        }
         Ok(())
     }
 }
@@ -446,4 +593,59 @@ mod tests {
             vec![&Q(Expected)],
         )
     }
+
+    #[test]
+    fn query_region() {
+        #[derive(Debug, Eq, PartialEq)]
+        struct Bounded(Option<Grid>);
+        impl Behavior<Space> for Bounded {
+            fn alive(&self, _context: &BehaviorContext<'_, Space>) -> bool {
+                true
+            }
+            fn ephemeral(&self) -> bool {
+                false
+            }
+            fn bounds(&self) -> Option<Grid> {
+                self.0
+            }
+        }
+        impl VisitRefs for Bounded {
+            // No references
+            fn visit_refs(&self, _visitor: &mut dyn RefVisitor) {}
+        }
+
+        let near = Grid::new([0, 0, 0], [2, 2, 2]);
+        let far = Grid::new([98, 98, 98], [2, 2, 2]);
+        let mut set = BehaviorSet::<Space>::new();
+        set.insert(Bounded(Some(near)));
+        set.insert(Bounded(None));
+        set.insert(Bounded(Some(Grid::new([0, 0, 0], [100, 100, 100]))));
+
+        let query = |region: Grid| -> Vec<&Bounded> {
+            set.query_region(region)
+                .map(|b| b.downcast_ref::<Bounded>().unwrap())
+                .collect()
+        };
+        assert_eq!(
+            query(near),
+            vec![
+                &Bounded(Some(near)),
+                &Bounded(None),
+                &Bounded(Some(Grid::new([0, 0, 0], [100, 100, 100]))),
+            ]
+        );
+        assert_eq!(
+            query(far),
+            vec![
+                &Bounded(None),
+                &Bounded(Some(Grid::new([0, 0, 0], [100, 100, 100]))),
+            ]
+        );
+        // Adjacent but not overlapping
+        assert_eq!(
+            query(Grid::new([2, 0, 0], [1, 1, 1])).len(),
+            2,
+            "should not include near"
+        );
+    }
 }
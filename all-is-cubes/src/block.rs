@@ -281,15 +281,34 @@ impl Block {
     /// Converts this `Block` into a “flattened” and snapshotted form which contains all
     /// information needed for rendering and physics, and does not require [`URef`] access
     /// to other objects.
+    ///
+    /// This is equivalent to [`Block::evaluate_limited`] with [`EvalBudget::default()`].
     pub fn evaluate(&self) -> Result<EvaluatedBlock, EvalBlockError> {
-        self.evaluate_impl(0)
+        self.evaluate_limited(EvalBudget::default())
+    }
+
+    /// As [`Block::evaluate`], but with an explicit limit on the amount of recursion
+    /// and voxel data processed, so that untrusted block definitions may be evaluated
+    /// without risking excessive time or memory usage.
+    ///
+    /// Exceeding the recursion limit produces [`EvalBlockError::StackOverflow`], and
+    /// exceeding the voxel limit produces [`EvalBlockError::BudgetExceeded`].
+    pub fn evaluate_limited(
+        &self,
+        mut budget: EvalBudget,
+    ) -> Result<EvaluatedBlock, EvalBlockError> {
+        self.evaluate_impl(&mut budget)
     }
 
     #[inline]
-    fn evaluate_impl(&self, depth: u8) -> Result<EvaluatedBlock, EvalBlockError> {
+    fn evaluate_impl(&self, budget: &mut EvalBudget) -> Result<EvaluatedBlock, EvalBlockError> {
         let mut value: EvaluatedBlock = match *self.primitive() {
             Primitive::Indirect(ref def_ref) => {
-                def_ref.try_borrow()?.evaluate_impl(next_depth(depth)?)?
+                let def = def_ref.try_borrow()?;
+                budget.enter()?;
+                let value = def.evaluate_impl(budget)?;
+                budget.exit();
+                value
             }
 
             Primitive::Atom(ref attributes, color) => {
@@ -324,13 +343,19 @@ impl Block {
                 let occupied_grid = full_resolution_grid
                     .intersection(block_space.grid())
                     .unwrap_or_else(|| Grid::new(offset, [1, 1, 1]) /* arbitrary value */);
+                budget.spend_voxels(occupied_grid.volume())?;
 
+                let voxel_collision = attributes.voxel_collision;
                 let voxels = block_space
                     .extract(
                         occupied_grid,
                         #[inline(always)]
                         |_index, sub_block_data, _lighting| {
-                            Evoxel::from_block(sub_block_data.evaluated())
+                            let mut voxel = Evoxel::from_block(sub_block_data.evaluated());
+                            if let Some(collision) = voxel_collision {
+                                voxel.collision = collision;
+                            }
+                            voxel
                         },
                     )
                     .translate(-offset.to_vec());
@@ -340,8 +365,7 @@ impl Block {
         };
 
         for (index, modifier) in self.modifiers().iter().enumerate() {
-            // TODO: Extend recursion depth model to catch stacking up lots of modifiers
-            value = modifier.evaluate(self, index, value, depth)?;
+            value = modifier.evaluate(self, index, value, budget)?;
         }
 
         Ok(value)
@@ -405,6 +429,9 @@ impl Block {
                         SpaceChange::BlockValue(_) => Some(BlockChange::new()),
                         SpaceChange::Lighting(_) => None,
                         SpaceChange::Number(_) => None,
+                        SpaceChange::Physics => None,
+                        SpaceChange::Spawn => None,
+                        SpaceChange::MoveCompleted(_) => None,
                     }
                 }));
             }
@@ -530,10 +557,14 @@ const AIR_ATTRIBUTES: BlockAttributes = BlockAttributes {
     display_name: Cow::Borrowed("<air>"),
     selectable: false,
     collision: BlockCollision::None,
+    voxel_collision: None,
     rotation_rule: RotationPlacementRule::Never,
     light_emission: Rgb::ZERO,
     tick_action: None,
+    activation_action: None,
     animation_hint: AnimationHint::UNCHANGING,
+    disable_culling: false,
+    monitor: None,
 };
 
 /// Given the `resolution` of some recursive block occupying `cube`, transform `ray`
@@ -6,11 +6,15 @@
 use std::borrow::Cow;
 use std::fmt;
 
-use crate::drawing::VoxelBrush;
-use crate::math::{Face6, Rgb};
+use cgmath::{Decomposed, InnerSpace as _, Point3, Transform as _, Vector2, Vector3};
+use ordered_float::NotNan;
 
-#[cfg(doc)]
-use crate::{block::Block, space::Space};
+use crate::block::{Block, Resolution};
+use crate::camera::{Camera, GraphicsOptions, Viewport};
+use crate::drawing::VoxelBrush;
+use crate::math::{Face6, FreeCoordinate, GridPoint, GridVector, Rgb};
+use crate::space::{Space, SpaceTransaction};
+use crate::universe::{RefVisitor, URef, VisitRefs};
 
 /// Collection of miscellaneous attribute data for blocks that doesn't come in variants.
 ///
@@ -35,6 +39,18 @@ pub struct BlockAttributes {
     /// The default value is [`BlockCollision::Hard`].
     pub collision: BlockCollision,
 
+    /// If present, overrides the collision of every voxel of a [`Primitive::Recur`] block,
+    /// which is otherwise taken from the [`collision`](Self::collision) attribute of the
+    /// block occupying that voxel.
+    ///
+    /// This allows, for example, a block built from decorative non-colliding blocks to be
+    /// solid where it has voxels, when used with [`BlockCollision::Recur`].
+    ///
+    /// The default value is [`None`].
+    ///
+    /// [`Primitive::Recur`]: crate::block::Primitive::Recur
+    pub voxel_collision: Option<BlockCollision>,
+
     /// Rule about how this block should be rotated, or not, when placed in a [`Space`] by
     /// some agent not otherwise specifying rotation.
     ///
@@ -48,14 +64,34 @@ pub struct BlockAttributes {
 
     /// Something this block does when time passes.
     ///
-    /// Currently the only possibility is “turn into another block”.
+    /// The default value is [`None`].
+    ///
+    /// TODO: This needs time/probability options.
+    pub tick_action: Option<TickAction>,
+
+    /// Something this block does when it is activated, such as by
+    /// [`Tool::Activate`](crate::inv::Tool::Activate).
     ///
-    /// TODO: Very placeholder. This needs more possible effects and also time/probability options.
-    pub tick_action: Option<VoxelBrush<'static>>,
+    /// The default value is [`None`].
+    pub activation_action: Option<TickAction>,
 
     /// Advice to the renderer about how to expect this block to change, and hence
     /// what rendering strategy to use.
     pub animation_hint: AnimationHint,
+
+    /// Whether the block's geometry should be exempt from being hidden by adjacent
+    /// opaque blocks, and should not itself hide adjacent blocks' faces.
+    ///
+    /// This is intended for blocks whose shapes are not cube-like, such as decals or
+    /// cross-shaped plants, for which culling would be incorrect or merely unhelpful.
+    ///
+    /// The default value is `false`.
+    pub disable_culling: bool,
+
+    /// If present, one face of the block displays a live view of another [`Space`].
+    ///
+    /// The default value is [`None`].
+    pub monitor: Option<MonitorView>,
     //
     // Reminder: When adding new fields, add them to the Debug implementation
     // and BlockBuilder.
@@ -81,6 +117,9 @@ impl fmt::Debug for BlockAttributes {
             if self.collision != Self::default().collision {
                 s.field("collision", &self.collision);
             }
+            if self.voxel_collision != Self::default().voxel_collision {
+                s.field("voxel_collision", &self.voxel_collision);
+            }
             if self.rotation_rule != Self::default().rotation_rule {
                 s.field("rotation_rule", &self.rotation_rule);
             }
@@ -90,9 +129,18 @@ impl fmt::Debug for BlockAttributes {
             if self.tick_action != Self::default().tick_action {
                 s.field("tick_action", &self.tick_action);
             }
+            if self.activation_action != Self::default().activation_action {
+                s.field("activation_action", &self.activation_action);
+            }
             if self.animation_hint != Self::default().animation_hint {
                 s.field("animation_hint", &self.animation_hint);
             }
+            if self.disable_culling != Self::default().disable_culling {
+                s.field("disable_culling", &self.disable_culling);
+            }
+            if self.monitor != Self::default().monitor {
+                s.field("monitor", &self.monitor);
+            }
             s.finish()
         }
     }
@@ -108,10 +156,14 @@ impl BlockAttributes {
             display_name: Cow::Borrowed(""),
             selectable: true,
             collision: BlockCollision::Hard,
+            voxel_collision: None,
             rotation_rule: RotationPlacementRule::Never,
             light_emission: Rgb::ZERO,
             tick_action: None,
+            activation_action: None,
             animation_hint: AnimationHint::UNCHANGING,
+            disable_culling: false,
+            monitor: None,
         }
     }
 }
@@ -124,6 +176,16 @@ impl Default for BlockAttributes {
     }
 }
 
+impl VisitRefs for BlockAttributes {
+    fn visit_refs(&self, visitor: &mut dyn RefVisitor) {
+        // TODO: tick_action and activation_action may also contain refs, but Block
+        // doesn't implement VisitRefs
+        if let Some(monitor) = &self.monitor {
+            visitor.visit(&monitor.space);
+        }
+    }
+}
+
 #[cfg(feature = "arbitrary")]
 impl<'a> arbitrary::Arbitrary<'a> for BlockAttributes {
     fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
@@ -131,10 +193,14 @@ impl<'a> arbitrary::Arbitrary<'a> for BlockAttributes {
             display_name: Cow::Owned(u.arbitrary()?),
             selectable: u.arbitrary()?,
             collision: u.arbitrary()?,
+            voxel_collision: u.arbitrary()?,
             rotation_rule: u.arbitrary()?,
             light_emission: u.arbitrary()?,
-            tick_action: None, // TODO: need Arbitrary for Block
+            tick_action: None,       // TODO: need Arbitrary for Block
+            activation_action: None, // TODO: need Arbitrary for Block
             animation_hint: u.arbitrary()?,
+            disable_culling: u.arbitrary()?,
+            monitor: None, // TODO: need Arbitrary for URef
         })
     }
 
@@ -143,16 +209,81 @@ impl<'a> arbitrary::Arbitrary<'a> for BlockAttributes {
             String::size_hint(depth),
             bool::size_hint(depth),
             BlockCollision::size_hint(depth),
+            Option::<BlockCollision>::size_hint(depth),
             RotationPlacementRule::size_hint(depth),
             Rgb::size_hint(depth),
             AnimationHint::size_hint(depth),
+            bool::size_hint(depth),
         ])
     }
 }
 
+/// Something a block does when time passes or when it is activated; the value of
+/// [`BlockAttributes::tick_action`] and [`BlockAttributes::activation_action`].
+///
+/// All offsets are relative to the position of the cube containing the block.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum TickAction {
+    /// Paint the brush, with its origin at the block's own position.
+    ///
+    /// As with [`VoxelBrush::paint_transaction()`], if any of the affected cubes are out
+    /// of bounds, the action fails and will be retried.
+    Paint(VoxelBrush<'static>),
+
+    /// Replace the block with the given block.
+    Replace(Block),
+
+    /// Do nothing if the cube at `offset` is solid, and otherwise perform `action`.
+    ///
+    /// A cube is solid if its block's [`BlockAttributes::collision`] is anything but
+    /// [`BlockCollision::None`], or if it is outside the bounds of the [`Space`].
+    ///
+    /// If the cube is solid, the condition is checked again on every following step
+    /// until it is not.
+    ///
+    /// TODO: This is done by polling; there is not yet any way to wait for the neighbor
+    /// to change.
+    UnlessSolid {
+        /// Offset of the cube to check.
+        offset: GridVector,
+        /// Action to perform if that cube is not solid.
+        action: Box<TickAction>,
+    },
+}
+
+impl TickAction {
+    /// Computes the effect of this action on the block at `cube` in `space`, or [`None`]
+    /// if it currently does nothing.
+    pub(crate) fn transaction(&self, space: &Space, cube: GridPoint) -> Option<SpaceTransaction> {
+        match self {
+            TickAction::Paint(brush) => Some(brush.paint_transaction(cube)),
+            TickAction::Replace(block) => {
+                Some(SpaceTransaction::set_cube(cube, None, Some(block.clone())))
+            }
+            TickAction::UnlessSolid { offset, action } => {
+                let neighbor = cube + offset;
+                let solid = !space.grid().contains_cube(neighbor)
+                    || space.get_evaluated(neighbor).attributes.collision != BlockCollision::None;
+                if solid {
+                    None
+                } else {
+                    action.transaction(space, cube)
+                }
+            }
+        }
+    }
+}
+
+impl From<VoxelBrush<'static>> for TickAction {
+    fn from(brush: VoxelBrush<'static>) -> Self {
+        TickAction::Paint(brush)
+    }
+}
+
 /// Specifies the effect on a [`Body`](crate::physics::Body) of colliding with the
 /// [`Block`] this applies to.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, serde::Serialize, serde::Deserialize)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[non_exhaustive]
 pub enum BlockCollision {
@@ -177,7 +308,7 @@ pub enum BlockCollision {
 /// TODO: We may want to replace this with a struct that also carries declared symmetries
 /// ("this is a vertical pillar so never make it upside down") and/or prohibited rotations
 /// rather than requiring each individual rule variant to be sufficiently expressive.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, serde::Serialize, serde::Deserialize)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[non_exhaustive]
 pub enum RotationPlacementRule {
@@ -197,7 +328,7 @@ pub enum RotationPlacementRule {
 /// Specifies how the appearance of a [`Block`] might change, for the benefit of rendering
 /// algorithms. This hint applies both to a block's definition changing and to it being
 /// replaced with some successor block.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, serde::Serialize, serde::Deserialize)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct AnimationHint {
     /// Expect that the block might soon be replaced with an unrelated block.
@@ -248,6 +379,29 @@ impl AnimationHint {
         ..Self::UNCHANGING
     };
 
+    /// Returns whether the block might soon be replaced with an unrelated block.
+    ///
+    /// Suggestion: avoid combining it with other block meshes.
+    pub const fn expect_replace(&self) -> bool {
+        self.expect_replace
+    }
+
+    /// Returns whether the block's shape is expected to change; some of its voxels will
+    /// not be the same [`OpacityCategory`](crate::math::OpacityCategory).
+    ///
+    /// Suggestion: use a rendering strategy which is shape-independent.
+    pub const fn expect_shape_update(&self) -> bool {
+        self.expect_shape_update
+    }
+
+    /// Returns whether the block's voxels' colors (and alpha other than the special 0
+    /// and 1 cases) are expected to change.
+    ///
+    /// Suggestion: prepare to update texturing without unnecesarily regenerating the mesh.
+    pub const fn expect_color_update(&self) -> bool {
+        self.expect_color_update
+    }
+
     /// Returns whether this block's value for [`EvaluatedBlock::visible`] is likely to
     /// change from `false` to `true`.
     pub(crate) fn might_become_visible(&self) -> bool {
@@ -260,3 +414,87 @@ impl Default for AnimationHint {
         Self::UNCHANGING
     }
 }
+
+/// Specifies that one face of a [`Block`] displays a live view of a [`Space`] as seen
+/// from a fixed viewpoint, like an in-world screen or monitor.
+///
+/// Currently, only the [raytracer](crate::raytracer) draws monitors; other renderers
+/// draw the block as if this attribute were absent.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct MonitorView {
+    /// The space to be viewed.
+    pub space: URef<Space>,
+    /// The face of the block on which the view is displayed.
+    pub face: Face6,
+    /// The position of the viewpoint, in `space`'s coordinates.
+    pub eye: Point3<NotNan<FreeCoordinate>>,
+    /// The point which will be at the center of the displayed image.
+    pub look_at: Point3<NotNan<FreeCoordinate>>,
+    /// Vertical field of view, in degrees.
+    pub fov_y: NotNan<FreeCoordinate>,
+    /// Number of texels along each edge of the displayed image.
+    pub resolution: Resolution,
+}
+
+impl MonitorView {
+    /// Constructs a [`MonitorView`] with a 90° field of view and a resolution of 16.
+    ///
+    /// Panics if any of the coordinates are NaN.
+    pub fn new(
+        space: URef<Space>,
+        face: Face6,
+        eye: impl Into<Point3<FreeCoordinate>>,
+        look_at: impl Into<Point3<FreeCoordinate>>,
+    ) -> Self {
+        Self {
+            space,
+            face,
+            eye: eye.into().map(|c| NotNan::new(c).unwrap()),
+            look_at: look_at.into().map(|c| NotNan::new(c).unwrap()),
+            fov_y: NotNan::new(90.0).unwrap(),
+            resolution: 16,
+        }
+    }
+
+    /// Returns a [`Camera`] whose viewport is the texels of the displayed image and which
+    /// is positioned as specified by this view.
+    pub fn camera(&self, mut graphics_options: GraphicsOptions) -> Camera {
+        let resolution = self.resolution.max(1);
+        graphics_options.fov_y = self.fov_y;
+        let mut camera = Camera::new(
+            graphics_options,
+            Viewport {
+                nominal_size: Vector2::new(resolution.into(), resolution.into()),
+                framebuffer_size: Vector2::new(resolution.into(), resolution.into()),
+            },
+        );
+
+        let eye = self.eye.map(NotNan::into_inner);
+        let look_at = self.look_at.map(NotNan::into_inner);
+        // Pick an up vector which is not parallel to the view direction.
+        let direction = look_at - eye;
+        let up = if direction.normalize().y.abs() > 0.99 {
+            Vector3::unit_z()
+        } else {
+            Vector3::unit_y()
+        };
+        if let Some(transform) = Decomposed::look_at_rh(eye, look_at, up).inverse_transform() {
+            camera.set_view_transform(transform);
+        }
+        camera
+    }
+
+    /// Returns the directions, in the block's coordinates, which correspond to the
+    /// displayed image's rightward and upward directions, as seen by a viewer facing
+    /// [`Self::face`].
+    pub(crate) fn face_axes(&self) -> (Vector3<FreeCoordinate>, Vector3<FreeCoordinate>) {
+        let up = match self.face {
+            Face6::PY => Face6::NZ,
+            Face6::NY => Face6::PZ,
+            _ => Face6::PY,
+        };
+        let right = self.face.opposite().cross(up);
+        (right.normal_vector(), up.normal_vector())
+    }
+}
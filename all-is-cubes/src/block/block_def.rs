@@ -86,8 +86,13 @@ impl VisitRefs for Primitive {
     fn visit_refs(&self, visitor: &mut dyn RefVisitor) {
         match self {
             Primitive::Indirect(block_ref) => visitor.visit(block_ref),
-            Primitive::Atom(_, _) => {}
-            Primitive::Recur { space, .. } => visitor.visit(space),
+            Primitive::Atom(attributes, _) => attributes.visit_refs(visitor),
+            Primitive::Recur {
+                attributes, space, ..
+            } => {
+                attributes.visit_refs(visitor);
+                visitor.visit(space);
+            }
         }
     }
 }
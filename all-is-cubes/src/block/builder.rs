@@ -9,9 +9,8 @@ use std::sync::Arc;
 
 use crate::block::{
     AnimationHint, Block, BlockAttributes, BlockCollision, BlockDef, BlockParts, BlockPtr,
-    Modifier, Primitive, Resolution, RotationPlacementRule,
+    Modifier, MonitorView, Primitive, Resolution, RotationPlacementRule, TickAction,
 };
-use crate::drawing::VoxelBrush;
 use crate::math::{GridPoint, Rgb, Rgba};
 use crate::space::{SetCubeError, Space};
 use crate::universe::{Name, URef, Universe, UniverseIndex};
@@ -93,6 +92,12 @@ impl<C> BlockBuilder<C> {
         self
     }
 
+    /// Sets the value for [`BlockAttributes::voxel_collision`].
+    pub const fn voxel_collision(mut self, value: Option<BlockCollision>) -> Self {
+        self.attributes.voxel_collision = value;
+        self
+    }
+
     /// Sets the value for [`BlockAttributes::rotation_rule`].
     pub const fn rotation_rule(mut self, value: RotationPlacementRule) -> Self {
         self.attributes.rotation_rule = value;
@@ -106,17 +111,35 @@ impl<C> BlockBuilder<C> {
     }
 
     /// Sets the value for [`BlockAttributes::tick_action`].
-    pub fn tick_action(mut self, value: Option<VoxelBrush<'static>>) -> Self {
+    pub fn tick_action(mut self, value: Option<TickAction>) -> Self {
         self.attributes.tick_action = value;
         self
     }
 
+    /// Sets the value for [`BlockAttributes::activation_action`].
+    pub fn activation_action(mut self, value: Option<TickAction>) -> Self {
+        self.attributes.activation_action = value;
+        self
+    }
+
     /// Sets the value for [`BlockAttributes::animation_hint`].
     pub fn animation_hint(mut self, value: AnimationHint) -> Self {
         self.attributes.animation_hint = value;
         self
     }
 
+    /// Sets the value for [`BlockAttributes::disable_culling`].
+    pub fn disable_culling(mut self, value: bool) -> Self {
+        self.attributes.disable_culling = value;
+        self
+    }
+
+    /// Sets the value for [`BlockAttributes::monitor`].
+    pub fn monitor(mut self, value: Option<MonitorView>) -> Self {
+        self.attributes.monitor = value;
+        self
+    }
+
     pub fn modifier(mut self, modifier: Modifier) -> Self {
         // TODO: implement a modifier canonicalization procedure here
         self.modifiers.push(modifier);
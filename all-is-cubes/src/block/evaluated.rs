@@ -8,6 +8,7 @@ use std::fmt;
 use cgmath::{Vector4, Zero as _};
 
 use crate::block::{BlockAttributes, BlockCollision, Resolution};
+use crate::content::palette;
 use crate::math::{OpacityCategory, Rgba};
 use crate::space::{Grid, GridArray};
 use crate::universe::RefError;
@@ -15,7 +16,7 @@ use crate::util::{ConciseDebug, CustomFormat};
 
 // Things mentioned in doc comments only
 #[cfg(doc)]
-use super::{Block, Primitive, URef, AIR, AIR_EVALUATED};
+use super::{Block, Modifier, Primitive, URef, AIR, AIR_EVALUATED};
 
 /// A snapshotted form of [`Block`] which contains all information needed for rendering
 /// and physics, and does not require dereferencing [`URef`]s or unbounded computation.
@@ -103,6 +104,35 @@ impl EvaluatedBlock {
         }
     }
 
+    /// Returns the appearance of a "broken block": a magenta and black checkerboard,
+    /// used wherever a block should be displayed but its actual appearance is
+    /// unavailable, such as when [`Block::evaluate()`](super::Block::evaluate) fails.
+    ///
+    /// ```
+    /// use all_is_cubes::block::EvaluatedBlock;
+    ///
+    /// let broken = EvaluatedBlock::broken();
+    /// assert!(broken.visible && broken.opaque);
+    /// assert_eq!(broken.resolution, 2);
+    /// ```
+    pub fn broken() -> EvaluatedBlock {
+        let resolution = 2;
+        EvaluatedBlock::from_voxels(
+            BlockAttributes {
+                display_name: "<broken block>".into(),
+                ..BlockAttributes::default()
+            },
+            resolution,
+            GridArray::from_fn(Grid::for_block(resolution), |cube| {
+                Evoxel::from_color(if (cube.x + cube.y + cube.z).rem_euclid(2) == 0 {
+                    palette::BROKEN_BLOCK
+                } else {
+                    Rgba::BLACK
+                })
+            }),
+        )
+    }
+
     /// Computes the derived values of a voxel block.
     pub(crate) fn from_voxels(
         attributes: BlockAttributes,
@@ -155,17 +185,93 @@ impl EvaluatedBlock {
     }
 }
 
+/// Limits on the amount of work done by [`Block::evaluate_limited`].
+///
+/// The [`Default`] value is the budget used by [`Block::evaluate`], which is generous
+/// enough for any reasonable block definition while still bounding the cost of
+/// unreasonable (possibly malicious) ones.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct EvalBudget {
+    /// Number of nested [`Primitive::Indirect`] or [`Modifier::Composite`] steps
+    /// which may be taken. Exceeding this is reported as
+    /// [`EvalBlockError::StackOverflow`].
+    pub recursion: u8,
+    /// Total number of voxels which may be produced, summed over the primitive and
+    /// every modifier (including those of nested blocks). Exceeding this is reported
+    /// as [`EvalBlockError::BudgetExceeded`].
+    pub voxels: usize,
+}
+
+impl EvalBudget {
+    /// Consumes one level of recursion, failing if none remain.
+    pub(crate) fn enter(&mut self) -> Result<(), EvalBlockError> {
+        match self.recursion.checked_sub(1) {
+            Some(remaining) => {
+                self.recursion = remaining;
+                Ok(())
+            }
+            None => Err(EvalBlockError::StackOverflow),
+        }
+    }
+
+    /// Returns the level of recursion consumed by [`Self::enter`].
+    pub(crate) fn exit(&mut self) {
+        self.recursion += 1;
+    }
+
+    /// Accounts for producing `count` voxels, failing if that exceeds the budget.
+    pub(crate) fn spend_voxels(&mut self, count: usize) -> Result<(), EvalBlockError> {
+        match self.voxels.checked_sub(count) {
+            Some(remaining) => {
+                self.voxels = remaining;
+                Ok(())
+            }
+            None => Err(EvalBlockError::BudgetExceeded),
+        }
+    }
+}
+
+impl Default for EvalBudget {
+    fn default() -> Self {
+        Self {
+            recursion: 32,
+            voxels: 1 << 26,
+        }
+    }
+}
+
 /// Errors resulting from [`Block::evaluate`].
 #[derive(Clone, Debug, Eq, Hash, PartialEq, thiserror::Error)]
 #[non_exhaustive]
 pub enum EvalBlockError {
     #[error("block definition contains too much recursion")]
     StackOverflow,
+    /// The block required more computation than the [`EvalBudget`] allowed.
+    #[error("block definition exceeded the evaluation budget")]
+    BudgetExceeded,
     /// This may be temporary or permanent.
     #[error("block data inaccessible: {0}")]
     DataRefIs(#[from] RefError),
 }
 
+impl EvalBlockError {
+    /// Returns an [`EvaluatedBlock`] which may be used in place of the block that
+    /// failed to evaluate, so that the failure is visible instead of fatal.
+    ///
+    /// It has the appearance of [`EvaluatedBlock::broken()`] and a name describing
+    /// the error.
+    pub(crate) fn to_placeholder(&self) -> EvaluatedBlock {
+        EvaluatedBlock {
+            attributes: BlockAttributes {
+                display_name: format!("Block error: {self}").into(),
+                ..BlockAttributes::default()
+            },
+            ..EvaluatedBlock::broken()
+        }
+    }
+}
+
 /// Properties of an individual voxel within [`EvaluatedBlock`].
 ///
 /// This is essentially a subset of the information in a full [`EvaluatedBlock`] and
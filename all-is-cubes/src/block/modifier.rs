@@ -1,15 +1,16 @@
 // Copyright 2020-2022 Kevin Reid under the terms of the MIT License as detailed
 // in the accompanying file README.md or <https://opensource.org/licenses/MIT>.
 
-use cgmath::Zero;
+use cgmath::{EuclideanSpace as _, Zero};
 
 use crate::block::{
-    Block, BlockAttributes, BlockChange, BlockCollision, EvalBlockError, EvaluatedBlock, Evoxel,
-    AIR,
+    next_depth, Block, BlockAttributes, BlockChange, BlockCollision, EvalBlockError, EvalBudget,
+    EvaluatedBlock, Evoxel, Resolution, TickAction, AIR,
 };
-use crate::drawing::VoxelBrush;
 use crate::listen::Listener;
-use crate::math::{Face6, GridCoordinate, GridRotation, Rgb, Rgba};
+use crate::math::{
+    Face6, Face7, GridCoordinate, GridMatrix, GridPoint, GridRotation, NotNan, Rgb, Rgba,
+};
 use crate::space::{Grid, GridArray};
 use crate::universe::{RefVisitor, VisitRefs};
 
@@ -20,8 +21,8 @@ use crate::universe::{RefVisitor, VisitRefs};
 pub enum Modifier {
     /// Suppresses all behaviors of the [`Block`] that might affect the space around it,
     /// (or itself).
-    // TODO: figure out how to publicly construct this given that we want to still have
-    // the option to add variants
+    ///
+    /// To construct this variant, use [`Modifier::quote()`].
     #[non_exhaustive]
     Quote {
         /// If true, also suppress light and sound effects.
@@ -31,6 +32,11 @@ pub enum Modifier {
     /// Rotate the block about its cube center by the given rotation.
     Rotate(GridRotation),
 
+    /// Reflect the block across the plane through its cube center that is
+    /// perpendicular to the given face's axis. [`Face6::PX`] and [`Face6::NX`] both
+    /// swap the left and right sides of the block, and so on.
+    Mirror(Face6),
+
     /// Displace the block out of the grid, cropping it. A pair of `Move`s can depict a
     /// block moving between two cubes.
     ///
@@ -42,7 +48,10 @@ pub enum Modifier {
     ///   never strt being in view, the block will be replaced with [`AIR`].
     ///
     /// (TODO: Define the conditions for “if possible”.)
-    // #[non_exhaustive] // TODO: needs a constructor
+    ///
+    /// To construct this variant, use [`Modifier::moving()`] or
+    /// [`Modifier::paired_move()`].
+    #[non_exhaustive]
     Move {
         /// The direction in which the block is displaced.
         direction: Face6,
@@ -53,6 +62,115 @@ pub enum Modifier {
         /// TODO: "Per tick" is a bad unit.
         velocity: i16,
     },
+
+    /// Resample the block's voxels to a different [`Resolution`], using the nearest
+    /// voxel. A block without voxels becomes a uniform grid of voxels.
+    ///
+    /// A resolution of zero is treated as 1.
+    Scale {
+        /// The resolution of the resulting block.
+        resolution: Resolution,
+    },
+
+    /// Multiply the color of the block, and of each of its voxels, by the given color
+    /// (componentwise, including alpha).
+    Tint {
+        /// The color to multiply by. [`Rgba::WHITE`] has no effect.
+        multiply: Rgba,
+    },
+
+    /// Combine the voxels of another block with those of this block, using an
+    /// alpha-compositing operator. This can be used to put a decal, such as a sign or
+    /// cracks, on any block.
+    ///
+    /// If either block has voxels, the result has voxels at the higher of the two
+    /// resolutions, with the other block resampled (using the nearest voxel) to match;
+    /// a block without voxels is treated as a uniform grid of voxels. If neither has
+    /// voxels, only the block colors are combined.
+    ///
+    /// The attributes of the result are those of the block being modified; only the
+    /// colors and voxels of `source` are used.
+    Composite {
+        /// The block to combine with this one. It is the “source” in the sense of the
+        /// compositing operator, and this block is the “destination”.
+        source: Block,
+        /// The compositing operator to use.
+        mode: CompositeMode,
+    },
+}
+
+/// Alpha-compositing operators used by [`Modifier::Composite`], as defined by
+/// Porter and Duff. In each, the “source” is the [`Modifier::Composite::source`] block
+/// and the “destination” is the block being modified.
+///
+/// Each voxel of the result takes its `selectable` and `collision` properties from
+/// whichever input voxel determines its shape, as described for each operator.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[non_exhaustive]
+pub enum CompositeMode {
+    /// The source is drawn over the destination, which shows through where the source
+    /// is transparent. Voxel properties come from the source where it is not fully
+    /// transparent, and from the destination elsewhere.
+    Over,
+    /// Only the part of the source which overlaps the destination is kept; the
+    /// destination is otherwise not drawn. Voxel properties come from the source where
+    /// the result is not fully transparent.
+    In,
+    /// The source is drawn over the destination, but only where the destination
+    /// exists. Voxel properties come from the destination.
+    Atop,
+}
+
+impl CompositeMode {
+    /// Combines a source and destination color (which are not premultiplied).
+    fn blend_color(self, source: Rgba, destination: Rgba) -> Rgba {
+        let sa = source.alpha().into_inner();
+        let da = destination.alpha().into_inner();
+        let (s, d) = (source.to_rgb(), destination.to_rgb());
+        let (color, alpha) = match self {
+            CompositeMode::Over => {
+                let alpha = sa + da * (1. - sa);
+                if alpha <= 0. {
+                    return Rgba::TRANSPARENT;
+                }
+                ((s * sa + d * (da * (1. - sa))) * alpha.recip(), alpha)
+            }
+            CompositeMode::In => (s, sa * da),
+            CompositeMode::Atop => (s * sa + d * (1. - sa), da),
+        };
+        color.with_alpha(NotNan::new(alpha).unwrap())
+    }
+
+    fn blend_voxel(self, source: Evoxel, destination: Evoxel) -> Evoxel {
+        let color = self.blend_color(source.color, destination.color);
+        let properties = match self {
+            CompositeMode::Over if source.color.fully_transparent() => destination,
+            CompositeMode::Over => source,
+            CompositeMode::In if color.fully_transparent() => Evoxel::AIR,
+            CompositeMode::In => source,
+            CompositeMode::Atop => destination,
+        };
+        Evoxel {
+            color,
+            ..properties
+        }
+    }
+}
+
+/// Returns the voxel of `block` at `cube` when it is resampled to `resolution`,
+/// treating a block without voxels as uniform.
+fn resampled_voxel(block: &EvaluatedBlock, resolution: Resolution, cube: GridPoint) -> Evoxel {
+    match block.voxels {
+        None => Evoxel::from_block(block),
+        Some(ref voxels) => {
+            let old = GridCoordinate::from(block.resolution);
+            let new = GridCoordinate::from(resolution);
+            // Sample at the center of the new voxel.
+            let source = cube.map(|c| (2 * c + 1) * old / (2 * new));
+            voxels.get(source).copied().unwrap_or(Evoxel::AIR)
+        }
+    }
 }
 
 impl Modifier {
@@ -68,18 +186,19 @@ impl Modifier {
     /// * `this_modifier_index` is the index in `block.modifiers()` of `self`.
     /// * `value` is the output of the preceding modifier or primitive, which is what the
     ///   current modifier should be applied to.
-    /// * `depth` is the current block evaluation recursion depth (which is *not*
-    ///   incremented by modifiers; TODO: define a computation limit strategy).
+    /// * `budget` is the remaining computation allowed for the evaluation of the
+    ///   outermost block; modifiers must charge it for the voxels they produce.
     pub(crate) fn evaluate(
         &self,
         block: &Block,
         this_modifier_index: usize,
         mut value: EvaluatedBlock,
-        depth: u8,
+        budget: &mut EvalBudget,
     ) -> Result<EvaluatedBlock, EvalBlockError> {
         Ok(match *self {
             Modifier::Quote { ambient } => {
                 value.attributes.tick_action = None;
+                value.attributes.activation_action = None;
                 if ambient {
                     value.attributes.light_emission = Rgb::ZERO;
                 }
@@ -91,31 +210,65 @@ impl Modifier {
                     // Skip computation of transforms
                     value
                 } else {
-                    // TODO: Add a shuffle-in-place rotation operation to GridArray and try implementing this using that, which should have less arithmetic involved than these matrix ops
+                    if let Some(voxels) = &value.voxels {
+                        budget.spend_voxels(voxels.grid().volume())?;
+                    }
                     let resolution = value.resolution;
-                    let inner_to_outer = rotation.to_positive_octant_matrix(resolution.into());
-                    let outer_to_inner = rotation
-                        .inverse()
-                        .to_positive_octant_matrix(resolution.into());
+                    // Rotating about the origin and then translating back into the
+                    // positive octant is equivalent to the positive octant matrix.
+                    let offset = rotation.to_positive_octant_matrix(resolution.into()).w;
+
+                    EvaluatedBlock {
+                        voxels: value
+                            .voxels
+                            .map(|voxels| voxels.rotate(rotation).translate(offset)),
+                        voxel_opacity_mask: value
+                            .voxel_opacity_mask
+                            .map(|mask| mask.rotate(rotation).translate(offset)),
+
+                        // Unaffected
+                        attributes: value.attributes,
+                        color: value.color,
+                        resolution,
+                        opaque: value.opaque,
+                        visible: value.visible,
+                    }
+                }
+            }
+
+            Modifier::Mirror(face) => {
+                if value.voxels.is_none() && value.voxel_opacity_mask.is_none() {
+                    // Skip computation of transforms
+                    value
+                } else {
+                    if let Some(voxels) = &value.voxels {
+                        budget.spend_voxels(voxels.grid().volume())?;
+                    }
+                    let axis = face.axis_number();
+                    let mut basis = [Face7::PX, Face7::PY, Face7::PZ];
+                    basis[axis] = basis[axis].opposite();
+                    let mut origin = GridPoint::origin();
+                    origin[axis] = value.resolution.into();
+                    // A reflection is its own inverse, so this matrix serves both ways.
+                    let reflection = GridMatrix::from_origin(origin, basis[0], basis[1], basis[2]);
 
                     EvaluatedBlock {
                         voxels: value.voxels.map(|voxels| {
                             GridArray::from_fn(
-                                voxels.grid().transform(inner_to_outer).unwrap(),
-                                |cube| voxels[outer_to_inner.transform_cube(cube)],
+                                voxels.grid().transform(reflection).unwrap(),
+                                |cube| voxels[reflection.transform_cube(cube)],
                             )
                         }),
                         voxel_opacity_mask: value.voxel_opacity_mask.map(|mask| {
-                            GridArray::from_fn(
-                                mask.grid().transform(inner_to_outer).unwrap(),
-                                |cube| mask[outer_to_inner.transform_cube(cube)],
-                            )
+                            GridArray::from_fn(mask.grid().transform(reflection).unwrap(), |cube| {
+                                mask[reflection.transform_cube(cube)]
+                            })
                         }),
 
                         // Unaffected
                         attributes: value.attributes,
                         color: value.color,
-                        resolution,
+                        resolution: value.resolution,
                         opaque: value.opaque,
                         visible: value.visible,
                     }
@@ -135,7 +288,7 @@ impl Modifier {
                     block,
                     this_modifier_index,
                     value,
-                    depth,
+                    budget,
                 )?;
 
                 let (original_bounds, effective_resolution) = match value.voxels.as_ref() {
@@ -157,7 +310,7 @@ impl Modifier {
 
                 let animation_action = if displaced_bounds.is_none() && velocity >= 0 {
                     // Displaced to invisibility; turn into just plain air.
-                    Some(VoxelBrush::single(AIR))
+                    Some(TickAction::Replace(AIR))
                 } else if translation_in_res.is_zero() && velocity == 0
                     || distance == 0 && velocity < 0
                 {
@@ -165,7 +318,7 @@ impl Modifier {
                     assert_eq!(&block.modifiers()[this_modifier_index], self);
                     let mut new_block = block.clone();
                     new_block.modifiers_mut().remove(this_modifier_index); // TODO: What if other modifiers want to do things?
-                    Some(VoxelBrush::single(new_block))
+                    Some(TickAction::Replace(new_block))
                 } else if velocity != 0 {
                     // Movement in progress.
                     assert_eq!(&block.modifiers()[this_modifier_index], self);
@@ -180,7 +333,7 @@ impl Modifier {
                             .try_into()
                             .unwrap(/* clamped to range */);
                     }
-                    Some(VoxelBrush::single(new_block))
+                    Some(TickAction::Replace(new_block))
                 } else {
                     // Stationary displacement; take no action
                     None
@@ -190,10 +343,12 @@ impl Modifier {
                 // out of `value`.
                 let voxel = Evoxel::from_block(&value);
 
+                // If the block was `Hard`, then all of its volume should stay solid even
+                // where it has non-colliding voxels.
+                let force_hard = value.attributes.collision == BlockCollision::Hard;
+
                 let attributes = BlockAttributes {
                     // Switch to `Recur` collision so that the displacement collides as expected.
-                    // TODO: If the collision was `Hard` then we may need to edit the collision
-                    // values of the individual voxels to preserve expected behavior.
                     collision: match value.attributes.collision {
                         BlockCollision::None => BlockCollision::None,
                         BlockCollision::Hard | BlockCollision::Recur => {
@@ -211,9 +366,14 @@ impl Modifier {
 
                 match displaced_bounds {
                     Some(displaced_bounds) => {
+                        budget.spend_voxels(displaced_bounds.volume())?;
                         let displaced_voxels = match value.voxels.as_ref() {
                             Some(voxels) => GridArray::from_fn(displaced_bounds, |cube| {
-                                voxels[cube - translation_in_res]
+                                let mut voxel = voxels[cube - translation_in_res];
+                                if force_hard {
+                                    voxel.collision = BlockCollision::Hard;
+                                }
+                                voxel
                             }),
                             None => {
                                 // Input block is a solid color; synthesize voxels.
@@ -229,23 +389,153 @@ impl Modifier {
                     None => EvaluatedBlock::from_color(attributes, Rgba::TRANSPARENT),
                 }
             }
+
+            Modifier::Scale { resolution } => {
+                let new_resolution = resolution.max(1);
+                let old_resolution = value.resolution;
+                match value.voxels {
+                    _ if new_resolution == old_resolution => value,
+                    None => {
+                        // Solid color block; synthesize voxels.
+                        let voxel = Evoxel::from_block(&value);
+                        budget.spend_voxels(Grid::for_block(new_resolution).volume())?;
+                        EvaluatedBlock::from_voxels(
+                            value.attributes,
+                            new_resolution,
+                            GridArray::from_fn(Grid::for_block(new_resolution), |_| voxel),
+                        )
+                    }
+                    Some(ref voxels) => {
+                        let old = GridCoordinate::from(old_resolution);
+                        let new = GridCoordinate::from(new_resolution);
+                        // Bounds of the new voxels, covering any part of the old voxels.
+                        let scaled_bounds = Grid::from_lower_upper(
+                            voxels
+                                .grid()
+                                .lower_bounds()
+                                .map(|c| (c * new).div_euclid(old)),
+                            voxels
+                                .grid()
+                                .upper_bounds()
+                                .map(|c| (c * new + old - 1).div_euclid(old)),
+                        )
+                        .intersection(Grid::for_block(new_resolution));
+
+                        match scaled_bounds {
+                            Some(scaled_bounds) => {
+                                budget.spend_voxels(scaled_bounds.volume())?;
+                                let new_voxels = GridArray::from_fn(scaled_bounds, |cube| {
+                                    // Sample at the center of the new voxel.
+                                    let source = cube.map(|c| (2 * c + 1) * old / (2 * new));
+                                    voxels.get(source).copied().unwrap_or(Evoxel::AIR)
+                                });
+                                EvaluatedBlock::from_voxels(
+                                    value.attributes,
+                                    new_resolution,
+                                    new_voxels,
+                                )
+                            }
+                            None => EvaluatedBlock::from_color(value.attributes, Rgba::TRANSPARENT),
+                        }
+                    }
+                }
+            }
+
+            Modifier::Tint { multiply } => {
+                if multiply == Rgba::WHITE {
+                    value
+                } else {
+                    match value.voxels {
+                        None => {
+                            EvaluatedBlock::from_color(value.attributes, value.color * multiply)
+                        }
+                        Some(voxels) => {
+                            budget.spend_voxels(voxels.grid().volume())?;
+                            EvaluatedBlock::from_voxels(
+                                value.attributes,
+                                value.resolution,
+                                voxels.map(|voxel| Evoxel {
+                                    color: voxel.color * multiply,
+                                    ..voxel
+                                }),
+                            )
+                        }
+                    }
+                }
+            }
+
+            Modifier::Composite { ref source, mode } => {
+                budget.enter()?;
+                let source = source.evaluate_impl(budget)?;
+                budget.exit();
+                if source.voxels.is_none() && value.voxels.is_none() {
+                    let color = mode.blend_color(source.color, value.color);
+                    EvaluatedBlock::from_color(value.attributes, color)
+                } else {
+                    let resolution = source.resolution.max(value.resolution);
+                    budget.spend_voxels(Grid::for_block(resolution).volume())?;
+                    let voxels = GridArray::from_fn(Grid::for_block(resolution), |cube| {
+                        mode.blend_voxel(
+                            resampled_voxel(&source, resolution, cube),
+                            resampled_voxel(&value, resolution, cube),
+                        )
+                    });
+                    EvaluatedBlock::from_voxels(value.attributes, resolution, voxels)
+                }
+            }
         })
     }
 
     /// Called by [`Block::listen()`]; not designed to be used otherwise.
     pub(crate) fn listen_impl(
         &self,
-        _listener: &(impl Listener<BlockChange> + Clone + Send + Sync),
-        _depth: u8,
+        listener: &(impl Listener<BlockChange> + Clone + Send + Sync + 'static),
+        depth: u8,
     ) -> Result<(), EvalBlockError> {
         match self {
             Modifier::Quote { .. } => {}
             Modifier::Rotate(_) => {}
+            Modifier::Mirror(_) => {}
             Modifier::Move { .. } => {}
+            Modifier::Scale { .. } => {}
+            Modifier::Tint { .. } => {}
+            Modifier::Composite { source, .. } => {
+                source.listen_impl(listener.clone(), next_depth(depth)?)?;
+            }
         }
         Ok(())
     }
 
+    /// Constructs a [`Modifier::Quote`].
+    ///
+    /// If `ambient` is true, light and sound effects are suppressed too.
+    ///
+    /// ```
+    /// use all_is_cubes::block::{Block, Modifier};
+    /// use all_is_cubes::math::Rgba;
+    ///
+    /// let block = Block::from(Rgba::WHITE);
+    /// let quoted = Modifier::quote(false).attach(block.clone());
+    /// assert_eq!(quoted.modifiers(), &[Modifier::quote(false)]);
+    /// assert_eq!(quoted.evaluate().unwrap().color, block.evaluate().unwrap().color);
+    /// ```
+    pub const fn quote(ambient: bool) -> Self {
+        Modifier::Quote { ambient }
+    }
+
+    /// Constructs a single [`Modifier::Move`].
+    ///
+    /// `distance` is in 1/256ths of a cube, and `velocity` is the change in `distance`
+    /// per tick. To depict a block moving between two cubes, use
+    /// [`Modifier::paired_move()`] instead.
+    pub const fn moving(direction: Face6, distance: u16, velocity: i16) -> Self {
+        Modifier::Move {
+            direction,
+            distance,
+            velocity,
+        }
+    }
+
     /// Create a pair of [`Modifier::Move`]s to displace a block.
     /// The first goes on the block being moved and the second on the air
     /// it's moving into.
@@ -254,30 +544,26 @@ impl Modifier {
     /// moving one block in and another out at the same time.
     pub fn paired_move(direction: Face6, distance: u16, velocity: i16) -> [Modifier; 2] {
         [
-            Modifier::Move {
-                direction,
-                distance,
-                velocity,
-            },
-            Modifier::Move {
-                direction: direction.opposite(),
-                distance: 256 - distance,
-                velocity: -velocity,
-            },
+            Modifier::moving(direction, distance, velocity),
+            Modifier::moving(direction.opposite(), 256 - distance, -velocity),
         ]
     }
 }
 
 impl VisitRefs for Modifier {
-    fn visit_refs(&self, _visitor: &mut dyn RefVisitor) {
+    fn visit_refs(&self, visitor: &mut dyn RefVisitor) {
         match self {
             Modifier::Quote { .. } => {}
             Modifier::Rotate(..) => {}
+            Modifier::Mirror(..) => {}
             Modifier::Move {
                 direction: _,
                 distance: _,
                 velocity: _,
             } => {}
+            Modifier::Scale { resolution: _ } => {}
+            Modifier::Tint { multiply: _ } => {}
+            Modifier::Composite { source, mode: _ } => source.visit_refs(visitor),
         }
     }
 }
@@ -287,12 +573,11 @@ mod tests {
     use super::*;
     use crate::block::{BlockAttributes, BlockCollision, Evoxel, Primitive, AIR};
     use crate::content::{make_some_blocks, make_some_voxel_blocks};
-    use crate::drawing::VoxelBrush;
     use crate::math::{GridPoint, OpacityCategory, Rgba};
     use crate::space::{Grid, Space};
     use crate::time::Tick;
     use crate::universe::Universe;
-    use cgmath::EuclideanSpace;
+    use cgmath::{EuclideanSpace, InnerSpace as _, Vector4};
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -369,6 +654,143 @@ mod tests {
         );
     }
 
+    #[test]
+    fn mirror_evaluation() {
+        let resolution = 2;
+        let mut universe = Universe::new();
+        let color_fn = |cube: GridPoint| {
+            Rgba::new(
+                cube.x as f32,
+                cube.y as f32,
+                cube.z as f32,
+                if cube.y == 0 { 1.0 } else { 0.0 },
+            )
+        };
+        let block = Block::builder()
+            .voxels_fn(&mut universe, resolution, |cube| {
+                // Construct a lower half block with all voxels distinct
+                Block::from(color_fn(cube))
+            })
+            .unwrap()
+            .build();
+        let original = block.evaluate().unwrap();
+        let mirrored = Modifier::Mirror(Face6::PX)
+            .attach(block.clone())
+            .evaluate()
+            .unwrap();
+
+        let voxels = mirrored.voxels.as_ref().unwrap();
+        let mask = mirrored.voxel_opacity_mask.as_ref().unwrap();
+        let res = GridCoordinate::from(resolution);
+        for y in 0..res {
+            for z in 0..res {
+                for x in 0..res {
+                    let cube = GridPoint::new(x, y, z);
+                    let swapped = GridPoint::new(res - 1 - x, y, z);
+                    assert_eq!(voxels[cube].color, color_fn(swapped));
+                    assert_eq!(
+                        mask[cube],
+                        original.voxel_opacity_mask.as_ref().unwrap()[swapped]
+                    );
+                }
+            }
+        }
+        assert_eq!(voxels.grid(), Grid::for_block(resolution));
+        assert_eq!(mirrored.color, original.color);
+        assert_eq!(mirrored.opaque, original.opaque);
+        assert_eq!(mirrored.visible, original.visible);
+
+        // Mirroring twice, or along the opposite face, is the identity.
+        assert_eq!(
+            Modifier::Mirror(Face6::NX)
+                .attach(Modifier::Mirror(Face6::PX).attach(block))
+                .evaluate()
+                .unwrap(),
+            original
+        );
+    }
+
+    #[test]
+    fn mirror_atom_is_noop() {
+        let [block] = make_some_blocks();
+        assert_eq!(
+            Modifier::Mirror(Face6::PY)
+                .attach(block.clone())
+                .evaluate()
+                .unwrap(),
+            block.evaluate().unwrap()
+        );
+    }
+
+    /// [`Modifier::Rotate`] must give the same results as transforming each voxel by
+    /// the positive octant matrix.
+    #[test]
+    fn rotate_matches_matrix_transform() {
+        let resolution = 4;
+        let mut universe = Universe::new();
+        let block = Block::builder()
+            .voxels_fn(&mut universe, resolution, |cube| {
+                Block::from(Rgba::new(
+                    cube.x as f32,
+                    cube.y as f32,
+                    cube.z as f32,
+                    if (cube.x + cube.y + cube.z) % 2 == 0 {
+                        1.0
+                    } else {
+                        0.0
+                    },
+                ))
+            })
+            .unwrap()
+            .build();
+        let original = block.evaluate().unwrap();
+
+        // The implementation of Modifier::Rotate before GridArray::rotate existed.
+        fn transform<V: Copy>(
+            array: &GridArray<V>,
+            rotation: GridRotation,
+            resolution: Resolution,
+        ) -> GridArray<V> {
+            let inner_to_outer = rotation.to_positive_octant_matrix(resolution.into());
+            let outer_to_inner = rotation
+                .inverse()
+                .to_positive_octant_matrix(resolution.into());
+            GridArray::from_fn(array.grid().transform(inner_to_outer).unwrap(), |cube| {
+                array[outer_to_inner.transform_cube(cube)]
+            })
+        }
+
+        for rotation in [
+            GridRotation::IDENTITY,
+            GridRotation::RYXZ,
+            GridRotation::CLOCKWISE,
+            GridRotation::COUNTERCLOCKWISE,
+            GridRotation::RxYz,
+            GridRotation::RXyZ,
+        ] {
+            let rotated = Modifier::Rotate(rotation)
+                .attach(block.clone())
+                .evaluate()
+                .unwrap();
+            assert_eq!(
+                rotated.voxels,
+                original
+                    .voxels
+                    .as_ref()
+                    .map(|a| transform(a, rotation, resolution)),
+                "{rotation:?}"
+            );
+            assert_eq!(
+                rotated.voxel_opacity_mask,
+                original
+                    .voxel_opacity_mask
+                    .as_ref()
+                    .map(|a| transform(a, rotation, resolution)),
+                "{rotation:?}"
+            );
+        }
+    }
+
     /// Check that [`Block::rotate`]'s pre-composition is consistent with the interpretation
     /// used by evaluating [`Modifier::Rotate`].
     #[test]
@@ -394,6 +816,278 @@ mod tests {
         );
     }
 
+    fn two_voxel_block(universe: &mut Universe, resolution: Resolution) -> Block {
+        Block::builder()
+            .voxels_fn(universe, resolution, |cube| {
+                // Distinct voxels in the lower half only
+                if cube.y == 0 {
+                    Block::from(Rgba::new(cube.x as f32, cube.y as f32, cube.z as f32, 1.0))
+                } else {
+                    AIR
+                }
+            })
+            .unwrap()
+            .build()
+    }
+
+    #[test]
+    fn scale_atom_synthesizes_voxels() {
+        let color = Rgba::new(1.0, 0.5, 0.0, 1.0);
+        let block = Modifier::Scale { resolution: 4 }.attach(Block::from(color));
+        let ev = block.evaluate().unwrap();
+        assert_eq!(ev.resolution, 4);
+        assert_eq!(ev.voxels.as_ref().unwrap().grid(), Grid::for_block(4));
+        assert_eq!(ev.voxels.unwrap().grid().volume(), 64);
+        assert_eq!(ev.color, color);
+        assert!(ev.opaque);
+        assert!(ev.visible);
+    }
+
+    #[test]
+    fn scale_same_resolution_is_noop() {
+        let mut universe = Universe::new();
+        let block = two_voxel_block(&mut universe, 2);
+        assert_eq!(
+            Modifier::Scale { resolution: 2 }
+                .attach(block.clone())
+                .evaluate()
+                .unwrap(),
+            block.evaluate().unwrap()
+        );
+    }
+
+    #[test]
+    fn scale_up_voxels() {
+        let mut universe = Universe::new();
+        let block = two_voxel_block(&mut universe, 2);
+        let original = block.evaluate().unwrap();
+        let scaled = Modifier::Scale { resolution: 4 }
+            .attach(block)
+            .evaluate()
+            .unwrap();
+
+        assert_eq!(scaled.resolution, 4);
+        let original_voxels = original.voxels.unwrap();
+        let scaled_voxels = scaled.voxels.unwrap();
+        assert_eq!(scaled_voxels.grid(), Grid::for_block(4));
+        for cube in scaled_voxels.grid().interior_iter() {
+            assert_eq!(
+                scaled_voxels[cube],
+                original_voxels[cube.map(|c| c / 2)],
+                "{cube:?}"
+            );
+        }
+        assert_eq!(scaled.color, original.color);
+        assert_eq!(scaled.opaque, original.opaque);
+        assert_eq!(scaled.visible, original.visible);
+        let mask = scaled.voxel_opacity_mask.unwrap();
+        assert_eq!(mask[GridPoint::new(3, 1, 3)], OpacityCategory::Opaque);
+        assert_eq!(mask[GridPoint::new(3, 2, 3)], OpacityCategory::Invisible);
+    }
+
+    #[test]
+    fn scale_down_preserves_color() {
+        let mut universe = Universe::new();
+        let block = Block::builder()
+            .voxels_fn(&mut universe, 16, |cube| {
+                Block::from(Rgba::new(
+                    cube.x as f32 / 15.0,
+                    0.5,
+                    cube.z as f32 / 15.0,
+                    1.0,
+                ))
+            })
+            .unwrap()
+            .build();
+        let original = block.evaluate().unwrap();
+        let scaled = Modifier::Scale { resolution: 4 }
+            .attach(block)
+            .evaluate()
+            .unwrap();
+
+        assert_eq!(scaled.resolution, 4);
+        assert_eq!(scaled.voxels.as_ref().unwrap().grid(), Grid::for_block(4));
+        assert!(scaled.opaque);
+        let difference = Vector4::from(scaled.color) - Vector4::from(original.color);
+        assert!(difference.magnitude() < 0.05, "{difference:?}");
+    }
+
+    #[test]
+    fn scale_after_rotate() {
+        let mut universe = Universe::new();
+        let block = two_voxel_block(&mut universe, 2);
+        let rotation = GridRotation::RYXZ;
+        let scale = Modifier::Scale { resolution: 4 };
+        // Upscaling by nearest-neighbor commutes with rotation.
+        assert_eq!(
+            scale
+                .clone()
+                .attach(block.clone().rotate(rotation))
+                .evaluate()
+                .unwrap(),
+            scale.attach(block).rotate(rotation).evaluate().unwrap(),
+        );
+    }
+
+    #[test]
+    fn tint_evaluation() {
+        let mut universe = Universe::new();
+        let color_a = Rgba::new(1.0, 1.0, 1.0, 1.0);
+        let color_b = Rgba::new(0.5, 0.5, 0.5, 1.0);
+        let block = Block::builder()
+            .voxels_fn(&mut universe, 2, |cube| match cube {
+                GridPoint { x: 0, y: 0, z: 0 } => Block::from(color_a),
+                GridPoint { x: 1, y: 0, z: 0 } => Block::from(color_b),
+                _ => AIR,
+            })
+            .unwrap()
+            .build();
+        let multiply = Rgba::new(1.0, 0.5, 0.0, 0.5);
+        let ev = Modifier::Tint { multiply }
+            .attach(block)
+            .evaluate()
+            .unwrap();
+
+        let voxels = ev.voxels.unwrap();
+        assert_eq!(
+            voxels[GridPoint::new(0, 0, 0)].color,
+            Rgba::new(1.0, 0.5, 0.0, 0.5)
+        );
+        assert_eq!(
+            voxels[GridPoint::new(1, 0, 0)].color,
+            Rgba::new(0.5, 0.25, 0.0, 0.5)
+        );
+        assert_eq!(voxels[GridPoint::new(1, 1, 1)].color, Rgba::TRANSPARENT);
+        assert_eq!(ev.color, Rgba::new(0.1875, 0.09375, 0.0, 0.125));
+        assert!(!ev.opaque);
+        assert!(ev.visible);
+    }
+
+    #[test]
+    fn tint_atom() {
+        let block = Modifier::Tint {
+            multiply: Rgba::new(0.5, 0.5, 0.5, 0.5),
+        }
+        .attach(Block::from(Rgba::WHITE));
+        let ev = block.evaluate().unwrap();
+        assert_eq!(ev.color, Rgba::new(0.5, 0.5, 0.5, 0.5));
+        assert!(!ev.opaque);
+        assert_eq!(ev.voxels, None);
+    }
+
+    #[test]
+    fn tint_white_is_noop() {
+        let mut universe = Universe::new();
+        let block = two_voxel_block(&mut universe, 2);
+        assert_eq!(
+            Modifier::Tint {
+                multiply: Rgba::WHITE
+            }
+            .attach(block.clone())
+            .evaluate()
+            .unwrap(),
+            block.evaluate().unwrap()
+        );
+    }
+
+    #[test]
+    fn composite_over_atoms() {
+        let block = Modifier::Composite {
+            source: Block::from(Rgba::new(1.0, 0.0, 0.0, 0.5)),
+            mode: CompositeMode::Over,
+        }
+        .attach(Block::from(Rgba::new(0.0, 0.0, 1.0, 1.0)));
+        let ev = block.evaluate().unwrap();
+        assert_eq!(ev.color, Rgba::new(0.5, 0.0, 0.5, 1.0));
+        assert_eq!(ev.voxels, None);
+    }
+
+    #[test]
+    fn composite_over_voxels_with_differing_resolutions() {
+        let mut universe = Universe::new();
+        let red = Rgba::new(1.0, 0.0, 0.0, 1.0);
+        let half_green = Rgba::new(0.0, 1.0, 0.0, 0.5);
+        // Resolution 2 decal, covering only the voxels with x = 0.
+        let decal = Block::builder()
+            .voxels_fn(&mut universe, 2, |cube| {
+                if cube.x == 0 {
+                    Block::from(half_green)
+                } else {
+                    AIR
+                }
+            })
+            .unwrap()
+            .build();
+        // Resolution 4 destination, solid only in its lower half.
+        let base = Block::builder()
+            .voxels_fn(&mut universe, 4, |cube| {
+                if cube.y < 2 {
+                    Block::from(red)
+                } else {
+                    AIR
+                }
+            })
+            .unwrap()
+            .build();
+
+        let ev = Modifier::Composite {
+            source: decal,
+            mode: CompositeMode::Over,
+        }
+        .attach(base)
+        .evaluate()
+        .unwrap();
+
+        assert_eq!(ev.resolution, 4);
+        let voxels = ev.voxels.unwrap();
+        assert_eq!(voxels.grid(), Grid::for_block(4));
+        // Decal over base
+        assert_eq!(
+            voxels[GridPoint::new(1, 0, 0)].color,
+            Rgba::new(0.5, 0.5, 0.0, 1.0)
+        );
+        assert_eq!(
+            voxels[GridPoint::new(1, 0, 0)].collision,
+            BlockCollision::Hard
+        );
+        // Decal over air
+        assert_eq!(voxels[GridPoint::new(1, 3, 0)].color, half_green);
+        // Base only
+        assert_eq!(voxels[GridPoint::new(2, 0, 0)].color, red);
+        // Neither
+        assert_eq!(voxels[GridPoint::new(2, 3, 0)], Evoxel::AIR);
+    }
+
+    #[test]
+    fn tint_commutes_with_rotate_and_move() {
+        let mut universe = Universe::new();
+        let tint = Modifier::Tint {
+            multiply: Rgba::new(0.0, 1.0, 0.5, 1.0),
+        };
+        let [move_modifier, _] = Modifier::paired_move(Face6::PX, 128, 0);
+        for block in [
+            two_voxel_block(&mut universe, 2),
+            Block::from(Rgba::new(1.0, 1.0, 1.0, 0.5)),
+        ] {
+            let tinted_first = tint
+                .clone()
+                .attach(block.clone())
+                .rotate(GridRotation::RYXZ);
+            let tinted_last = tint
+                .clone()
+                .attach(block.clone().rotate(GridRotation::RYXZ));
+            assert_eq!(tinted_first.evaluate(), tinted_last.evaluate());
+
+            let tinted_first = move_modifier
+                .clone()
+                .attach(tint.clone().attach(block.clone()));
+            let tinted_last = tint
+                .clone()
+                .attach(move_modifier.clone().attach(block.clone()));
+            assert_eq!(tinted_first.evaluate(), tinted_last.evaluate());
+        }
+    }
+
     #[test]
     fn move_atom_block_evaluation() {
         let color = rgba_const!(1.0, 0.0, 0.0, 1.0);
@@ -480,7 +1174,7 @@ mod tests {
     fn move_also_quotes() {
         let original = Block::builder()
             .color(Rgba::WHITE)
-            .tick_action(Some(VoxelBrush::single(AIR)))
+            .tick_action(Some(TickAction::Replace(AIR)))
             .build();
         let moved = Modifier::Move {
             direction: Face6::PY,
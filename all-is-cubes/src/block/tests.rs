@@ -15,8 +15,8 @@ use pretty_assertions::assert_eq;
 
 use crate::block::{
     builder, AnimationHint, Block, BlockAttributes, BlockBuilder, BlockCollision, BlockDef,
-    BlockDefTransaction, EvalBlockError, Evoxel, Modifier, Primitive, Resolution,
-    RotationPlacementRule, AIR, AIR_EVALUATED,
+    BlockDefTransaction, EvalBlockError, EvalBudget, Evoxel, Modifier, Primitive, Resolution,
+    RotationPlacementRule, TickAction, AIR, AIR_EVALUATED,
 };
 use crate::content::make_some_blocks;
 use crate::drawing::VoxelBrush;
@@ -102,6 +102,40 @@ fn evaluate_opaque_atom_and_attributes() {
     )
 }
 
+#[test]
+fn evaluate_animation_hint() {
+    let mut universe = Universe::new();
+    let atom = Block::builder()
+        .color(Rgba::WHITE)
+        .animation_hint(AnimationHint::CONTINUOUS)
+        .build();
+    let voxels = Block::builder()
+        .animation_hint(AnimationHint::TEMPORARY)
+        .voxels_fn(&mut universe, 2, |_| &atom)
+        .unwrap()
+        .build();
+
+    let hint = atom.evaluate().unwrap().attributes.animation_hint;
+    assert_eq!(hint, AnimationHint::CONTINUOUS);
+    assert!(hint.expect_color_update() && hint.expect_shape_update() && !hint.expect_replace());
+
+    // The hint of the voxels' own attributes does not leak into the outer block.
+    let hint = voxels.evaluate().unwrap().attributes.animation_hint;
+    assert_eq!(hint, AnimationHint::TEMPORARY);
+    assert!(hint.expect_replace() && !hint.expect_color_update());
+
+    assert_eq!(
+        Block::builder()
+            .color(Rgba::WHITE)
+            .build()
+            .evaluate()
+            .unwrap()
+            .attributes
+            .animation_hint,
+        AnimationHint::UNCHANGING
+    );
+}
+
 #[test]
 fn evaluate_transparent_atom() {
     let color = Rgba::new(1.0, 2.0, 3.0, 0.5);
@@ -175,6 +209,27 @@ fn evaluate_voxels_checked_individually() {
     )
 }
 
+#[test]
+fn evaluate_voxel_collision_override() {
+    let resolution = 2;
+    let mut universe = Universe::new();
+    let voxel_block = Block::builder()
+        .color(Rgba::WHITE)
+        .collision(BlockCollision::None)
+        .build();
+    let block = Block::builder()
+        .voxel_collision(Some(BlockCollision::Hard))
+        .voxels_fn(&mut universe, resolution, |_| &voxel_block)
+        .unwrap()
+        .build();
+
+    let voxels = block.evaluate().unwrap().voxels.unwrap();
+    assert!(voxels
+        .grid()
+        .interior_iter()
+        .all(|cube| voxels[cube].collision == BlockCollision::Hard));
+}
+
 #[test]
 fn evaluate_transparent_voxels() {
     let mut universe = Universe::new();
@@ -440,27 +495,34 @@ fn builder_every_field() {
     let color = Rgba::new(0.1, 0.2, 0.3, 0.4);
     let light_emission = Rgb::new(0.1, 3.0, 0.1);
     let rotation_rule = RotationPlacementRule::Attach { by: Face6::NZ };
-    let tick_action = Some(VoxelBrush::single(AIR));
+    let tick_action = Some(TickAction::from(VoxelBrush::single(AIR)));
     assert_eq!(
         Block::builder()
             .color(color)
             .display_name("hello world")
             .collision(BlockCollision::Recur)
+            .voxel_collision(Some(BlockCollision::None))
             .rotation_rule(rotation_rule)
             .selectable(false)
             .light_emission(light_emission)
             .tick_action(tick_action.clone())
+            .activation_action(tick_action.clone())
             .animation_hint(AnimationHint::TEMPORARY)
+            .disable_culling(true)
             .build(),
         Block::from_primitive(Primitive::Atom(
             BlockAttributes {
                 display_name: "hello world".into(),
                 collision: BlockCollision::Recur,
+                voxel_collision: Some(BlockCollision::None),
                 rotation_rule,
                 selectable: false,
                 light_emission,
-                tick_action,
+                tick_action: tick_action.clone(),
+                activation_action: tick_action,
                 animation_hint: AnimationHint::TEMPORARY,
+                disable_culling: true,
+                monitor: None,
             },
             color
         )),
@@ -607,6 +669,48 @@ fn overflow_evaluate() {
     assert_eq!(block.evaluate(), Err(EvalBlockError::StackOverflow));
 }
 
+#[test]
+fn evaluate_limited_recursion() {
+    let mut universe = Universe::new();
+    let mut block = Block::from(Rgba::WHITE);
+    for _ in 0..3 {
+        let def = universe.insert_anonymous(BlockDef::new(block));
+        block = Block::from_primitive(Primitive::Indirect(def));
+    }
+
+    let budget = |recursion| EvalBudget {
+        recursion,
+        ..EvalBudget::default()
+    };
+    assert_eq!(
+        block.evaluate_limited(budget(2)),
+        Err(EvalBlockError::StackOverflow)
+    );
+    assert_eq!(block.evaluate_limited(budget(3)), block.evaluate());
+}
+
+#[test]
+fn evaluate_limited_voxels() {
+    let mut universe = Universe::new();
+    let [atom] = make_some_blocks();
+    // 4³ = 64 voxels from the primitive, plus another 64 from the rotation.
+    let block = Block::builder()
+        .voxels_fn(&mut universe, 4, |_| &atom)
+        .unwrap()
+        .build()
+        .rotate(GridRotation::CLOCKWISE);
+
+    let budget = |voxels| EvalBudget {
+        voxels,
+        ..EvalBudget::default()
+    };
+    assert_eq!(
+        block.evaluate_limited(budget(100)),
+        Err(EvalBlockError::BudgetExceeded)
+    );
+    assert_eq!(block.evaluate_limited(budget(128)), block.evaluate());
+}
+
 #[test]
 fn overflow_listen() {
     let mut universe = Universe::new();
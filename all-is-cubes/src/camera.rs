@@ -132,6 +132,12 @@ impl Camera {
         Deg(self.options.fov_y.into_inner())
     }
 
+    /// Returns the distance to the near clipping plane, as given by
+    /// [`GraphicsOptions::near_plane`].
+    pub fn near_plane(&self) -> FreeCoordinate {
+        self.options.near_plane.into_inner()
+    }
+
     /// Returns the view distance; the far plane of the projection matrix, or the distance
     /// at which rendering may be truncated.
     pub fn view_distance(&self) -> FreeCoordinate {
@@ -206,6 +212,22 @@ impl Camera {
         mask
     }
 
+    /// Returns the corner points of the view frustum in world coordinates.
+    ///
+    /// The order is left-bottom, right-bottom, left-top, right-top on the far plane,
+    /// followed by the same on the near plane.
+    pub fn view_frustum_corners(&self) -> [Point3<FreeCoordinate>; 8] {
+        self.view_frustum.corners()
+    }
+
+    /// Returns the smallest [`Aab`] containing the view frustum (in world coordinates).
+    ///
+    /// Any [`Aab`] not intersecting this box is definitely not visible; this is a
+    /// cheaper but less precise test than [`Camera::aab_in_view()`].
+    pub fn view_frustum_aab(&self) -> Aab {
+        self.view_frustum.bounds
+    }
+
     /// Converts a screen position in normalized device coordinates (as produced by
     /// [`Viewport::normalize_nominal_point`]) into a ray in world space.
     /// Uses the view transformation given by [`set_view_transform`](Self::set_view_transform).
@@ -222,6 +244,29 @@ impl Camera {
         }
     }
 
+    /// Converts a point in world space into normalized device coordinates; the inverse
+    /// of [`project_ndc_into_world`](Self::project_ndc_into_world).
+    ///
+    /// The X and Y components of the result are screen positions in the range -1 to 1
+    /// if the point is within the field of view, and the Z component is its depth
+    /// (-1 at the near plane and 1 at the far plane).
+    ///
+    /// Returns [`None`] if the point is not in front of the camera (and so has no
+    /// meaningful screen position).
+    pub fn project_world_into_ndc(
+        &self,
+        point: Point3<FreeCoordinate>,
+    ) -> Option<Point3<FreeCoordinate>> {
+        let eye = self.world_to_eye_matrix.transform_point(point);
+        if eye.z >= 0.0 {
+            // Behind the eye, or exactly at its depth.
+            return None;
+        }
+        Some(Point3::from_homogeneous(
+            self.projection * eye.to_homogeneous(),
+        ))
+    }
+
     fn project_point_into_world(&self, p: Point3<FreeCoordinate>) -> Point3<FreeCoordinate> {
         Point3::from_homogeneous(self.inverse_projection_view * p.to_homogeneous())
     }
@@ -301,12 +346,27 @@ impl Camera {
     }
 
     fn compute_matrices(&mut self) {
-        self.projection = cgmath::perspective(
-            self.fov_y(),
-            self.viewport.nominal_aspect_ratio(),
-            /* near: */ 1. / 32., // half a voxel at resolution=16
-            /* far: */ self.view_distance(),
-        );
+        let near = self.near_plane();
+        self.projection = match self.options.projection {
+            ProjectionMode::Perspective => cgmath::perspective(
+                self.fov_y(),
+                self.viewport.nominal_aspect_ratio(),
+                near,
+                /* far: */ self.view_distance(),
+            ),
+            ProjectionMode::Orthographic { half_height } => {
+                let half_height = half_height.into_inner();
+                let half_width = half_height * self.viewport.nominal_aspect_ratio();
+                cgmath::ortho(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    near,
+                    /* far: */ self.view_distance(),
+                )
+            }
+        };
 
         self.world_to_eye_matrix = self.eye_to_world_transform
             .inverse_transform()
@@ -421,8 +481,28 @@ impl Viewport {
         w.checked_mul(h)
     }
 
-    // TODO: Maybe have a validate() that checks if the data is not fit for producing an
-    // invertible transform.
+    /// Returns whether this viewport contains any pixels and has a finite, nonzero
+    /// aspect ratio, and is therefore fit for producing an invertible projection.
+    ///
+    /// Renderers should skip drawing, rather than producing meaningless output, when
+    /// the viewport is not valid; this commonly happens when a window is minimized.
+    ///
+    /// ```
+    /// use all_is_cubes::camera::Viewport;
+    /// use all_is_cubes::cgmath::Vector2;
+    ///
+    /// assert!(Viewport::with_scale(1.0, Vector2::new(640, 480)).is_valid());
+    /// assert!(!Viewport::with_scale(1.0, Vector2::new(640, 0)).is_valid());
+    /// ```
+    pub fn is_valid(&self) -> bool {
+        let Vector2 { x: w, y: h } = self.nominal_size;
+        self.framebuffer_size.x > 0
+            && self.framebuffer_size.y > 0
+            && w.is_finite()
+            && h.is_finite()
+            && w > 0.0
+            && h > 0.0
+    }
 }
 
 /// Calculate an “eye position” (camera position) to view the entire given `grid`.
@@ -471,11 +551,14 @@ impl Default for FrustumPoints {
 }
 
 impl FrustumPoints {
-    fn iter(self) -> impl Iterator<Item = Point3<FreeCoordinate>> {
+    fn corners(self) -> [Point3<FreeCoordinate>; 8] {
         [
             self.lbf, self.rbf, self.ltf, self.rtf, self.lbn, self.rbn, self.ltn, self.rtn,
         ]
-        .into_iter()
+    }
+
+    fn iter(self) -> impl Iterator<Item = Point3<FreeCoordinate>> {
+        self.corners().into_iter()
     }
 
     fn compute_bounds(&mut self) {
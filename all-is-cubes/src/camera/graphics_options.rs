@@ -16,13 +16,20 @@ use crate::math::{FreeCoordinate, Rgb, Rgba};
 #[non_exhaustive]
 pub struct GraphicsOptions {
     /// Whether and how to draw fog obscuring the view distance limit.
-    ///
-    /// TODO: Implement fog in raytracer.
     pub fog: FogOption,
 
+    /// Distance at which the fog becomes fully opaque, as a fraction of
+    /// [`view_distance`](Self::view_distance).
+    pub fog_distance: NotNan<FreeCoordinate>,
+
     /// Field of view, in degrees from top to bottom edge of the viewport.
+    ///
+    /// Ignored if [`projection`](Self::projection) is orthographic.
     pub fov_y: NotNan<FreeCoordinate>,
 
+    /// Whether to use perspective or orthographic projection.
+    pub projection: ProjectionMode,
+
     /// Method to use to remap colors to fit within the displayable range.
     pub tone_mapping: ToneMappingOperator,
 
@@ -31,18 +38,35 @@ pub struct GraphicsOptions {
     /// [`tone_mapping`](ToneMappingOperator).
     pub exposure: ExposureOption,
 
+    /// Distance, in unit cubes, from the camera to the nearest visible point;
+    /// anything closer is clipped.
+    ///
+    /// Smaller values allow viewing objects more closely, at the cost of depth
+    /// precision for distant ones. [`repair()`](Self::repair) keeps this positive and
+    /// no more than half of [`view_distance`](Self::view_distance).
+    pub near_plane: NotNan<FreeCoordinate>,
+
     /// Distance, in unit cubes, from the camera to the farthest visible point.
     ///
-    /// TODO: Implement view distance limit (and fog) in raytracer.
+    /// TODO: Implement view distance limit in raytracer.
     pub view_distance: NotNan<FreeCoordinate>,
 
     /// Style in which to draw the lighting of [`Space`](crate::space::Space)s.
     /// This does not affect the *computation* of lighting.
     pub lighting_display: LightingOption,
 
+    /// Whether to darken the corners where block faces meet other blocks, by computing
+    /// per-vertex ambient occlusion.
+    ///
+    /// This is implemented by the GPU renderers, but not yet by the raytracer.
+    pub ambient_occlusion: bool,
+
     /// Method/fidelity to use for transparency.
     pub transparency: TransparencyOption,
 
+    /// Whether and how to smooth the edges of objects.
+    pub antialiasing: AntialiasingOption,
+
     /// Whether to show the HUD or other UI elements.
     ///
     /// This does not affect UI state or clickability; it purely controls display.
@@ -58,6 +82,9 @@ pub struct GraphicsOptions {
 
     /// Whether to use frustum culling for drawing only in-view chunks and objects.
     ///
+    /// In the luminance renderer, this also defers uploading light data for regions
+    /// which are not in view.
+    ///
     /// This option is for debugging and performance testing and should not have any
     /// visible effects.
     pub use_frustum_culling: bool,
@@ -80,10 +107,29 @@ impl GraphicsOptions {
     #[must_use]
     pub fn repair(mut self) -> Self {
         self.fov_y = self.fov_y.max(NotNan::from(1)).min(NotNan::from(189));
+        if let ProjectionMode::Orthographic { half_height } = &mut self.projection {
+            *half_height = (*half_height).max(notnan!(0.01)).min(NotNan::from(10000));
+        }
         self.view_distance = self
             .view_distance
             .max(NotNan::from(1))
             .min(NotNan::from(10000));
+        self.near_plane = self
+            .near_plane
+            .max(notnan!(0.0009765625)) // 1/1024
+            .min(self.view_distance / notnan!(2.0));
+        self.fog_distance = self.fog_distance.max(notnan!(0.01)).min(NotNan::from(1));
+        match &mut self.antialiasing {
+            AntialiasingOption::None => {}
+            AntialiasingOption::Ssaa { samples } => {
+                *samples = (*samples).clamp(1, 8);
+            }
+            AntialiasingOption::Msaa { samples } => {
+                // Round down to a power of two, since that is what GPUs support.
+                let clamped = (*samples).clamp(1, AntialiasingOption::MAX_MSAA_SAMPLES);
+                *samples = 1 << (7 - clamped.leading_zeros());
+            }
+        }
         self
     }
 }
@@ -92,13 +138,18 @@ impl Default for GraphicsOptions {
     fn default() -> Self {
         Self {
             fog: FogOption::Abrupt,
+            fog_distance: NotNan::from(1),
             fov_y: NotNan::from(90),
+            projection: ProjectionMode::Perspective,
             // TODO: Change tone mapping default once we have a good implementation.
             tone_mapping: ToneMappingOperator::Clamp,
             exposure: ExposureOption::default(),
+            near_plane: notnan!(0.03125), // half a voxel at resolution=16
             view_distance: NotNan::from(200),
             lighting_display: LightingOption::Smooth,
+            ambient_occlusion: false,
             transparency: TransparencyOption::Volumetric,
+            antialiasing: AntialiasingOption::None,
             show_ui: true,
             use_frustum_culling: true,
             debug_info_text: true,
@@ -122,6 +173,49 @@ pub enum FogOption {
     Physical,
 }
 
+impl FogOption {
+    /// Returns the opacity of the fog (0 to 1) at the given distance, where a distance
+    /// of 1 is the [fog distance](GraphicsOptions::fog_distance).
+    ///
+    /// Renderers should mix the color of each surface with the sky color by this
+    /// amount. This is the same function as is used by the GPU renderers' shaders.
+    pub fn opacity_at(&self, normalized_distance: f32) -> f32 {
+        let mode_blend = match self {
+            FogOption::None => return 0.0,
+            FogOption::Abrupt => 1.0,
+            FogOption::Compromise => 0.5,
+            FogOption::Physical => 0.0,
+        };
+        // Physically realistic exponential fog (constant density), adjusted so that it
+        // reaches 1 at the fog distance.
+        fn exponential(distance: f32) -> f32 {
+            const DENSITY: f32 = 1.6;
+            1.0 - (-DENSITY * distance).exp()
+        }
+        let physical = exponential(normalized_distance) / exponential(1.0);
+        // Slower-starting fog, so that nearby things are clearer.
+        let abrupt = normalized_distance.powi(4);
+        (physical * (1.0 - mode_blend) + abrupt * mode_blend).clamp(0.0, 1.0)
+    }
+}
+
+/// Which kind of projection to use to map the scene onto the viewport; part of a
+/// [`GraphicsOptions`].
+#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[non_exhaustive]
+pub enum ProjectionMode {
+    /// Perspective projection, with the field of view given by
+    /// [`GraphicsOptions::fov_y`].
+    Perspective,
+    /// Orthographic (parallel) projection, in which objects do not appear smaller when
+    /// farther away.
+    Orthographic {
+        /// Half of the height of the visible region, in world units; the width is
+        /// determined by the viewport's aspect ratio.
+        half_height: NotNan<FreeCoordinate>,
+    },
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
 #[non_exhaustive]
 pub enum ToneMappingOperator {
@@ -237,3 +331,48 @@ impl TransparencyOption {
         !matches!(self, Self::Threshold(_))
     }
 }
+
+/// How to antialias the rendered image; part of a [`GraphicsOptions`].
+#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[non_exhaustive]
+pub enum AntialiasingOption {
+    /// Each pixel shows a single point of the scene.
+    None,
+    /// Supersampling: each pixel is the average of a square grid of points.
+    Ssaa {
+        /// Number of samples along each axis of a pixel; the total number of samples
+        /// per pixel is the square of this.
+        samples: u8,
+    },
+    /// Multisample antialiasing: GPU renderers take several coverage samples per pixel
+    /// at the edges of geometry, which is cheaper than supersampling.
+    ///
+    /// The raytracer has no notion of multisampling and approximates this with
+    /// [`Ssaa`](Self::Ssaa) of a similar total sample count.
+    Msaa {
+        /// Total number of samples per pixel. [`GraphicsOptions::repair()`] will round
+        /// this down to a power of two no greater than 8, and renderers may use a
+        /// different count if the requested one is unsupported.
+        samples: u8,
+    },
+}
+
+impl AntialiasingOption {
+    const MAX_MSAA_SAMPLES: u8 = 8;
+
+    /// Returns the number of samples along each axis of a pixel to be traced.
+    #[inline]
+    pub(crate) fn samples_per_axis(&self) -> u8 {
+        match *self {
+            Self::None => 1,
+            Self::Ssaa { samples } => samples.max(1),
+            Self::Msaa { samples } => {
+                if samples >= 4 {
+                    2
+                } else {
+                    1
+                }
+            }
+        }
+    }
+}
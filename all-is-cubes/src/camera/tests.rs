@@ -2,6 +2,7 @@
 // in the accompanying file README.md or <https://opensource.org/licenses/MIT>.
 
 use super::*;
+use cgmath::Rotation3 as _;
 use pretty_assertions::assert_eq;
 
 #[test]
@@ -15,6 +16,27 @@ fn camera_bad_viewport_doesnt_panic() {
     );
 }
 
+#[test]
+fn viewport_is_valid() {
+    let zero_width = Viewport {
+        nominal_size: Vector2::new(0.0, 10.0),
+        framebuffer_size: Vector2::new(0, 10),
+    };
+    let zero_height = Viewport {
+        nominal_size: Vector2::new(10.0, 0.0),
+        framebuffer_size: Vector2::new(10, 0),
+    };
+    let zero_nominal_only = Viewport {
+        nominal_size: Vector2::new(0.0, 0.0),
+        framebuffer_size: Vector2::new(10, 10),
+    };
+    assert!(!zero_width.is_valid());
+    assert!(!zero_height.is_valid());
+    assert!(!zero_nominal_only.is_valid());
+    assert!(Viewport::ARBITRARY.is_valid());
+    assert!(Viewport::with_scale(2.0, Vector2::new(1280, 720)).is_valid());
+}
+
 #[test]
 fn set_options_updates_matrices() {
     let mut camera = Camera::new(GraphicsOptions::default(), Viewport::ARBITRARY);
@@ -73,6 +95,156 @@ fn view_frustum() {
     );
 }
 
+#[test]
+fn orthographic_rays_are_parallel() {
+    let camera = Camera::new(
+        GraphicsOptions {
+            projection: ProjectionMode::Orthographic {
+                half_height: notnan!(4.0),
+            },
+            ..GraphicsOptions::default()
+        },
+        Viewport::with_scale(1.0, Vector2::new(10, 5)),
+    );
+    let ray_1 = camera.project_ndc_into_world(Point2::new(-0.5, 0.0));
+    let ray_2 = camera.project_ndc_into_world(Point2::new(0.75, 0.0));
+    assert_eq!(
+        ray_1.direction.normalize(),
+        ray_2.direction.normalize(),
+        "{ray_1:?} {ray_2:?}"
+    );
+    assert_eq!(ray_1.direction.normalize(), Vector3::new(0.0, 0.0, -1.0));
+    // The ray origins are offset according to the half-height and aspect ratio.
+    assert_eq!(ray_2.origin.x - ray_1.origin.x, 1.25 * 8.0);
+}
+
+#[test]
+fn project_world_into_ndc_round_trip() {
+    for projection in [
+        ProjectionMode::Perspective,
+        ProjectionMode::Orthographic {
+            half_height: notnan!(4.0),
+        },
+    ] {
+        let mut camera = Camera::new(
+            GraphicsOptions {
+                projection: projection.clone(),
+                ..GraphicsOptions::default()
+            },
+            Viewport::with_scale(1.0, Vector2::new(10, 5)),
+        );
+        camera.set_view_transform(Decomposed {
+            scale: 1.0,
+            rot: Basis3::from_angle_y(Deg(30.0)),
+            disp: Vector3::new(1.0, 2.0, 3.0),
+        });
+
+        let point = Point3::new(-1.0, 2.5, -4.0);
+        let ndc = camera
+            .project_world_into_ndc(point)
+            .expect("point should be in front of the camera");
+        let ray = camera.project_ndc_into_world(Point2::new(ndc.x, ndc.y));
+
+        // The point should lie on the ray, i.e. be parallel to its direction.
+        let offset = point - ray.origin;
+        let sine = offset
+            .normalize()
+            .cross(ray.direction.normalize())
+            .magnitude();
+        assert!(sine < 1e-9, "{projection:?}: {point:?} not on {ray:?}");
+        // And the depth should be in range.
+        assert!((-1.0..=1.0).contains(&ndc.z), "{projection:?}: {ndc:?}");
+    }
+}
+
+#[test]
+fn project_world_into_ndc_behind() {
+    let camera = Camera::new(GraphicsOptions::default(), Viewport::ARBITRARY);
+    // The default camera looks toward -Z from the origin.
+    assert_eq!(
+        camera.project_world_into_ndc(Point3::new(0.0, 0.0, 5.0)),
+        None
+    );
+    assert_eq!(
+        camera.project_world_into_ndc(Point3::new(0.0, 0.0, 0.0)),
+        None
+    );
+    assert!(camera
+        .project_world_into_ndc(Point3::new(0.0, 0.0, -5.0))
+        .is_some());
+}
+
+#[test]
+fn near_plane_option() {
+    let near_clip_z = |near_plane: f64| {
+        let camera = Camera::new(
+            GraphicsOptions {
+                near_plane: NotNan::new(near_plane).unwrap(),
+                ..GraphicsOptions::default()
+            },
+            Viewport::ARBITRARY,
+        );
+        // The origin of a ray is on the near clipping plane.
+        camera
+            .project_ndc_into_world(Point2::new(0.0, 0.0))
+            .origin
+            .z
+    };
+    assert_eq!(near_clip_z(1. / 32.), -1. / 32.);
+    assert!((near_clip_z(2.0) - -2.0).abs() < 1e-9);
+}
+
+#[test]
+fn repair_near_plane() {
+    let repaired = |near_plane: f64, view_distance: f64| {
+        GraphicsOptions {
+            near_plane: NotNan::new(near_plane).unwrap(),
+            view_distance: NotNan::new(view_distance).unwrap(),
+            ..GraphicsOptions::default()
+        }
+        .repair()
+        .near_plane
+        .into_inner()
+    };
+    assert_eq!(repaired(0.5, 100.0), 0.5);
+    assert_eq!(repaired(0.0, 100.0), 1. / 1024.);
+    assert_eq!(repaired(-3.0, 100.0), 1. / 1024.);
+    assert_eq!(repaired(80.0, 100.0), 50.0);
+}
+
+#[test]
+fn view_frustum_aab() {
+    let mut camera = Camera::new(
+        GraphicsOptions {
+            view_distance: notnan!(100.0),
+            fov_y: notnan!(90.0),
+            ..GraphicsOptions::default()
+        },
+        Viewport::with_scale(1.0, Vector2::new(10, 5)),
+    );
+    // Look along +X from (10, 0, 0) instead of the default -Z from the origin.
+    camera.set_view_transform(Decomposed {
+        scale: 1.0,
+        rot: Basis3::from_angle_y(Deg(-90.0)),
+        disp: Vector3::new(10.0, 0.0, 0.0),
+    });
+
+    let aab = camera.view_frustum_aab();
+    let corners = camera.view_frustum_corners();
+    for corner in corners {
+        assert!(aab.contains(corner), "{corner:?} not in {aab:?}");
+    }
+    // Compare with the known extent of the frustum (to within rounding error).
+    let expected = Aab::new(10.03125, 110.0, -100.0, 100.0, -200.0, 200.0);
+    for axis in 0..3 {
+        assert!(
+            (aab.lower_bounds_p()[axis] - expected.lower_bounds_p()[axis]).abs() < 1e-6
+                && (aab.upper_bounds_p()[axis] - expected.upper_bounds_p()[axis]).abs() < 1e-6,
+            "{aab:?} != {expected:?}"
+        );
+    }
+}
+
 #[test]
 fn post_process() {
     let mut options = GraphicsOptions::default();
@@ -90,3 +262,45 @@ fn post_process() {
         color.map_rgb(|rgb| rgb * 0.5)
     );
 }
+
+/// Over-bright colors are clipped by [`ToneMappingOperator::Clamp`] but brought into
+/// range by [`ToneMappingOperator::Reinhard`].
+#[test]
+fn post_process_tone_mapping() {
+    let bright = rgba_const!(2.0, 2.0, 2.0, 1.0);
+    let processed_with = |tone_mapping: ToneMappingOperator| {
+        Camera::new(
+            GraphicsOptions {
+                tone_mapping,
+                ..GraphicsOptions::default()
+            },
+            Viewport::ARBITRARY,
+        )
+        .post_process_color(bright)
+    };
+
+    assert_eq!(
+        processed_with(ToneMappingOperator::Clamp),
+        rgba_const!(1.0, 1.0, 1.0, 1.0)
+    );
+    let reinhard = processed_with(ToneMappingOperator::Reinhard);
+    let red = reinhard.red().into_inner();
+    assert!(red < 1.0 && red > 0.0, "{reinhard:?}");
+}
+
+#[test]
+fn repair_msaa_samples() {
+    let repaired = |samples: u8| {
+        GraphicsOptions {
+            antialiasing: AntialiasingOption::Msaa { samples },
+            ..GraphicsOptions::default()
+        }
+        .repair()
+        .antialiasing
+    };
+    assert_eq!(repaired(0), AntialiasingOption::Msaa { samples: 1 });
+    assert_eq!(repaired(3), AntialiasingOption::Msaa { samples: 2 });
+    assert_eq!(repaired(4), AntialiasingOption::Msaa { samples: 4 });
+    assert_eq!(repaired(7), AntialiasingOption::Msaa { samples: 4 });
+    assert_eq!(repaired(255), AntialiasingOption::Msaa { samples: 8 });
+}
@@ -11,7 +11,7 @@ use crate::universe::{RefVisitor, VisitRefs};
 
 /// Defines the initial state of a [`Character`] that is being created or moved into a [`Space`].
 ///
-/// TODO: This is lacking a full set of accessor methods to be viewable+editable.
+/// To construct one, use [`Spawn::builder()`] or [`Spawn::default_for_new_space()`].
 ///
 /// [`Character`]: super::Character
 /// [`Space`]: crate::space::Space
@@ -34,6 +34,12 @@ pub struct Spawn {
 }
 
 impl Spawn {
+    /// Returns a [`SpawnBuilder`] for a [`Spawn`] whose character will be placed within
+    /// `bounds`.
+    pub fn builder(bounds: Grid) -> SpawnBuilder {
+        SpawnBuilder::new(bounds)
+    }
+
     /// Create the default Spawn configuration for a Space.
     ///
     /// TODO: There is no good default, really: we don't know if it is better to be
@@ -80,6 +86,30 @@ impl Spawn {
         });
     }
 
+    /// Returns the volume which the character is permitted to occupy.
+    pub fn bounds(&self) -> Grid {
+        self.bounds
+    }
+
+    /// Returns the position at which the character will appear, in terms of its
+    /// viewpoint, if one has been set.
+    pub fn eye_position(&self) -> Option<Point3<FreeCoordinate>> {
+        self.eye_position.map(|p| p.map(NotNan::into_inner))
+    }
+
+    /// Returns the direction the character should be facing, or looking at.
+    ///
+    /// If not otherwise set, this is `[0, 0, -1]`.
+    pub fn look_direction(&self) -> Vector3<FreeCoordinate> {
+        self.look_direction.map(NotNan::into_inner)
+    }
+
+    /// Returns the starting inventory items.
+    pub fn inventory(&self) -> &[Slot] {
+        &self.inventory
+    }
+
+    /// Sets the volume which the character is permitted to occupy.
     pub fn set_bounds(&mut self, bounds: Grid) {
         self.bounds = bounds;
     }
@@ -102,6 +132,54 @@ impl Spawn {
     }
 }
 
+/// Tool for constructing new [`Spawn`]s.
+///
+/// To create one, call [`Spawn::builder(bounds)`](Spawn::builder).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[must_use]
+pub struct SpawnBuilder {
+    spawn: Spawn,
+}
+
+impl SpawnBuilder {
+    fn new(bounds: Grid) -> Self {
+        Self {
+            spawn: Spawn {
+                bounds,
+                ..Spawn::default_for_new_space(bounds)
+            },
+        }
+    }
+
+    /// Sets the position at which the character will appear, in terms of its viewpoint.
+    ///
+    /// If not set, the character will stand on the floor of the bounds.
+    pub fn eye_position(mut self, position: impl Into<Point3<FreeCoordinate>>) -> Self {
+        self.spawn.set_eye_position(position);
+        self
+    }
+
+    /// Sets the direction the character should be facing, or looking at.
+    ///
+    /// If not set, the character looks in the −Z direction. The results are
+    /// unspecified but harmless if the direction is zero or NaN.
+    pub fn look_direction(mut self, direction: impl Into<Vector3<FreeCoordinate>>) -> Self {
+        self.spawn.set_look_direction(direction);
+        self
+    }
+
+    /// Sets the starting inventory items.
+    pub fn inventory(mut self, inventory: Vec<Slot>) -> Self {
+        self.spawn.set_inventory(inventory);
+        self
+    }
+
+    /// Converts this builder into a [`Spawn`].
+    pub fn build(self) -> Spawn {
+        self.spawn
+    }
+}
+
 impl VisitRefs for Spawn {
     fn visit_refs(&self, visitor: &mut dyn RefVisitor) {
         let Self {
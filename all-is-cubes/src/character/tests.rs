@@ -76,6 +76,31 @@ fn spawn_look_direction() {
     assert_eq!(character.body.pitch, Deg::atan2(-1., 2.0f64.sqrt()).0);
 }
 
+#[test]
+fn spawn_builder() {
+    let bounds = Grid::new([1, 2, 3], [4, 5, 6]);
+    let inventory = vec![Slot::from(Tool::RemoveBlock { keep: true })];
+    let spawn = Spawn::builder(bounds)
+        .eye_position([2.5, 3.5, 4.5])
+        .look_direction([1., 0., 0.])
+        .inventory(inventory.clone())
+        .build();
+    assert_eq!(spawn.bounds(), bounds);
+    assert_eq!(spawn.eye_position(), Some(Point3::new(2.5, 3.5, 4.5)));
+    assert_eq!(spawn.look_direction(), Vector3::new(1., 0., 0.));
+    assert_eq!(spawn.inventory(), &inventory[..]);
+}
+
+#[test]
+fn spawn_builder_defaults() {
+    let bounds = Grid::new([1, 2, 3], [4, 5, 6]);
+    let spawn = Spawn::builder(bounds).build();
+    assert_eq!(spawn.bounds(), bounds);
+    assert_eq!(spawn.eye_position(), None);
+    assert_eq!(spawn.look_direction(), Vector3::new(0., 0., -1.));
+    assert_eq!(spawn.inventory(), &[]);
+}
+
 #[test]
 fn inventory_transaction() {
     let mut universe = Universe::new();
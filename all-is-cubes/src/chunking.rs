@@ -104,7 +104,9 @@ impl<const CHUNK_SIZE: GridCoordinate> ChunkChart<CHUNK_SIZE> {
         }
     }
 
-    fn sanitize_and_square_distance(view_distance: FreeCoordinate) -> GridCoordinate {
+    /// Converts a distance in cubes to the squared distance in chunks used by
+    /// [`chunk_distance_squared_for_view`].
+    pub(crate) fn sanitize_and_square_distance(view_distance: FreeCoordinate) -> GridCoordinate {
         let sanitized = if view_distance.is_finite() {
             view_distance.max(0.)
         } else {
@@ -199,18 +201,7 @@ fn compute_chart_octant(view_distance_in_squared_chunks: GridCoordinate) -> Arc<
     // (This for loop has been measured as slightly faster than a .filter().collect().)
     for chunk in candidates.interior_iter() {
         let chunk = chunk.to_vec();
-        // By subtracting 1 from all coordinates, we include the chunks intersecting
-        // the view sphere centered on the _farthest corner point_ of the
-        // viewpoint-containing chunk. By taking the max, we include those chunks
-        // visible from anywhere else in the chunk.
-        //
-        // The shape formed (after mirroring) is the Minkowski sum of the view sphere
-        // and the chunk cube.
-        if int_magnitude_squared(chunk.map(
-            #[inline(always)]
-            |s| (s - 1).max(0),
-        )) <= view_distance_in_squared_chunks
-        {
+        if chunk_distance_squared_for_view(chunk) <= view_distance_in_squared_chunks {
             octant_chunks.push(chunk);
         }
     }
@@ -220,6 +211,23 @@ fn compute_chart_octant(view_distance_in_squared_chunks: GridCoordinate) -> Arc<
     octant_chunks.into()
 }
 
+/// Returns the squared distance, in units of whole chunks, from the viewpoint-containing
+/// chunk to the chunk at offset `chunk` from it, as used to decide whether a chunk is
+/// within view distance.
+pub(crate) fn chunk_distance_squared_for_view(chunk: GridVector) -> GridCoordinate {
+    // By subtracting 1 from all coordinates, we include the chunks intersecting
+    // the view sphere centered on the _farthest corner point_ of the
+    // viewpoint-containing chunk. By taking the max, we include those chunks
+    // visible from anywhere else in the chunk.
+    //
+    // The shape formed (after mirroring) is the Minkowski sum of the view sphere
+    // and the chunk cube.
+    int_magnitude_squared(chunk.map(
+        #[inline(always)]
+        |s| (s.abs() - 1).max(0),
+    ))
+}
+
 /// A specification of which octants to include in [`ChunkChart::chunks()`].
 #[derive(Clone, Copy, Eq, Hash, PartialEq)]
 pub struct OctantMask {
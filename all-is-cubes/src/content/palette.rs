@@ -69,6 +69,11 @@ palette! {
     MISSING_TEXTURE_FALLBACK = srgb[0xFF 0x00 0xBB 0xFF];
     /// Used when a recursive block definition should have provided a voxel color but did not.
     MISSING_VOXEL_FALLBACK = srgb[0xBB 0x00 0xFF 0xFF];
+    /// Used, in a checkerboard pattern with black, as the appearance of a block which
+    /// could not be evaluated or meshed; see [`EvaluatedBlock::broken()`].
+    ///
+    /// [`EvaluatedBlock::broken()`]: crate::block::EvaluatedBlock::broken
+    BROKEN_BLOCK = srgb[0xFF 0x00 0xFF 0xFF];
     /// Used in unallocated texture atlas space.
     ///
     /// TODO: Not currently used.
@@ -136,4 +141,8 @@ palette! {
     DEBUG_COLLISION_CUBES = srgb[0xFF 0x00 0x00 0xFF];
     DEBUG_CHUNK_MAJOR = srgb[0x00 0x00 0xE8 0xFF];
     DEBUG_CHUNK_MINOR = srgb[0x00 0xE8 0xE8 0xFF];
+    DEBUG_CHUNK_EMPTY = srgb[0x40 0x40 0x40 0xFF];
+    DEBUG_CHUNK_OPAQUE = srgb[0x00 0xC0 0x00 0xFF];
+    DEBUG_CHUNK_TRANSPARENT = srgb[0xE8 0x80 0x00 0xFF];
+    DEBUG_CHUNK_UPDATED = srgb[0xFF 0xFF 0xFF 0xFF];
 }
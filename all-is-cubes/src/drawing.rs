@@ -280,6 +280,11 @@ impl<'a> VoxelBrush<'a> {
         }
     }
 
+    /// Returns the offsets and blocks making up this brush.
+    pub(crate) fn entries(&self) -> &[(GridPoint, Cow<'a, Block>)] {
+        &self.0
+    }
+
     /// Converts a `&VoxelBrush` into a `VoxelBrush` that borrows it.
     pub fn as_ref(&self) -> VoxelBrush<'_> {
         VoxelBrush(
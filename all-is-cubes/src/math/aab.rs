@@ -5,9 +5,10 @@ use std::cmp::Ordering;
 use std::fmt;
 use std::iter::FusedIterator;
 
-use cgmath::{EuclideanSpace as _, Point3, Vector3, Zero as _};
+use cgmath::{EuclideanSpace as _, Matrix4, Point3, Transform as _, Vector3, Zero as _};
 
 use crate::math::{Face6, FreeCoordinate, Geometry, GridCoordinate, GridPoint, Rgba};
+use crate::raycast::Ray;
 use crate::space::Grid;
 
 /// Axis-Aligned Box data type.
@@ -191,6 +192,137 @@ impl Aab {
         true
     }
 
+    /// Returns the values of `t` at which `ray.origin + t * ray.direction` enters and
+    /// exits this AAB (including the boundary), or [`None`] if the ray does not meet it.
+    ///
+    /// Since a [`Ray`] extends only forward from its origin, the exit parameter is never
+    /// negative. The entry parameter is negative when the origin is inside the box.
+    ///
+    /// ```
+    /// use all_is_cubes::cgmath::{Point3, Vector3};
+    /// use all_is_cubes::math::Aab;
+    /// use all_is_cubes::raycast::Ray;
+    ///
+    /// let aab = Aab::new(1.0, 2.0, 0.0, 1.0, 0.0, 1.0);
+    /// let ray = Ray::new(Point3::new(0.0, 0.5, 0.5), Vector3::new(2.0, 0.0, 0.0));
+    /// assert_eq!(aab.intersect_ray(&ray), Some((0.5, 1.0)));
+    /// ```
+    pub fn intersect_ray(&self, ray: &Ray) -> Option<(FreeCoordinate, FreeCoordinate)> {
+        let mut t_near = FreeCoordinate::NEG_INFINITY;
+        let mut t_far = FreeCoordinate::INFINITY;
+        for axis in 0..3 {
+            let origin = ray.origin[axis];
+            let direction = ray.direction[axis];
+            let (lower, upper) = (self.lower_bounds[axis], self.upper_bounds[axis]);
+            if direction == 0.0 {
+                // Parallel to this slab, so the ray is either always or never within it.
+                if !(lower <= origin && origin <= upper) {
+                    return None;
+                }
+            } else {
+                let t_lower = (lower - origin) / direction;
+                let t_upper = (upper - origin) / direction;
+                t_near = t_near.max(t_lower.min(t_upper));
+                t_far = t_far.min(t_lower.max(t_upper));
+            }
+        }
+        if t_near <= t_far && t_far >= 0.0 {
+            Some((t_near, t_far))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the intersection of this AAB and the other AAB, or [`None`] if they do
+    /// not intersect.
+    ///
+    /// As with [`Aab::intersects()`], the boundary is included, so boxes which touch
+    /// without overlapping have a zero-size intersection rather than none.
+    ///
+    /// ```
+    /// use all_is_cubes::math::Aab;
+    ///
+    /// let a = Aab::new(0.0, 2.0, 0.0, 2.0, 0.0, 2.0);
+    /// assert_eq!(
+    ///     a.intersection(Aab::new(1.0, 3.0, 1.0, 3.0, -1.0, 1.0)),
+    ///     Some(Aab::new(1.0, 2.0, 1.0, 2.0, 0.0, 1.0))
+    /// );
+    /// assert_eq!(
+    ///     a.intersection(Aab::new(2.0, 3.0, 0.0, 1.0, 0.0, 1.0)),
+    ///     Some(Aab::new(2.0, 2.0, 0.0, 1.0, 0.0, 1.0))
+    /// );
+    /// assert_eq!(a.intersection(Aab::new(2.5, 3.0, 0.0, 1.0, 0.0, 1.0)), None);
+    /// ```
+    pub fn intersection(self, other: Aab) -> Option<Aab> {
+        let lower = self
+            .lower_bounds
+            .zip(other.lower_bounds, FreeCoordinate::max);
+        let upper = self
+            .upper_bounds
+            .zip(other.upper_bounds, FreeCoordinate::min);
+        for axis in 0..3 {
+            match lower[axis].partial_cmp(&upper[axis]) {
+                Some(Ordering::Less | Ordering::Equal) => {}
+                _ => return None,
+            }
+        }
+        Some(Aab::from_lower_upper(lower, upper))
+    }
+
+    /// Returns the smallest [`Aab`] which contains both this AAB and the other AAB.
+    ///
+    /// ```
+    /// use all_is_cubes::math::Aab;
+    ///
+    /// assert_eq!(
+    ///     Aab::new(0.0, 1.0, 0.0, 1.0, 0.0, 1.0).union(Aab::new(3.0, 4.0, -1.0, 0.5, 0.0, 0.0)),
+    ///     Aab::new(0.0, 4.0, -1.0, 1.0, 0.0, 1.0)
+    /// );
+    /// ```
+    #[must_use]
+    pub fn union(self, other: Aab) -> Aab {
+        Aab::from_lower_upper(
+            self.lower_bounds
+                .zip(other.lower_bounds, FreeCoordinate::min),
+            self.upper_bounds
+                .zip(other.upper_bounds, FreeCoordinate::max),
+        )
+    }
+
+    /// Splits this box into two by a plane perpendicular to the given axis (0 = X,
+    /// 1 = Y, 2 = Z) at the coordinate `at`, returning the lower and upper parts.
+    ///
+    /// Returns [`None`] if `at` is outside the box's bounds on that axis (or is NaN).
+    /// If `at` is exactly on a boundary, one of the parts has zero size.
+    ///
+    /// Panics if `axis` is not 0, 1, or 2.
+    ///
+    /// ```
+    /// use all_is_cubes::math::Aab;
+    ///
+    /// assert_eq!(
+    ///     Aab::new(0.0, 4.0, 0.0, 1.0, 0.0, 1.0).split(0, 1.0),
+    ///     Some((
+    ///         Aab::new(0.0, 1.0, 0.0, 1.0, 0.0, 1.0),
+    ///         Aab::new(1.0, 4.0, 0.0, 1.0, 0.0, 1.0),
+    ///     ))
+    /// );
+    /// ```
+    pub fn split(self, axis: usize, at: FreeCoordinate) -> Option<(Aab, Aab)> {
+        assert!(axis < 3, "axis out of range: {axis}");
+        if !(self.lower_bounds[axis] <= at && at <= self.upper_bounds[axis]) {
+            return None;
+        }
+        let mut lower_half_upper = self.upper_bounds;
+        lower_half_upper[axis] = at;
+        let mut upper_half_lower = self.lower_bounds;
+        upper_half_lower[axis] = at;
+        Some((
+            Aab::from_lower_upper(self.lower_bounds, lower_half_upper),
+            Aab::from_lower_upper(upper_half_lower, self.upper_bounds),
+        ))
+    }
+
     /// Returns a random point within this box, using inclusive ranges
     /// (`lower_bounds[axis] ≤ random_point()[axis] ≤ upper_bounds[axis]`).
     pub fn random_point(self, rng: &mut impl rand::Rng) -> Point3<FreeCoordinate> {
@@ -206,6 +338,40 @@ impl Aab {
         Self::from_lower_upper(self.lower_bounds * scalar, self.upper_bounds * scalar)
     }
 
+    /// Transforms the eight corners of the box by `matrix` and returns the smallest
+    /// [`Aab`] containing all of them.
+    ///
+    /// The result is exact for transforms consisting of scaling, translation, and
+    /// right-angle rotations; other rotations will produce a larger box than the
+    /// transformed shape.
+    ///
+    /// Panics if the transformed coordinates are NaN.
+    ///
+    /// ```
+    /// use all_is_cubes::cgmath::{Matrix4, Vector3};
+    /// use all_is_cubes::math::Aab;
+    ///
+    /// assert_eq!(
+    ///     Aab::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0)
+    ///         .transform(Matrix4::from_translation(Vector3::new(10.0, 0.0, 0.0))),
+    ///     Aab::new(11.0, 12.0, 3.0, 4.0, 5.0, 6.0)
+    /// );
+    /// ```
+    #[must_use]
+    pub fn transform(self, matrix: Matrix4<FreeCoordinate>) -> Self {
+        let mut corners = self
+            .corner_points()
+            .map(|point| matrix.transform_point(point));
+        let first = corners.next().unwrap();
+        let (lower, upper) = corners.fold((first, first), |(lower, upper), point| {
+            (
+                lower.zip(point, FreeCoordinate::min),
+                upper.zip(point, FreeCoordinate::max),
+            )
+        });
+        Self::from_lower_upper(lower, upper)
+    }
+
     /// Enlarges the AAB by moving each face outward by the specified distance.
     ///
     /// Panics if the distance is negative or NaN.
@@ -299,6 +465,39 @@ impl Aab {
             self.upper_bounds.map(|c| c.ceil() as GridCoordinate),
         )
     }
+
+    /// Construct the largest [`Grid`] whose cubes are all entirely contained within this
+    /// [`Aab`].
+    ///
+    /// This is the counterpart of [`Aab::round_up_to_grid`]: grid cubes are considered
+    /// to be half-open ranges, and non-integer bounds are rounded inward rather than
+    /// outward. If on some axis the box does not contain any whole cube, the result has
+    /// zero size on that axis (positioned at the rounded-up lower bound).
+    ///
+    /// ```
+    /// use all_is_cubes::{math::Aab, space::Grid};
+    ///
+    /// let aab = Aab::from_lower_upper([3.0, 0.5, 0.0], [5.0, 2.5, 1.0]);
+    /// assert_eq!(aab.round_down_to_grid(), Grid::from_lower_upper([3, 1, 0], [5, 2, 1]));
+    /// assert_eq!(aab.round_up_to_grid(), Grid::from_lower_upper([3, 0, 0], [5, 3, 1]));
+    ///
+    /// // Too thin to contain a cube.
+    /// assert_eq!(
+    ///     Aab::from_lower_upper([0.25, 0.0, 0.0], [0.75, 1.0, 1.0]).round_down_to_grid(),
+    ///     Grid::from_lower_upper([1, 0, 0], [1, 1, 1]),
+    /// );
+    /// ```
+    ///
+    /// For any [`Grid`] `g`, `g.to_aab().round_down_to_grid() == g` and
+    /// `g.to_aab().round_up_to_grid() == g`.
+    ///
+    /// As with [`Aab::round_up_to_grid`], coordinates out of [`GridCoordinate`]'s
+    /// numeric range are clamped.
+    pub fn round_down_to_grid(self) -> Grid {
+        let lower = self.lower_bounds.map(|c| c.ceil() as GridCoordinate);
+        let upper = self.upper_bounds.map(|c| c.floor() as GridCoordinate);
+        Grid::from_lower_upper(lower, upper.zip(lower, GridCoordinate::max))
+    }
 }
 
 impl fmt::Debug for Aab {
@@ -359,6 +558,58 @@ impl Geometry for Aab {
 mod tests {
     use super::*;
 
+    #[test]
+    fn transform_rotation_grows_extent() {
+        let aab = Aab::new(-0.5, 0.5, -0.5, 0.5, -0.5, 0.5);
+        let rotated = aab.transform(Matrix4::from_angle_z(cgmath::Deg(45.0)));
+        let half_diagonal = FreeCoordinate::sqrt(2.0) / 2.0;
+        for (actual, expected) in [
+            (rotated.lower_bounds_p().x, -half_diagonal),
+            (rotated.upper_bounds_p().x, half_diagonal),
+            (rotated.lower_bounds_p().y, -half_diagonal),
+            (rotated.upper_bounds_p().y, half_diagonal),
+            (rotated.lower_bounds_p().z, -0.5),
+            (rotated.upper_bounds_p().z, 0.5),
+        ] {
+            assert!((actual - expected).abs() < 1e-12, "{rotated:?}");
+        }
+    }
+
+    #[test]
+    fn intersect_ray_through() {
+        let aab = Aab::new(1.0, 2.0, 1.0, 2.0, 1.0, 2.0);
+        let ray = Ray::new([0.0, 0.0, 0.0], [1.0, 1.0, 1.0]);
+        assert_eq!(aab.intersect_ray(&ray), Some((1.0, 2.0)));
+        // Reversed, the ray points away from the box.
+        let ray = Ray::new([0.0, 0.0, 0.0], [-1.0, -1.0, -1.0]);
+        assert_eq!(aab.intersect_ray(&ray), None);
+    }
+
+    #[test]
+    fn intersect_ray_miss() {
+        let aab = Aab::new(1.0, 2.0, 1.0, 2.0, 1.0, 2.0);
+        let ray = Ray::new([0.0, 0.0, 0.0], [1.0, 0.1, 1.0]);
+        assert_eq!(aab.intersect_ray(&ray), None);
+    }
+
+    #[test]
+    fn intersect_ray_inside() {
+        let aab = Aab::new(0.0, 4.0, 0.0, 4.0, 0.0, 4.0);
+        let ray = Ray::new([1.0, 2.0, 2.0], [1.0, 0.0, 0.0]);
+        assert_eq!(aab.intersect_ray(&ray), Some((-1.0, 3.0)));
+    }
+
+    #[test]
+    fn intersect_ray_parallel() {
+        let aab = Aab::new(0.0, 1.0, 0.0, 1.0, 0.0, 1.0);
+        // In the plane of the NY face: included, since the boundary is.
+        let ray = Ray::new([-1.0, 0.0, 0.5], [1.0, 0.0, 0.0]);
+        assert_eq!(aab.intersect_ray(&ray), Some((1.0, 2.0)));
+        // Parallel to the NY face but outside the slab.
+        let ray = Ray::new([-1.0, -0.5, 0.5], [1.0, 0.0, 0.0]);
+        assert_eq!(aab.intersect_ray(&ray), None);
+    }
+
     #[test]
     /// Test `Debug` formatting. Note this should be similar to the [`Grid`] formatting.
     fn aab_debug() {
@@ -379,6 +630,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn round_up_and_down_fractional() {
+        let aab = Aab::new(-1.5, 2.5, 0.0, 1.0, 0.25, 3.75);
+        assert_eq!(
+            aab.round_up_to_grid(),
+            Grid::from_lower_upper([-2, 0, 0], [3, 1, 4])
+        );
+        assert_eq!(
+            aab.round_down_to_grid(),
+            Grid::from_lower_upper([-1, 0, 1], [2, 1, 3])
+        );
+    }
+
+    #[test]
+    fn round_down_smaller_than_cube() {
+        // Straddling a cube boundary
+        let aab = Aab::new(0.5, 1.5, 0.0, 1.0, 0.0, 1.0);
+        assert_eq!(aab.round_up_to_grid(), Grid::new([0, 0, 0], [2, 1, 1]));
+        assert_eq!(aab.round_down_to_grid(), Grid::new([1, 0, 0], [0, 1, 1]));
+
+        // Entirely within one cube
+        let aab = Aab::new(0.25, 0.75, 0.0, 1.0, 0.0, 1.0);
+        assert_eq!(aab.round_up_to_grid(), Grid::new([0, 0, 0], [1, 1, 1]));
+        assert_eq!(aab.round_down_to_grid(), Grid::new([1, 0, 0], [0, 1, 1]));
+    }
+
     #[test]
     #[should_panic]
     fn aab_expand_nan() {
@@ -391,6 +668,30 @@ mod tests {
         let _ = Aab::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0).expand(-0.1);
     }
 
+    #[test]
+    fn split_at_midpoint() {
+        let aab = Aab::new(1.0, 2.0, 3.0, 5.0, 5.0, 6.0);
+        assert_eq!(
+            aab.split(1, aab.center().y),
+            Some((
+                Aab::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0),
+                Aab::new(1.0, 2.0, 4.0, 5.0, 5.0, 6.0),
+            ))
+        );
+        assert_eq!(
+            aab.split(2, 5.0),
+            Some((Aab::new(1.0, 2.0, 3.0, 5.0, 5.0, 5.0), aab))
+        );
+    }
+
+    #[test]
+    fn split_out_of_range() {
+        let aab = Aab::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0);
+        assert_eq!(aab.split(0, 0.5), None);
+        assert_eq!(aab.split(0, 2.5), None);
+        assert_eq!(aab.split(2, FreeCoordinate::NAN), None);
+    }
+
     #[test]
     fn aab_expand_inf() {
         const INF: FreeCoordinate = FreeCoordinate::INFINITY;
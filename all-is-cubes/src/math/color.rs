@@ -409,6 +409,14 @@ impl Mul<Rgb> for Rgb {
         Self(self.0.mul_element_wise(other.0))
     }
 }
+/// Multiplies two color values componentwise, including alpha.
+impl Mul<Rgba> for Rgba {
+    type Output = Self;
+    /// Multiplies two color values componentwise, including alpha.
+    fn mul(self, other: Rgba) -> Self {
+        Self(self.0.mul_element_wise(other.0))
+    }
+}
 /// Multiplies this color value by a scalar.
 impl Mul<NotNan<f32>> for Rgb {
     type Output = Self;
@@ -487,6 +495,33 @@ impl<'a> arbitrary::Arbitrary<'a> for Rgba {
     }
 }
 
+/// Serialized as an array of three components.
+impl serde::Serialize for Rgb {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let array: [NotNan<f32>; 3] = self.0.into();
+        array.serialize(serializer)
+    }
+}
+impl<'de> serde::Deserialize<'de> for Rgb {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let [r, g, b] = <[NotNan<f32>; 3]>::deserialize(deserializer)?;
+        Ok(Rgb::new_nn(r, g, b))
+    }
+}
+/// Serialized as an array of four components.
+impl serde::Serialize for Rgba {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let array: [NotNan<f32>; 4] = self.0.into();
+        array.serialize(serializer)
+    }
+}
+impl<'de> serde::Deserialize<'de> for Rgba {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let [r, g, b, a] = <[NotNan<f32>; 4]>::deserialize(deserializer)?;
+        Ok(Rgba::new_nn(r, g, b, a))
+    }
+}
+
 fn component_to_srgb(c: NotNan<f32>) -> f32 {
     // Source: <https://en.wikipedia.org/w/index.php?title=SRGB&oldid=1002296118#The_forward_transformation_(CIE_XYZ_to_sRGB)> (version as of Feb 3, 2020)
     // Strip NotNan
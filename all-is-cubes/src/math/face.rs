@@ -17,7 +17,9 @@ use crate::math::*;
 /// variant. The two enums use the same discriminant numbering.
 #[allow(clippy::upper_case_acronyms)]
 #[allow(clippy::exhaustive_enums)]
-#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, exhaust::Exhaust)]
+#[derive(
+    Clone, Copy, Debug, Hash, Eq, PartialEq, exhaust::Exhaust, serde::Serialize, serde::Deserialize,
+)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[repr(u8)]
 pub enum Face6 {
@@ -64,6 +66,10 @@ pub enum Face7 {
 
 impl Face6 {
     /// All the values of [`Face6`].
+    ///
+    /// They are in discriminant order: the negative faces before the positive ones,
+    /// and within each, X before Y before Z. This is also the order in which
+    /// [`FaceMap`] iterates, after [`Face7::Within`].
     pub const ALL: [Face6; 6] = [
         Face6::NX,
         Face6::NY,
@@ -86,6 +92,50 @@ impl Face6 {
         }
     }
 
+    /// Returns the face whose normal vector is `vector`, or [`None`] if `vector` is not
+    /// exactly a unit vector along one of the axes.
+    ///
+    /// This is equivalent to `Face6::try_from(vector).ok()`.
+    ///
+    /// ```
+    /// use all_is_cubes::math::{Face6, GridVector};
+    ///
+    /// assert_eq!(Face6::from_normal_vector(GridVector::new(0, -1, 0)), Some(Face6::NY));
+    /// assert_eq!(Face6::from_normal_vector(GridVector::new(0, -2, 0)), None);
+    /// ```
+    #[inline]
+    pub fn from_normal_vector(vector: GridVector) -> Option<Self> {
+        Self::try_from(vector).ok()
+    }
+
+    /// Returns the face whose normal vector is parallel to the given axis, numbered as
+    /// in [`Face6::axis_number()`], and points in the positive direction if `positive`
+    /// is true. Returns [`None`] if `axis` is not 0, 1, or 2.
+    ///
+    /// ```
+    /// use all_is_cubes::math::Face6;
+    ///
+    /// for face in Face6::ALL {
+    ///     assert_eq!(
+    ///         Face6::try_from_axis_and_sign(face.axis_number(), face.is_positive()),
+    ///         Some(face),
+    ///     );
+    /// }
+    /// assert_eq!(Face6::try_from_axis_and_sign(3, true), None);
+    /// ```
+    #[inline]
+    pub const fn try_from_axis_and_sign(axis: usize, positive: bool) -> Option<Self> {
+        match (axis, positive) {
+            (0, false) => Some(Self::NX),
+            (1, false) => Some(Self::NY),
+            (2, false) => Some(Self::NZ),
+            (0, true) => Some(Self::PX),
+            (1, true) => Some(Self::PY),
+            (2, true) => Some(Self::PZ),
+            _ => None,
+        }
+    }
+
     /// Returns which axis this face's normal vector is parallel to, with the numbering
     /// X = 0, Y = 1, Z = 2, which matches the indexes used by most arrays.
     ///
@@ -578,7 +628,9 @@ pub struct Faceless;
 
 /// Container for values keyed by [`Face7`]s.
 #[allow(clippy::exhaustive_structs)]
-#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+#[derive(
+    Clone, Copy, Debug, Default, Hash, PartialEq, Eq, serde::Serialize, serde::Deserialize,
+)]
 pub struct FaceMap<V> {
     /// The value whose key is `Face7::Within`.
     pub within: V,
@@ -612,6 +664,35 @@ impl<V> FaceMap<V> {
         }
     }
 
+    /// Constructs a [`FaceMap`] by using the provided function to compute
+    /// a value for each [`Face6`] enum variant; the [`Face7::Within`] value is
+    /// the default.
+    ///
+    /// Use this instead of [`FaceMap::from_fn()`] when only the six faces of a cube
+    /// are meaningful.
+    ///
+    /// ```
+    /// use all_is_cubes::math::{Face6, FaceMap};
+    ///
+    /// let map = FaceMap::from_fn6(|face| face == Face6::PY);
+    /// assert_eq!(map, FaceMap::default().with(Face6::PY, true));
+    /// ```
+    #[inline]
+    pub fn from_fn6(mut f: impl FnMut(Face6) -> V) -> Self
+    where
+        V: Default,
+    {
+        Self {
+            within: V::default(),
+            nx: f(Face6::NX),
+            ny: f(Face6::NY),
+            nz: f(Face6::NZ),
+            px: f(Face6::PX),
+            py: f(Face6::PY),
+            pz: f(Face6::PZ),
+        }
+    }
+
     /// Constructs a [`FaceMap`] whose negative and positive directions are
     /// equal, and whose [`Face7::Within`] value is the default.
     // TODO: Evaluate whether this is a good API.
@@ -650,10 +731,54 @@ impl<V> FaceMap<V> {
     }
 
     /// Iterate over the map entries by reference.
+    ///
+    /// The entries are always in the same order as [`Face7::ALL`]:
+    ///
+    /// ```
+    /// use all_is_cubes::math::{Face7, FaceMap};
+    ///
+    /// let map = FaceMap::from_fn(|face| face.normal_vector::<i32>().x);
+    /// assert_eq!(
+    ///     map.iter().collect::<Vec<_>>(),
+    ///     vec![
+    ///         (Face7::Within, &0),
+    ///         (Face7::NX, &-1),
+    ///         (Face7::NY, &0),
+    ///         (Face7::NZ, &0),
+    ///         (Face7::PX, &1),
+    ///         (Face7::PY, &0),
+    ///         (Face7::PZ, &0),
+    ///     ],
+    /// );
+    /// ```
     pub fn iter<'s>(&'s self) -> impl Iterator<Item = (Face7, &V)> + 's {
         Face7::ALL.iter().copied().map(move |f| (f, &self[f]))
     }
 
+    /// Iterate over the map entries by mutable reference, in the same order as
+    /// [`Self::iter()`].
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Face7, &mut V)> {
+        let Self {
+            within,
+            nx,
+            ny,
+            nz,
+            px,
+            py,
+            pz,
+        } = self;
+        [
+            (Face7::Within, within),
+            (Face7::NX, nx),
+            (Face7::NY, ny),
+            (Face7::NZ, nz),
+            (Face7::PX, px),
+            (Face7::PY, py),
+            (Face7::PZ, pz),
+        ]
+        .into_iter()
+    }
+
     pub fn into_values(self) -> [V; 7] {
         [
             self.within,
@@ -702,10 +827,10 @@ impl<V> FaceMap<V> {
     /// This may be used for constructing a map with only one interesting entry:
     ///
     /// ```
-    /// use all_is_cubes::math::{Face7, FaceMap};
+    /// use all_is_cubes::math::{Face6, Face7, FaceMap};
     ///
     /// assert_eq!(
-    ///     FaceMap::default().with(Face7::PY, 10),
+    ///     FaceMap::default().with(Face6::PY, 10),
     ///     {
     ///         let mut m = FaceMap::default();
     ///         m[Face7::PY] = 10;
@@ -714,8 +839,8 @@ impl<V> FaceMap<V> {
     /// );
     /// ```
     #[must_use]
-    pub fn with(mut self, face: Face7, value: V) -> Self {
-        self[face] = value;
+    pub fn with(mut self, face: impl Into<Face7>, value: V) -> Self {
+        self[face.into()] = value;
         self
     }
 
@@ -767,6 +892,20 @@ impl<V> IndexMut<Face7> for FaceMap<V> {
     }
 }
 
+/// Access to the six face entries of a [`FaceMap`], never [`Face7::Within`].
+impl<V> Index<Face6> for FaceMap<V> {
+    type Output = V;
+    fn index(&self, face: Face6) -> &V {
+        &self[face.into7()]
+    }
+}
+
+impl<V> IndexMut<Face6> for FaceMap<V> {
+    fn index_mut(&mut self, face: Face6) -> &mut V {
+        &mut self[face.into7()]
+    }
+}
+
 /// The combination of a [`GridPoint`] identifying a unit cube and a [`Face7`] identifying
 /// one face of it. This pattern recurs in selection and collision detection.
 #[derive(Clone, Copy, Hash, Eq, PartialEq)]
@@ -858,6 +997,31 @@ mod tests {
 
     // TODO: More tests of face.matrix()
 
+    #[test]
+    fn face6_from_normal_vector() {
+        for face in Face6::ALL {
+            assert_eq!(Face6::from_normal_vector(face.normal_vector()), Some(face));
+        }
+        assert_eq!(Face6::from_normal_vector(GridVector::new(0, 0, 0)), None);
+        assert_eq!(Face6::from_normal_vector(GridVector::new(2, 0, 0)), None);
+        assert_eq!(Face6::from_normal_vector(GridVector::new(1, 1, 0)), None);
+    }
+
+    #[test]
+    fn face6_all_order() {
+        assert_eq!(
+            Face6::ALL.map(|face| face as u8),
+            [1, 2, 3, 4, 5, 6],
+            "ALL should be in discriminant order"
+        );
+        for face in Face6::ALL {
+            assert_eq!(
+                Face6::try_from_axis_and_sign(face.axis_number(), face.is_positive()),
+                Some(face)
+            );
+        }
+    }
+
     #[test]
     fn face_map_iter_in_enum_order() {
         // TODO: Maybe generalize this to _all_ the Face/FaceMap methods that have an ordering?
@@ -868,6 +1032,44 @@ mod tests {
         )
     }
 
+    #[test]
+    fn face_map_iter_mut() {
+        let mut map = FaceMap::repeat(Face7::Within);
+        for (face, value) in map.iter_mut() {
+            *value = face;
+        }
+        assert_eq!(map, FaceMap::from_fn(|f| f));
+    }
+
+    #[test]
+    fn face6_face7_conversions() {
+        for face in Face6::ALL {
+            let face7 = Face7::from(face);
+            assert_eq!(Face6::try_from(face7), Ok(face));
+            assert_eq!(face7.normal_vector::<i32>(), face.normal_vector());
+        }
+        assert_eq!(Face6::try_from(Face7::Within), Err(Faceless));
+    }
+
+    #[test]
+    fn face_map_index_face6() {
+        let mut map = FaceMap::from_fn(|f| f);
+        for face in Face6::ALL {
+            assert_eq!(map[face], Face7::from(face));
+            map[face] = Face7::Within;
+        }
+        assert_eq!(map, FaceMap::repeat(Face7::Within));
+    }
+
+    #[test]
+    fn face_map_from_fn6() {
+        let map = FaceMap::from_fn6(|f| Some(f));
+        assert_eq!(map.within, None);
+        for face in Face6::ALL {
+            assert_eq!(map[face], Some(face));
+        }
+    }
+
     // TODO: More tests of FaceMap
 
     #[test]
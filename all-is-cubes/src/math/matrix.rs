@@ -180,9 +180,9 @@ impl GridMatrix {
     pub fn decompose(self) -> Option<(GridRotation, GridVector)> {
         Some((
             GridRotation::from_basis([
-                Face6::try_from(self.x).ok()?,
-                Face6::try_from(self.y).ok()?,
-                Face6::try_from(self.z).ok()?,
+                Face6::from_normal_vector(self.x)?,
+                Face6::from_normal_vector(self.y)?,
+                Face6::from_normal_vector(self.z)?,
             ]),
             self.w,
         ))
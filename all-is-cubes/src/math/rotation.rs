@@ -27,7 +27,7 @@ use crate::math::*;
 #[rustfmt::skip]
 #[allow(clippy::upper_case_acronyms)]
 #[allow(clippy::exhaustive_enums)]
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, serde::Serialize, serde::Deserialize)]
 #[repr(u8)]
 pub enum GridRotation {
     // TODO: shuffle or explicitly number these to choose a meaningful numbering
@@ -422,6 +422,54 @@ impl GridRotation {
             }
         }))
     }
+
+    /// Combines `function`, which defines some values within a cube of side length
+    /// `size` (such as the voxels of part of a block), with copies of itself rotated by
+    /// each of `rotations`.
+    ///
+    /// For each cube, the returned function calls `function` with the cube transformed
+    /// by each rotation in turn (as by [`Self::to_positive_octant_matrix`] with the
+    /// given `size`), and returns the first result which is not equal to `empty`, or
+    /// `empty` if all are.
+    ///
+    /// This is useful for building symmetric blocks with
+    /// [`BlockBuilder::voxels_fn()`](crate::block::BlockBuilder::voxels_fn):
+    ///
+    /// ```
+    /// use all_is_cubes::block::{AIR, Block};
+    /// use all_is_cubes::math::{GridPoint, GridRotation, Rgba};
+    ///
+    /// let block = Block::from(Rgba::WHITE);
+    /// // A wall along one edge of a resolution-4 block...
+    /// let wall = |cube: GridPoint| if cube.x == 0 { block.clone() } else { AIR };
+    /// // ...becomes walls along all four edges.
+    /// let walls = GridRotation::symmetrize(4, GridRotation::CLOCKWISE.iterate(), AIR, wall);
+    /// assert_eq!(walls(GridPoint::new(3, 0, 1)), block);
+    /// assert_eq!(walls(GridPoint::new(1, 0, 3)), block);
+    /// assert_eq!(walls(GridPoint::new(1, 0, 1)), AIR);
+    /// ```
+    pub fn symmetrize<V, F>(
+        size: GridCoordinate,
+        rotations: impl IntoIterator<Item = Self>,
+        empty: V,
+        function: F,
+    ) -> impl Fn(GridPoint) -> V
+    where
+        V: Clone + PartialEq,
+        F: Fn(GridPoint) -> V,
+    {
+        let matrices: Vec<GridMatrix> = rotations
+            .into_iter()
+            .map(|rotation| rotation.to_positive_octant_matrix(size))
+            .collect();
+        move |cube| {
+            matrices
+                .iter()
+                .map(|matrix| function(matrix.transform_cube(cube)))
+                .find(|value| *value != empty)
+                .unwrap_or_else(|| empty.clone())
+        }
+    }
 }
 
 impl Default for GridRotation {
@@ -572,4 +620,35 @@ mod tests {
             }
         }
     }
+
+    /// Check that the matrix form of [`GridRotation::from_to`]'s result agrees
+    /// with the face mapping, for some specific cases.
+    #[test]
+    fn from_to_matrix_maps_normals() {
+        use cgmath::Transform as _;
+        use Face6::*;
+        for (from_face, to_face, up_face) in [
+            (PX, PZ, PY),
+            (NZ, PX, PY),
+            (PY, NX, PZ),
+            (PX, NX, PY),
+            (PZ, NY, PX),
+        ] {
+            let matrix = GridRotation::from_to(from_face, to_face, up_face)
+                .unwrap()
+                .to_rotation_matrix();
+            assert_eq!(
+                matrix.transform_vector(from_face.normal_vector()),
+                to_face.normal_vector(),
+                "{from_face:?} -> {to_face:?} with up {up_face:?}"
+            );
+            assert_eq!(
+                matrix.transform_vector(up_face.normal_vector()),
+                up_face.normal_vector(),
+                "{from_face:?} -> {to_face:?} with up {up_face:?}"
+            );
+        }
+        assert_eq!(GridRotation::from_to(PX, PY, PY), None);
+        assert_eq!(GridRotation::from_to(PX, PZ, NX), None);
+    }
 }
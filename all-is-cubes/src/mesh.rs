@@ -45,6 +45,10 @@ pub struct MeshOptions {
     /// Whether to copy light from the space to the vertices (true), or substitute
     /// [`Rgb::ONE`](crate::math::Rgb::ONE) (false).
     use_space_light: bool,
+    /// Level of detail of block meshes.
+    lod: MeshLod,
+    /// Whether to compute [`BlockVertex::ambient_occlusion`] when triangulating spaces.
+    ambient_occlusion: bool,
 }
 
 impl MeshOptions {
@@ -60,9 +64,26 @@ impl MeshOptions {
                     LightingOption::None => false,
                     LightingOption::Flat | LightingOption::Smooth => true,
                 },
+            lod: MeshLod::Full,
+            ambient_occlusion: graphics_options.ambient_occlusion,
         }
     }
 
+    /// Returns these options with the level of detail changed to `lod`.
+    ///
+    /// This is not derived from [`GraphicsOptions`]; it is intended to be chosen by
+    /// the caller for each mesh, such as by the distance from the camera to a chunk.
+    #[must_use]
+    pub fn with_lod(mut self, lod: MeshLod) -> Self {
+        self.lod = lod;
+        self
+    }
+
+    /// Returns the level of detail these options specify.
+    pub fn lod(&self) -> MeshLod {
+        self.lod
+    }
+
     /// Placeholder for use in tests which do not care about any of the
     /// characteristics that are affected by options (yet).
     #[doc(hidden)]
@@ -70,6 +91,25 @@ impl MeshOptions {
         Self {
             transparency: TransparencyOption::Volumetric,
             use_space_light: true,
+            lod: MeshLod::Full,
+            ambient_occlusion: false,
         }
     }
 }
+
+/// Level of detail with which [`triangulate_block`] meshes blocks, as specified by
+/// [`MeshOptions::with_lod()`].
+///
+/// Reduced detail is suitable for blocks that are far enough away that individual
+/// voxels would be too small to see, and produces far fewer vertices.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[non_exhaustive]
+pub enum MeshLod {
+    /// Mesh every voxel of blocks that have voxels. This is the default.
+    #[default]
+    Full,
+    /// Mesh every block as a single cube of its overall
+    /// [`color`](crate::block::EvaluatedBlock::color), as if it were an atom block.
+    Collapsed,
+}
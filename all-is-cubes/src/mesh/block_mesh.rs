@@ -9,11 +9,10 @@ use cgmath::{Point2, Point3, Transform as _};
 use std::fmt::Debug;
 
 use crate::block::{EvaluatedBlock, Evoxel};
-use crate::content::palette;
 use crate::math::{Face6, Face7, FaceMap, FreeCoordinate, GridCoordinate, OpacityCategory, Rgba};
 use crate::mesh::{
     copy_voxels_into_existing_texture, copy_voxels_to_texture, push_quad, BlockVertex,
-    GreedyMesher, MeshOptions, QuadColoring, TextureAllocator, TextureTile,
+    GreedyMesher, MeshLod, MeshOptions, QuadColoring, TextureAllocator, TextureTile,
 };
 use crate::space::{Grid, GridArray, Space};
 
@@ -107,6 +106,35 @@ impl<V, T> BlockMesh<V, T> {
         &self.textures_used
     }
 
+    /// Moves all geometry into the [`Face7::Within`] face mesh, so that none of it will
+    /// be culled, and marks all faces as not opaque, so that no neighbors will be culled.
+    fn move_all_to_within(&mut self) {
+        let mut within = std::mem::take(&mut self.faces.within);
+        for face in Face6::ALL {
+            let face_mesh = std::mem::take(&mut self.faces[face]);
+            let index_offset: u32 = within
+                .vertices
+                .len()
+                .try_into()
+                .expect("vertex index overflow");
+            within.vertices.extend(face_mesh.vertices);
+            within.indices_opaque.extend(
+                face_mesh
+                    .indices_opaque
+                    .into_iter()
+                    .map(|i| i + index_offset),
+            );
+            within.indices_transparent.extend(
+                face_mesh
+                    .indices_transparent
+                    .into_iter()
+                    .map(|i| i + index_offset),
+            );
+        }
+        within.fully_opaque = false;
+        self.faces.within = within;
+    }
+
     /// Returns whether this mesh contains no vertices so it has no visual effect.
     pub fn is_empty(&self) -> bool {
         self.faces.iter().all(|(_, ft)| ft.is_empty())
@@ -158,6 +186,9 @@ impl<V, T> Default for BlockMesh<V, T> {
 /// Generate [`BlockMesh`] for a block's current appearance.
 ///
 /// This may then be may be used as input to [`triangulate_space`](super::triangulate_space).
+///
+/// If `options` specifies [`MeshLod::Collapsed`], then blocks with voxels are meshed
+/// as if they were atom blocks of their overall color.
 pub fn triangulate_block<V: From<BlockVertex>, A: TextureAllocator>(
     block: &EvaluatedBlock,
     texture_allocator: &mut A,
@@ -168,7 +199,12 @@ pub fn triangulate_block<V: From<BlockVertex>, A: TextureAllocator>(
 
     let mut used_any_vertex_colors = false;
 
-    match &block.voxels {
+    let voxels = match options.lod {
+        MeshLod::Full => block.voxels.as_ref(),
+        MeshLod::Collapsed => None,
+    };
+
+    let mut mesh = match voxels {
         None => {
             let faces = FaceMap::from_fn(|face| {
                 let face = match Face6::try_from(face) {
@@ -267,7 +303,7 @@ pub fn triangulate_block<V: From<BlockVertex>, A: TextureAllocator>(
                     || rotated_voxel_range.x_range() != (0..block_resolution)
                     || rotated_voxel_range.y_range() != (0..block_resolution)
                 {
-                    output_by_face[Face7::from(face)].fully_opaque = false;
+                    output_by_face[face].fully_opaque = false;
                 }
 
                 // Layer 0 is the outside surface of the cube and successive layers are
@@ -301,7 +337,7 @@ pub fn triangulate_block<V: From<BlockVertex>, A: TextureAllocator>(
                             if layer == 0 && !color.fully_opaque() {
                                 // If the first layer is transparent in any cube at all, then the face is
                                 // not fully opaque
-                                output_by_face[Face7::from(face)].fully_opaque = false;
+                                output_by_face[face].fully_opaque = false;
                             }
 
                             let voxel_is_visible = {
@@ -398,14 +434,37 @@ pub fn triangulate_block<V: From<BlockVertex>, A: TextureAllocator>(
                             if let Some(ref texture) = texture_if_needed {
                                 QuadColoring::Texture(texture)
                             } else {
-                                // Texture allocation failure.
+                                // Texture allocation failure. Draw each voxel as its own
+                                // solid-colored quad instead, which is costlier but still
+                                // shows the block as it should look.
                                 // TODO: Mark this mesh as defective in the return value, so
                                 // that when more space is available, it can be retried, rather than
                                 // having lingering failures.
-                                // TODO: Add other fallback strategies such as using multiple quads instead
-                                // of textures.
                                 used_any_vertex_colors = true;
-                                QuadColoring::Solid(palette::MISSING_TEXTURE_FALLBACK)
+                                for t in low_corner.y..high_corner.y {
+                                    for s in low_corner.x..high_corner.x {
+                                        let cube =
+                                            transform.transform_point(Point3::new(s, t, layer));
+                                        let color = options.transparency.limit_alpha(
+                                            voxels.get(cube).unwrap_or(&Evoxel::AIR).color,
+                                        );
+                                        push_quad(
+                                            vertices,
+                                            if color.fully_opaque() {
+                                                indices_opaque
+                                            } else {
+                                                indices_transparent
+                                            },
+                                            face,
+                                            depth,
+                                            Point2::new(s, t).map(FreeCoordinate::from),
+                                            Point2::new(s + 1, t + 1).map(FreeCoordinate::from),
+                                            QuadColoring::<A::Tile>::Solid(color),
+                                            block_resolution,
+                                        );
+                                    }
+                                }
+                                return;
                             }
                         };
 
@@ -437,7 +496,13 @@ pub fn triangulate_block<V: From<BlockVertex>, A: TextureAllocator>(
                 },
             }
         }
+    };
+
+    if block.attributes.disable_culling {
+        mesh.move_all_to_within();
     }
+
+    mesh
 }
 
 /// Computes [`BlockMeshes`] for blocks present in a [`Space`].
@@ -28,6 +28,14 @@ pub struct BlockVertex {
     pub face: Face6,
     /// Surface color or texture coordinate.
     pub coloring: Coloring,
+    /// Ambient occlusion factor, from 0 (fully occluded) to 1 (not occluded), by which
+    /// the lighting of this vertex should be multiplied.
+    ///
+    /// This is always 1 in block meshes; [`triangulate_space`](super::triangulate_space)
+    /// computes it when [`GraphicsOptions::ambient_occlusion`] is enabled.
+    ///
+    /// [`GraphicsOptions::ambient_occlusion`]: crate::camera::GraphicsOptions::ambient_occlusion
+    pub ambient_occlusion: f32,
 }
 
 impl BlockVertex {
@@ -128,7 +136,15 @@ pub trait GfxVertex: From<BlockVertex> + Copy + Sized {
 
     /// Transforms a vertex belonging to a general model of a block to its instantiation
     /// in a specific location in space and lighting conditions.
-    fn instantiate_vertex(&mut self, block: Self::BlockInst, lighting: PackedLight);
+    ///
+    /// `ambient_occlusion` is a factor from 0 to 1 by which the vertex's lighting should
+    /// be multiplied; it is 1 unless [`BlockVertex::ambient_occlusion`] is being computed.
+    fn instantiate_vertex(
+        &mut self,
+        block: Self::BlockInst,
+        lighting: PackedLight,
+        ambient_occlusion: f32,
+    );
 
     /// Returns the position of this vertex.
     ///
@@ -156,8 +172,14 @@ impl GfxVertex for BlockVertex {
     }
 
     #[inline]
-    fn instantiate_vertex(&mut self, offset: Self::BlockInst, _lighting: PackedLight) {
+    fn instantiate_vertex(
+        &mut self,
+        offset: Self::BlockInst,
+        _lighting: PackedLight,
+        ambient_occlusion: f32,
+    ) {
         self.position += offset;
+        self.ambient_occlusion = ambient_occlusion;
     }
 
     #[inline]
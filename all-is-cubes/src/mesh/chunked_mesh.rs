@@ -12,9 +12,12 @@ use instant::{Duration, Instant};
 
 use crate::block::EvaluatedBlock;
 use crate::camera::Camera;
-use crate::chunking::{cube_to_chunk, point_to_chunk, ChunkChart, ChunkPos, OctantMask};
+use crate::chunking::{
+    chunk_distance_squared_for_view, cube_to_chunk, point_to_chunk, ChunkChart, ChunkPos,
+    OctantMask,
+};
 use crate::listen::Listener;
-use crate::math::{GridCoordinate, GridPoint};
+use crate::math::{FreeCoordinate, GridCoordinate, GridPoint, GridVector};
 use crate::mesh::{
     triangulate_block, BlockMesh, GfxVertex, MeshOptions, SpaceMesh, TextureAllocator, TextureTile,
 };
@@ -57,6 +60,10 @@ where
     /// If so, then we prioritize adding new chunks over updating existing ones.
     chunks_were_missing: bool,
 
+    /// Distance beyond the view distance at which chunks are discarded.
+    /// See [`Self::set_unload_margin()`].
+    unload_margin: FreeCoordinate,
+
     /// The [`MeshOptions`] specified by the last [`Camera`] provided.
     last_mesh_options: Option<MeshOptions>,
 }
@@ -82,10 +89,21 @@ where
             chunk_chart: ChunkChart::new(0.0),
             view_chunk: ChunkPos(Point3::new(0, 0, 0)),
             chunks_were_missing: true,
+            unload_margin: FreeCoordinate::from(CHUNK_SIZE) * 2.0,
             last_mesh_options: None,
         }
     }
 
+    /// Sets how far beyond the view distance a chunk must be before its mesh (and the
+    /// associated `D` value) is discarded. If the camera comes near enough again, the
+    /// chunk will be recomputed.
+    ///
+    /// A larger margin uses more memory but avoids recomputing chunks when the camera
+    /// moves back and forth. The default is two chunks' width.
+    pub fn set_unload_margin(&mut self, margin: FreeCoordinate) {
+        self.unload_margin = margin;
+    }
+
     /// Returns a reference to the [`Space`] this triangulates.
     pub fn space(&self) -> &URef<Space> {
         &self.space
@@ -152,6 +170,7 @@ where
 
         if Some(&mesh_options) != self.last_mesh_options.as_ref() {
             todo.all_blocks_and_chunks = true;
+            todo.ambient_occlusion = mesh_options.ambient_occlusion;
             self.last_mesh_options = Some(mesh_options);
         }
         let mesh_options = self.last_mesh_options.as_ref().unwrap();
@@ -251,7 +270,19 @@ where
             None
         };
 
-        // TODO: flush todo.chunks and self.chunks of out-of-range chunks.
+        // Discard chunks which are far enough out of view.
+        let unload_distance_squared = ChunkChart::<CHUNK_SIZE>::sanitize_and_square_distance(
+            camera.view_distance() + self.unload_margin,
+        );
+        self.chunks.retain(|&p, _| {
+            let keep =
+                chunk_distance_squared_for_view(p.0 - view_chunk.0) <= unload_distance_squared;
+            if !keep {
+                // Maintain the invariant that todo.chunks has the same keys.
+                todo.chunks.remove(&p);
+            }
+            keep
+        });
 
         CsmUpdateInfo {
             total_time: depth_sort_end_time
@@ -265,6 +296,7 @@ where
             chunk_mesh_callback_times,
             depth_sort_time: depth_sort_end_time.map(|t| t.duration_since(chunk_scan_end_time)),
             block_updates,
+            chunk_count: self.chunks.len(),
         }
     }
 
@@ -293,6 +325,8 @@ pub struct CsmUpdateInfo {
     depth_sort_time: Option<Duration>,
     /// Time spent on building block meshes this frame.
     pub block_updates: TimeStats,
+    /// Number of chunk meshes currently held.
+    pub chunk_count: usize,
 }
 
 impl CustomFormat<StatusText> for CsmUpdateInfo {
@@ -305,7 +339,8 @@ impl CustomFormat<StatusText> for CsmUpdateInfo {
                 Chunk scan     {}
                       mesh gen {}
                       upload   {}
-                      depthsort {}\
+                      depthsort {}
+                Chunks resident {}\
             "},
             self.prep_time.custom_format(StatusText),
             self.block_updates,
@@ -315,6 +350,7 @@ impl CustomFormat<StatusText> for CsmUpdateInfo {
             self.depth_sort_time
                 .unwrap_or(Duration::ZERO)
                 .custom_format(StatusText),
+            self.chunk_count,
         )
     }
 }
@@ -558,6 +594,9 @@ struct CsmTodo<const CHUNK_SIZE: GridCoordinate> {
     /// Membership in this table indicates that the chunk *exists;* todos for chunks
     /// outside of the view area are not tracked.
     chunks: HashMap<ChunkPos<CHUNK_SIZE>, ChunkTodo>,
+    /// Whether the chunk meshes depend on diagonal neighbors of each cube, because
+    /// ambient occlusion is being computed.
+    ambient_occlusion: bool,
 }
 
 impl<const CHUNK_SIZE: GridCoordinate> CsmTodo<CHUNK_SIZE> {
@@ -566,6 +605,7 @@ impl<const CHUNK_SIZE: GridCoordinate> CsmTodo<CHUNK_SIZE> {
             all_blocks_and_chunks: true,
             blocks: HashSet::new(),
             chunks: HashMap::new(),
+            ambient_occlusion: false,
         }
     }
 
@@ -573,6 +613,23 @@ impl<const CHUNK_SIZE: GridCoordinate> CsmTodo<CHUNK_SIZE> {
     where
         F: FnMut(&mut ChunkTodo),
     {
+        if self.ambient_occlusion {
+            // Ambient occlusion samples the edge and corner neighbors of each face,
+            // so any chunk containing a cube diagonally adjacent to this one may
+            // need to be remeshed.
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        let adjacent = cube + GridVector::new(dx, dy, dz);
+                        if let Some(chunk) = self.chunks.get_mut(&cube_to_chunk(adjacent)) {
+                            f(chunk);
+                        }
+                    }
+                }
+            }
+            return;
+        }
+
         // Mark adjacent blocks to account for opaque faces hiding adjacent
         // blocks' faces. We don't need to bother with the current block since
         // the adjacent chunks will always include it (presuming that the chunk
@@ -621,6 +678,9 @@ impl<const CHUNK_SIZE: GridCoordinate> Listener<SpaceChange> for TodoListener<CH
                             todo.blocks.insert(index);
                         }
                     }
+                    SpaceChange::Physics => {}
+                    SpaceChange::Spawn => {}
+                    SpaceChange::MoveCompleted(_) => {}
                 }
             }
         }
@@ -647,11 +707,12 @@ impl ChunkTodo {
 mod tests {
     use super::*;
     use crate::block::Block;
-    use crate::camera::{GraphicsOptions, TransparencyOption, Viewport};
+    use crate::camera::{GraphicsOptions, TransparencyOption, ViewTransform, Viewport};
     use crate::math::GridCoordinate;
     use crate::mesh::{BlockVertex, NoTextures};
     use crate::space::SpaceTransaction;
     use crate::universe::Universe;
+    use cgmath::{One as _, Vector3};
 
     const CHUNK_SIZE: GridCoordinate = 16;
 
@@ -743,6 +804,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn update_diagonal_chunk_with_ambient_occlusion() {
+        let todo: Arc<Mutex<CsmTodo<CHUNK_SIZE>>> = Default::default();
+        let listener = TodoListener(Arc::downgrade(&todo));
+        let corner_chunks = vec![
+            (ChunkPos::new(0, 0, 0), ChunkTodo::CLEAN),
+            (ChunkPos::new(0, 1, 0), ChunkTodo::CLEAN),
+            (ChunkPos::new(1, 0, 0), ChunkTodo::CLEAN),
+            (ChunkPos::new(1, 1, 0), ChunkTodo::CLEAN),
+        ];
+        let p = GridPoint::new(CHUNK_SIZE, CHUNK_SIZE, 5);
+
+        // Without ambient occlusion, only chunks containing face neighbors are marked.
+        todo.lock().unwrap().chunks.extend(corner_chunks.clone());
+        listener.receive(SpaceChange::Block(p));
+        assert_eq!(
+            read_todo_chunks(&todo)[0],
+            (ChunkPos::new(0, 0, 0), ChunkTodo::CLEAN)
+        );
+
+        // With ambient occlusion, the diagonal chunk is marked too.
+        {
+            let mut todo = todo.lock().unwrap();
+            todo.ambient_occlusion = true;
+            todo.chunks.extend(corner_chunks);
+        }
+        listener.receive(SpaceChange::Block(p));
+        let dirty = ChunkTodo {
+            recompute_mesh: true,
+            ..ChunkTodo::CLEAN
+        };
+        assert_eq!(
+            read_todo_chunks(&todo),
+            vec![
+                (ChunkPos::new(0, 0, 0), dirty),
+                (ChunkPos::new(0, 1, 0), dirty),
+                (ChunkPos::new(1, 0, 0), dirty),
+                (ChunkPos::new(1, 1, 0), dirty),
+            ],
+        );
+    }
+
     #[test]
     fn todo_ignores_absent_chunks() {
         let todo: Arc<Mutex<CsmTodo<CHUNK_SIZE>>> = Default::default();
@@ -825,6 +928,38 @@ mod tests {
         // TODO: Check that chunks end at the view distance.
     }
 
+    #[test]
+    fn far_chunks_are_unloaded() {
+        let mut tester = CsmTester::new(Space::empty_positive(CHUNK_SIZE * 12, 1, 1));
+        tester.camera.set_options(GraphicsOptions {
+            view_distance: notnan!(16.0),
+            ..GraphicsOptions::default()
+        });
+        let move_camera = |tester: &mut CsmTester, x: FreeCoordinate| {
+            tester.camera.set_view_transform(ViewTransform {
+                disp: Vector3::new(x, 0.5, 0.5),
+                ..ViewTransform::one()
+            });
+        };
+
+        move_camera(&mut tester, 8.0);
+        let near_count = tester.update(|_, _| {}, |_, _| {}).chunk_count;
+        assert!(near_count > 0);
+        assert_ne!(None, tester.csm.chunk(ChunkPos::new(0, 0, 0)));
+
+        // Far away from the space, nothing should be kept.
+        move_camera(&mut tester, 1000.0);
+        let info = tester.update(|_, _| {}, |_, _| {});
+        assert_eq!(info.chunk_count, 0);
+        assert_eq!(None, tester.csm.chunk(ChunkPos::new(0, 0, 0)));
+        assert!(tester.csm.todo.lock().unwrap().chunks.is_empty());
+
+        // Coming back recomputes the chunks.
+        move_camera(&mut tester, 8.0);
+        let info = tester.update(|_, _| {}, |_, _| {});
+        assert_eq!(info.chunk_count, near_count);
+    }
+
     #[test]
     fn sort_view_every_frame_only_if_transparent() {
         let mut tester = CsmTester::new(Space::empty_positive(1, 1, 1));
@@ -228,6 +228,7 @@ pub(super) fn push_quad<V: From<BlockVertex>>(
                     clamp_max,
                 },
             },
+            ambient_occlusion: 1.0,
         }));
     }
     for &i in QUAD_INDICES {
@@ -5,10 +5,14 @@ use bitvec::vec::BitVec;
 use cgmath::{EuclideanSpace as _, MetricSpace as _, Point3, Vector3, Zero as _};
 use ordered_float::OrderedFloat;
 use std::fmt::Debug;
+use std::marker::PhantomData;
 use std::ops::Range;
 
-use crate::math::{Face7, FaceMap, GridCoordinate, GridRotation};
-use crate::mesh::{BlockMesh, GfxVertex, MeshOptions, TextureTile};
+use crate::block::EvaluatedBlock;
+use crate::math::{Face6, Face7, FaceMap, GridCoordinate, GridPoint, GridRotation, GridVector};
+use crate::mesh::{
+    triangulate_block, BlockMesh, GfxVertex, MeshOptions, TextureAllocator, TextureTile,
+};
 use crate::space::{BlockIndex, Grid, PackedLight, Space};
 
 /// Computes a triangle mesh of a [`Space`].
@@ -159,6 +163,8 @@ impl<V: GfxVertex, T: TextureTile> SpaceMesh<V, T> {
     /// at all. Thus, it always has a consistent interpretation based on
     /// `block_meshes` (as opposed to, for example, using face opacity data not the
     /// same as the meshes and thus producing a rendering with gaps in it).
+    /// The exception is ambient occlusion, if enabled by `options`, which consults
+    /// [`EvaluatedBlock::opaque`] of the neighboring cubes; it affects only shading.
     ///
     /// [`triangulate_blocks`]: super::triangulate_blocks
     pub fn compute<'p, P>(
@@ -182,6 +188,9 @@ impl<V: GfxVertex, T: TextureTile> SpaceMesh<V, T> {
         // TODO: Consider reuse
         let mut transparent_indices = Vec::new();
 
+        // Mesh to use for blocks which `block_meshes` has no mesh for; computed only if needed.
+        let mut broken_mesh: Option<BlockMesh<V, T>> = None;
+
         for cube in bounds.interior_iter() {
             // TODO: On out-of-range, draw an obviously invalid block instead of an invisible one?
             // Do we want to make it the caller's responsibility to specify in-bounds?
@@ -190,9 +199,17 @@ impl<V: GfxVertex, T: TextureTile> SpaceMesh<V, T> {
                 None => continue,
             };
             let already_seen_index = bitset_set_and_get(&mut self.block_indices_used, index.into());
-            let block_mesh = match block_meshes.get(index) {
+            let block_mesh: &BlockMesh<V, T> = match block_meshes.get(index) {
                 Some(mesh) => mesh,
-                None => continue,
+                // The provider is out of date or otherwise wrong; make that visible
+                // instead of leaving a gap.
+                None => broken_mesh.get_or_insert_with(|| {
+                    triangulate_block(
+                        &EvaluatedBlock::broken(),
+                        &mut NoTexturesFor(PhantomData),
+                        options,
+                    )
+                }),
             };
 
             if !already_seen_index {
@@ -242,6 +259,15 @@ impl<V: GfxVertex, T: TextureTile> SpaceMesh<V, T> {
                     }
                 }
 
+                // Ambient occlusion is only computed for the surface of the cube,
+                // since only there do we know which neighbors are relevant.
+                let occlusion = match Face6::try_from(face) {
+                    Ok(face6) if options.ambient_occlusion => {
+                        Some(FaceOcclusion::new(space, cube, face6))
+                    }
+                    _ => None,
+                };
+
                 // Copy vertices, offset to the block position and with lighting
                 let index_offset_usize = self.vertices.len();
                 let index_offset: u32 = index_offset_usize
@@ -249,6 +275,10 @@ impl<V: GfxVertex, T: TextureTile> SpaceMesh<V, T> {
                     .expect("vertex index overflow");
                 self.vertices.extend(face_mesh.vertices.iter());
                 for vertex in &mut self.vertices[index_offset_usize..] {
+                    let ambient_occlusion = match &occlusion {
+                        Some(occlusion) => occlusion.at(vertex.position()),
+                        None => 1.0,
+                    };
                     vertex.instantiate_vertex(
                         inst,
                         if V::WANTS_LIGHT {
@@ -256,6 +286,7 @@ impl<V: GfxVertex, T: TextureTile> SpaceMesh<V, T> {
                         } else {
                             PackedLight::ONE
                         },
+                        ambient_occlusion,
                     );
                 }
                 self.indices
@@ -459,6 +490,81 @@ impl<'a, V, T> BlockMeshProvider<'a, V, T> for &'a [BlockMesh<V, T>] {
     }
 }
 
+/// [`TextureAllocator`] which rejects all allocations, like
+/// [`NoTextures`](super::NoTextures), but claims to produce tiles of type `T`.
+///
+/// Used for meshing [`EvaluatedBlock::broken()`] inside [`SpaceMesh::compute()`], which
+/// has no texture allocator available but must produce a mesh of the same type as the
+/// other block meshes.
+struct NoTexturesFor<T>(PhantomData<T>);
+
+impl<T: TextureTile> TextureAllocator for NoTexturesFor<T> {
+    type Tile = T;
+
+    fn allocate(&mut self, _: Grid) -> Option<Self::Tile> {
+        None
+    }
+}
+
+/// Ambient occlusion factors for the corners of one face of a cube, determined by which
+/// of the cubes in front of that face are opaque.
+struct FaceOcclusion {
+    /// The two axes (as in [`Face6::axis_number()`]) which are parallel to the face.
+    tangents: [usize; 2],
+    /// Occlusion factor for each corner, indexed by whether the corner is at the low (0)
+    /// or high (1) end of each tangent axis.
+    corners: [[f32; 2]; 2],
+}
+
+impl FaceOcclusion {
+    fn new(space: &Space, cube: GridPoint, face: Face6) -> Self {
+        let axis = face.axis_number();
+        let tangents = [(axis + 1) % 3, (axis + 2) % 3];
+        let in_front = cube + face.normal_vector();
+        let opaque_at = |offsets: [GridCoordinate; 2]| -> bool {
+            let mut offset = GridVector::zero();
+            offset[tangents[0]] = offsets[0];
+            offset[tangents[1]] = offsets[1];
+            space.get_evaluated(in_front + offset).opaque
+        };
+
+        let corner = |high_0: bool, high_1: bool| -> f32 {
+            let d0 = if high_0 { 1 } else { -1 };
+            let d1 = if high_1 { 1 } else { -1 };
+            let side_0 = opaque_at([d0, 0]);
+            let side_1 = opaque_at([0, d1]);
+            let diagonal = opaque_at([d0, d1]);
+            let occluders = if side_0 && side_1 {
+                // The diagonal cube can't be seen past both sides, so count it regardless.
+                3
+            } else {
+                u8::from(side_0) + u8::from(side_1) + u8::from(diagonal)
+            };
+            1.0 - f32::from(occluders) / 6.0
+        };
+
+        Self {
+            tangents,
+            corners: [
+                [corner(false, false), corner(false, true)],
+                [corner(true, false), corner(true, true)],
+            ],
+        }
+    }
+
+    /// Returns the occlusion factor at the given position relative to the cube,
+    /// interpolated between the corners.
+    fn at<S: num_traits::ToPrimitive>(&self, position: Point3<S>) -> f32 {
+        let [u, v] = self
+            .tangents
+            .map(|axis| position[axis].to_f32().unwrap_or(0.0).clamp(0.0, 1.0));
+        let [[c00, c01], [c10, c11]] = self.corners;
+        let low = c00 * (1.0 - v) + c01 * v;
+        let high = c10 * (1.0 - v) + c11 * v;
+        low * (1.0 - u) + high * u
+    }
+}
+
 /// Identifies a back-to-front order in which to draw triangles (resulting from
 /// [`triangulate_space`]), based on the direction from which they are being viewed.
 #[allow(clippy::exhaustive_enums)]
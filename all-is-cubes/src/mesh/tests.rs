@@ -3,16 +3,16 @@
 
 //! Tests for [`crate::mesh`].
 
-use cgmath::{MetricSpace as _, Point3, Transform as _, Vector3};
+use cgmath::{EuclideanSpace as _, MetricSpace as _, Point3, Transform as _, Vector3};
 use pretty_assertions::assert_eq;
 
 use super::*;
-use crate::block::{Block, BlockAttributes, Primitive, AIR};
+use crate::block::{Block, BlockAttributes, EvaluatedBlock, Primitive, AIR};
 use crate::camera::{GraphicsOptions, TransparencyOption};
-use crate::content::make_some_blocks;
+use crate::content::{make_some_blocks, palette};
 use crate::math::{
     Face6::{self, *},
-    Face7, FaceMap, FreeCoordinate, GridPoint, GridRotation, Rgba,
+    Face7, FaceMap, FreeCoordinate, GridCoordinate, GridPoint, GridRotation, Rgba,
 };
 use crate::mesh::BlockMesh;
 use crate::space::{Grid, Space, SpacePhysics};
@@ -24,6 +24,7 @@ fn v_c(position: [FreeCoordinate; 3], face: Face6, color: [f32; 4]) -> BlockVert
         position: position.into(),
         face,
         coloring: Coloring::Solid(Rgba::new(color[0], color[1], color[2], color[3])),
+        ambient_occlusion: 1.0,
     }
 }
 
@@ -38,6 +39,7 @@ fn v_t(position: [FreeCoordinate; 3], face: Face6, texture: [TextureCoordinate;
             clamp_min: texture,
             clamp_max: texture,
         },
+        ambient_occlusion: 1.0,
     }
 }
 
@@ -122,7 +124,56 @@ fn excludes_hidden_faces_of_blocks() {
     );
 }
 
-/// Run [`triangulate_space`] with stale block data and confirm it does not panic.
+/// A block with [`BlockAttributes::disable_culling`] has all of its geometry drawn even
+/// when surrounded by opaque blocks, and does not hide its neighbors' faces.
+#[test]
+fn disable_culling_surrounded_by_opaque() {
+    let mut u = Universe::new();
+    let resolution = 4;
+    let [solid] = make_some_blocks();
+    let mut cross_plane_space = |disable_culling: bool| -> Space {
+        let cross = Block::builder()
+            .voxels_fn(&mut u, resolution, |p| {
+                let r = GridCoordinate::from(resolution);
+                if p.x == p.z || p.x == r - 1 - p.z {
+                    &solid
+                } else {
+                    &AIR
+                }
+            })
+            .unwrap()
+            .disable_culling(disable_culling)
+            .build();
+        let mut space = Space::empty_positive(3, 3, 3);
+        space.fill_uniform(space.grid(), &solid).unwrap();
+        space.set([1, 1, 1], &cross).unwrap();
+        space
+    };
+
+    let space = cross_plane_space(true);
+    let (_, block_meshes, space_mesh) = triangulate_blocks_and_space(&space);
+    let cross_mesh = &block_meshes[usize::from(space.get_block_index([1, 1, 1]).unwrap())];
+    let cross_vertex_count: usize = cross_mesh
+        .faces
+        .iter()
+        .map(|(_, face_mesh)| face_mesh.vertices.len())
+        .sum();
+    assert!(cross_vertex_count > 0);
+    assert_eq!(
+        space_mesh.vertices().len(),
+        4 /* vertices per face */ * (
+            9 /* block faces per exterior side of space */ * 6 /* sides of space */
+            + 6 /* neighbors' faces adjacent to the cross block */
+        ) + cross_vertex_count,
+    );
+
+    // For comparison, without the attribute, the cross's geometry on its faces is culled.
+    let (_, _, culled_space_mesh) = triangulate_blocks_and_space(&cross_plane_space(false));
+    assert!(culled_space_mesh.vertices().len() < space_mesh.vertices().len());
+}
+
+/// Run [`triangulate_space`] with stale block data and confirm it does not panic,
+/// and draws the missing block as [`EvaluatedBlock::broken()`].
 #[test]
 fn no_panic_on_missing_blocks() {
     let [block] = make_some_blocks();
@@ -136,12 +187,36 @@ fn no_panic_on_missing_blocks() {
 
     // This should not panic; visual glitches are preferable to failure.
     space.set((0, 0, 0), &block).unwrap(); // render data does not know about this
-    triangulate_space(
+    let space_mesh: SpaceMesh<BlockVertex, TestTextureTile> = triangulate_space(
         &space,
         space.grid(),
         &MeshOptions::dont_care_for_test(),
         &*block_meshes,
     );
+
+    // The broken block is meshed without textures, since `triangulate_space` has
+    // no texture allocator to use, but it still shows its pattern rather than one color.
+    let broken_mesh: BlockMesh<BlockVertex, NoTextures> = triangulate_block(
+        &EvaluatedBlock::broken(),
+        &mut NoTextures,
+        &MeshOptions::dont_care_for_test(),
+    );
+    assert_eq!(
+        space_mesh.vertices().to_vec(),
+        broken_mesh
+            .faces
+            .iter()
+            .flat_map(|(_face, face_render)| face_render.vertices.clone().into_iter())
+            .collect::<Vec<_>>()
+    );
+    let has_color = |color: Rgba| {
+        space_mesh
+            .vertices()
+            .iter()
+            .any(|v| v.coloring == Coloring::Solid(color))
+    };
+    assert!(has_color(palette::BROKEN_BLOCK));
+    assert!(has_color(Rgba::BLACK));
 }
 
 /// Construct a 1x1 recursive block and test that this is equivalent in geometry
@@ -204,6 +279,86 @@ fn space_mesh_equals_block_mesh() {
     assert_eq!(tex.count_allocated(), 1); // for striped faces
 }
 
+/// [`MeshLod::Collapsed`] meshes a recursive block as an atom block of its color.
+#[test]
+fn collapsed_lod_meshes_recursive_block_as_atom() {
+    let resolution = 4;
+    let mut u = Universe::new();
+    let mut blocks = Vec::from(make_some_blocks::<2>());
+    blocks.push(AIR);
+    let recursive_block = Block::builder()
+        .voxels_fn(&mut u, resolution, |p| {
+            &blocks[(p.x as usize).rem_euclid(blocks.len())]
+        })
+        .unwrap()
+        .build();
+    let evaluated = recursive_block.evaluate().unwrap();
+    let atom_evaluated = Block::from(evaluated.color).evaluate().unwrap();
+    let vertex_count = |mesh: &BlockMesh<BlockVertex, TestTextureTile>| -> usize {
+        mesh.faces
+            .iter()
+            .map(|(_, face_mesh)| face_mesh.vertices.len())
+            .sum()
+    };
+    let triangulate = |ev: &EvaluatedBlock, lod: MeshLod| {
+        triangulate_block(
+            ev,
+            &mut TestTextureAllocator::new(),
+            &MeshOptions::dont_care_for_test().with_lod(lod),
+        )
+    };
+
+    let full = triangulate(&evaluated, MeshLod::Full);
+    let collapsed = triangulate(&evaluated, MeshLod::Collapsed);
+    let atom = triangulate(&atom_evaluated, MeshLod::Full);
+    assert_eq!(vertex_count(&collapsed), vertex_count(&atom));
+    assert!(vertex_count(&full) > vertex_count(&collapsed));
+    assert!(collapsed.textures_used.is_empty());
+}
+
+/// [`triangulate_space`] of part of a space produces the same geometry for those cubes
+/// as triangulating the whole space, including culling faces against neighbors outside
+/// the region.
+#[test]
+fn space_mesh_of_region_equals_part_of_whole() {
+    let mut space = Space::empty_positive(4, 4, 4);
+    space
+        .fill(space.grid(), |p| {
+            if (p.x * p.y + p.z) % 3 == 0 {
+                Some(&AIR)
+            } else {
+                Some(non_uniform_fill(p))
+            }
+        })
+        .unwrap();
+    let region = Grid::new([1, 1, 1], [2, 2, 2]);
+
+    let (_, block_meshes, whole_mesh) = triangulate_blocks_and_space(&space);
+    let region_mesh: SpaceMesh<BlockVertex, TestTextureTile> = triangulate_space(
+        &space,
+        region,
+        &MeshOptions::new(&GraphicsOptions::default(), true),
+        &*block_meshes,
+    );
+
+    // Each quad belongs to the cube on the inner side of its face.
+    let region_aab = region.to_aab();
+    let expected: Vec<BlockVertex> = whole_mesh
+        .vertices()
+        .chunks_exact(4)
+        .filter(|quad| {
+            let center = quad.iter().fold(Point3::new(0., 0., 0.), |a, v| {
+                a + v.position.to_vec() / 4.0
+            });
+            region_aab.contains(center - quad[0].face.normal_vector() * 0.01)
+        })
+        .flatten()
+        .copied()
+        .collect();
+    assert!(!expected.is_empty());
+    assert_eq!(region_mesh.vertices().to_vec(), expected);
+}
+
 /// TODO: This test stops being meaningful when we finish migrating the texture allocator to use arbitrary-sized tiles
 #[test]
 fn block_resolution_greater_than_tile() {
@@ -342,6 +497,63 @@ fn shrunken_box_uniform_color() {
     );
 }
 
+/// Greedy meshing should cover each flat, uniform surface of a voxel block with a single
+/// quad rather than one per voxel, without changing what area is covered.
+#[test]
+fn greedy_meshing_merges_half_block() {
+    let resolution = 8;
+    let mut u = Universe::new();
+    let half_block = Block::builder()
+        .voxels_fn(&mut u, resolution, |cube| {
+            if cube.y < 4 {
+                Block::from(Rgba::new(0.0, 1.0, 0.5, 1.0))
+            } else {
+                AIR
+            }
+        })
+        .unwrap()
+        .build();
+    let mesh = test_triangulate_block(half_block);
+
+    // Number of quads that would be needed with one quad per visible voxel face:
+    // full bottom and top surfaces, and half-height sides.
+    let per_voxel_quad_count = 8 * 8 + 4 * (8 * 4) + 8 * 8;
+    let quad_count: usize = mesh.faces.iter().map(|(_, fm)| fm.vertices.len() / 4).sum();
+    assert_eq!(quad_count, 6);
+    assert!(quad_count < per_voxel_quad_count);
+
+    // Compute the area covered by each face's quads.
+    let areas = mesh.faces.map(|_, face_mesh| {
+        face_mesh
+            .vertices
+            .chunks_exact(4)
+            .map(|quad| {
+                let min = quad.iter().fold(Point3::new(1., 1., 1.), |a, v| {
+                    a.zip(v.position, FreeCoordinate::min)
+                });
+                let max = quad.iter().fold(Point3::new(0., 0., 0.), |a, v| {
+                    a.zip(v.position, FreeCoordinate::max)
+                });
+                let e = max - min;
+                // One of the components is zero since the quad is axis-aligned.
+                e.x * e.y + e.y * e.z + e.z * e.x
+            })
+            .sum::<FreeCoordinate>()
+    });
+    assert_eq!(
+        areas,
+        FaceMap {
+            within: 1.0, // top surface, inside the cube
+            nx: 0.5,
+            ny: 1.0,
+            nz: 0.5,
+            px: 0.5,
+            py: 0.0,
+            pz: 0.5,
+        }
+    );
+}
+
 /// Make a [`FaceMap`] with uniform values except for [`Face7::Within`].
 fn except_within<T: Clone>(without: T, within: T) -> FaceMap<T> {
     FaceMap::from_fn(|face| {
@@ -375,6 +587,29 @@ fn atom_transparency() {
     );
 }
 
+/// A partially transparent atom block should be drawn, in the transparent pass only.
+#[test]
+fn atom_partial_alpha_is_transparent_geometry() {
+    let block = Block::from(Rgba::new(1.0, 1.0, 1.0, 0.5));
+
+    let block_mesh = test_triangulate_block(block.clone());
+    for face in Face6::ALL {
+        let face_mesh = &block_mesh.faces[face];
+        assert_eq!(face_mesh.vertices.len(), 4, "{face:?}");
+        assert!(face_mesh.indices_opaque.is_empty(), "{face:?}");
+        assert_eq!(face_mesh.indices_transparent.len(), 6, "{face:?}");
+    }
+
+    let mut space = Space::empty_positive(1, 1, 1);
+    space.set([0, 0, 0], &block).unwrap();
+    let (_, _, space_mesh) = triangulate_blocks_and_space(&space);
+    assert_eq!(space_mesh.opaque_range(), 0..0);
+    assert_eq!(
+        space_mesh.transparent_range(DepthOrdering::Within).len(),
+        6 * 6
+    );
+}
+
 #[test]
 fn atom_transparency_thresholded() {
     // Threshold means that partial transparency should produce exactly the same mesh as 0 or 1
@@ -457,6 +692,53 @@ fn fully_opaque_partial_block() {
     );
 }
 
+/// A hole in one face of a voxel block means that face is not fully opaque, so the
+/// adjacent block's face must not be culled.
+#[test]
+fn hole_in_face_prevents_neighbor_culling() {
+    let resolution = 4;
+    let mut u = Universe::new();
+    let holey_block = Block::builder()
+        .voxels_fn(&mut u, resolution, |cube| {
+            if cube == GridPoint::new(3, 1, 1) {
+                AIR
+            } else {
+                Block::from(Rgba::WHITE)
+            }
+        })
+        .unwrap()
+        .build();
+    let mut space = Space::empty_positive(2, 1, 1);
+    space.set([0, 0, 0], &holey_block).unwrap();
+    space.set([1, 0, 0], Block::from(Rgba::BLACK)).unwrap();
+
+    let (_, block_meshes, space_mesh) = triangulate_blocks_and_space(&space);
+
+    let holey_index = usize::from(space.get_block_index([0, 0, 0]).unwrap());
+    assert_eq!(
+        FaceMap::from_fn(|face| block_meshes[holey_index].faces[face].fully_opaque),
+        FaceMap {
+            within: false,
+            nx: true,
+            ny: true,
+            nz: true,
+            px: false,
+            py: true,
+            pz: true,
+        }
+    );
+
+    // The neighbor's NX face, which is visible through the hole, is present.
+    assert_eq!(
+        space_mesh
+            .vertices()
+            .iter()
+            .filter(|v| v.face == NX && v.position.x == 1.0)
+            .count(),
+        4
+    );
+}
+
 #[test]
 fn transparency_split() {
     let mut space = Space::empty_positive(3, 1, 1);
@@ -517,6 +799,91 @@ fn handling_allocation_failure() {
     let _complex_block_mesh = &block_meshes[0];
 }
 
+/// A block whose voxel colors change but whose shape does not can have its existing
+/// texture rewritten by [`BlockMesh::try_update_texture_only`] instead of being
+/// re-triangulated with a newly allocated texture.
+#[test]
+fn update_texture_only_reuses_tile() {
+    let resolution = 4;
+    let mut u = Universe::new();
+    let mut striped = |colors: [Rgba; 2], hole: bool| {
+        Block::builder()
+            .voxels_fn(&mut u, resolution, |cube| {
+                if hole && cube == GridPoint::new(0, 0, 0) {
+                    AIR
+                } else {
+                    Block::from(colors[cube.x.rem_euclid(2) as usize])
+                }
+            })
+            .unwrap()
+            .build()
+            .evaluate()
+            .unwrap()
+    };
+    let block_1 = striped([Rgba::WHITE, Rgba::BLACK], false);
+    let block_2 = striped([Rgba::BLACK, Rgba::new(1.0, 0.0, 0.0, 1.0)], false);
+    let block_3 = striped([Rgba::WHITE, Rgba::BLACK], true);
+
+    let mut tex = TestTextureAllocator::new();
+    let mut mesh: BlockMesh<BlockVertex, TestTextureTile> =
+        triangulate_block(&block_1, &mut tex, &MeshOptions::dont_care_for_test());
+    assert_eq!(tex.count_allocated(), 1);
+
+    // Same shape, different colors: the texture is updated in place.
+    assert!(mesh.try_update_texture_only(&block_2));
+    assert_eq!(tex.count_allocated(), 1);
+
+    // Different shape: a new mesh is required.
+    assert!(!mesh.try_update_texture_only(&block_3));
+}
+
+#[test]
+fn ambient_occlusion_inside_corner() {
+    let [block] = make_some_blocks();
+    // A floor with one block standing on it, forming an inside corner along the
+    // block's edge.
+    let mut space = Space::empty_positive(3, 2, 3);
+    space
+        .fill_uniform(Grid::new([0, 0, 0], [3, 1, 3]), &block)
+        .unwrap();
+    space.set([1, 1, 0], &block).unwrap();
+
+    let ao_of_floor_vertex = |graphics_options: &GraphicsOptions, position: [FreeCoordinate; 3]| {
+        let options = &MeshOptions::new(graphics_options, false);
+        let block_meshes = triangulate_blocks(&space, &mut TestTextureAllocator::new(), options);
+        let space_mesh: SpaceMesh<BlockVertex, TestTextureTile> =
+            triangulate_space(&space, space.grid(), options, &*block_meshes);
+        let vertices: Vec<&BlockVertex> = space_mesh
+            .vertices()
+            .iter()
+            .filter(|v| v.face == PY && v.position == Point3::from(position))
+            .collect();
+        assert!(!vertices.is_empty(), "no vertex at {position:?}");
+        vertices
+            .iter()
+            .map(|v| v.ambient_occlusion)
+            .fold(f32::INFINITY, f32::min)
+    };
+
+    let mut graphics_options = GraphicsOptions::default();
+    graphics_options.ambient_occlusion = true;
+    // Vertex in the inside corner, touching the standing block.
+    let corner = ao_of_floor_vertex(&graphics_options, [1.0, 1.0, 1.0]);
+    // Vertex on the open floor.
+    let open = ao_of_floor_vertex(&graphics_options, [2.0, 1.0, 3.0]);
+    assert_eq!(open, 1.0);
+    assert!(
+        corner < open,
+        "corner {corner} should be darker than {open}"
+    );
+
+    // Disabled by default.
+    assert_eq!(
+        ao_of_floor_vertex(&GraphicsOptions::default(), [1.0, 1.0, 1.0]),
+        1.0
+    );
+}
+
 #[test]
 fn space_mesh_empty() {
     let t = SpaceMesh::<BlockVertex, TestTextureTile>::new();
@@ -19,7 +19,7 @@ mod tests {
     use crate::block::{Resolution, AIR};
     use crate::content::{make_slab, make_some_blocks};
     use crate::math::{Aab, CubeFace, Face7, Geometry, GridPoint};
-    use crate::space::{Grid, Space, SpacePhysics};
+    use crate::space::{Gravity, Grid, Space, SpacePhysics};
     use crate::time::Tick;
     use crate::universe::Universe;
     use cgmath::{EuclideanSpace, InnerSpace as _, Point3, Vector3, Zero as _};
@@ -55,7 +55,7 @@ mod tests {
     fn freefall_with_gravity() {
         let mut space = Space::empty_positive(1, 1, 1);
         space.set_physics(SpacePhysics {
-            gravity: Vector3::new(0, -20, 0).map(NotNan::from),
+            gravity: Gravity::Uniform(Vector3::new(0, -20, 0).map(NotNan::from)),
             ..SpacePhysics::default()
         });
         let mut body = Body {
@@ -159,7 +159,7 @@ impl Body {
 
         if !self.flying && !tick.paused() {
             if let Some(space) = colliding_space {
-                self.velocity += space.physics().gravity.map(|c| c.into_inner()) * dt;
+                self.velocity += space.physics().gravity_at(self.position) * dt;
             }
         }
 
@@ -543,7 +543,7 @@ pub(crate) fn nudge_on_ray(
 
 #[cfg(test)]
 mod tests {
-    use crate::block::{Block, AIR};
+    use crate::block::{Block, Modifier, AIR};
     use crate::content::{make_slab, make_some_blocks};
     use crate::math::point_to_enclosing_cube;
     use crate::raytracer::print_space;
@@ -601,6 +601,41 @@ mod tests {
         );
     }
 
+    /// A [`BlockCollision::Hard`] block which is displaced by [`Modifier::Move`] should
+    /// collide as a solid occupying its displaced volume, even where its voxels are
+    /// non-colliding.
+    #[test]
+    fn collide_along_ray_moved_hard_block() {
+        collide_along_ray_tester(
+            1.5,
+            |u| {
+                let [voxel] = make_some_blocks();
+                let block = Block::builder()
+                    .collision(BlockCollision::Hard)
+                    .voxels_fn(u, 2, |cube| if cube.y >= 1 { &AIR } else { &voxel })
+                    .unwrap()
+                    .build();
+                // Move the block down so that only its upper, air half is in its cube.
+                let moved = Modifier::Move {
+                    direction: Face6::NY,
+                    distance: 128,
+                    velocity: 0,
+                }
+                .attach(block);
+                [AIR, moved]
+            },
+            Some(CollisionRayEnd {
+                t_distance: 0.5, // half of a ray with magnitude 2
+                contact: Contact::Voxel {
+                    cube: GridPoint::new(1, 0, 0),
+                    resolution: 2,
+                    // TODO: the voxel reported here is arbitrary, so this test is fragile
+                    voxel: CubeFace::new([0, 0, 0], Face7::PY),
+                },
+            }),
+        );
+    }
+
     /// Check that colliding against two recursive blocks correctly picks the taller one,
     /// in either ordering.
     #[test]
@@ -12,6 +12,7 @@
 //! by the terminal UI and in unit tests via [`print_space`].
 
 use std::fmt;
+use std::sync::Arc;
 
 use cgmath::{EuclideanSpace as _, InnerSpace as _, Point2, Vector2, Vector3};
 use cgmath::{Point3, Vector4};
@@ -19,12 +20,14 @@ use ordered_float::NotNan;
 #[cfg(feature = "rayon")]
 use rayon::iter::{IntoParallelIterator as _, ParallelIterator as _};
 
-use crate::block::{Evoxel, Resolution, AIR};
+use crate::block::{Evoxel, MonitorView, Resolution, AIR};
 use crate::camera::{Camera, GraphicsOptions, TransparencyOption};
 use crate::math::{point_to_enclosing_cube, smoothstep};
-use crate::math::{Face7, FreeCoordinate, GridPoint, Rgb, Rgba};
+use crate::math::{Face6, Face7, FaceMap, FreeCoordinate, GridPoint, Rgb, Rgba};
 use crate::raycast::Ray;
-use crate::space::{BlockIndex, GridArray, PackedLight, Space, SpaceBlockData};
+use crate::space::{
+    pack_sky, sky_face_for_cube, BlockIndex, GridArray, PackedLight, Sky, Space, SpaceBlockData,
+};
 use crate::util::{CustomFormat, StatusText};
 
 mod pixel_buf;
@@ -45,9 +48,9 @@ pub struct SpaceRaytracer<D: RtBlockData> {
 
     graphics_options: GraphicsOptions,
     custom_options: D::Options,
-    sky_color: Rgb,
+    sky: Sky,
     sky_data: D,
-    packed_sky_color: PackedLight,
+    packed_sky_color: FaceMap<PackedLight>,
 }
 
 impl<D: RtBlockData> SpaceRaytracer<D> {
@@ -56,43 +59,87 @@ impl<D: RtBlockData> SpaceRaytracer<D> {
         space: &Space,
         graphics_options: GraphicsOptions,
         custom_options: D::Options,
+    ) -> Self {
+        Self::new_impl(space, graphics_options, custom_options, true)
+    }
+
+    /// Implementation of [`Self::new`] which allows disabling drawing of monitor blocks
+    /// so that monitors showing other monitors do not recurse without limit.
+    fn new_impl(
+        space: &Space,
+        graphics_options: GraphicsOptions,
+        custom_options: D::Options,
+        draw_monitors: bool,
     ) -> Self {
         let options = RtOptionsRef {
             graphics_options: &graphics_options,
             custom_options: &custom_options,
         };
-        let sky_color = space.physics().sky_color;
+        let sky = space.physics().sky.clone();
         SpaceRaytracer {
             blocks: space
                 .block_data()
                 .iter()
-                .map(|sbd| TracingBlock::<D>::from_block(options, sbd))
+                .enumerate()
+                .map(|(index, sbd)| {
+                    TracingBlock::<D>::from_block(options, index as BlockIndex, sbd, draw_monitors)
+                })
                 .collect(),
             cubes: prepare_cubes(space),
-            sky_color,
+            packed_sky_color: pack_sky(&sky),
+            sky,
             sky_data: D::sky(options),
-            packed_sky_color: sky_color.into(),
 
             graphics_options,
             custom_options,
         }
     }
 
+    /// Replaces the sky used for background and out-of-bounds lighting.
+    fn set_sky(&mut self, sky: &Sky) {
+        self.packed_sky_color = pack_sky(sky);
+        self.sky = sky.clone();
+    }
+
     /// Computes a single image pixel from the given ray.
+    ///
+    /// If `include_sky` is false, then rays which do not hit anything opaque are left
+    /// transparent rather than filled with the sky color, and fog fades surfaces toward
+    /// transparency rather than toward the sky color. This is suitable for layers which
+    /// are to be composited over something else, such as the UI over the world.
     pub fn trace_ray<P: PixelBuf<BlockData = D>>(
         &self,
         ray: Ray,
         include_sky: bool,
+    ) -> (P, RaytraceInfo) {
+        self.trace_ray_into(ray, include_sky, P::default())
+    }
+
+    /// Computes a single image pixel from the given ray, continuing to accumulate into
+    /// `pixel_buf`, which may already contain partially transparent content that is in
+    /// front of this space (such as the UI in front of the world).
+    ///
+    /// Since [`PixelBuf`]s accumulate front to back, this composites the content of this
+    /// space _behind_ the existing content, with the same result as premultiplied-alpha
+    /// “over” blending.
+    pub fn trace_ray_into<P: PixelBuf<BlockData = D>>(
+        &self,
+        ray: Ray,
+        include_sky: bool,
+        pixel_buf: P,
     ) -> (P, RaytraceInfo) {
         let options = RtOptionsRef {
             graphics_options: &self.graphics_options,
             custom_options: &self.custom_options,
         };
 
+        let sky_color = self.sky.sample(ray.direction);
         let mut state: TracingState<P> = TracingState {
             t_to_absolute_distance: ray.direction.magnitude(),
+            fog_color: if include_sky { Some(sky_color) } else { None },
+            fogged_out: false,
             cubes_traced: 0,
-            pixel_buf: P::default(),
+            pixel_buf,
         };
         let surface_iter = SurfaceIter::new(self, ray);
 
@@ -136,7 +183,7 @@ impl<D: RtBlockData> SpaceRaytracer<D> {
         }
         state.finish(
             if include_sky {
-                self.sky_color.with_alpha_one()
+                sky_color.with_alpha_one()
             } else {
                 Rgba::TRANSPARENT
             },
@@ -149,7 +196,7 @@ impl<D: RtBlockData> SpaceRaytracer<D> {
         self.cubes
             .get(cube)
             .map(|b| b.lighting)
-            .unwrap_or(self.packed_sky_color)
+            .unwrap_or_else(|| self.packed_sky_color[sky_face_for_cube(self.cubes.grid(), cube)])
     }
 
     #[inline]
@@ -157,7 +204,10 @@ impl<D: RtBlockData> SpaceRaytracer<D> {
         self.cubes
             .get(cube)
             .map(|b| b.lighting.value())
-            .unwrap_or(self.sky_color)
+            .unwrap_or_else(|| {
+                self.sky
+                    .face_color(sky_face_for_cube(self.cubes.grid(), cube))
+            })
     }
 
     fn get_interpolated_light(&self, point: Point3<FreeCoordinate>, face: Face7) -> Rgb {
@@ -203,7 +253,7 @@ impl<D: RtBlockData> SpaceRaytracer<D> {
             Point3::from_vec(origin) + p,
         ) {
             Some(cube) => self.get_packed_light(cube),
-            None => self.packed_sky_color,
+            None => self.packed_sky_color.within,
         };
         let lin_lo = -0.5;
         let lin_hi = 0.5;
@@ -356,7 +406,7 @@ where
             .field("cubes.grid", &self.cubes.grid())
             .field("graphics_options", &self.graphics_options)
             .field("custom_options", &self.custom_options)
-            .field("sky_color", &self.sky_color)
+            .field("sky", &self.sky)
             .finish_non_exhaustive()
     }
 }
@@ -369,10 +419,31 @@ where
 #[non_exhaustive]
 pub struct RaytraceInfo {
     cubes_traced: usize,
+    /// Number of pixels traced, when this is the result of drawing an image.
+    pixels_traced: usize,
+    /// Number of pixels which would have been traced but for running out of time.
+    pixels_untraced: usize,
+}
+impl RaytraceInfo {
+    /// Returns the fraction, from 0 to 1, of the requested pixels which were actually
+    /// traced.
+    ///
+    /// This is less than 1 only if tracing was stopped early, as by
+    /// [`RtRenderer::draw_with_deadline()`].
+    pub fn completion(&self) -> f32 {
+        let requested = self.pixels_traced + self.pixels_untraced;
+        if requested == 0 {
+            1.0
+        } else {
+            self.pixels_traced as f32 / requested as f32
+        }
+    }
 }
 impl std::ops::AddAssign<RaytraceInfo> for RaytraceInfo {
     fn add_assign(&mut self, other: Self) {
         self.cubes_traced += other.cubes_traced;
+        self.pixels_traced += other.pixels_traced;
+        self.pixels_untraced += other.pixels_untraced;
     }
 }
 impl std::iter::Sum for RaytraceInfo {
@@ -390,8 +461,16 @@ impl std::iter::Sum for RaytraceInfo {
 
 impl CustomFormat<StatusText> for RaytraceInfo {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>, _format_type: StatusText) -> fmt::Result {
-        let &Self { cubes_traced } = self;
-        write!(fmt, "Cubes traced: {cubes_traced}")
+        let &Self {
+            cubes_traced,
+            pixels_traced: _,
+            pixels_untraced,
+        } = self;
+        write!(fmt, "Cubes traced: {cubes_traced}")?;
+        if pixels_untraced > 0 {
+            write!(fmt, " (stopped at {:.0}%)", self.completion() * 100.0)?;
+        }
+        Ok(())
     }
 }
 
@@ -425,28 +504,122 @@ struct TracingCubeData {
 enum TracingBlock<D> {
     Atom(D, Rgba),
     Recur(D, Resolution, GridArray<Evoxel>),
+    /// A block with a [`MonitorView`] face. Its other faces are drawn as the inner block.
+    Monitor(Box<TracingBlock<D>>, Arc<MonitorTexture>),
 }
 
 impl<D: RtBlockData> TracingBlock<D> {
-    fn from_block(options: RtOptionsRef<'_, D::Options>, block_data: &SpaceBlockData) -> Self {
+    fn from_block(
+        options: RtOptionsRef<'_, D::Options>,
+        index: BlockIndex,
+        block_data: &SpaceBlockData,
+        draw_monitors: bool,
+    ) -> Self {
         let evaluated = block_data.evaluated();
-        let pixel_block_data = D::from_block(options, block_data);
-        if let Some(ref voxels) = evaluated.voxels {
+        let pixel_block_data = D::from_block(options, index, block_data);
+        let tracing_block = if let Some(ref voxels) = evaluated.voxels {
             TracingBlock::Recur(pixel_block_data, evaluated.resolution, voxels.clone())
         } else {
             TracingBlock::Atom(pixel_block_data, evaluated.color)
+        };
+
+        match &evaluated.attributes.monitor {
+            Some(view) if draw_monitors => {
+                match MonitorTexture::new(view, options.graphics_options) {
+                    Some(texture) => {
+                        TracingBlock::Monitor(Box::new(tracing_block), Arc::new(texture))
+                    }
+                    // If the space is unavailable, draw the block without the monitor.
+                    None => tracing_block,
+                }
+            }
+            _ => tracing_block,
         }
     }
 }
 
+impl<D> TracingBlock<D> {
+    fn block_data(&self) -> &D {
+        match self {
+            TracingBlock::Atom(block_data, _) | TracingBlock::Recur(block_data, _, _) => block_data,
+            TracingBlock::Monitor(inner, _) => inner.block_data(),
+        }
+    }
+}
+
+/// The image displayed by a [`MonitorView`], traced when the block is prepared.
+///
+/// This is a snapshot; [`UpdatingSpaceRaytracer`] listens to the viewed space and
+/// prepares the block again when it changes.
+#[derive(Clone, Debug)]
+struct MonitorTexture {
+    face: Face6,
+    /// Right and up directions of the image, as per [`MonitorView::face_axes`].
+    axes: (Vector3<FreeCoordinate>, Vector3<FreeCoordinate>),
+    resolution: usize,
+    /// Texels in row-major order, top row first.
+    texels: Box<[Rgba]>,
+}
+
+impl MonitorTexture {
+    /// Traces the image for `view`, or returns [`None`] if its space cannot be borrowed.
+    fn new(view: &MonitorView, graphics_options: &GraphicsOptions) -> Option<Self> {
+        let space = view.space.try_borrow().ok()?;
+        let rt = SpaceRaytracer::<()>::new_impl(&space, graphics_options.clone(), (), false);
+        let camera = view.camera(graphics_options.clone());
+        let viewport = camera.viewport();
+        let resolution = viewport.framebuffer_size.x as usize;
+
+        let mut texels = Vec::with_capacity(resolution * resolution);
+        for y in 0..resolution {
+            let y = viewport.normalize_fb_y(y);
+            for x in 0..resolution {
+                let x = viewport.normalize_fb_x(x);
+                let (buf, _) = rt
+                    .trace_ray::<ColorBuf>(camera.project_ndc_into_world(Point2::new(x, y)), true);
+                texels.push(Rgba::from(buf));
+            }
+        }
+
+        Some(MonitorTexture {
+            face: view.face,
+            axes: view.face_axes(),
+            resolution,
+            texels: texels.into_boxed_slice(),
+        })
+    }
+
+    /// Returns the color of the texel containing `point_in_cube`, which should be a point
+    /// on the monitor face in the coordinates of the block's cube (that is, in the range
+    /// 0 to 1).
+    fn texel_at(&self, point_in_cube: Point3<FreeCoordinate>) -> Rgba {
+        let (right, up) = self.axes;
+        let centered = point_in_cube - Point3::new(0.5, 0.5, 0.5);
+        let scale = self.resolution as FreeCoordinate;
+        let texel_coordinate =
+            |c: FreeCoordinate| ((c + 0.5) * scale).clamp(0.0, scale - 1.0) as usize;
+        let x = texel_coordinate(centered.dot(right));
+        let y = self.resolution - 1 - texel_coordinate(centered.dot(up));
+        self.texels[y * self.resolution + x]
+    }
+}
+
 /// Holds a [`PixelBuf`] and other per-ray state, and updates it
 /// according to the things it encounters.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
 struct TracingState<P: PixelBuf> {
     /// Conversion factor from raycaster `t` values to “true” [`Space`] distance values
     /// where 1 unit = 1 block thickness.
     t_to_absolute_distance: f64,
 
+    /// Color which surfaces fade into with distance, or [`None`] if they should fade
+    /// into transparency instead.
+    fog_color: Option<Rgb>,
+
+    /// Whether an opaque surface has been faded toward transparency by fog, which
+    /// must hide everything behind it just as the surface would without fog.
+    fogged_out: bool,
+
     /// Number of cubes traced through -- controlled by the caller, so not necessarily
     /// equal to the number of calls to [`Self::trace_through_surface()`].
     cubes_traced: usize,
@@ -467,7 +640,7 @@ impl<P: PixelBuf> TracingState<P> {
                 .add(Rgba::new(1.0, 1.0, 1.0, 1.0), &P::BlockData::error(options));
             true
         } else {
-            self.pixel_buf.opaque()
+            self.fogged_out || self.pixel_buf.opaque()
         }
     }
 
@@ -495,6 +668,7 @@ impl<P: PixelBuf> TracingState<P> {
             self.pixel_buf,
             RaytraceInfo {
                 cubes_traced: self.cubes_traced,
+                ..RaytraceInfo::default()
             },
         )
     }
@@ -507,6 +681,33 @@ impl<P: PixelBuf> TracingState<P> {
         rt: &SpaceRaytracer<P::BlockData>,
     ) {
         if let Some(color) = surface.to_lit_color(rt) {
+            let options = &rt.graphics_options;
+            let fog_distance = options.view_distance * options.fog_distance;
+            let fog = options.fog.opacity_at(
+                (surface.t_distance * self.t_to_absolute_distance / fog_distance.into_inner())
+                    as f32,
+            );
+            let color = if fog > 0.0 {
+                match self.fog_color {
+                    Some(fog_color) => {
+                        (color.to_rgb() * (1.0 - fog) + fog_color * fog).with_alpha(color.alpha())
+                    }
+                    None => {
+                        // Fading to transparency must not reveal what is behind an
+                        // opaque surface, so stop there.
+                        self.fogged_out = color.fully_opaque();
+                        color
+                            .to_rgb()
+                            .with_alpha(color.alpha() * NotNan::new(1.0 - fog).unwrap())
+                    }
+                }
+            } else {
+                color
+            };
+            self.pixel_buf.set_surface(
+                surface.t_distance * self.t_to_absolute_distance,
+                surface.normal,
+            );
             self.pixel_buf.add(color, surface.block_data);
         }
     }
@@ -575,3 +776,124 @@ mod rayon_helper {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::Block;
+    use crate::camera::{FogOption, LightingOption};
+    use crate::space::Grid;
+
+    #[test]
+    fn fog_blends_far_surfaces_toward_sky() {
+        let sky_color = rgb_const!(0.0, 0.0, 1.0);
+        let mut space = Space::builder(Grid::new([0, 0, 0], [100, 2, 1]))
+            .sky_color(sky_color)
+            .build_empty();
+        space.set([2, 0, 0], Block::from(Rgba::WHITE)).unwrap();
+        space.set([90, 1, 0], Block::from(Rgba::WHITE)).unwrap();
+        let near_ray = Ray::new([0.5, 0.5, 0.5], [1.0, 0.0, 0.0]);
+        let far_ray = Ray::new([0.5, 1.5, 0.5], [1.0, 0.0, 0.0]);
+
+        let trace = |fog: FogOption, ray: Ray| -> Rgba {
+            let options = GraphicsOptions {
+                fog,
+                view_distance: NotNan::from(100),
+                lighting_display: LightingOption::None,
+                ..GraphicsOptions::default()
+            };
+            let rt = SpaceRaytracer::<()>::new(&space, options, ());
+            rt.trace_ray::<ColorBuf>(ray, true).0.into()
+        };
+
+        // Without fog, both are unaffected.
+        assert_eq!(trace(FogOption::None, near_ray), Rgba::WHITE);
+        assert_eq!(trace(FogOption::None, far_ray), Rgba::WHITE);
+
+        // With fog, the near surface is (nearly) unaffected...
+        let near = trace(FogOption::Abrupt, near_ray);
+        assert!(near.red().into_inner() > 0.999, "{near:?}");
+        assert_eq!(near.blue().into_inner(), 1.0);
+        // ...and the far surface is mostly fogged.
+        let far = trace(FogOption::Abrupt, far_ray);
+        let expected_fog = FogOption::Abrupt.opacity_at(89.5 / 100.0);
+        assert!(expected_fog > 0.5);
+        assert!(
+            (far.red().into_inner() - (1.0 - expected_fog)).abs() < 1e-4,
+            "{far:?}"
+        );
+        assert_eq!(far.blue().into_inner(), 1.0);
+        assert_eq!(far.alpha().into_inner(), 1.0);
+    }
+
+    #[test]
+    fn sky_is_transparent_if_not_included() {
+        let sky_color = rgb_const!(0.0, 0.0, 1.0);
+        let mut space = Space::builder(Grid::new([0, 0, 0], [100, 2, 1]))
+            .sky_color(sky_color)
+            .build_empty();
+        space.set([90, 1, 0], Block::from(Rgba::WHITE)).unwrap();
+        // Behind the fogged surface, and should not be seen through it.
+        space
+            .set([95, 1, 0], Block::from(rgba_const!(1.0, 0.0, 0.0, 1.0)))
+            .unwrap();
+        let rt = SpaceRaytracer::<()>::new(
+            &space,
+            GraphicsOptions {
+                fog: FogOption::Abrupt,
+                view_distance: NotNan::from(100),
+                lighting_display: LightingOption::None,
+                ..GraphicsOptions::default()
+            },
+            (),
+        );
+        let trace = |ray: Ray, include_sky: bool| -> Rgba {
+            rt.trace_ray::<ColorBuf>(ray, include_sky).0.into()
+        };
+        let empty_ray = Ray::new([0.5, 0.5, 0.5], [1.0, 0.0, 0.0]);
+        let far_ray = Ray::new([0.5, 1.5, 0.5], [1.0, 0.0, 0.0]);
+
+        // A ray which misses everything is the sky color only if asked for.
+        assert_eq!(trace(empty_ray, true), sky_color.with_alpha_one());
+        assert_eq!(trace(empty_ray, false), Rgba::TRANSPARENT);
+
+        // A fogged surface fades to transparent instead of to the sky color, without
+        // revealing the surface behind it.
+        let far = trace(far_ray, false);
+        let expected_fog = FogOption::Abrupt.opacity_at(89.5 / 100.0);
+        assert!(
+            (far.alpha().into_inner() - (1.0 - expected_fog)).abs() < 1e-4,
+            "{far:?}"
+        );
+        assert_eq!(far.to_rgb(), Rgb::ONE);
+    }
+
+    /// A semi-transparent surface in one space is blended over the other space traced
+    /// into the same buffer, as the UI is over the world.
+    #[test]
+    fn trace_ray_into_blends_behind_transparent_surface() {
+        let grid = Grid::new([0, 0, 0], [3, 1, 1]);
+        let mut ui_space = Space::empty(grid);
+        ui_space
+            .set([1, 0, 0], Block::from(Rgba::new(1.0, 0.0, 0.0, 0.5)))
+            .unwrap();
+        let mut world_space = Space::empty(grid);
+        world_space
+            .set([1, 0, 0], Block::from(Rgba::new(0.0, 0.0, 1.0, 1.0)))
+            .unwrap();
+        let options = GraphicsOptions {
+            fog: FogOption::None,
+            lighting_display: LightingOption::None,
+            transparency: TransparencyOption::Surface,
+            ..GraphicsOptions::default()
+        };
+        let ui_rt = SpaceRaytracer::<()>::new(&ui_space, options.clone(), ());
+        let world_rt = SpaceRaytracer::<()>::new(&world_space, options, ());
+        let ray = Ray::new([0.5, 0.5, 0.5], [1.0, 0.0, 0.0]);
+
+        let (ui_buf, _) = ui_rt.trace_ray::<ColorBuf>(ray, false);
+        assert!(!ui_buf.opaque());
+        let (buf, _) = world_rt.trace_ray_into(ray, true, ui_buf);
+        assert_eq!(Rgba::from(buf), Rgba::new(0.5, 0.0, 0.5, 1.0));
+    }
+}
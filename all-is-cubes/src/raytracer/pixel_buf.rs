@@ -8,8 +8,8 @@ use std::convert::TryFrom as _;
 use cgmath::{Vector3, Zero as _};
 
 use crate::camera::GraphicsOptions;
-use crate::math::Rgba;
-use crate::space::SpaceBlockData;
+use crate::math::{Face7, FreeCoordinate, GridCoordinate, Rgba};
+use crate::space::{BlockIndex, SpaceBlockData};
 
 /// Borrowed data which may be used to customize the result of raytracing.
 #[derive(Debug, Eq, PartialEq)]
@@ -60,6 +60,14 @@ pub trait PixelBuf: Default {
     /// more future-proof.
     fn add(&mut self, surface_color: Rgba, block_data: &Self::BlockData);
 
+    /// Informs the buffer of the geometry of the surface whose color will be passed to
+    /// the next call to [`Self::add`]: its distance along the ray, in world units, from
+    /// the ray's origin, and the face of the cube or voxel which the ray struck.
+    /// Not called before adding the sky color.
+    ///
+    /// The default implementation does nothing.
+    fn set_surface(&mut self, _distance: FreeCoordinate, _normal: Face7) {}
+
     /// Indicates that the trace did not intersect any space that could have contained
     /// anything to draw. May be used for special diagnostic drawing. If used, should
     /// disable the effects of future [`Self::add`] calls.
@@ -82,6 +90,19 @@ pub trait PixelBuf: Default {
         result.add(color, &Self::BlockData::sky(options));
         result
     }
+
+    /// Combines several buffers into one, such as the multiple samples traced for
+    /// a single pixel when antialiasing.
+    ///
+    /// The default implementation returns the first item (or the default value if
+    /// there are none), which is suitable for formats whose values cannot be
+    /// meaningfully averaged, such as characters.
+    fn mean<I>(items: I) -> Self
+    where
+        I: IntoIterator<Item = Self>,
+    {
+        items.into_iter().next().unwrap_or_default()
+    }
 }
 
 /// Precomputed data about a [`Space`]'s blocks that may be used by a [`PixelBuf`].
@@ -98,7 +119,16 @@ pub trait RtBlockData: Send + Sync {
 
     /// Returns the data that should be stored for a particular block and passed
     /// to [`PixelBuf::add`] when that block is traced onto/through.
-    fn from_block(options: RtOptionsRef<'_, Self::Options>, block: &SpaceBlockData) -> Self;
+    ///
+    /// `index` is the block's index in the [`Space`] being traced, as would be returned
+    /// by [`Space::get_block_index()`].
+    ///
+    /// [`Space::get_block_index()`]: crate::space::Space::get_block_index
+    fn from_block(
+        options: RtOptionsRef<'_, Self::Options>,
+        index: BlockIndex,
+        block: &SpaceBlockData,
+    ) -> Self;
 
     /// Returns what should be passed to [`PixelBuf::add`] when the raytracer
     /// encounters an error.
@@ -112,11 +142,31 @@ pub trait RtBlockData: Send + Sync {
 /// Trivial implementation of [`RtBlockData`] which stores nothing.
 impl RtBlockData for () {
     type Options = ();
-    fn from_block(_: RtOptionsRef<'_, Self::Options>, _: &SpaceBlockData) -> Self {}
+    fn from_block(_: RtOptionsRef<'_, Self::Options>, _: BlockIndex, _: &SpaceBlockData) -> Self {}
     fn error(_: RtOptionsRef<'_, Self::Options>) -> Self {}
     fn sky(_: RtOptionsRef<'_, Self::Options>) -> Self {}
 }
 
+/// Value of `ray_alpha`, the fraction of a ray not yet obstructed, below which the
+/// [`PixelBuf`]s in this module consider themselves [opaque](PixelBuf::opaque).
+///
+/// Let's suppose that we don't care about differences that can't be represented
+/// in 8-bit color...not considering gamma. All buffers use the same threshold, so
+/// that they stop at the same surface.
+const OPAQUE_RAY_ALPHA: f32 = 1.0 / 256.0;
+
+/// Accumulates the opacity of `surface_color` into `ray_alpha`, for buffers which
+/// record something about the surface at which the ray becomes opaque, and returns
+/// whether it is this surface.
+#[inline]
+fn becomes_opaque(ray_alpha: &mut f32, surface_color: Rgba) -> bool {
+    if *ray_alpha < OPAQUE_RAY_ALPHA {
+        return false;
+    }
+    *ray_alpha *= 1.0 - surface_color.alpha().into_inner();
+    *ray_alpha < OPAQUE_RAY_ALPHA
+}
+
 /// Implements [`PixelBuf`] for RGB(A) color with [`f32`] components,
 /// and conversion to [`Rgba`].
 #[derive(Clone, Debug, PartialEq)]
@@ -140,9 +190,7 @@ impl PixelBuf for ColorBuf {
 
     #[inline]
     fn opaque(&self) -> bool {
-        // Let's suppose that we don't care about differences that can't be represented
-        // in 8-bit color...not considering gamma.
-        self.ray_alpha < 1.0 / 256.0
+        self.ray_alpha < OPAQUE_RAY_ALPHA
     }
 
     #[inline]
@@ -153,6 +201,32 @@ impl PixelBuf for ColorBuf {
         self.ray_alpha *= 1.0 - surface_alpha;
         self.color_accumulator += color_vector * alpha_for_add;
     }
+
+    fn mean<I>(items: I) -> Self
+    where
+        I: IntoIterator<Item = Self>,
+    {
+        let mut count: usize = 0;
+        let mut sum = Self {
+            color_accumulator: Vector3::zero(),
+            ray_alpha: 0.0,
+        };
+        for item in items {
+            count += 1;
+            sum.color_accumulator += item.color_accumulator;
+            sum.ray_alpha += item.ray_alpha;
+        }
+        if count == 0 {
+            return Self::default();
+        }
+        // Averaging the premultiplied values gives the correct result for partially
+        // transparent samples.
+        let scale = (count as f32).recip();
+        Self {
+            color_accumulator: sum.color_accumulator * scale,
+            ray_alpha: sum.ray_alpha * scale,
+        }
+    }
 }
 
 impl Default for ColorBuf {
@@ -184,6 +258,206 @@ impl From<ColorBuf> for Rgba {
     }
 }
 
+/// Implements [`PixelBuf`] for recording depth: the distance along the ray, in world
+/// units, from the ray's origin to the surface at which the ray became opaque (that is,
+/// the ray's `t` value there, scaled by the length of its direction vector), for
+/// compositing or debugging.
+///
+/// Rays which never became opaque before reaching the sky have a depth of
+/// [`f32::INFINITY`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DepthBuf {
+    /// Fraction of the ray not yet obstructed; as [`ColorBuf`]'s `ray_alpha`.
+    ray_alpha: f32,
+    /// Distance of the surface about to be added, if known.
+    next_distance: FreeCoordinate,
+    /// Distance at which the ray became opaque.
+    depth: f32,
+}
+
+impl DepthBuf {
+    /// Returns the recorded depth, or [`f32::INFINITY`] if nothing opaque was hit.
+    pub fn depth(&self) -> f32 {
+        self.depth
+    }
+}
+
+impl PixelBuf for DepthBuf {
+    type BlockData = ();
+
+    #[inline]
+    fn opaque(&self) -> bool {
+        self.ray_alpha < OPAQUE_RAY_ALPHA
+    }
+
+    #[inline]
+    fn add(&mut self, surface_color: Rgba, _block_data: &Self::BlockData) {
+        if becomes_opaque(&mut self.ray_alpha, surface_color) {
+            self.depth = self.next_distance as f32;
+        }
+        self.next_distance = FreeCoordinate::INFINITY;
+    }
+
+    #[inline]
+    fn set_surface(&mut self, distance: FreeCoordinate, _normal: Face7) {
+        self.next_distance = distance;
+    }
+}
+
+impl Default for DepthBuf {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            ray_alpha: 1.0,
+            next_distance: FreeCoordinate::INFINITY,
+            depth: f32::INFINITY,
+        }
+    }
+}
+
+/// Implements [`PixelBuf`] for recording the surface normal, i.e. the face which the
+/// ray struck, of the surface at which the ray became opaque; for screen-space effects
+/// or debugging.
+///
+/// Rays which never became opaque before reaching the sky have a normal of
+/// [`Face7::Within`], i.e. zero.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NormalBuf {
+    /// Fraction of the ray not yet obstructed; as [`ColorBuf`]'s `ray_alpha`.
+    ray_alpha: f32,
+    /// Normal of the surface about to be added, if known.
+    next_normal: Face7,
+    /// Normal of the surface at which the ray became opaque.
+    normal: Face7,
+}
+
+impl NormalBuf {
+    /// Returns the recorded normal, or [`Face7::Within`] if nothing opaque was hit.
+    pub fn normal(&self) -> Face7 {
+        self.normal
+    }
+
+    /// Returns the normal encoded as a color: each component of the normal vector
+    /// is mapped from the range −1 to 1 to the range 0 to 255, as is conventional
+    /// for normal maps. Alpha is always 255.
+    pub fn to_rgba8(&self) -> [u8; 4] {
+        // Normals are always axis-aligned, so the components are -1, 0, or 1.
+        let encode = |c: GridCoordinate| match c {
+            c if c < 0 => 0,
+            0 => 127,
+            _ => 255,
+        };
+        let n = self.normal.normal_vector::<GridCoordinate>();
+        [encode(n.x), encode(n.y), encode(n.z), 255]
+    }
+}
+
+impl PixelBuf for NormalBuf {
+    type BlockData = ();
+
+    #[inline]
+    fn opaque(&self) -> bool {
+        self.ray_alpha < OPAQUE_RAY_ALPHA
+    }
+
+    #[inline]
+    fn add(&mut self, surface_color: Rgba, _block_data: &Self::BlockData) {
+        if becomes_opaque(&mut self.ray_alpha, surface_color) {
+            self.normal = self.next_normal;
+        }
+        self.next_normal = Face7::Within;
+    }
+
+    #[inline]
+    fn set_surface(&mut self, _distance: FreeCoordinate, normal: Face7) {
+        self.next_normal = normal;
+    }
+}
+
+impl Default for NormalBuf {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            ray_alpha: 1.0,
+            next_normal: Face7::Within,
+            normal: Face7::Within,
+        }
+    }
+}
+
+/// [`RtBlockData`] for [`IdBuf`]: the index of the block in its [`Space`], or [`None`]
+/// for the sky and for errors.
+///
+/// [`Space`]: crate::space::Space
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[allow(clippy::exhaustive_structs)]
+pub struct IdRtData(pub Option<BlockIndex>);
+
+impl RtBlockData for IdRtData {
+    type Options = ();
+
+    fn from_block(
+        _: RtOptionsRef<'_, Self::Options>,
+        index: BlockIndex,
+        _: &SpaceBlockData,
+    ) -> Self {
+        Self(Some(index))
+    }
+
+    fn error(_: RtOptionsRef<'_, Self::Options>) -> Self {
+        Self(None)
+    }
+
+    fn sky(_: RtOptionsRef<'_, Self::Options>) -> Self {
+        Self(None)
+    }
+}
+
+/// Implements [`PixelBuf`] for recording which block is visible: the [`BlockIndex`] of
+/// the block at whose surface the ray became opaque, for picking objects on screen.
+///
+/// Rays which never became opaque before reaching the sky have an ID of [`None`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct IdBuf {
+    /// Fraction of the ray not yet obstructed; as [`ColorBuf`]'s `ray_alpha`.
+    ray_alpha: f32,
+    /// Block at which the ray became opaque.
+    id: Option<BlockIndex>,
+}
+
+impl IdBuf {
+    /// Returns the recorded block index, or [`None`] if nothing opaque was hit.
+    pub fn id(&self) -> Option<BlockIndex> {
+        self.id
+    }
+}
+
+impl PixelBuf for IdBuf {
+    type BlockData = IdRtData;
+
+    #[inline]
+    fn opaque(&self) -> bool {
+        self.ray_alpha < OPAQUE_RAY_ALPHA
+    }
+
+    #[inline]
+    fn add(&mut self, surface_color: Rgba, block_data: &Self::BlockData) {
+        if becomes_opaque(&mut self.ray_alpha, surface_color) {
+            self.id = block_data.0;
+        }
+    }
+}
+
+impl Default for IdBuf {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            ray_alpha: 1.0,
+            id: None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -220,4 +494,77 @@ mod tests {
         //);
         assert!(buf.opaque());
     }
+
+    #[test]
+    fn color_buf_mean() {
+        let mut red = ColorBuf::default();
+        red.add(Rgba::new(1.0, 0.0, 0.0, 1.0), &());
+        let empty = ColorBuf::default();
+
+        assert_eq!(
+            Rgba::from(ColorBuf::mean([red.clone(), red.clone()])),
+            Rgba::new(1.0, 0.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            Rgba::from(ColorBuf::mean([red, empty])),
+            Rgba::new(1.0, 0.0, 0.0, 0.5)
+        );
+        assert_eq!(ColorBuf::mean([]), ColorBuf::default());
+    }
+
+    #[test]
+    fn depth_buf() {
+        let mut buf = DepthBuf::default();
+        assert_eq!(buf.depth(), f32::INFINITY);
+
+        buf.set_surface(2.0, Face7::PZ);
+        buf.add(Rgba::new(1.0, 0.0, 0.0, 0.5), &());
+        assert_eq!(buf.depth(), f32::INFINITY);
+        assert!(!buf.opaque());
+
+        buf.set_surface(3.0, Face7::PZ);
+        buf.add(Rgba::new(1.0, 0.0, 0.0, 1.0), &());
+        assert_eq!(buf.depth(), 3.0);
+        assert!(buf.opaque());
+
+        // The sky, which has no distance, does not affect an opaque result.
+        buf.add(Rgba::WHITE, &());
+        assert_eq!(buf.depth(), 3.0);
+
+        // but does produce infinity if nothing was hit.
+        let mut buf = DepthBuf::default();
+        buf.add(Rgba::WHITE, &());
+        assert_eq!(buf.depth(), f32::INFINITY);
+    }
+
+    #[test]
+    fn normal_buf() {
+        let mut buf = NormalBuf::default();
+        buf.set_surface(1.0, Face7::NX);
+        buf.add(Rgba::new(1.0, 0.0, 0.0, 0.5), &());
+        buf.set_surface(2.0, Face7::PZ);
+        buf.add(Rgba::new(1.0, 0.0, 0.0, 1.0), &());
+        buf.add(Rgba::WHITE, &());
+        assert_eq!(buf.normal(), Face7::PZ);
+        assert_eq!(buf.to_rgba8(), [127, 127, 255, 255]);
+
+        let mut buf = NormalBuf::default();
+        buf.add(Rgba::WHITE, &());
+        assert_eq!(buf.normal(), Face7::Within);
+        assert_eq!(buf.to_rgba8(), [127, 127, 127, 255]);
+    }
+
+    #[test]
+    fn id_buf() {
+        let mut buf = IdBuf::default();
+        buf.add(Rgba::new(1.0, 0.0, 0.0, 0.5), &IdRtData(Some(1)));
+        assert_eq!(buf.id(), None);
+        buf.add(Rgba::new(1.0, 0.0, 0.0, 1.0), &IdRtData(Some(2)));
+        buf.add(Rgba::WHITE, &IdRtData(None));
+        assert_eq!(buf.id(), Some(2));
+
+        let mut buf = IdBuf::default();
+        buf.add(Rgba::WHITE, &IdRtData(None));
+        assert_eq!(buf.id(), None);
+    }
 }
@@ -2,22 +2,24 @@
 // in the accompanying file README.md or <https://opensource.org/licenses/MIT>.
 
 use std::fmt;
+use std::marker::PhantomData;
 
-use cgmath::{Point2, Vector2};
+use cgmath::{InnerSpace as _, Point2, Vector2};
 use futures_core::future::BoxFuture;
 use image::RgbaImage;
+use instant::Instant;
 
 use crate::apps::{Layers, StandardCameras};
 use crate::camera::{Camera, GraphicsOptions, HeadlessRenderer, RenderError, Viewport};
 use crate::character::Cursor;
 use crate::content::palette;
 use crate::listen::ListenableSource;
-use crate::math::Rgba;
+use crate::math::{Aab, Face7, FreeCoordinate, Rgba};
 use crate::raytracer::{
-    ColorBuf, PixelBuf, RaytraceInfo, RtBlockData, RtOptionsRef, SpaceRaytracer,
-    UpdatingSpaceRaytracer,
+    ColorBuf, DepthBuf, IdBuf, IdRtData, NormalBuf, PixelBuf, RaytraceInfo, RtBlockData,
+    RtOptionsRef, SpaceRaytracer, UpdatingSpaceRaytracer,
 };
-use crate::space::Space;
+use crate::space::{BlockIndex, Space};
 use crate::universe::URef;
 
 /// Builds upon [`UpdatingSpaceRaytracer`] to make a complete [`HeadlessRenderer`],
@@ -29,6 +31,8 @@ pub struct RtRenderer<D: RtBlockData = ()> {
     /// The output images will alway
     size_policy: Box<dyn Fn(Viewport) -> Viewport + Send + Sync>,
     custom_options: ListenableSource<D::Options>,
+    /// Cursor to draw, as of the last [`Self::update()`].
+    cursor: Option<Cursor>,
 }
 
 impl<D: RtBlockData> RtRenderer<D>
@@ -50,6 +54,7 @@ where
             cameras,
             size_policy,
             custom_options,
+            cursor: None,
         }
     }
 
@@ -62,9 +67,9 @@ where
     /// This method is equivalent to [`HeadlessRenderer::update()`] except for
     /// fitting the raytracer's needs and capabilities (works with all types;
     /// not `async`).
-    pub fn update(&mut self, _cursor: Option<&Cursor>) -> Result<(), RenderError> {
-        // TODO: raytracer needs to implement drawing the cursor
+    pub fn update(&mut self, cursor: Option<&Cursor>) -> Result<(), RenderError> {
         self.cameras.update();
+        self.cursor = cursor.cloned();
 
         fn sync_space<D: RtBlockData>(
             rt: &mut Option<UpdatingSpaceRaytracer<D>>,
@@ -128,6 +133,101 @@ where
         E: Fn(P) -> O + Send + Sync,
         O: Clone + Send + Sync, // Clone is used in the no-data case
         IF: FnOnce(&RaytraceInfo) -> String,
+    {
+        let info = self.trace_subset(PixelSubset::ALL, None, true, &encoder, output);
+
+        let info_text: String = info_text_fn(&info);
+        if !info_text.is_empty() && self.cameras.cameras().world.options().debug_info_text {
+            let options = RtOptionsRef {
+                graphics_options: self.cameras.graphics_options(),
+                custom_options: &*self.custom_options.get(),
+            };
+            eg::draw_info_text(
+                output,
+                (self.size_policy)(self.cameras.cameras().world.viewport()),
+                [
+                    encoder(P::paint(Rgba::BLACK, options)),
+                    encoder(P::paint(Rgba::WHITE, options)),
+                ],
+                &info_text,
+            );
+        }
+
+        info
+    }
+
+    /// As [`Self::draw()`], but traces only part of the pixels, leaving the rest of
+    /// `output` unchanged, so that an image may be built up over several calls for
+    /// interactive use.
+    ///
+    /// Each call traces approximately `fraction` of the pixels (rounded to one of
+    /// 1, 1/2, 1/3, …, 1/16), chosen in an ordered-dither pattern so that the partial
+    /// image is evenly spread over the viewport. `pass` selects which subset is
+    /// traced; calling this with `pass` equal to `0, 1, 2, …` traces every pixel
+    /// exactly once per `1 / fraction` calls, after which the pattern repeats.
+    ///
+    /// No info text is drawn, since it would not be overwritten by later passes.
+    pub fn draw_partial<P, E, O>(
+        &self,
+        fraction: f32,
+        pass: usize,
+        encoder: E,
+        output: &mut [O],
+    ) -> RaytraceInfo
+    where
+        P: PixelBuf<BlockData = D>,
+        E: Fn(P) -> O + Send + Sync,
+        O: Clone + Send + Sync,
+    {
+        self.trace_subset(
+            PixelSubset::new(fraction, pass),
+            None,
+            true,
+            &encoder,
+            output,
+        )
+    }
+
+    /// As [`Self::draw()`], but stops tracing once `deadline` has passed, leaving the
+    /// remaining pixels of `output` unchanged, so that a slow scene does not take
+    /// unboundedly long to draw.
+    ///
+    /// The deadline is checked before each row of pixels is traced. Rows may be traced
+    /// in parallel and in any order, so the untraced pixels are not necessarily at the
+    /// bottom of the image. [`RaytraceInfo::completion()`] reports how much of the
+    /// image was traced.
+    ///
+    /// No info text is drawn, since the image may be incomplete.
+    pub fn draw_with_deadline<P, E, O>(
+        &self,
+        deadline: Instant,
+        encoder: E,
+        output: &mut [O],
+    ) -> RaytraceInfo
+    where
+        P: PixelBuf<BlockData = D>,
+        E: Fn(P) -> O + Send + Sync,
+        O: Clone + Send + Sync,
+    {
+        self.trace_subset(PixelSubset::ALL, Some(deadline), true, &encoder, output)
+    }
+
+    /// Common part of [`Self::draw()`], [`Self::draw_partial()`], and
+    /// [`Self::draw_with_deadline()`].
+    ///
+    /// If `include_ui` is false, the UI layer is not traced, as if it were absent.
+    fn trace_subset<P, E, O>(
+        &self,
+        subset: PixelSubset,
+        deadline: Option<Instant>,
+        include_ui: bool,
+        encoder: &E,
+        output: &mut [O],
+    ) -> RaytraceInfo
+    where
+        P: PixelBuf<BlockData = D>,
+        E: Fn(P) -> O + Send + Sync,
+        O: Send + Sync,
     {
         let mut cameras = self.cameras.cameras().clone();
         let viewport = (self.size_policy)(cameras.world.viewport());
@@ -144,31 +244,29 @@ where
             custom_options: &*self.custom_options.get(),
         };
 
-        let scene = RtScene {
+        let mut scene = RtScene {
             rts: self
                 .rts
                 .as_refs()
                 .map(|opt_urt| opt_urt.as_ref().map(|urt| urt.get())),
             cameras: &cameras,
+            cursor: self
+                .rts
+                .as_refs()
+                .map(|opt_urt| match (opt_urt, &self.cursor) {
+                    (Some(urt), Some(cursor)) if *urt.space() == cursor.space => {
+                        Some(Aab::from_cube(cursor.place.cube))
+                    }
+                    _ => None,
+                }),
             options,
         };
-
-        let info = trace_image::trace_scene_to_image_impl(scene, &encoder, output);
-
-        let info_text: String = info_text_fn(&info);
-        if !info_text.is_empty() && self.cameras.cameras().world.options().debug_info_text {
-            eg::draw_info_text(
-                output,
-                viewport,
-                [
-                    encoder(P::paint(Rgba::BLACK, options)),
-                    encoder(P::paint(Rgba::WHITE, options)),
-                ],
-                &info_text,
-            );
+        if !include_ui {
+            scene.rts.ui = None;
+            scene.cursor.ui = None;
         }
 
-        info
+        trace_image::trace_scene_to_image_impl(scene, subset, deadline, encoder, output)
     }
 
     /// Returns the [`StandardCameras`] this renderer contains.
@@ -212,6 +310,62 @@ impl RtRenderer<()> {
 
         (image, info)
     }
+
+    /// Produce a depth image of the current scene: for each pixel, in the same order
+    /// as [`Self::draw()`], the distance along the pixel's ray, from where it starts on
+    /// the camera's near plane to the first opaque surface, or [`f32::INFINITY`] if
+    /// there is none. This is not the perpendicular distance from the near plane; see
+    /// [`DepthBuf`] for details.
+    pub fn draw_depth(&self) -> (Vec<f32>, RaytraceInfo) {
+        let mut output = vec![f32::INFINITY; self.modified_viewport().pixel_count().unwrap_or(0)];
+        let info = self.draw::<DepthBuf, _, f32, _>(
+            |_| String::new(),
+            |pixel_buf| pixel_buf.depth(),
+            &mut output,
+        );
+        (output, info)
+    }
+
+    /// Produce an image of the surface normals of the current scene: each pixel's
+    /// color encodes the direction which the first opaque surface faces, as described
+    /// in [`NormalBuf::to_rgba8()`]. Pixels where no surface was hit have the color
+    /// of a zero vector.
+    pub fn draw_normals(&self) -> (RgbaImage, RaytraceInfo) {
+        let Vector2 {
+            x: width,
+            y: height,
+        } = self.modified_viewport().framebuffer_size;
+        let mut image = RgbaImage::new(width, height);
+
+        let info = self.draw::<NormalBuf, _, [u8; 4], _>(
+            |_| String::new(),
+            |pixel_buf| pixel_buf.to_rgba8(),
+            bytemuck::cast_slice_mut::<u8, [u8; 4]>(image.as_mut()),
+        );
+
+        (image, info)
+    }
+}
+
+impl RtRenderer<IdRtData> {
+    /// Produce an image of which block is visible at each pixel, for picking objects on
+    /// screen: for each pixel, in the same order as [`Self::draw()`], the
+    /// [`BlockIndex`] of the block at which the ray became opaque, or [`None`] if
+    /// there is none.
+    ///
+    /// Only the world space is traced; the UI, if any, is ignored, so every index refers
+    /// to a block of the world space.
+    pub fn draw_ids(&self) -> (Vec<Option<BlockIndex>>, RaytraceInfo) {
+        let mut output = vec![None; self.modified_viewport().pixel_count().unwrap_or(0)];
+        let info = self.trace_subset(
+            PixelSubset::ALL,
+            None,
+            false,
+            &|pixel_buf: IdBuf| pixel_buf.id(),
+            &mut output,
+        );
+        (output, info)
+    }
 }
 
 // manual impl avoids `D: Debug` bound
@@ -224,6 +378,7 @@ where
         f.debug_struct("RtRenderer")
             .field("cameras", &self.cameras)
             .field("rts", &self.rts)
+            .field("cursor", &self.cursor)
             .finish()
     }
 }
@@ -253,6 +408,8 @@ struct RtScene<'a, P: PixelBuf> {
     rts: Layers<Option<&'a SpaceRaytracer<P::BlockData>>>,
     /// Cameras *with* size_policy applied.
     cameras: &'a Layers<Camera>,
+    /// Box to outline as the cursor, in whichever layer the cursor's space is.
+    cursor: Layers<Option<Aab>>,
     options: RtOptionsRef<'a, <P::BlockData as RtBlockData>::Options>,
 }
 
@@ -264,25 +421,214 @@ impl<'a, P: PixelBuf> Clone for RtScene<'a, P> {
 impl<P: PixelBuf> Copy for RtScene<'_, P> {}
 
 impl<P: PixelBuf> RtScene<'_, P> {
+    /// Trace the pixel whose center is at `ndc_pos`, taking multiple samples if
+    /// antialiasing is enabled.
+    #[inline]
+    fn trace_pixel(&self, ndc_pos: Point2<f64>) -> (P, RaytraceInfo) {
+        let samples = self
+            .options
+            .graphics_options
+            .antialiasing
+            .samples_per_axis();
+        if samples == 1 {
+            return self.trace_ray(ndc_pos);
+        }
+
+        let framebuffer_size = self.cameras.world.viewport().framebuffer_size;
+        let pixel_size = Vector2::new(
+            2.0 / f64::from(framebuffer_size.x),
+            2.0 / f64::from(framebuffer_size.y),
+        );
+        let sample_offset = |i: u8| (f64::from(i) + 0.5) / f64::from(samples) - 0.5;
+        let mut info = RaytraceInfo::default();
+        let pixel = P::mean(
+            (0..samples)
+                .flat_map(|sy| (0..samples).map(move |sx| (sx, sy)))
+                .map(|(sx, sy)| {
+                    let offset = Vector2::new(
+                        sample_offset(sx) * pixel_size.x,
+                        sample_offset(sy) * pixel_size.y,
+                    );
+                    let (sample, sample_info) = self.trace_ray(ndc_pos + offset);
+                    info += sample_info;
+                    sample
+                }),
+        );
+        (pixel, info)
+    }
+
     #[inline]
     fn trace_ray(&self, ndc_pos: Point2<f64>) -> (P, RaytraceInfo) {
+        // Partially transparent UI content is left in this buffer, and the world is then
+        // traced into it, which blends the world behind the UI.
+        let mut ui_pixel = P::default();
+        let mut info = RaytraceInfo::default();
         if let Some(ui) = self.rts.ui {
-            // TODO: need to ask the UI scene to not have a sky color
-            let (pixel, info): (P, RaytraceInfo) =
-                ui.trace_ray(self.cameras.ui.project_ndc_into_world(ndc_pos), false);
+            let ray = self.cameras.ui.project_ndc_into_world(ndc_pos);
+            let (pixel, ui_info): (P, RaytraceInfo) = ui.trace_ray(ray, false);
+            info += ui_info;
+            if let Some(distance) =
+                visible_cursor_outline(ui, self.cursor.ui, &self.cameras.ui, ndc_pos)
+            {
+                return (self.add_cursor_outline(P::default(), distance), info);
+            }
             if pixel.opaque() {
-                // TODO: We should be doing alpha blending, but doing that requires
-                // having control over the PixelBuf that trace_ray starts with.
                 return (pixel, info);
             }
+            ui_pixel = pixel;
         }
         if let Some(world) = self.rts.world {
-            return world.trace_ray(self.cameras.world.project_ndc_into_world(ndc_pos), true);
+            if let Some(distance) =
+                visible_cursor_outline(world, self.cursor.world, &self.cameras.world, ndc_pos)
+            {
+                return (self.add_cursor_outline(ui_pixel, distance), info);
+            }
+            let ray = self.cameras.world.project_ndc_into_world(ndc_pos);
+            let (pixel, world_info) = world.trace_ray_into(ray, true, ui_pixel);
+            info += world_info;
+            return (pixel, info);
         }
-        (
-            P::paint(palette::NO_WORLD_TO_SHOW, self.options),
-            RaytraceInfo::default(),
-        )
+        ui_pixel.add(palette::NO_WORLD_TO_SHOW, &P::BlockData::sky(self.options));
+        (ui_pixel, info)
+    }
+
+    /// Adds the cursor outline to `pixel` as a surface at `distance` along the ray,
+    /// so that buffers such as [`DepthBuf`] record it like any other surface.
+    fn add_cursor_outline(&self, mut pixel: P, distance: FreeCoordinate) -> P {
+        // The outline is made of lines, which have no normal.
+        pixel.set_surface(distance, Face7::Within);
+        pixel.add(palette::CURSOR_OUTLINE, &P::BlockData::sky(self.options));
+        pixel
+    }
+}
+
+/// Determines whether the cursor outline should be drawn at `ndc_pos` instead of the
+/// scene traced by `rt`: the ray passes close to an edge of the cursor box, and no
+/// opaque surface is in front of that edge. If so, returns the distance along the ray
+/// to the outline.
+///
+/// Occlusion is checked with a separate depth-only trace, which is only done for the
+/// few pixels which are near the outline.
+fn visible_cursor_outline<D: RtBlockData>(
+    rt: &SpaceRaytracer<D>,
+    cursor_box: Option<Aab>,
+    camera: &Camera,
+    ndc_pos: Point2<f64>,
+) -> Option<FreeCoordinate> {
+    /// Allowance for the outline lying on the very surface it outlines, which would
+    /// otherwise hide it by rounding error.
+    const TOLERANCE: FreeCoordinate = 1e-3;
+
+    let outline_distance = cursor_outline_hit(cursor_box, camera, ndc_pos)?;
+    let ray = camera.project_ndc_into_world(ndc_pos);
+    let (depth, _): (DepthOnlyBuf<D>, _) = rt.trace_ray(ray, true);
+    (FreeCoordinate::from(depth.0.depth()) >= outline_distance - TOLERANCE)
+        .then_some(outline_distance)
+}
+
+/// If the ray through `ndc_pos` passes close to an edge of the cursor box, returns
+/// the distance along the ray at which it does so, in the same units as
+/// [`PixelBuf::set_surface()`].
+///
+/// Only the point where the ray enters the box is considered, so this finds the
+/// edges which are facing the camera and the silhouette of the box, much like the
+/// wireframe drawn by the GPU renderers.
+fn cursor_outline_hit(
+    cursor_box: Option<Aab>,
+    camera: &Camera,
+    ndc_pos: Point2<f64>,
+) -> Option<FreeCoordinate> {
+    let cursor_box = cursor_box?;
+    let ray = camera.project_ndc_into_world(ndc_pos);
+    let t_near = match cursor_box.intersect_ray(&ray) {
+        Some((t_near, _)) if t_near > 0.0 => t_near,
+        _ => return None,
+    };
+    let point = ray.origin + ray.direction * t_near;
+
+    // Make the lines about one pixel wide regardless of distance and projection,
+    // by measuring how far apart the rays through adjacent pixels are at this depth.
+    // Only rays which enter the box are considered, so at the silhouette only the
+    // inner half of the line exists; use a full pixel on each side of the edge so that
+    // the silhouette is still reliably one pixel wide.
+    let pixel_ndc_width = 2.0 / FreeCoordinate::from(camera.viewport().framebuffer_size.x.max(1));
+    let neighbor_ray = camera.project_ndc_into_world(ndc_pos + Vector2::new(pixel_ndc_width, 0.0));
+    let line_half_width =
+        (neighbor_ray.origin + neighbor_ray.direction * t_near - point).magnitude();
+
+    let lower = cursor_box.lower_bounds_p();
+    let upper = cursor_box.upper_bounds_p();
+    let near_bound_count = (0..3)
+        .filter(|&axis| {
+            (point[axis] - lower[axis]).abs() <= line_half_width
+                || (point[axis] - upper[axis]).abs() <= line_half_width
+        })
+        .count();
+    (near_bound_count >= 2).then(|| t_near * ray.direction.magnitude())
+}
+
+/// [`DepthBuf`] accepting any [`RtBlockData`], so that the depth of a layer can be
+/// traced regardless of what kind of image is being drawn.
+struct DepthOnlyBuf<D>(DepthBuf, PhantomData<D>);
+
+impl<D> Default for DepthOnlyBuf<D> {
+    fn default() -> Self {
+        Self(DepthBuf::default(), PhantomData)
+    }
+}
+
+impl<D: RtBlockData> PixelBuf for DepthOnlyBuf<D> {
+    type BlockData = D;
+
+    #[inline]
+    fn opaque(&self) -> bool {
+        self.0.opaque()
+    }
+
+    #[inline]
+    fn add(&mut self, surface_color: Rgba, _block_data: &Self::BlockData) {
+        self.0.add(surface_color, &());
+    }
+
+    #[inline]
+    fn set_surface(&mut self, distance: FreeCoordinate, normal: Face7) {
+        self.0.set_surface(distance, normal);
+    }
+}
+
+/// Selects which pixels are traced by [`RtRenderer::draw_partial()`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct PixelSubset {
+    passes: usize,
+    pass: usize,
+}
+
+impl PixelSubset {
+    const ALL: Self = Self { passes: 1, pass: 0 };
+
+    /// Ordered-dither ranks; each 4×4 tile of pixels is traced in this order.
+    const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+    fn new(fraction: f32, pass: usize) -> Self {
+        let passes = (1.0 / fraction.clamp(1.0 / 16.0, 1.0)).ceil() as usize;
+        // NaN fraction converts to zero passes; treat it as a full pass.
+        let passes = passes.max(1);
+        Self {
+            passes,
+            pass: pass % passes,
+        }
+    }
+
+    #[inline]
+    fn contains(self, x: usize, y: usize) -> bool {
+        self.passes == 1
+            || usize::from(Self::BAYER_4X4[y % 4][x % 4]) * self.passes / 16 == self.pass
+    }
+
+    /// Returns the number of pixels in row `y`, of length `width`, which are in this
+    /// subset.
+    fn count_in_row(self, width: usize, y: usize) -> usize {
+        (0..width).filter(|&x| self.contains(x, y)).count()
     }
 }
 
@@ -292,6 +638,24 @@ impl<P: PixelBuf> RtScene<'_, P> {
 mod trace_image {
     use crate::raytracer::{PixelBuf, RaytraceInfo};
     use cgmath::Point2;
+    use instant::Instant;
+
+    /// Info for a row which was not traced because `deadline` has passed, or [`None`]
+    /// if it should be traced.
+    fn skip_row(
+        deadline: Option<Instant>,
+        subset: super::PixelSubset,
+        width: usize,
+        y: usize,
+    ) -> Option<RaytraceInfo> {
+        match deadline {
+            Some(deadline) if Instant::now() >= deadline => Some(RaytraceInfo {
+                pixels_untraced: subset.count_in_row(width, y),
+                ..RaytraceInfo::default()
+            }),
+            _ => None,
+        }
+    }
 
     /// Compute a full image, writing it into `output`.
     ///
@@ -303,11 +667,13 @@ mod trace_image {
     ///
     /// Panics if `output`'s length does not match the area of `camera.framebuffer_size`.
     ///
-    /// TODO: Add a mechanism for incrementally rendering (not 100% of pixels) for
-    /// interactive use.
+    /// Only pixels in `subset` are traced, and no rows are started after `deadline`;
+    /// the rest of `output` is left unchanged.
     #[cfg(feature = "rayon")]
     pub(super) fn trace_scene_to_image_impl<P, E, O>(
         scene: super::RtScene<'_, P>,
+        subset: super::PixelSubset,
+        deadline: Option<Instant>,
         encoder: E,
         output: &mut [O],
     ) -> RaytraceInfo
@@ -331,18 +697,25 @@ mod trace_image {
             .par_chunks_mut(viewport_size.x.max(1))
             .enumerate()
             .map(move |(ych, raster_row)| {
+                if let Some(info) = skip_row(deadline, subset, raster_row.len(), ych) {
+                    return info;
+                }
                 let y = viewport.normalize_fb_y(ych);
                 raster_row
                     .into_par_iter()
                     .enumerate()
+                    .filter(move |&(xch, _)| subset.contains(xch, ych))
                     .map(move |(xch, pixel_out)| {
                         let x = viewport.normalize_fb_x(xch);
-                        let (pixel, info) = scene.trace_ray(Point2::new(x, y));
+                        let (pixel, info) = scene.trace_pixel(Point2::new(x, y));
                         *pixel_out = encoder(pixel);
-                        info
+                        RaytraceInfo {
+                            pixels_traced: 1,
+                            ..info
+                        }
                     })
+                    .sum::<RaytraceInfo>()
             })
-            .flatten()
             .sum();
 
         total_info
@@ -358,11 +731,13 @@ mod trace_image {
     ///
     /// Panics if `output`'s length does not match the area of `camera.framebuffer_size`.
     ///
-    /// TODO: Add a mechanism for incrementally rendering (not 100% of pixels) for
-    /// interactive use.
+    /// Only pixels in `subset` are traced, and no rows are started after `deadline`;
+    /// the rest of `output` is left unchanged.
     #[cfg(not(feature = "rayon"))]
     pub(super) fn trace_scene_to_image_impl<P, E, O>(
         scene: super::RtScene<'_, P>,
+        subset: super::PixelSubset,
+        deadline: Option<Instant>,
         encoder: E,
         output: &mut [O],
     ) -> RaytraceInfo
@@ -377,12 +752,24 @@ mod trace_image {
         let mut total_info = RaytraceInfo::default();
         let mut index = 0;
         for ych in 0..viewport_size.y {
+            if let Some(info) = skip_row(deadline, subset, viewport_size.x, ych) {
+                total_info += info;
+                index += viewport_size.x;
+                continue;
+            }
             let y = viewport.normalize_fb_y(ych);
             for xch in 0..viewport_size.x {
+                if !subset.contains(xch, ych) {
+                    index += 1;
+                    continue;
+                }
                 let x = viewport.normalize_fb_x(xch);
-                let (pixel, info) = scene.trace_ray(Point2::new(x, y));
+                let (pixel, info) = scene.trace_pixel(Point2::new(x, y));
                 output[index] = encoder(pixel);
-                total_info += info;
+                total_info += RaytraceInfo {
+                    pixels_traced: 1,
+                    ..info
+                };
                 index += 1;
             }
         }
@@ -478,10 +865,331 @@ mod eg {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cgmath::Point3;
+
+    use crate::block::Block;
+    use crate::camera::{AntialiasingOption, FogOption, LightingOption};
+    use crate::character::{Character, Spawn};
+    use crate::math::Rgb;
+    use crate::space::Grid;
+    use crate::universe::{Universe, UniverseIndex as _};
 
     fn _renderer_is_send_sync()
     where
         RtRenderer: Send + Sync + 'static,
     {
     }
+
+    #[test]
+    fn draw_partial_covers_each_pixel_once() {
+        let mut universe = Universe::new();
+        let space_ref = universe
+            .insert("space".into(), Space::empty_positive(1, 1, 1))
+            .unwrap();
+        universe
+            .insert("character".into(), Character::spawn_default(space_ref))
+            .unwrap();
+        let viewport = Viewport::with_scale(1.0, Vector2::new(13, 7));
+        let mut renderer = RtRenderer::new(
+            StandardCameras::from_constant_for_test(
+                GraphicsOptions::default(),
+                viewport,
+                &universe,
+            ),
+            Box::new(|v| v),
+            ListenableSource::constant(()),
+        );
+        renderer.update(None).unwrap();
+
+        let mut trace_counts = vec![0; viewport.pixel_count().unwrap()];
+        for pass in 0..4 {
+            let mut output = vec![false; trace_counts.len()];
+            renderer.draw_partial::<ColorBuf, _, _>(0.25, pass, |_| true, &mut output);
+            for (count, traced) in trace_counts.iter_mut().zip(output) {
+                *count += u32::from(traced);
+            }
+        }
+        assert_eq!(trace_counts, vec![1; trace_counts.len()]);
+    }
+
+    #[test]
+    fn draw_with_deadline_reports_incomplete() {
+        let universe = one_cube_universe([0.5, 0.5, 2.0]);
+        let viewport = Viewport::with_scale(1.0, Vector2::new(8, 8));
+        let mut renderer = RtRenderer::new(
+            StandardCameras::from_constant_for_test(
+                GraphicsOptions::default(),
+                viewport,
+                &universe,
+            ),
+            Box::new(|v| v),
+            ListenableSource::constant(()),
+        );
+        renderer.update(None).unwrap();
+        let mut output = vec![false; viewport.pixel_count().unwrap()];
+
+        // A deadline which has already passed allows no tracing at all.
+        let info =
+            renderer.draw_with_deadline::<ColorBuf, _, _>(Instant::now(), |_| true, &mut output);
+        assert_eq!(info.completion(), 0.0);
+        assert_eq!(output, vec![false; output.len()]);
+
+        // A generous deadline allows tracing everything.
+        let info = renderer.draw_with_deadline::<ColorBuf, _, _>(
+            Instant::now() + instant::Duration::from_secs(1000),
+            |_| true,
+            &mut output,
+        );
+        assert_eq!(info.completion(), 1.0);
+        assert_eq!(output, vec![true; output.len()]);
+    }
+
+    /// Universe containing a single green cube, viewed from `eye`.
+    fn one_cube_universe(eye: [FreeCoordinate; 3]) -> Universe {
+        let mut universe = Universe::new();
+        let bounds = Grid::new([0, 0, 0], [1, 1, 1]);
+        let mut spawn = Spawn::default_for_new_space(bounds);
+        spawn.set_eye_position(eye);
+        spawn.set_look_direction(Point3::new(0.5, 0.5, 0.5) - Point3::from(eye));
+        let mut space = Space::builder(bounds).spawn(spawn).build_empty();
+        space
+            .set([0, 0, 0], Block::from(Rgb::new(0.0, 1.0, 0.0)))
+            .unwrap();
+        let space_ref = universe.insert("space".into(), space).unwrap();
+        universe
+            .insert("character".into(), Character::spawn_default(space_ref))
+            .unwrap();
+        universe
+    }
+
+    #[test]
+    fn antialiasing_blends_edges() {
+        let universe = one_cube_universe([1.5, 2.0, 2.5]);
+        let count_colors = |antialiasing: AntialiasingOption| {
+            let mut options = GraphicsOptions::default();
+            options.fog = FogOption::None;
+            options.lighting_display = LightingOption::None;
+            options.antialiasing = antialiasing;
+            let viewport = Viewport::with_scale(1.0, Vector2::new(24, 24));
+            let mut renderer = RtRenderer::new(
+                StandardCameras::from_constant_for_test(options, viewport, &universe),
+                Box::new(|v| v),
+                ListenableSource::constant(()),
+            );
+            renderer.update(None).unwrap();
+            let (image, _) = renderer.draw_rgba(|_| String::new());
+            assert_eq!(
+                image.dimensions(),
+                (viewport.framebuffer_size.x, viewport.framebuffer_size.y)
+            );
+            image
+                .pixels()
+                .map(|p| p.0)
+                .collect::<std::collections::HashSet<[u8; 4]>>()
+                .len()
+        };
+
+        // Without antialiasing, every pixel is either the cube or the sky.
+        assert_eq!(count_colors(AntialiasingOption::None), 2);
+        // With antialiasing, pixels on the cube's diagonal edges are blended.
+        assert!(count_colors(AntialiasingOption::Ssaa { samples: 4 }) > 2);
+    }
+
+    #[test]
+    fn depth_of_near_and_far_cubes() {
+        let center_and_corner_depth = |eye_z: FreeCoordinate| {
+            let universe = one_cube_universe([0.5, 0.5, eye_z]);
+            let viewport = Viewport::with_scale(1.0, Vector2::new(9, 9));
+            let mut renderer = RtRenderer::new(
+                StandardCameras::from_constant_for_test(
+                    GraphicsOptions::default(),
+                    viewport,
+                    &universe,
+                ),
+                Box::new(|v| v),
+                ListenableSource::constant(()),
+            );
+            renderer.update(None).unwrap();
+            let (depths, _) = renderer.draw_depth();
+            assert_eq!(depths.len(), 81);
+            (depths[4 * 9 + 4], depths[0])
+        };
+
+        let (near_depth, _) = center_and_corner_depth(2.0);
+        let (far_depth, far_corner_depth) = center_and_corner_depth(4.0);
+        assert!((near_depth - 1.0).abs() < 0.1, "near_depth = {near_depth}");
+        assert!((far_depth - 3.0).abs() < 0.1, "far_depth = {far_depth}");
+        assert_eq!(far_corner_depth, f32::INFINITY);
+    }
+
+    #[test]
+    fn normals_of_pz_face() {
+        let universe = one_cube_universe([0.5, 0.5, 2.0]);
+        let mut renderer = RtRenderer::new(
+            StandardCameras::from_constant_for_test(
+                GraphicsOptions::default(),
+                Viewport::with_scale(1.0, Vector2::new(9, 9)),
+                &universe,
+            ),
+            Box::new(|v| v),
+            ListenableSource::constant(()),
+        );
+        renderer.update(None).unwrap();
+        let (image, _) = renderer.draw_normals();
+        assert_eq!(image.get_pixel(4, 4).0, [127, 127, 255, 255]);
+        assert_eq!(image.get_pixel(0, 0).0, [127, 127, 127, 255]);
+    }
+
+    #[test]
+    fn ids_of_cube_and_sky() {
+        let universe = one_cube_universe([0.5, 0.5, 2.0]);
+        let mut renderer = RtRenderer::<IdRtData>::new(
+            StandardCameras::from_constant_for_test(
+                GraphicsOptions::default(),
+                Viewport::with_scale(1.0, Vector2::new(9, 9)),
+                &universe,
+            ),
+            Box::new(|v| v),
+            ListenableSource::constant(()),
+        );
+        renderer.update(None).unwrap();
+        let space: URef<Space> = universe.get(&"space".into()).unwrap();
+
+        let (ids, _) = renderer.draw_ids();
+        assert_eq!(ids.len(), 81);
+        let center_id = ids[4 * 9 + 4].expect("center pixel should hit the cube");
+        assert_eq!(space.borrow().get_block_index([0, 0, 0]), Some(center_id));
+        assert_eq!(
+            space.borrow().block_data()[usize::from(center_id)].block(),
+            &Block::from(Rgb::new(0.0, 1.0, 0.0))
+        );
+        assert_eq!(ids[0], None);
+    }
+
+    #[test]
+    fn ids_ignore_ui() {
+        let mut universe = one_cube_universe([0.5, 0.5, 2.0]);
+        let ui_block = Block::from(Rgb::new(1.0, 0.0, 0.0));
+        let mut ui_space = Space::builder(Grid::new([0, 0, -1], [9, 9, 1])).build_empty();
+        ui_space.fill_uniform(ui_space.grid(), &ui_block).unwrap();
+        let ui_space = universe.insert("ui".into(), ui_space).unwrap();
+        let mut renderer = RtRenderer::<IdRtData>::new(
+            StandardCameras::new(
+                ListenableSource::constant(GraphicsOptions::default()),
+                ListenableSource::constant(Viewport::with_scale(1.0, Vector2::new(9, 9))),
+                ListenableSource::constant(universe.get_default_character()),
+                ListenableSource::constant(Some(ui_space)),
+                Layers::default(),
+            )
+            .unwrap(),
+            Box::new(|v| v),
+            ListenableSource::constant(()),
+        );
+        renderer.update(None).unwrap();
+        let space: URef<Space> = universe.get(&"space".into()).unwrap();
+
+        // The UI covers the whole view when drawn normally...
+        let mut ui_ids = vec![None; 81];
+        renderer.draw::<IdBuf, _, Option<BlockIndex>, _>(
+            |_| String::new(),
+            |pixel_buf| pixel_buf.id(),
+            &mut ui_ids,
+        );
+        assert!(ui_ids.iter().all(Option::is_some));
+
+        // ...but is not reported by draw_ids().
+        let (ids, _) = renderer.draw_ids();
+        assert_eq!(ids[4 * 9 + 4], space.borrow().get_block_index([0, 0, 0]));
+        assert_eq!(ids[0], None);
+    }
+
+    #[test]
+    fn cursor_is_drawn() {
+        let universe = one_cube_universe([0.5, 0.5, 2.0]);
+        let cameras = StandardCameras::from_constant_for_test(
+            GraphicsOptions::default(),
+            Viewport::with_scale(1.0, Vector2::new(32, 32)),
+            &universe,
+        );
+        let cursor = cameras.project_cursor(Point2::new(0.0, 0.0)).unwrap();
+        let mut renderer =
+            RtRenderer::new(cameras, Box::new(|v| v), ListenableSource::constant(()));
+
+        renderer.update(None).unwrap();
+        let (image_without, _) = renderer.draw_rgba(|_| String::new());
+        renderer.update(Some(&cursor)).unwrap();
+        let (image_with, _) = renderer.draw_rgba(|_| String::new());
+
+        let outline = palette::CURSOR_OUTLINE.to_srgb8();
+        let changed: Vec<(u32, u32)> = image_without
+            .enumerate_pixels()
+            .zip(image_with.pixels())
+            .filter(|((_, _, before), after)| before != after)
+            .map(|((x, y, _), _)| (x, y))
+            .collect();
+        assert!(!changed.is_empty());
+        for &(x, y) in &changed {
+            assert_eq!(image_with.get_pixel(x, y).0, outline);
+        }
+        // The center of the cube's face is not part of the outline.
+        assert_eq!(
+            image_with.get_pixel(16, 16),
+            image_without.get_pixel(16, 16)
+        );
+    }
+
+    #[test]
+    fn cursor_has_depth() {
+        let universe = one_cube_universe([0.5, 0.5, 2.0]);
+        let cameras = StandardCameras::from_constant_for_test(
+            GraphicsOptions::default(),
+            Viewport::with_scale(1.0, Vector2::new(32, 32)),
+            &universe,
+        );
+        // Move the cursor to the empty cube beside the visible one, so that its outline
+        // is drawn against the sky.
+        let mut cursor = cameras.project_cursor(Point2::new(0.0, 0.0)).unwrap();
+        cursor.place.cube = Point3::new(1, 0, 0);
+        let mut renderer =
+            RtRenderer::new(cameras, Box::new(|v| v), ListenableSource::constant(()));
+
+        renderer.update(None).unwrap();
+        let (depth_without, _) = renderer.draw_depth();
+        renderer.update(Some(&cursor)).unwrap();
+        let (depth_with, _) = renderer.draw_depth();
+
+        let outline_depths: Vec<f32> = depth_without
+            .iter()
+            .zip(&depth_with)
+            .filter(|(before, after)| before != after)
+            .map(|(_, &after)| after)
+            .collect();
+        assert!(!outline_depths.is_empty());
+        for depth in outline_depths {
+            // The cursor box is between 1 and 3 units from the eye.
+            assert!((0.5..3.5).contains(&depth), "{depth}");
+        }
+    }
+
+    #[test]
+    fn cursor_is_hidden_by_occluding_block() {
+        let universe = one_cube_universe([0.5, 0.5, 2.0]);
+        let cameras = StandardCameras::from_constant_for_test(
+            GraphicsOptions::default(),
+            Viewport::with_scale(1.0, Vector2::new(32, 32)),
+            &universe,
+        );
+        // Move the cursor to the cube directly behind the visible one.
+        let mut cursor = cameras.project_cursor(Point2::new(0.0, 0.0)).unwrap();
+        cursor.place.cube = Point3::new(0, 0, -1);
+        let mut renderer =
+            RtRenderer::new(cameras, Box::new(|v| v), ListenableSource::constant(()));
+
+        renderer.update(None).unwrap();
+        let (image_without, _) = renderer.draw_rgba(|_| String::new());
+        renderer.update(Some(&cursor)).unwrap();
+        let (image_with, _) = renderer.draw_rgba(|_| String::new());
+
+        assert_eq!(image_with, image_without);
+    }
 }
@@ -6,7 +6,7 @@ use cgmath::{EuclideanSpace as _, Point3, Vector3};
 use crate::block::{recursive_ray, Evoxel};
 use crate::camera::LightingOption;
 use crate::math::{Face7, FaceMap, FreeCoordinate, GridPoint, Rgb, Rgba};
-use crate::raycast::{Ray, Raycaster};
+use crate::raycast::{Ray, RaycastStep, Raycaster};
 use crate::raytracer::{RtBlockData, SpaceRaytracer, TracingBlock, TracingCubeData};
 use crate::space::GridArray;
 
@@ -26,6 +26,9 @@ pub(crate) struct Surface<'a, D> {
     /// The point in the [`Space`]'s coordinate system where the ray intersected the surface.
     intersection_point: Point3<FreeCoordinate>,
     pub normal: Face7,
+    /// Whether the surface's color is unaffected by lighting, as for the image
+    /// displayed by a monitor block.
+    emissive: bool,
 }
 
 impl<D: RtBlockData> Surface<'_, D> {
@@ -43,7 +46,11 @@ impl<D: RtBlockData> Surface<'_, D> {
         if diffuse_color.fully_transparent() {
             return None;
         }
-        let adjusted_rgb = diffuse_color.to_rgb() * self.compute_illumination(rt);
+        let adjusted_rgb = if self.emissive {
+            diffuse_color.to_rgb()
+        } else {
+            diffuse_color.to_rgb() * self.compute_illumination(rt)
+        };
         Some(adjusted_rgb.with_alpha(diffuse_color.alpha()))
     }
 
@@ -188,7 +195,22 @@ impl<'a, D> Iterator for SurfaceIter<'a, D> {
             });
         }
 
-        Some(match &self.blocks[cube_data.block_index as usize] {
+        let blocks = self.blocks;
+        Some(self.enter_block(&blocks[cube_data.block_index as usize], rc_step))
+    }
+
+    // TODO: implement fold if it helps
+}
+
+impl<'a, D> SurfaceIter<'a, D> {
+    /// Produces the step for the ray entering the cube of `rc_step`, which contains
+    /// `tracing_block`, and sets up iteration over its voxels if needed.
+    fn enter_block(
+        &mut self,
+        tracing_block: &'a TracingBlock<D>,
+        rc_step: RaycastStep,
+    ) -> TraceStep<'a, D> {
+        match tracing_block {
             TracingBlock::Atom(block_data, color) => {
                 if color.fully_transparent() {
                     // The caller could generically skip transparent, but if we do it then
@@ -204,6 +226,7 @@ impl<'a, D> Iterator for SurfaceIter<'a, D> {
                         t_distance: rc_step.t_distance(),
                         intersection_point: rc_step.intersection_point(self.ray),
                         normal: rc_step.face(),
+                        emissive: false,
                     })
                 }
             }
@@ -226,10 +249,26 @@ impl<'a, D> Iterator for SurfaceIter<'a, D> {
                     t_distance: rc_step.t_distance(),
                 }
             }
-        })
+            TracingBlock::Monitor(inner, texture) => {
+                if rc_step.face() == Face7::from(texture.face) {
+                    let cube = rc_step.cube_ahead();
+                    let intersection_point = rc_step.intersection_point(self.ray);
+                    TraceStep::EnterSurface(Surface {
+                        block_data: inner.block_data(),
+                        diffuse_color: texture
+                            .texel_at(intersection_point - cube.map(FreeCoordinate::from).to_vec()),
+                        cube,
+                        t_distance: rc_step.t_distance(),
+                        intersection_point,
+                        normal: rc_step.face(),
+                        emissive: true,
+                    })
+                } else {
+                    self.enter_block(inner, rc_step)
+                }
+            }
+        }
     }
-
-    // TODO: implement fold if it helps
 }
 
 /// Iterates over a [`Block`]'s voxels. Internal helper for [`SurfaceIter`].
@@ -269,6 +308,7 @@ impl<'a, D> VoxelSurfaceIter<'a, D> {
             intersection_point: rc_step.intersection_point(self.voxel_ray) * self.antiscale
                 + self.block_cube.map(FreeCoordinate::from).to_vec(),
             normal: rc_step.face(),
+            emissive: false,
         }))
     }
 }
@@ -330,9 +370,10 @@ pub(crate) enum DepthStep<'a, D> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::block::Block;
-    use crate::camera::GraphicsOptions;
+    use crate::block::{Block, MonitorView};
+    use crate::camera::{FogOption, GraphicsOptions};
     use crate::content::{make_slab, palette};
+    use crate::math::Face6;
     use crate::space::{Grid, Space};
     use crate::universe::Universe;
     use pretty_assertions::assert_eq;
@@ -361,7 +402,8 @@ mod tests {
                     cube: GridPoint::new(0, 1, 0),
                     t_distance: 1.5, // half-block starting point + 1 empty block
                     intersection_point: Point3::new(0.5, 1.0, 0.5),
-                    normal: Face7::NY
+                    normal: Face7::NY,
+                    emissive: false,
                 }),
                 EnterBlock { t_distance: 2.5 },
                 EnterSurface(Surface {
@@ -370,7 +412,8 @@ mod tests {
                     cube: GridPoint::new(0, 2, 0),
                     t_distance: 2.5,
                     intersection_point: Point3::new(0.5, 2.0, 0.5),
-                    normal: Face7::NY
+                    normal: Face7::NY,
+                    emissive: false,
                 }),
                 // Second layer of slab.
                 // TODO: Make this test not dependent on make_slab's colors,
@@ -381,7 +424,8 @@ mod tests {
                     cube: GridPoint::new(0, 2, 0),
                     t_distance: 2.75, // previous surface + 1/4 block of depth
                     intersection_point: Point3::new(0.5, 2.25, 0.5),
-                    normal: Face7::NY
+                    normal: Face7::NY,
+                    emissive: false,
                 }),
                 // Two top layers of slab.
                 Invisible { t_distance: 3.0 },
@@ -413,11 +457,71 @@ mod tests {
                     cube: GridPoint::new(0, 0, 0),
                     t_distance: 0.5, // half-block starting point
                     intersection_point: Point3::new(0.0, 0.5, 0.5),
-                    normal: Face7::NX
+                    normal: Face7::NX,
+                    emissive: false,
                 }),
                 // Exit block -- this is the critical step that we're checking for.
                 Invisible { t_distance: 1.5 },
             ]
         );
     }
+
+    /// Test that a monitor block's face displays the contents of the space it views.
+    #[test]
+    fn surface_iter_monitor_face() {
+        let universe = &mut Universe::new();
+        let viewed_color = rgba_const!(1., 0., 0., 1.);
+        let block_color = rgba_const!(0., 0., 1., 1.);
+
+        let mut viewed_space = Space::empty_positive(1, 1, 1);
+        viewed_space
+            .set([0, 0, 0], Block::from(viewed_color))
+            .unwrap();
+        let sky_color = viewed_space.physics().sky.overall_color().with_alpha_one();
+        let viewed_space = universe.insert_anonymous(viewed_space);
+
+        let monitor_block = Block::builder()
+            .color(block_color)
+            .monitor(Some(MonitorView::new(
+                viewed_space,
+                Face6::NZ,
+                [0.5, 0.5, -3.0],
+                [0.5, 0.5, 0.5],
+            )))
+            .build();
+        let mut space = Space::empty_positive(1, 1, 1);
+        space.set([0, 0, 0], monitor_block).unwrap();
+
+        let options = GraphicsOptions {
+            lighting_display: LightingOption::None,
+            fog: FogOption::None,
+            ..GraphicsOptions::default()
+        };
+        let rt = SpaceRaytracer::<()>::new(&space, options, ());
+
+        let first_surface = |ray: Ray| {
+            SurfaceIter::new(&rt, ray)
+                .find_map(|step| match step {
+                    EnterSurface(surface) => Some((surface.diffuse_color, surface.emissive)),
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        // Center of the monitor face shows the viewed block.
+        assert_eq!(
+            first_surface(Ray::new([0.5, 0.5, -0.5], [0., 0., 1.])),
+            (viewed_color, true)
+        );
+        // Corner of the monitor face shows the viewed space's sky.
+        assert_eq!(
+            first_surface(Ray::new([0.02, 0.98, -0.5], [0., 0., 1.])),
+            (sky_color, true)
+        );
+        // Other faces are drawn as the block normally would be.
+        assert_eq!(
+            first_surface(Ray::new([1.5, 0.5, 0.5], [-1., 0., 0.])),
+            (block_color, false)
+        );
+    }
 }
@@ -10,7 +10,7 @@ use cgmath::{Decomposed, Transform, Vector2, Vector3};
 use crate::camera::{eye_for_look_at, Camera, GraphicsOptions, Viewport};
 use crate::math::{FreeCoordinate, Rgba};
 use crate::raytracer::{PixelBuf, RaytraceInfo, RtBlockData, RtOptionsRef, SpaceRaytracer};
-use crate::space::{Space, SpaceBlockData};
+use crate::space::{BlockIndex, Space, SpaceBlockData};
 
 // TODO: better name, docs
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -20,7 +20,7 @@ pub struct CharacterRtData(pub Cow<'static, str>);
 impl RtBlockData for CharacterRtData {
     type Options = ();
 
-    fn from_block(_: RtOptionsRef<'_, Self::Options>, s: &SpaceBlockData) -> Self {
+    fn from_block(_: RtOptionsRef<'_, Self::Options>, _: BlockIndex, s: &SpaceBlockData) -> Self {
         // TODO: For more Unicode correctness, index by grapheme cluster
         Self(
             s.evaluated()
@@ -1,21 +1,21 @@
 // Copyright 2020-2022 Kevin Reid under the terms of the MIT License as detailed
 // in the accompanying file README.md or <https://opensource.org/licenses/MIT>.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::mem;
 use std::sync::{Arc, Mutex, Weak};
 
 use crate::block::AIR;
 use crate::camera::GraphicsOptions;
-use crate::listen::{ListenableSource, Listener};
+use crate::listen::{Gate, ListenableSource, Listener};
 use crate::math::GridPoint;
 use crate::raytracer::RtOptionsRef;
 use crate::raytracer::TracingBlock;
 use crate::raytracer::TracingCubeData;
 use crate::raytracer::{RtBlockData, SpaceRaytracer};
 use crate::space::BlockIndex;
-use crate::space::{Space, SpaceChange};
+use crate::space::{Space, SpaceBlockData, SpaceChange};
 use crate::universe::{RefError, URef};
 
 /// Manages a [`SpaceRaytracer`] so that it can be cheaply updated when the [`Space`] is
@@ -26,6 +26,8 @@ pub struct UpdatingSpaceRaytracer<D: RtBlockData> {
     custom_options: ListenableSource<D::Options>,
     state: SpaceRaytracer<D>,
     todo: Arc<Mutex<SrtTodo>>,
+    /// Gates for the listeners on the spaces viewed by monitor blocks, by block index.
+    monitor_gates: HashMap<BlockIndex, Gate>,
 }
 
 // manual impl avoids `D: Debug` bound
@@ -40,6 +42,7 @@ where
             .field("custom_options", &self.custom_options)
             .field("state", &self.state)
             .field("todo", &self.todo)
+            .field("monitor_gates", &self.monitor_gates)
             .finish()
     }
 }
@@ -64,6 +67,11 @@ where
         graphics_options
             .listen(TodoListener(Arc::downgrade(&todo)).filter(|()| Some(SpaceChange::EveryBlock)));
 
+        let mut monitor_gates = HashMap::new();
+        for (index, block_data) in space_borrowed.block_data().iter().enumerate() {
+            listen_to_monitor(&todo, &mut monitor_gates, index as BlockIndex, block_data);
+        }
+
         Self {
             state: SpaceRaytracer::new(
                 &space_borrowed,
@@ -74,6 +82,7 @@ where
             graphics_options,
             custom_options,
             todo,
+            monitor_gates,
         }
     }
 
@@ -98,7 +107,7 @@ where
         // we must reorder the actions here (or perhaps acquire the todo lock twice) to
         // avoid deadlock.
         let mut todo = self.todo.lock().unwrap();
-        if !todo.everything && todo.blocks.is_empty() && todo.cubes.is_empty() {
+        if !todo.everything && !todo.physics && todo.blocks.is_empty() && todo.cubes.is_empty() {
             // Nothing to do
             return Ok(());
         }
@@ -110,9 +119,28 @@ where
                 self.graphics_options.snapshot(),
                 self.custom_options.snapshot(),
             );
+            todo.physics = false;
             todo.blocks.clear();
             todo.cubes.clear();
+            self.monitor_gates.clear();
+            for (index, block_data) in space.block_data().iter().enumerate() {
+                listen_to_monitor(
+                    &self.todo,
+                    &mut self.monitor_gates,
+                    index as BlockIndex,
+                    block_data,
+                );
+            }
         } else {
+            if mem::take(&mut todo.physics) {
+                self.state.set_sky(&space.physics().sky);
+                // Light physics may have changed, which does not send individual
+                // light notifications.
+                for cube in space.grid().interior_iter() {
+                    self.state.cubes[cube].lighting = space.get_lighting(cube);
+                }
+            }
+
             let graphics_options = &*self.graphics_options.get();
             let custom_options = &*self.custom_options.get();
             let options = RtOptionsRef {
@@ -122,17 +150,31 @@ where
 
             let block_data_slice = space.block_data();
             if block_data_slice.len() > self.state.blocks.len() {
-                for block_data in block_data_slice[self.state.blocks.len()..].iter() {
-                    self.state
-                        .blocks
-                        .push(TracingBlock::from_block(options, block_data));
+                for (index, block_data) in block_data_slice
+                    .iter()
+                    .enumerate()
+                    .skip(self.state.blocks.len())
+                {
+                    self.state.blocks.push(TracingBlock::from_block(
+                        options,
+                        index as BlockIndex,
+                        block_data,
+                        true,
+                    ));
+                    listen_to_monitor(
+                        &self.todo,
+                        &mut self.monitor_gates,
+                        index as BlockIndex,
+                        block_data,
+                    );
                 }
             }
             for block_index in todo.blocks.drain() {
                 // TODO: handle extending the vector
-                let block_index = usize::from(block_index);
-                self.state.blocks[block_index] =
-                    TracingBlock::from_block(options, &block_data_slice[block_index]);
+                let block_data = &block_data_slice[usize::from(block_index)];
+                self.state.blocks[usize::from(block_index)] =
+                    TracingBlock::from_block(options, block_index, block_data, true);
+                listen_to_monitor(&self.todo, &mut self.monitor_gates, block_index, block_data);
             }
 
             for cube in todo.cubes.drain() {
@@ -150,9 +192,33 @@ where
     }
 }
 
+/// If the block at `index` is a monitor, listens to the space it views so that the
+/// block is prepared again (redrawing the monitor's image) when that space changes.
+/// Any previous listener for `index` is dropped.
+fn listen_to_monitor(
+    todo: &Arc<Mutex<SrtTodo>>,
+    monitor_gates: &mut HashMap<BlockIndex, Gate>,
+    index: BlockIndex,
+    block_data: &SpaceBlockData,
+) {
+    monitor_gates.remove(&index);
+    if let Some(view) = &block_data.evaluated().attributes.monitor {
+        // If the space is unavailable, the monitor is not drawn either.
+        if let Ok(viewed_space) = view.space.try_borrow() {
+            let (gate, listener) = TodoListener(Arc::downgrade(todo))
+                .filter(move |_: SpaceChange| Some(SpaceChange::BlockValue(index)))
+                .gate();
+            viewed_space.listen(listener);
+            monitor_gates.insert(index, gate);
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 struct SrtTodo {
     everything: bool,
+    /// The sky and lighting must be refreshed.
+    physics: bool,
     // TODO: Benchmark using a BitVec instead.
     blocks: HashSet<BlockIndex>,
     cubes: HashSet<GridPoint>,
@@ -178,6 +244,11 @@ impl Listener<SpaceChange> for TodoListener {
                     SpaceChange::Number(index) | SpaceChange::BlockValue(index) => {
                         todo.blocks.insert(index);
                     }
+                    SpaceChange::Physics => {
+                        todo.physics = true;
+                    }
+                    SpaceChange::Spawn => {}
+                    SpaceChange::MoveCompleted(_) => {}
                 }
             }
         }
@@ -191,10 +262,13 @@ impl Listener<SpaceChange> for TodoListener {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::block::AIR;
-    use crate::camera::{eye_for_look_at, Camera, Viewport};
+    use crate::block::{Block, MonitorView, AIR};
+    use crate::camera::{eye_for_look_at, Camera, LightingOption, Viewport};
     use crate::content::make_some_voxel_blocks;
-    use crate::raytracer::{CharacterBuf, CharacterRtData};
+    use crate::math::{Face6, Rgb, Rgba};
+    use crate::raycast::Ray;
+    use crate::raytracer::{CharacterBuf, CharacterRtData, ColorBuf};
+    use crate::space::{LightPhysics, Sky, SpacePhysics};
     use crate::universe::Universe;
     use crate::util::{CustomFormat, Unquote};
     use cgmath::{Decomposed, Transform as _, Vector2, Vector3};
@@ -302,6 +376,74 @@ mod tests {
             .unwrap();
         tester.update_and_assert().unwrap();
 
+        // Change the sky and lighting
+        space
+            .try_modify(|space| {
+                space.set_physics(SpacePhysics {
+                    sky: Sky::Uniform(Rgb::ONE),
+                    light: LightPhysics::None,
+                    ..SpacePhysics::default()
+                })
+            })
+            .unwrap();
+        tester.update_and_assert().unwrap();
+
         // TODO: Also test changing existing block's data
     }
+
+    /// A monitor block's image is redrawn when the space it views changes.
+    #[test]
+    fn monitor_follows_viewed_space() {
+        let mut universe = Universe::new();
+        let viewed_space = universe.insert_anonymous(Space::empty_positive(1, 1, 1));
+        let monitor_block = Block::builder()
+            .color(Rgba::WHITE)
+            .monitor(Some(MonitorView::new(
+                viewed_space.clone(),
+                Face6::NZ,
+                [0.5, 0.5, -3.0],
+                [0.5, 0.5, 0.5],
+            )))
+            .build();
+        let mut space = Space::empty_positive(1, 1, 1);
+        space.set([0, 0, 0], monitor_block).unwrap();
+        let space = universe.insert_anonymous(space);
+
+        let options = ListenableSource::constant(GraphicsOptions {
+            lighting_display: LightingOption::None,
+            ..GraphicsOptions::default()
+        });
+        let mut updating = UpdatingSpaceRaytracer::<()>::new(
+            space.clone(),
+            options.clone(),
+            ListenableSource::constant(()),
+        );
+        let trace = |rt: &SpaceRaytracer<()>| {
+            Rgba::from(
+                rt.trace_ray::<ColorBuf>(Ray::new([0.5, 0.5, -0.5], [0., 0., 1.]), true)
+                    .0,
+            )
+        };
+        let before = trace(updating.get());
+
+        viewed_space
+            .try_modify(|viewed_space| {
+                viewed_space
+                    .set([0, 0, 0], Block::from(rgba_const!(1., 0., 0., 1.)))
+                    .unwrap()
+            })
+            .unwrap();
+        updating.update().unwrap();
+        let after = trace(updating.get());
+
+        assert_ne!(before, after);
+        assert_eq!(
+            after,
+            trace(&SpaceRaytracer::new(
+                &space.borrow(),
+                options.snapshot(),
+                ()
+            ))
+        );
+    }
 }
@@ -6,19 +6,23 @@
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::sync::atomic::{AtomicU16, Ordering};
 use std::sync::{Arc, Mutex, Weak};
 
-use cgmath::Vector3;
+use cgmath::{EuclideanSpace as _, InnerSpace as _, Point3, Vector3, Zero as _};
 
 use crate::behavior::{Behavior, BehaviorSet};
 use crate::block::{
-    Block, BlockChange, EvalBlockError, EvaluatedBlock, Resolution, AIR, AIR_EVALUATED,
+    Block, BlockChange, EvalBlockError, EvaluatedBlock, Modifier, Resolution, AIR, AIR_EVALUATED,
 };
 use crate::character::Spawn;
 use crate::content::palette;
 use crate::drawing::DrawingPlane;
 use crate::listen::{Gate, Listener, Notifier};
-use crate::math::{Face6, FreeCoordinate, GridCoordinate, GridMatrix, GridPoint, NotNan, Rgb};
+use crate::math::{
+    FaceMap, FreeCoordinate, GridCoordinate, GridMatrix, GridPoint, GridRotation, GridVector,
+    NotNan, Rgb,
+};
 use crate::time::Tick;
 use crate::transaction::{Merge, Transaction as _};
 use crate::universe::{RefVisitor, URef, UniverseTransaction, VisitRefs};
@@ -26,7 +30,10 @@ use crate::util::ConciseDebug;
 use crate::util::{CustomFormat, StatusText};
 
 mod builder;
-pub use builder::SpaceBuilder;
+pub use builder::{LightRaysBuilder, SpaceBuilder};
+
+mod contents;
+use contents::ContentsArray;
 
 mod grid;
 pub use grid::*;
@@ -34,9 +41,15 @@ pub use grid::*;
 mod light;
 #[doc(hidden)] // pub only for visualization by all-is-cubes-gpu
 pub use light::LightUpdateCubeInfo;
-use light::{opaque_for_light_computation, LightUpdateQueue, PackedLightScalar};
+use light::{LightUpdateQueue, PackedLightScalar};
 pub use light::{LightUpdatesInfo, PackedLight};
 
+pub(crate) mod save;
+
+mod sky;
+pub(crate) use sky::sky_face_for_cube;
+pub use sky::Sky;
+
 mod space_txn;
 pub use space_txn::*;
 
@@ -58,10 +71,8 @@ pub struct Space {
     ///
     /// * Coordinates are transformed to indices by [`Grid::index`].
     /// * Each element is an index into [`Self::block_data`].
-    // TODO: Consider making this use different integer types depending on how
-    // many blocks there are, so we can save memory in simple spaces but not have
-    // a cap on complex ones.
-    contents: Box<[BlockIndex]>,
+    /// * The integer type used is as small as possible for the number of indices.
+    contents: ContentsArray,
 
     /// Parallel array to `contents` for lighting data.
     pub(crate) lighting: Box<[PackedLight]>,
@@ -75,11 +86,9 @@ pub struct Space {
     /// Global characteristics such as the behavior of light and gravity.
     physics: SpacePhysics,
 
-    /// A converted copy of `physics.sky_color`.
-    packed_sky_color: PackedLight,
+    /// A converted copy of `physics.sky`, for each direction.
+    packed_sky_color: FaceMap<PackedLight>,
 
-    // TODO: Replace this with something that has a spatial index so we can
-    // search for behaviors in specific regions
     behaviors: BehaviorSet<Space>,
 
     spawn: Spawn,
@@ -106,6 +115,34 @@ pub struct SpaceBlockData {
     evaluated: EvaluatedBlock,
     #[allow(dead_code)] // Used only for its `Drop`
     block_listen_gate: Option<Gate>,
+    /// The index reported by the listener behind `block_listen_gate`, which must be
+    /// updated if this entry is moved to a different index.
+    listener_index: Option<Arc<AtomicU16>>,
+}
+
+/// Converts the sky colors to the form used for lighting.
+pub(crate) fn pack_sky(sky: &Sky) -> FaceMap<PackedLight> {
+    sky.to_face_map().map(|_, color| PackedLight::from(color))
+}
+
+/// Whether the block has a [`Modifier::Move`], for detecting when one has completed.
+fn has_move_modifier(block: &Block) -> bool {
+    block
+        .modifiers()
+        .iter()
+        .any(|modifier| matches!(modifier, Modifier::Move { .. }))
+}
+
+/// Whether replacing every cube of `old` with `new` has side effects which must be
+/// applied to each cube individually: lighting updates or scheduling ticks.
+///
+/// This must compare every property of the block that the light updater reads.
+fn needs_per_cube_side_effects(old: &EvaluatedBlock, new: &EvaluatedBlock) -> bool {
+    old.opaque != new.opaque
+        || old.visible_or_animated() != new.visible_or_animated()
+        || old.color != new.color
+        || old.attributes.light_emission != new.attributes.light_emission
+        || old.attributes.tick_action != new.attributes.tick_action
 }
 
 impl fmt::Debug for Space {
@@ -135,6 +172,29 @@ impl fmt::Debug for SpaceBlockData {
 /// Number used to identify distinct blocks within a [`Space`].
 pub type BlockIndex = u16;
 
+/// Describes how [`Space::gc_block_indices`] changed the [`BlockIndex`]es of a [`Space`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BlockIndexRemapping {
+    /// Indexed by old index.
+    map: Vec<Option<BlockIndex>>,
+}
+
+impl BlockIndexRemapping {
+    /// Returns the new index corresponding to the old index `old`, or [`None`] if that
+    /// index was unused and has been removed.
+    pub fn get(&self, old: BlockIndex) -> Option<BlockIndex> {
+        self.map.get(usize::from(old)).copied().flatten()
+    }
+
+    /// Returns whether every index is unchanged.
+    pub fn is_identity(&self) -> bool {
+        self.map
+            .iter()
+            .enumerate()
+            .all(|(old, &new)| new == Some(old as BlockIndex))
+    }
+}
+
 impl Space {
     /// Returns a [`SpaceBuilder`] configured for a block,
     /// which may be used to construct a new [`Space`].
@@ -164,6 +224,7 @@ impl Space {
             grid,
             spawn,
             physics,
+            .. // contents are handled by SpaceBuilder::build()
         } = builder;
 
         let volume = grid.volume();
@@ -185,10 +246,10 @@ impl Space {
             } else {
                 vec![]
             },
-            contents: vec![0; volume].into_boxed_slice(),
+            contents: ContentsArray::new(volume),
 
             lighting: physics.light.initialize_lighting(grid),
-            packed_sky_color: physics.sky_color.into(),
+            packed_sky_color: pack_sky(&physics.sky),
             light_update_queue: LightUpdateQueue::new(),
             last_light_updates: Vec::new(),
 
@@ -207,11 +268,151 @@ impl Space {
         Space::empty(Grid::new((0, 0, 0), (wx, wy, wz)))
     }
 
+    /// Constructs a `Space` with the given bounds and contents, in the form produced by
+    /// [`Space::contents_runs()`].
+    ///
+    /// `blocks` gives the block for each [`BlockIndex`], as in [`Space::block_data()`];
+    /// blocks which are not used by any run are ignored. `runs` must cover exactly the
+    /// volume of `grid`.
+    ///
+    /// Light is computed approximately, as by [`Space::fast_evaluate_light()`].
+    ///
+    /// ```
+    /// use all_is_cubes::block::Block;
+    /// use all_is_cubes::math::Rgba;
+    /// use all_is_cubes::space::{Grid, Space};
+    ///
+    /// let block = Block::from(Rgba::WHITE);
+    /// let mut space = Space::empty_positive(4, 4, 4);
+    /// space.fill_uniform(Grid::new([0, 0, 0], [4, 2, 4]), &block).unwrap();
+    ///
+    /// let blocks: Vec<Block> = space.block_data().iter().map(|d| d.block().clone()).collect();
+    /// let copy = Space::from_contents_runs(space.grid(), &blocks, space.contents_runs())
+    ///     .unwrap();
+    /// assert_eq!(copy[[3, 1, 3]], block);
+    /// ```
+    pub fn from_contents_runs(
+        grid: Grid,
+        blocks: &[Block],
+        runs: impl IntoIterator<Item = (BlockIndex, usize)>,
+    ) -> Result<Space, ContentsRunsError> {
+        Self::from_builder_and_contents_runs(Space::builder(grid), blocks, runs)
+    }
+
+    /// Implementation of [`Space::from_contents_runs()`] which also accepts the other
+    /// settings of a [`SpaceBuilder`].
+    fn from_builder_and_contents_runs(
+        builder: SpaceBuilder,
+        blocks: &[Block],
+        runs: impl IntoIterator<Item = (BlockIndex, usize)>,
+    ) -> Result<Space, ContentsRunsError> {
+        let mut space = builder.build_empty();
+        let volume = space.grid.volume();
+
+        // Check the runs before evaluating any blocks.
+        let mut counts = vec![0usize; blocks.len()];
+        let mut total: usize = 0;
+        let runs: Vec<(BlockIndex, usize)> = runs.into_iter().collect();
+        for &(index, length) in &runs {
+            let count = counts
+                .get_mut(usize::from(index))
+                .ok_or(ContentsRunsError::IndexOutOfRange { index })?;
+            // If a count overflows, then so does the total, which can't be the volume.
+            *count = count
+                .checked_add(length)
+                .ok_or(ContentsRunsError::WrongLength {
+                    expected: volume,
+                    actual: usize::MAX,
+                })?;
+            total = total.saturating_add(length);
+        }
+        if total != volume {
+            return Err(ContentsRunsError::WrongLength {
+                expected: volume,
+                actual: total,
+            });
+        }
+        // Drop unused blocks at the end, so that the remainder fits in BlockIndex.
+        while counts.last() == Some(&0) {
+            counts.pop();
+        }
+
+        space.block_data.clear();
+        space.block_to_index.clear();
+        for (index, (block, count)) in blocks.iter().zip(counts).enumerate() {
+            let index = index as BlockIndex;
+            if count == 0 {
+                space.block_data.push(SpaceBlockData::tombstone());
+                continue;
+            }
+            if space.block_to_index.contains_key(block) {
+                return Err(ContentsRunsError::DuplicateBlock { index });
+            }
+            let mut data = SpaceBlockData::new(block.clone(), space.listener_for_block(index))
+                .map_err(|error| ContentsRunsError::Block { index, error })?;
+            data.count = count;
+            space.block_data.push(data);
+            space.block_to_index.insert(block.clone(), index);
+        }
+
+        if let Some(last) = space.block_data.len().checked_sub(1) {
+            space.contents.reserve_index(last as BlockIndex);
+        }
+        let mut position = 0;
+        for (index, length) in runs {
+            for _ in 0..length {
+                space.contents.set(position, index);
+                position += 1;
+            }
+        }
+
+        let grid = space.grid;
+        for cube in grid.interior_iter() {
+            let index = space.contents.get(grid.index(cube).unwrap());
+            if space.block_data[usize::from(index)]
+                .evaluated
+                .attributes
+                .tick_action
+                .is_some()
+            {
+                space.cubes_wanting_ticks.insert(cube);
+            }
+        }
+
+        space.fast_evaluate_light();
+        Ok(space)
+    }
+
     /// Registers a listener for mutations of this space.
     pub fn listen(&self, listener: impl Listener<SpaceChange> + Send + Sync + 'static) {
         self.notifier.listen(listener)
     }
 
+    /// Registers a listener for mutations of this space which may affect the cubes
+    /// within `region`.
+    ///
+    /// [`SpaceChange::Block`], [`SpaceChange::Lighting`], and
+    /// [`SpaceChange::MoveCompleted`] messages are delivered only if their cube is
+    /// within `region`; all other messages are delivered regardless,
+    /// since they may affect any cube.
+    pub fn listen_region(
+        &self,
+        region: Grid,
+        listener: impl Listener<SpaceChange> + Send + Sync + 'static,
+    ) {
+        self.notifier
+            .listen(listener.filter(move |change| match change {
+                SpaceChange::Block(cube)
+                | SpaceChange::Lighting(cube)
+                | SpaceChange::MoveCompleted(cube)
+                    if !region.contains_cube(cube) =>
+                {
+                    None
+                }
+                change => Some(change),
+            }))
+    }
+
     /// Returns the [`Grid`] describing the bounds of this space; no blocks may exist
     /// outside it.
     pub fn grid(&self) -> Grid {
@@ -229,14 +430,14 @@ impl Space {
     pub fn get_block_index(&self, position: impl Into<GridPoint>) -> Option<BlockIndex> {
         self.grid
             .index(position.into())
-            .map(|contents_index| self.contents[contents_index])
+            .map(|contents_index| self.contents.get(contents_index))
     }
 
     /// Copy data out of a portion of the space in a caller-chosen format.
     ///
     /// If the provided [`Grid`] contains portions outside of this space's grid,
     /// those positions in the output will be treated as if they are filled with [`AIR`]
-    /// and lit by [`SpacePhysics::sky_color`].
+    /// and lit by [`SpacePhysics::sky`].
     pub fn extract<V>(
         &self,
         subgrid: Grid,
@@ -247,7 +448,7 @@ impl Space {
             // interior_iter().enumerate() because it's a sub-grid).
             match self.grid.index(cube) {
                 Some(cube_index) => {
-                    let block_index = self.contents[cube_index];
+                    let block_index = self.contents.get(cube_index);
                     extractor(
                         Some(block_index),
                         &self.block_data[block_index as usize],
@@ -259,16 +460,130 @@ impl Space {
                 }
                 // The light value would be more consistent if it were PackedLight::NO_RAYS when
                 // there is no interior adjacent block, but probably nobody will actually care.
-                None => extractor(None, &SpaceBlockData::NOTHING, self.packed_sky_color),
+                None => extractor(
+                    None,
+                    &SpaceBlockData::NOTHING,
+                    self.packed_sky_color[sky_face_for_cube(self.grid, cube)],
+                ),
             }
         })
     }
 
+    /// Returns whether every cube in `region` contains [`AIR`].
+    ///
+    /// Portions of `region` outside of this space's bounds are considered to be [`AIR`].
+    ///
+    /// ```
+    /// use all_is_cubes::block::Block;
+    /// use all_is_cubes::math::Rgba;
+    /// use all_is_cubes::space::{Grid, Space};
+    ///
+    /// let mut space = Space::empty_positive(4, 4, 4);
+    /// space.set([0, 0, 0], Block::from(Rgba::WHITE)).unwrap();
+    /// assert!(!space.region_is_empty(Grid::new([0, 0, 0], [2, 2, 2])));
+    /// assert!(space.region_is_empty(Grid::new([1, 0, 0], [10, 10, 10])));
+    /// ```
+    pub fn region_is_empty(&self, region: Grid) -> bool {
+        let region = match region.intersection(self.grid) {
+            Some(region) => region,
+            None => return true,
+        };
+        let air_index = match self.block_to_index.get(&AIR) {
+            Some(&index) => index,
+            // If there is no AIR in the space, then no region within it is empty.
+            None => return false,
+        };
+        region
+            .interior_iter()
+            .all(|cube| self.contents.get(self.grid.index(cube).unwrap()) == air_index)
+    }
+
+    /// Returns the number of cubes in `region` whose blocks satisfy `predicate`.
+    ///
+    /// Portions of `region` outside of this space's bounds are considered to be [`AIR`].
+    /// `predicate` is called at most once per distinct block.
+    ///
+    /// ```
+    /// use all_is_cubes::block::Block;
+    /// use all_is_cubes::math::Rgba;
+    /// use all_is_cubes::space::{Grid, Space};
+    ///
+    /// let mut space = Space::empty_positive(4, 4, 4);
+    /// space.set([0, 0, 0], Block::from(Rgba::WHITE)).unwrap();
+    /// space.set([1, 0, 0], Block::from(Rgba::WHITE)).unwrap();
+    /// assert_eq!(space.count_blocks(space.grid(), |ev| ev.visible), 2);
+    /// ```
+    pub fn count_blocks(&self, region: Grid, predicate: impl Fn(&EvaluatedBlock) -> bool) -> usize {
+        let clipped = region.intersection(self.grid);
+        let outside_volume = region.volume() - clipped.map_or(0, |c| c.volume());
+        let mut count = if outside_volume > 0 && predicate(&AIR_EVALUATED) {
+            outside_volume
+        } else {
+            0
+        };
+
+        if let Some(clipped) = clipped {
+            let mut matches: Vec<Option<bool>> = vec![None; self.block_data.len()];
+            for cube in clipped.interior_iter() {
+                let block_index = usize::from(self.contents.get(self.grid.index(cube).unwrap()));
+                let matched = *matches[block_index]
+                    .get_or_insert_with(|| predicate(&self.block_data[block_index].evaluated));
+                if matched {
+                    count += 1;
+                }
+            }
+        }
+
+        count
+    }
+
+    /// Iterates over the cubes of `region` which are within this space's bounds,
+    /// yielding each cube's position, block, and light level (as
+    /// [`Space::get_lighting()`] would report).
+    ///
+    /// The cubes are visited in the same order as [`Grid::interior_iter()`], and no
+    /// memory is allocated; this is a lighter-weight alternative to
+    /// [`Space::extract()`] for passes which only need to look at each cube once.
+    ///
+    /// ```
+    /// use all_is_cubes::block::{Block, AIR};
+    /// use all_is_cubes::math::Rgba;
+    /// use all_is_cubes::space::{Grid, Space};
+    ///
+    /// let mut space = Space::empty_positive(4, 4, 4);
+    /// let block = Block::from(Rgba::WHITE);
+    /// space.set([0, 0, 0], &block).unwrap();
+    ///
+    /// let blocks: Vec<&Block> = space
+    ///     .iter_region(Grid::new([-1, 0, 0], [3, 1, 1]))
+    ///     .map(|(_cube, block, _light)| block)
+    ///     .collect();
+    /// assert_eq!(blocks, vec![&block, &AIR]);
+    /// ```
+    pub fn iter_region(
+        &self,
+        region: Grid,
+    ) -> impl Iterator<Item = (GridPoint, &Block, PackedLight)> + '_ {
+        region
+            .intersection(self.grid)
+            .into_iter()
+            .flat_map(Grid::interior_iter)
+            .map(move |cube| {
+                let contents_index = self.grid.index(cube).unwrap();
+                let block = &self.block_data[usize::from(self.contents.get(contents_index))].block;
+                let light = match self.physics.light {
+                    LightPhysics::None => PackedLight::ONE,
+                    _ => self.lighting[contents_index],
+                };
+                (cube, block, light)
+            })
+    }
+
     /// Gets the [`EvaluatedBlock`] of the block in this space at the given position.
     #[inline(always)]
     pub fn get_evaluated(&self, position: impl Into<GridPoint>) -> &EvaluatedBlock {
         if let Some(index) = self.grid.index(position) {
-            &self.block_data[self.contents[index] as usize].evaluated
+            &self.block_data[self.contents.get(index) as usize].evaluated
         } else {
             &AIR_EVALUATED
         }
@@ -286,13 +601,14 @@ impl Space {
     /// made”.
     #[inline(always)]
     pub fn get_lighting(&self, position: impl Into<GridPoint>) -> PackedLight {
+        let position = position.into();
         match self.physics.light {
             LightPhysics::None => PackedLight::ONE,
             _ => self
                 .grid
-                .index(position.into())
+                .index(position)
                 .map(|contents_index| self.lighting[contents_index])
-                .unwrap_or(self.packed_sky_color),
+                .unwrap_or_else(|| self.packed_sky_color[sky_face_for_cube(self.grid, position)]),
         }
     }
 
@@ -316,17 +632,57 @@ impl Space {
     ) -> Result<bool, SetCubeError> {
         // Delegate to a monomorphic function.
         // This may reduce compile time and code size.
-        self.set_impl(position.into(), block.into())
+        self.set_impl(position.into(), block.into(), true)
     }
 
+    /// Replace the block in this space at the given position, but only if the block
+    /// currently there is equal to `expected`.
+    ///
+    /// Returns `Ok(true)` if the block matched (so the cube now contains `new`, even if
+    /// `new` is equal to `expected`), and `Ok(false)` if it did not and nothing was
+    /// changed. Fails in the same ways as [`Space::set`].
+    ///
+    /// ```
+    /// use all_is_cubes::block::{Block, AIR};
+    /// use all_is_cubes::math::Rgba;
+    /// use all_is_cubes::space::Space;
+    ///
+    /// let mut space = Space::empty_positive(1, 1, 1);
+    /// let a_block = Block::builder().color(Rgba::new(1.0, 0.0, 0.0, 1.0)).build();
+    /// assert_eq!(space.set_if((0, 0, 0), &AIR, &a_block), Ok(true));
+    /// assert_eq!(space.set_if((0, 0, 0), &AIR, &a_block), Ok(false));
+    /// assert_eq!(space[(0, 0, 0)], a_block);
+    /// ```
+    pub fn set_if<'a>(
+        &mut self,
+        position: impl Into<GridPoint>,
+        expected: &Block,
+        new: impl Into<Cow<'a, Block>>,
+    ) -> Result<bool, SetCubeError> {
+        let position = position.into();
+        if let Some(contents_index) = self.grid.index(position) {
+            let current_block_index = self.contents.get(contents_index);
+            if self.block_data[current_block_index as usize].block != *expected {
+                return Ok(false);
+            }
+        }
+        // If out of bounds, set_impl() will report the error.
+        self.set_impl(position, new.into(), true).map(|_| true)
+    }
+
+    /// Implementation of [`Space::set`].
+    ///
+    /// If `update_light` is false, the caller is responsible for calling
+    /// [`Space::light_side_effects_of_fill`] for the changed cubes.
     fn set_impl(
         &mut self,
         position: GridPoint,
         // TODO: Is the `Cow` actually gaining us any performance, now that `Block` is an Arc-like type?
         block: Cow<'_, Block>,
+        update_light: bool,
     ) -> Result<bool, SetCubeError> {
         if let Some(contents_index) = self.grid.index(position) {
-            let old_block_index = self.contents[contents_index];
+            let old_block_index = self.contents.get(contents_index);
             let old_block = &self.block_data[old_block_index as usize].block;
             if *old_block == *block {
                 // No change.
@@ -363,29 +719,14 @@ impl Space {
 
                 // Side effects.
                 self.notifier.notify(SpaceChange::Number(old_block_index));
-                self.side_effects_of_set(old_block_index, position, contents_index);
+                self.side_effects_of_set(old_block_index, position, contents_index, update_light);
                 return Ok(true);
             }
 
             // Find or allocate index for new block. This must be done before other mutations since it can fail.
-            let new_block_index = self.ensure_block_index(block)?;
-
-            // Decrement count of old block.
-            let old_data: &mut SpaceBlockData = &mut self.block_data[old_block_index as usize];
-            old_data.count -= 1;
-            if old_data.count == 0 {
-                // Free data of old entry.
-                self.block_to_index.remove(&old_data.block);
-                *old_data = SpaceBlockData::tombstone();
-            }
+            let new_block_index = self.ensure_block_index(block, None)?;
 
-            // Increment count of new block.
-            self.block_data[new_block_index as usize].count += 1;
-
-            // Write actual space change.
-            self.contents[contents_index] = new_block_index;
-
-            self.side_effects_of_set(new_block_index, position, contents_index);
+            self.replace_block_index(position, contents_index, new_block_index, update_light);
             Ok(true)
         } else {
             Err(SetCubeError::OutOfBounds {
@@ -395,6 +736,38 @@ impl Space {
         }
     }
 
+    /// Replaces the block index at `contents_index` with `new_block_index`, which must
+    /// be a valid index different from the current one, updating counts and performing
+    /// side effects.
+    ///
+    /// `content_index` is redundant with `position` but saves computation.
+    fn replace_block_index(
+        &mut self,
+        position: GridPoint,
+        contents_index: usize,
+        new_block_index: BlockIndex,
+        update_light: bool,
+    ) {
+        let old_block_index = self.contents.get(contents_index);
+
+        // Decrement count of old block.
+        let old_data: &mut SpaceBlockData = &mut self.block_data[old_block_index as usize];
+        old_data.count -= 1;
+        if old_data.count == 0 {
+            // Free data of old entry.
+            self.block_to_index.remove(&old_data.block);
+            *old_data = SpaceBlockData::tombstone();
+        }
+
+        // Increment count of new block.
+        self.block_data[new_block_index as usize].count += 1;
+
+        // Write actual space change.
+        self.contents.set(contents_index, new_block_index);
+
+        self.side_effects_of_set(new_block_index, position, contents_index, update_light);
+    }
+
     /// Implement the consequences of changing a block.
     ///
     /// `content_index` is redundant with `position` but saves computation.
@@ -404,6 +777,7 @@ impl Space {
         block_index: BlockIndex,
         position: GridPoint,
         contents_index: usize,
+        update_light: bool,
     ) {
         let evaluated = &self.block_data[block_index as usize].evaluated;
 
@@ -411,26 +785,8 @@ impl Space {
             self.cubes_wanting_ticks.insert(position);
         }
 
-        // TODO: Move this into a function in the lighting module since it is so tied to lighting
-        if self.physics.light != LightPhysics::None {
-            if opaque_for_light_computation(evaluated) {
-                // Since we already have the information, immediately update light value
-                // to zero rather than putting it in the queue.
-                // (It would be mostly okay to skip doing this entirely, but doing it gives
-                // more determinism, and the old value could be temporarily revealed when
-                // the block is removed.)
-                self.lighting[contents_index] = PackedLight::OPAQUE;
-                self.notifier.notify(SpaceChange::Lighting(position));
-            } else {
-                self.light_needs_update(position, PackedLightScalar::MAX);
-            }
-            for face in Face6::ALL {
-                let neighbor = position + face.normal_vector();
-                // Skip neighbor light updates in the definitely-black-inside case.
-                if !self.get_evaluated(neighbor).opaque {
-                    self.light_needs_update(neighbor, PackedLightScalar::MAX);
-                }
-            }
+        if update_light {
+            self.light_side_effects_of_set(position, contents_index, |_| false);
         }
 
         self.notifier.notify(SpaceChange::Block(position));
@@ -444,7 +800,8 @@ impl Space {
     /// The operation will stop on the first error, potentially leaving some blocks
     /// replaced. (Exception: If the `grid` extends outside of
     /// [`self.grid()`](Self::grid), that will always be rejected before any changes are
-    /// made.)
+    /// made.) If `region` has zero volume, the operation succeeds without doing anything,
+    /// regardless of where the region is.
     ///
     /// ```
     /// use all_is_cubes::block::{AIR, Block};
@@ -461,25 +818,93 @@ impl Space {
     /// assert_eq!(space[(0, 1, 0)], AIR);
     /// ```
     ///
-    /// TODO: Support providing the previous block as a parameter (take cues from `extract`).
-    ///
-    /// See also [`Space::fill_uniform`] for filling a region with one block.
+    /// See also [`Space::fill_uniform`] for filling a region with one block, and
+    /// [`Space::fill_replace`] for a function which is given the previous block.
     pub fn fill<F, B>(&mut self, region: Grid, mut function: F) -> Result<(), SetCubeError>
     where
         F: FnMut(GridPoint) -> Option<B>,
         B: std::borrow::Borrow<Block>,
     {
+        if region.volume() == 0 {
+            return Ok(());
+        }
         if !self.grid.contains_grid(region) {
             return Err(SetCubeError::OutOfBounds {
                 modification: region,
                 space_bounds: self.grid,
             });
         }
+        // Lighting side effects are processed after all blocks are written, so that
+        // cubes which are about to be filled in are not needlessly queued.
+        let mut changed = Vec::new();
+        let mut result = Ok(());
         for cube in region.interior_iter() {
             if let Some(block) = function(cube) {
-                // TODO: Optimize side effect processing by batching lighting updates for
-                // when we know what's now opaque or not.
-                self.set(cube, block.borrow())?;
+                match self.set_impl(cube, Cow::Borrowed(block.borrow()), false) {
+                    Ok(true) => changed.push(cube),
+                    Ok(false) => {}
+                    Err(e) => {
+                        result = Err(e);
+                        break;
+                    }
+                }
+            }
+        }
+        self.light_side_effects_of_fill(region, &changed);
+        result
+    }
+
+    /// Replace blocks in `region` with a block computed by the function, which is given
+    /// the position and the block currently at that position.
+    ///
+    /// If the function returns [`None`], the existing block is left unchanged.
+    ///
+    /// Errors are handled as in [`Space::fill`]: the operation will stop on the first
+    /// error, except that a `region` extending outside of [`self.grid()`](Self::grid)
+    /// is always rejected before any changes are made.
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    /// use all_is_cubes::block::{AIR, Block};
+    /// use all_is_cubes::math::Rgba;
+    /// use all_is_cubes::space::{Grid, Space};
+    ///
+    /// let grass: Block = Rgba::new(0.0, 1.0, 0.0, 1.0).into();
+    /// let dirt: Block = Rgba::new(0.5, 0.3, 0.0, 1.0).into();
+    /// let mut space = Space::empty_positive(2, 1, 1);
+    /// space.set((0, 0, 0), &grass).unwrap();
+    ///
+    /// space.fill_replace(space.grid(), |_point, old_block| {
+    ///     if *old_block == grass {
+    ///         Some(Cow::Borrowed(&dirt))
+    ///     } else {
+    ///         None
+    ///     }
+    /// }).unwrap();
+    ///
+    /// assert_eq!(space[(0, 0, 0)], dirt);
+    /// assert_eq!(space[(1, 0, 0)], AIR);
+    /// ```
+    pub fn fill_replace<'a, F>(&mut self, region: Grid, mut function: F) -> Result<(), SetCubeError>
+    where
+        F: FnMut(GridPoint, &Block) -> Option<Cow<'a, Block>>,
+    {
+        if region.volume() == 0 {
+            return Ok(());
+        }
+        if !self.grid.contains_grid(region) {
+            return Err(SetCubeError::OutOfBounds {
+                modification: region,
+                space_bounds: self.grid,
+            });
+        }
+        // Note: There is no equivalent of the whole-space fast path of `fill_uniform`,
+        // because the function may produce different results for every cube.
+        for cube in region.interior_iter() {
+            // Cloning a block is cheap, and frees `self` for mutation.
+            let old_block = self[cube].clone();
+            if let Some(block) = function(cube, &old_block) {
+                self.set(cube, block)?;
             }
         }
         Ok(())
@@ -510,7 +935,11 @@ impl Space {
         region: Grid,
         block: impl Into<Cow<'b, Block>>,
     ) -> Result<(), SetCubeError> {
-        if !self.grid.contains_grid(region) {
+        if region.volume() == 0 {
+            // Nothing to do. This also keeps the whole-space case below from adding a
+            // block with a count of zero to a zero-volume space.
+            Ok(())
+        } else if !self.grid.contains_grid(region) {
             Err(SetCubeError::OutOfBounds {
                 modification: region,
                 space_bounds: self.grid,
@@ -533,9 +962,7 @@ impl Space {
                 count: region.volume(),
                 ..new_block_data
             }];
-            for i in self.contents.iter_mut() {
-                *i = new_block_index;
-            }
+            self.contents.fill(new_block_index);
             self.notifier.notify(SpaceChange::EveryBlock);
             Ok(())
         } else {
@@ -545,6 +972,237 @@ impl Space {
         }
     }
 
+    /// Replaces the block at `start`, and every cube connected to it which contains the
+    /// same block, with `new`, like a “paint bucket” tool. Returns the number of cubes
+    /// changed.
+    ///
+    /// `connectivity` determines which cubes are considered adjacent. The region never
+    /// extends beyond the bounds of the space, and the search uses memory proportional
+    /// to the size of the region rather than recursion.
+    ///
+    /// Fails if `start` is out of bounds. Other errors are as in [`Space::set`], and
+    /// stop the operation partway.
+    ///
+    /// ```
+    /// use all_is_cubes::block::{Block, AIR};
+    /// use all_is_cubes::math::Rgba;
+    /// use all_is_cubes::space::{Connectivity, Space};
+    ///
+    /// let mut space = Space::empty_positive(3, 1, 1);
+    /// let wall = Block::from(Rgba::BLACK);
+    /// let paint = Block::from(Rgba::WHITE);
+    /// space.set([1, 0, 0], &wall).unwrap();
+    ///
+    /// assert_eq!(space.flood_fill([0, 0, 0], &paint, Connectivity::Face), Ok(1));
+    /// assert_eq!(space[[0, 0, 0]], paint);
+    /// assert_eq!(space[[2, 0, 0]], AIR);
+    /// ```
+    pub fn flood_fill(
+        &mut self,
+        start: impl Into<GridPoint>,
+        new: &Block,
+        connectivity: Connectivity,
+    ) -> Result<usize, SetCubeError> {
+        let start = start.into();
+        if !self.grid.contains_cube(start) {
+            return Err(SetCubeError::OutOfBounds {
+                modification: Grid::single_cube(start),
+                space_bounds: self.grid,
+            });
+        }
+        let target = self[start].clone();
+        if target == *new {
+            return Ok(0);
+        }
+
+        // Each cube is replaced as soon as it is found, so that it cannot be found again
+        // and the stack never holds more entries than the size of the region.
+        let mut count = 0;
+        let mut stack = vec![start];
+        self.set(start, new)?;
+        count += 1;
+        while let Some(cube) = stack.pop() {
+            for offset in connectivity.neighbor_offsets() {
+                let neighbor = cube + offset;
+                if self.grid.contains_cube(neighbor) && self[neighbor] == target {
+                    self.set(neighbor, new)?;
+                    count += 1;
+                    stack.push(neighbor);
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    /// Write the blocks in `array` into this space, such that the lower corner of
+    /// `array.grid()` is placed at `origin`. This is the inverse of extracting blocks
+    /// with [`Space::extract`].
+    ///
+    /// Errors are handled as in [`Space::fill`]: if the destination region is not
+    /// within [`self.grid()`](Self::grid), that will be rejected before any changes
+    /// are made; other errors stop the operation partway.
+    ///
+    /// ```
+    /// use all_is_cubes::block::{AIR, Block};
+    /// use all_is_cubes::math::{GridPoint, Rgba};
+    /// use all_is_cubes::space::{Grid, GridArray, Space};
+    ///
+    /// let a_block: Block = Rgba::new(1.0, 0.0, 0.0, 1.0).into();
+    /// let array = GridArray::from_elements(
+    ///     Grid::new([0, 0, 0], [2, 1, 1]),
+    ///     [a_block.clone(), AIR],
+    /// ).unwrap();
+    ///
+    /// let mut space = Space::empty_positive(10, 10, 10);
+    /// space.fill_uniform(space.grid(), &a_block).unwrap();
+    /// space.paste_array(GridPoint::new(5, 5, 5), &array).unwrap();
+    /// assert_eq!(space[(5, 5, 5)], a_block);
+    /// assert_eq!(space[(6, 5, 5)], AIR);
+    /// ```
+    pub fn paste_array(
+        &mut self,
+        origin: GridPoint,
+        array: &GridArray<Block>,
+    ) -> Result<(), SetCubeError> {
+        let offset = origin - array.grid().lower_bounds();
+        self.fill(array.grid().translate(offset), |cube| {
+            array.get(cube - offset)
+        })
+    }
+
+    /// Copies the blocks in `src_region` of `src` into this space, rotated by `transform`,
+    /// such that the lower corner of the rotated region is at `dst_origin`.
+    ///
+    /// This is more efficient than [`Space::set`]ting each cube individually, because
+    /// each distinct block is looked up once rather than once per cube, and when a block
+    /// is not already present in this space, its [`EvaluatedBlock`] is copied from `src`
+    /// rather than evaluated again. (That is most useful when both spaces contain blocks
+    /// from the same [`Universe`](crate::universe::Universe).)
+    ///
+    /// Light is not copied; the copied region will be relit as with any other change.
+    /// Blocks are not rotated, only their positions; use
+    /// [`Block::rotate`] beforehand if that is desired.
+    ///
+    /// Errors are handled as in [`Space::fill`]: if the destination region is not
+    /// within [`self.grid()`](Self::grid), or `src_region` is not within `src.grid()`,
+    /// that will be rejected before any changes are made; other errors stop the copy
+    /// partway.
+    ///
+    /// ```
+    /// use all_is_cubes::block::{AIR, Block};
+    /// use all_is_cubes::math::{GridPoint, GridRotation, Rgba};
+    /// use all_is_cubes::space::{Grid, Space};
+    ///
+    /// let a_block: Block = Rgba::new(1.0, 0.0, 0.0, 1.0).into();
+    /// let mut src = Space::empty_positive(2, 1, 1);
+    /// src.set((0, 0, 0), &a_block).unwrap();
+    ///
+    /// let mut dst = Space::empty_positive(10, 10, 10);
+    /// dst.copy_from(GridPoint::new(5, 5, 5), &src, src.grid(), GridRotation::IDENTITY)
+    ///     .unwrap();
+    /// assert_eq!(dst[(5, 5, 5)], a_block);
+    /// assert_eq!(dst[(6, 5, 5)], AIR);
+    /// ```
+    pub fn copy_from(
+        &mut self,
+        dst_origin: GridPoint,
+        src: &Space,
+        src_region: Grid,
+        transform: GridRotation,
+    ) -> Result<(), SetCubeError> {
+        if !src.grid.contains_grid(src_region) {
+            return Err(SetCubeError::OutOfBounds {
+                modification: src_region,
+                space_bounds: src.grid,
+            });
+        }
+        let rotation_matrix = transform.to_rotation_matrix();
+        // Grid::transform() currently never fails.
+        let rotated_region = src_region.transform(rotation_matrix).unwrap();
+        let offset = dst_origin - rotated_region.lower_bounds();
+        let dst_region = rotated_region.translate(offset);
+        if !self.grid.contains_grid(dst_region) {
+            return Err(SetCubeError::OutOfBounds {
+                modification: dst_region,
+                space_bounds: self.grid,
+            });
+        }
+        let matrix = GridMatrix::from_translation(offset) * rotation_matrix;
+
+        // Cache of which index in self corresponds to each index in src.
+        // Entries must be revalidated before use, since copying may have caused an index
+        // to be freed and reused.
+        let mut index_map: Vec<Option<BlockIndex>> = vec![None; src.block_data.len()];
+
+        for src_cube in src_region.interior_iter() {
+            let src_index = src.contents.get(src.grid.index(src_cube).unwrap());
+            let src_data = &src.block_data[usize::from(src_index)];
+            let dst_index = match index_map[usize::from(src_index)] {
+                Some(i)
+                    if self.block_data[usize::from(i)].count > 0
+                        && self.block_data[usize::from(i)].block == src_data.block =>
+                {
+                    i
+                }
+                _ => {
+                    let i = self.ensure_block_index(
+                        Cow::Borrowed(&src_data.block),
+                        Some(&src_data.evaluated),
+                    )?;
+                    index_map[usize::from(src_index)] = Some(i);
+                    i
+                }
+            };
+
+            let dst_cube = matrix.transform_cube(src_cube);
+            let contents_index = self.grid.index(dst_cube).unwrap();
+            if self.contents.get(contents_index) != dst_index {
+                self.replace_block_index(dst_cube, contents_index, dst_index, true);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a new [`Space`] with the bounds `new_grid`, containing the blocks of this
+    /// space which are within those bounds, and [`AIR`] elsewhere.
+    ///
+    /// The [`physics`](Self::physics) and [`spawn`](Self::spawn) are copied unchanged.
+    /// Behaviors are not copied. Light is computed approximately, as by
+    /// [`Space::fast_evaluate_light()`].
+    ///
+    /// This may fail, as [`Space::copy_from()`] does, if a block cannot be added to the
+    /// new space (for example, because its definition is currently being modified).
+    ///
+    /// ```
+    /// use all_is_cubes::block::{AIR, Block};
+    /// use all_is_cubes::math::Rgba;
+    /// use all_is_cubes::space::{Grid, Space};
+    ///
+    /// let block = Block::from(Rgba::WHITE);
+    /// let mut space = Space::empty_positive(2, 1, 1);
+    /// space.set([1, 0, 0], &block).unwrap();
+    ///
+    /// let grown = space.resized(Grid::new([0, 0, 0], [4, 2, 2])).unwrap();
+    /// assert_eq!(grown[[1, 0, 0]], block);
+    /// assert_eq!(grown[[3, 1, 1]], AIR);
+    /// ```
+    pub fn resized(&self, new_grid: Grid) -> Result<Space, SetCubeError> {
+        let mut new_space = Space::builder(new_grid)
+            .physics(self.physics.clone())
+            .spawn(self.spawn.clone())
+            .build_empty();
+        if let Some(overlap) = self.grid.intersection(new_grid) {
+            new_space.copy_from(
+                overlap.lower_bounds(),
+                self,
+                overlap,
+                GridRotation::IDENTITY,
+            )?;
+        }
+        new_space.fast_evaluate_light();
+        Ok(new_space)
+    }
+
     /// Provides an [`DrawTarget`](embedded_graphics::prelude::DrawTarget)
     /// adapter for 2.5D drawing.
     ///
@@ -576,6 +1234,30 @@ impl Space {
         &self.block_data
     }
 
+    /// Returns the contents of the space as run-length encoded [`BlockIndex`]es: each
+    /// item is a block index and the number of consecutive cubes containing it.
+    ///
+    /// The cubes are in the order of [`Grid::index()`], and the indices refer to
+    /// [`Space::block_data()`]. This is a compact representation of spaces containing
+    /// large uniform regions, and can be turned back into a [`Space`] with
+    /// [`Space::from_contents_runs()`].
+    pub fn contents_runs(&self) -> impl Iterator<Item = (BlockIndex, usize)> + '_ {
+        let volume = self.grid.volume();
+        let mut position = 0;
+        std::iter::from_fn(move || {
+            if position >= volume {
+                return None;
+            }
+            let index = self.contents.get(position);
+            let start = position;
+            position += 1;
+            while position < volume && self.contents.get(position) == index {
+                position += 1;
+            }
+            Some((index, position - start))
+        })
+    }
+
     /// Advance time in the space.
     pub fn step(
         &mut self,
@@ -586,29 +1268,70 @@ impl Space {
         for block_index in self.todo.lock().unwrap().blocks.drain() {
             self.notifier.notify(SpaceChange::BlockValue(block_index));
             let data: &mut SpaceBlockData = &mut self.block_data[usize::from(block_index)];
-            // TODO: handle error by switching to a "broken block" state.
+            // On failure, switch to a visible "broken block" placeholder.
             // We may want to have a higher-level error handling by pausing the world
             // and giving the user choices like reverting to save, editing to fix, or
             // continuing with a partly broken world.
-            data.evaluated = data.block.evaluate().expect("block reevaluation failed");
+            data.evaluated = data.block.evaluate().unwrap_or_else(|e| e.to_placeholder());
             // TODO: Process side effects on individual cubes such as reevaluating the
             // lighting influenced by the block.
         }
 
         // Process cubes_wanting_ticks.
+        // Cubes are visited in a fixed order, so that when their actions conflict,
+        // which one wins is deterministic. The losers are retried on the next step.
+        let mut positions: Vec<GridPoint> = std::mem::take(&mut self.cubes_wanting_ticks)
+            .into_iter()
+            .collect();
+        positions.sort_unstable_by_key(|&p| (p.x, p.y, p.z));
         let mut tick_txn = SpaceTransaction::default();
-        // TODO: don't empty the queue until the transaction succeeds
-        for position in std::mem::take(&mut self.cubes_wanting_ticks) {
-            if let Some(brush) = self.get_evaluated(position).attributes.tick_action.as_ref() {
-                // TODO: nonconserved should be at the block's choice
-                tick_txn = tick_txn
-                    .merge(brush.paint_transaction(position).nonconserved())
-                    .expect("TODO: don't panic on tick conflict");
+        let mut ticked_cubes: Vec<GridPoint> = Vec::new();
+        // Cubes being ticked which contain a `Modifier::Move`, to check for completion.
+        let mut moving_cubes: Vec<GridPoint> = Vec::new();
+        let mut cube_ticks_deferred = 0;
+        for position in positions {
+            let txn = match self.get_evaluated(position).attributes.tick_action.as_ref() {
+                Some(action) => action.transaction(self, position),
+                None => continue,
+            };
+            let txn = match txn {
+                Some(txn) => txn,
+                None => {
+                    // The action is waiting for a condition, such as a neighbor no longer
+                    // being solid; check again on the next step.
+                    self.cubes_wanting_ticks.insert(position);
+                    continue;
+                }
+            };
+            // TODO: nonconserved should be at the block's choice
+            let txn = txn.nonconserved();
+            match tick_txn.check_merge(&txn) {
+                Ok(check) => {
+                    tick_txn = tick_txn.commit_merge(txn, check);
+                    ticked_cubes.push(position);
+                    if has_move_modifier(&self[position]) {
+                        moving_cubes.push(position);
+                    }
+                }
+                Err(_) => {
+                    self.cubes_wanting_ticks.insert(position);
+                    cube_ticks_deferred += 1;
+                }
             }
         }
-        // TODO: We need a strategy for, if this transaction fails, trying again while finding
-        // the non-conflicting pieces in a deterministic fashion.
-        let _ignored_failure = tick_txn.execute(self);
+        let cube_ticks = if tick_txn.execute(self).is_ok() {
+            for position in moving_cubes {
+                if !has_move_modifier(&self[position]) {
+                    self.notifier.notify(SpaceChange::MoveCompleted(position));
+                }
+            }
+            ticked_cubes.len()
+        } else {
+            // The combined transaction failed its preconditions; try again next step.
+            cube_ticks_deferred += ticked_cubes.len();
+            self.cubes_wanting_ticks.extend(ticked_cubes);
+            0
+        };
 
         let mut transaction = UniverseTransaction::default();
         if let Some(self_ref) = self_ref {
@@ -624,7 +1347,15 @@ impl Space {
 
         let light = self.update_lighting_from_queue();
 
-        (SpaceStepInfo { spaces: 1, light }, transaction)
+        (
+            SpaceStepInfo {
+                spaces: 1,
+                cube_ticks,
+                cube_ticks_deferred,
+                light,
+            },
+            transaction,
+        )
     }
 
     /// Perform lighting updates until there are none left to do. Returns the number of
@@ -661,6 +1392,37 @@ impl Space {
         total
     }
 
+    /// Perform lighting updates, like [`Space::evaluate_light`], but only for cubes
+    /// within `region`. Returns the number of updates performed.
+    ///
+    /// This is useful for bringing a small area up to date after an edit without
+    /// waiting for the rest of the space. Queued updates for cubes outside `region`,
+    /// including those caused by changes within `region`, are kept for a later
+    /// [`Space::evaluate_light`] or [`Space::step`] to process.
+    ///
+    /// Since light arriving from outside `region` is taken as it currently is, the
+    /// result matches a full evaluation only to the extent that the outside
+    /// lighting is already up to date.
+    pub fn evaluate_light_region(
+        &mut self,
+        region: Grid,
+        epsilon: u8,
+        mut progress_callback: impl FnMut(LightUpdatesInfo),
+    ) -> usize {
+        let mut total = 0;
+        loop {
+            let (info, finished) = self.update_lighting_in_region(region, epsilon);
+
+            progress_callback(info);
+
+            total += info.update_count;
+            if finished {
+                break;
+            }
+        }
+        total
+    }
+
     /// Returns the current [`SpacePhysics`] data, which determines global characteristics
     /// such as the behavior of light and gravity.
     pub fn physics(&self) -> &SpacePhysics {
@@ -671,10 +1433,12 @@ impl Space {
     ///
     /// This may cause recomputation of lighting.
     pub fn set_physics(&mut self, physics: SpacePhysics) {
-        self.packed_sky_color = physics.sky_color.into();
+        self.packed_sky_color = pack_sky(&physics.sky);
         let old_physics = std::mem::replace(&mut self.physics, physics);
         if self.physics.light != old_physics.light {
-            // TODO: == comparison is too broad once there are parameters -- might be a minor change of color etc.
+            // Any change to the parameters, not just the choice of algorithm, requires
+            // recomputing from scratch.
+            // TODO: This is too broad for minor changes such as reflectance.
             self.lighting = self.physics.light.initialize_lighting(self.grid);
 
             match self.physics.light {
@@ -688,15 +1452,23 @@ impl Space {
 
             // TODO: Need to force light updates
         }
-        // TODO: Also send out a SpaceChange notification, if anything is different.
+
+        if self.physics != old_physics {
+            self.notifier.notify(SpaceChange::Physics);
+        }
     }
 
     pub fn spawn(&self) -> &Spawn {
         &self.spawn
     }
 
+    /// Sets the default spawn location of new characters, sending
+    /// [`SpaceChange::Spawn`] if it differs from the previous value.
     pub fn set_spawn(&mut self, spawn: Spawn) {
-        self.spawn = spawn;
+        if spawn != self.spawn {
+            self.spawn = spawn;
+            self.notifier.notify(SpaceChange::Spawn);
+        }
     }
 
     pub fn add_behavior<B>(&mut self, behavior: B)
@@ -708,9 +1480,16 @@ impl Space {
 
     /// Finds or assigns an index to denote the block.
     ///
+    /// If `evaluated` is given, it is used instead of evaluating the block, if a new
+    /// index is needed. It must be a current evaluation of `block`.
+    ///
     /// The caller is responsible for incrementing `self.block_data[index].count`.
     #[inline]
-    fn ensure_block_index(&mut self, block: Cow<'_, Block>) -> Result<BlockIndex, SetCubeError> {
+    fn ensure_block_index(
+        &mut self,
+        block: Cow<'_, Block>,
+        evaluated: Option<&EvaluatedBlock>,
+    ) -> Result<BlockIndex, SetCubeError> {
         if let Some(&old_index) = self.block_to_index.get(&*block) {
             Ok(old_index)
         } else {
@@ -719,8 +1498,9 @@ impl Space {
             let high_mark = self.block_data.len();
             for new_index in 0..high_mark {
                 if self.block_data[new_index].count == 0 {
-                    self.block_data[new_index] = SpaceBlockData::new(
+                    self.block_data[new_index] = SpaceBlockData::new_maybe_evaluated(
                         block.clone().into_owned(),
+                        evaluated,
                         self.listener_for_block(new_index as BlockIndex),
                     )?;
                     self.block_to_index
@@ -735,11 +1515,13 @@ impl Space {
             }
             let new_index = high_mark as BlockIndex;
             // Evaluate the new block type. Can fail, but we haven't done any mutation yet.
-            let new_data = SpaceBlockData::new(
+            let new_data = SpaceBlockData::new_maybe_evaluated(
                 block.clone().into_owned(),
+                evaluated,
                 self.listener_for_block(new_index),
             )?;
-            // Grow the vector.
+            // Grow the vector, and the contents' integer type if needed.
+            self.contents.reserve_index(new_index);
             self.block_data.push(new_data);
             self.block_to_index.insert(block.into_owned(), new_index);
             self.notifier.notify(SpaceChange::Number(new_index));
@@ -750,17 +1532,216 @@ impl Space {
     fn listener_for_block(&self, index: BlockIndex) -> SpaceBlockChangeListener {
         SpaceBlockChangeListener {
             todo: Arc::downgrade(&self.todo),
-            index,
+            index: Arc::new(AtomicU16::new(index)),
         }
     }
 
+    /// Replaces every occurrence of the block `old` in this space with `new`.
+    ///
+    /// Rather than setting each cube individually, this changes which block the
+    /// existing block index refers to, sending a single [`SpaceChange::Number`]
+    /// notification. If `new` is already present in the space, the two indices are
+    /// merged instead, and [`SpaceChange::Block`] is sent for each changed cube.
+    ///
+    /// Returns `Ok(true)` if `old` was present in the space (so that its cubes now
+    /// contain `new`), and `Ok(false)` if it was not and nothing was changed.
+    /// Fails if `new` cannot be evaluated, in which case nothing is changed.
+    ///
+    /// ```
+    /// use all_is_cubes::block::Block;
+    /// use all_is_cubes::math::Rgba;
+    /// use all_is_cubes::space::Space;
+    ///
+    /// let old_stone: Block = Rgba::new(0.5, 0.5, 0.5, 1.0).into();
+    /// let new_stone: Block = Rgba::new(0.6, 0.5, 0.4, 1.0).into();
+    /// let mut space = Space::empty_positive(3, 1, 1);
+    /// space.set((0, 0, 0), &old_stone).unwrap();
+    /// space.set((2, 0, 0), &old_stone).unwrap();
+    ///
+    /// assert_eq!(space.replace_block_definition(&old_stone, new_stone.clone()), Ok(true));
+    /// assert_eq!(space[(0, 0, 0)], new_stone);
+    /// assert_eq!(space[(2, 0, 0)], new_stone);
+    /// assert_eq!(space.replace_block_definition(&old_stone, new_stone), Ok(false));
+    /// ```
+    pub fn replace_block_definition(
+        &mut self,
+        old: &Block,
+        new: Block,
+    ) -> Result<bool, SetCubeError> {
+        let old_index = match self.block_to_index.get(old) {
+            Some(&index) => index,
+            None => return Ok(false),
+        };
+        if *old == new {
+            return Ok(true);
+        }
+
+        let (new_index, cubes_of_old_index) =
+            if let Some(&new_index) = self.block_to_index.get(&new) {
+                // Merge the old index into the existing index for the new block.
+                // Each changed cube must be notified, so we need to find them all.
+                let cubes_of_old_index = self.cubes_of_index(old_index);
+                let old_data = std::mem::replace(
+                    &mut self.block_data[usize::from(old_index)],
+                    SpaceBlockData::tombstone(),
+                );
+                self.block_to_index.remove(&old_data.block);
+                self.block_data[usize::from(new_index)].count += old_data.count;
+                self.contents
+                    .map_in_place(|index| if index == old_index { new_index } else { index });
+                for &cube in &cubes_of_old_index {
+                    self.notifier.notify(SpaceChange::Block(cube));
+                }
+                let cubes_of_old_index = if needs_per_cube_side_effects(
+                    &old_data.evaluated,
+                    &self.block_data[usize::from(new_index)].evaluated,
+                ) {
+                    cubes_of_old_index
+                } else {
+                    Vec::new()
+                };
+                (new_index, cubes_of_old_index)
+            } else {
+                // Reuse the old index for the new block.
+                // Evaluation can fail, so do it before any mutation.
+                let mut new_data =
+                    SpaceBlockData::new(new.clone(), self.listener_for_block(old_index))?;
+                // The cubes are only needed for side effects which depend on how the
+                // block evaluates, so don't scan the whole space unless those changed.
+                let cubes_of_old_index = if needs_per_cube_side_effects(
+                    &self.block_data[usize::from(old_index)].evaluated,
+                    &new_data.evaluated,
+                ) {
+                    self.cubes_of_index(old_index)
+                } else {
+                    Vec::new()
+                };
+                new_data.count = self.block_data[usize::from(old_index)].count;
+                let old_data =
+                    std::mem::replace(&mut self.block_data[usize::from(old_index)], new_data);
+                self.block_to_index.remove(&old_data.block);
+                self.block_to_index.insert(new, old_index);
+                self.notifier.notify(SpaceChange::Number(old_index));
+                (old_index, cubes_of_old_index)
+            };
+
+        if self.block_data[usize::from(new_index)]
+            .evaluated
+            .attributes
+            .tick_action
+            .is_some()
+        {
+            self.cubes_wanting_ticks
+                .extend(cubes_of_old_index.iter().copied());
+        }
+        self.light_side_effects_of_fill(self.grid, &cubes_of_old_index);
+
+        Ok(true)
+    }
+
+    /// Returns all cubes in this space whose block is `index`.
+    fn cubes_of_index(&self, index: BlockIndex) -> Vec<GridPoint> {
+        self.grid
+            .interior_iter()
+            .filter(|&cube| self.contents.get(self.grid.index(cube).unwrap()) == index)
+            .collect()
+    }
+
+    /// Removes unused entries from [`Space::block_data`] and renumbers the remaining
+    /// ones so that the indices are contiguous, as they may become sparse after many
+    /// different blocks have been placed and removed.
+    ///
+    /// Indices in use keep their relative order, so in particular an index 0 in use
+    /// (as [`AIR`] typically is) remains index 0. Index 0 is also kept, even if unused,
+    /// when it is [`AIR`].
+    ///
+    /// If any indices changed, a single [`SpaceChange::EveryBlock`] notification is
+    /// sent. The returned [`BlockIndexRemapping`] may be used to update any other
+    /// data which refers to block indices.
+    ///
+    /// ```
+    /// use all_is_cubes::block::{AIR, Block};
+    /// use all_is_cubes::math::Rgba;
+    /// use all_is_cubes::space::Space;
+    ///
+    /// let block_1: Block = Rgba::new(1.0, 0.0, 0.0, 1.0).into();
+    /// let block_2: Block = Rgba::new(0.0, 1.0, 0.0, 1.0).into();
+    /// let mut space = Space::empty_positive(3, 1, 1);
+    /// space.set((0, 0, 0), &block_1).unwrap();
+    /// space.set((1, 0, 0), &block_2).unwrap();
+    /// space.set((0, 0, 0), &AIR).unwrap();
+    /// assert_eq!(space.get_block_index((1, 0, 0)), Some(2));
+    ///
+    /// let remapping = space.gc_block_indices();
+    /// assert_eq!(remapping.get(2), Some(1));
+    /// assert_eq!(space.get_block_index((1, 0, 0)), Some(1));
+    /// ```
+    pub fn gc_block_indices(&mut self) -> BlockIndexRemapping {
+        let keep = |index: usize, data: &SpaceBlockData| {
+            data.count > 0 || (index == 0 && data.block == AIR)
+        };
+
+        let mut next_index: BlockIndex = 0;
+        let remapping = BlockIndexRemapping {
+            map: self
+                .block_data
+                .iter()
+                .enumerate()
+                .map(|(index, data)| {
+                    keep(index, data).then(|| {
+                        let new_index = next_index;
+                        next_index += 1;
+                        new_index
+                    })
+                })
+                .collect(),
+        };
+        if remapping.is_identity() {
+            return remapping;
+        }
+
+        // Hold the lock while renumbering so that no change notification can be
+        // recorded under an index that is neither old nor new.
+        let todo_mutex = Arc::clone(&self.todo);
+        let mut todo = todo_mutex.lock().unwrap();
+
+        let old_block_data = std::mem::take(&mut self.block_data);
+        self.block_data = old_block_data
+            .into_iter()
+            .enumerate()
+            .filter(|(index, data)| keep(*index, data))
+            .map(|(_, data)| data)
+            .collect();
+        for (new_index, data) in self.block_data.iter().enumerate() {
+            if let Some(listener_index) = &data.listener_index {
+                listener_index.store(new_index as BlockIndex, Ordering::Relaxed);
+            }
+        }
+        for index in self.block_to_index.values_mut() {
+            *index = remapping
+                .get(*index)
+                .expect("block_to_index had unused index");
+        }
+        self.contents
+            .map_in_place(|index| remapping.get(index).expect("contents had unused index"));
+        todo.blocks = todo
+            .blocks
+            .drain()
+            .filter_map(|index| remapping.get(index))
+            .collect();
+        drop(todo);
+
+        self.notifier.notify(SpaceChange::EveryBlock);
+        remapping
+    }
+
     #[cfg(test)]
     #[track_caller]
     pub(crate) fn consistency_check(&self) {
         let mut problems = Vec::new();
 
         let mut actual_counts: HashMap<BlockIndex, usize> = HashMap::new();
-        for index in self.contents.iter().copied() {
+        for index in self.contents.iter() {
             *actual_counts.entry(index).or_insert(0) += 1;
         }
 
@@ -836,7 +1817,7 @@ impl<T: Into<GridPoint>> std::ops::Index<T> for Space {
     #[inline(always)]
     fn index(&self, position: T) -> &Self::Output {
         if let Some(index) = self.grid.index(position) {
-            &self.block_data[self.contents[index] as usize].block
+            &self.block_data[self.contents.get(index) as usize].block
         } else {
             &AIR
         }
@@ -877,6 +1858,7 @@ impl SpaceBlockData {
         count: 0,
         evaluated: AIR_EVALUATED,
         block_listen_gate: None,
+        listener_index: None,
     };
 
     /// Value used to fill empty entries in the block data vector.
@@ -889,15 +1871,27 @@ impl SpaceBlockData {
             count: 0,
             evaluated: AIR_EVALUATED,
             block_listen_gate: None,
+            listener_index: None,
         }
     }
 
-    fn new(
+    fn new(block: Block, listener: SpaceBlockChangeListener) -> Result<Self, SetCubeError> {
+        Self::new_maybe_evaluated(block, None, listener)
+    }
+
+    /// As [`Self::new`], but if `evaluated` is given, uses it instead of evaluating
+    /// the block.
+    fn new_maybe_evaluated(
         block: Block,
-        listener: impl Listener<BlockChange> + Clone + Send + Sync + 'static,
+        evaluated: Option<&EvaluatedBlock>,
+        listener: SpaceBlockChangeListener,
     ) -> Result<Self, SetCubeError> {
         // TODO: double ref error check suggests that maybe evaluate() and listen() should be one combined operation.
-        let evaluated = block.evaluate().map_err(SetCubeError::EvalBlock)?;
+        let evaluated = match evaluated {
+            Some(evaluated) => evaluated.clone(),
+            None => block.evaluate().map_err(SetCubeError::EvalBlock)?,
+        };
+        let listener_index = listener.index.clone();
         let (gate, block_listener) = listener.gate();
         block
             .listen(block_listener)
@@ -907,6 +1901,7 @@ impl SpaceBlockData {
             count: 0,
             evaluated,
             block_listen_gate: Some(gate),
+            listener_index: Some(listener_index),
         })
     }
 
@@ -938,17 +1933,13 @@ impl SpaceBlockData {
 #[derive(Clone, Eq, Hash, PartialEq)]
 #[non_exhaustive]
 pub struct SpacePhysics {
-    /// Gravity vector for moving objects, in cubes/s².
-    ///
-    /// TODO: Expand this to an enum which allows non-uniform gravity patterns.
-    pub gravity: Vector3<NotNan<FreeCoordinate>>,
+    /// Gravitational acceleration of moving objects.
+    /// Use [`SpacePhysics::gravity_at()`] to find its value at a particular point.
+    pub gravity: Gravity,
 
     /// Color of light arriving from outside the space, used for light calculation
     /// and rendering.
-    ///
-    /// TODO: Consider replacing this with some sort of cube map, spherical harmonics,
-    /// or some such to allow for non-uniform illumination.
-    pub sky_color: Rgb,
+    pub sky: Sky,
 
     /// Method used to compute the illumination of individual blocks.
     pub light: LightPhysics,
@@ -957,31 +1948,31 @@ pub struct SpacePhysics {
 
 impl SpacePhysics {
     pub(crate) const DEFAULT: Self = Self {
-        gravity: Vector3::new(notnan!(0.), notnan!(-20.), notnan!(0.)),
-        sky_color: palette::DAY_SKY_COLOR,
+        gravity: Gravity::Uniform(Vector3::new(notnan!(0.), notnan!(-20.), notnan!(0.))),
+        sky: Sky::Uniform(palette::DAY_SKY_COLOR),
         light: LightPhysics::DEFAULT,
     };
 
     /// Recommended defaults for spaces which are going to define a [`Block`]'s voxels.
     /// In particular, disables light since it will not be used.
     pub const DEFAULT_FOR_BLOCK: Self = Self {
-        gravity: Vector3::new(notnan!(0.), notnan!(0.), notnan!(0.)),
-        sky_color: rgb_const!(0.5, 0.5, 0.5),
+        gravity: Gravity::Uniform(Vector3::new(notnan!(0.), notnan!(0.), notnan!(0.))),
+        sky: Sky::Uniform(rgb_const!(0.5, 0.5, 0.5)),
         light: LightPhysics::None,
     };
+
+    /// Returns the gravitational acceleration, in cubes/s², experienced by an object
+    /// at `point`.
+    pub fn gravity_at(&self, point: Point3<FreeCoordinate>) -> Vector3<FreeCoordinate> {
+        self.gravity.at(point)
+    }
 }
 
 impl fmt::Debug for SpacePhysics {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("SpacePhysics")
-            .field(
-                "gravity",
-                &self
-                    .gravity
-                    .map(NotNan::into_inner)
-                    .custom_format(ConciseDebug),
-            )
-            .field("sky_color", &self.sky_color)
+            .field("gravity", &self.gravity)
+            .field("sky", &self.sky)
             .field("light", &self.light)
             .finish()
     }
@@ -997,8 +1988,8 @@ impl Default for SpacePhysics {
 impl<'a> arbitrary::Arbitrary<'a> for SpacePhysics {
     fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
         Ok(Self {
-            gravity: Vector3::new(u.arbitrary()?, u.arbitrary()?, u.arbitrary()?),
-            sky_color: u.arbitrary()?,
+            gravity: u.arbitrary()?,
+            sky: u.arbitrary()?,
             light: u.arbitrary()?,
         })
     }
@@ -1006,38 +1997,153 @@ impl<'a> arbitrary::Arbitrary<'a> for SpacePhysics {
     fn size_hint(depth: usize) -> (usize, Option<usize>) {
         use arbitrary::{size_hint::and_all, Arbitrary};
         and_all(&[
-            <f64 as Arbitrary>::size_hint(depth),
-            <f64 as Arbitrary>::size_hint(depth),
-            <Rgb as Arbitrary>::size_hint(depth),
+            <Gravity as Arbitrary>::size_hint(depth),
+            <Sky as Arbitrary>::size_hint(depth),
             <LightPhysics as Arbitrary>::size_hint(depth),
         ])
     }
 }
 
+/// Gravitational field of a [`Space`]; part of [`SpacePhysics`].
+#[non_exhaustive]
+#[derive(Clone, Eq, Hash, PartialEq)]
+pub enum Gravity {
+    /// The same acceleration, in cubes/s², everywhere.
+    Uniform(Vector3<NotNan<FreeCoordinate>>),
+    /// Acceleration of constant magnitude `strength`, in cubes/s², directed towards
+    /// `center`. There is no acceleration exactly at `center`.
+    ///
+    /// The magnitude does not fall off with distance (as it would for real gravity),
+    /// so that the gravity at the surface of a “planet” of any size is predictable.
+    Radial {
+        /// The point towards which objects are pulled.
+        center: Point3<NotNan<FreeCoordinate>>,
+        /// Magnitude of the acceleration. Negative values push away from the center.
+        strength: NotNan<FreeCoordinate>,
+    },
+}
+
+impl Gravity {
+    /// Returns the acceleration, in cubes/s², experienced by an object at `point`.
+    ///
+    /// ```
+    /// use all_is_cubes::cgmath::{Point3, Vector3};
+    /// use all_is_cubes::math::NotNan;
+    /// use all_is_cubes::space::Gravity;
+    ///
+    /// let gravity = Gravity::Radial {
+    ///     center: Point3::new(0., 0., 0.).map(|c| NotNan::new(c).unwrap()),
+    ///     strength: NotNan::new(10.).unwrap(),
+    /// };
+    /// assert_eq!(gravity.at(Point3::new(0., 5., 0.)), Vector3::new(0., -10., 0.));
+    /// ```
+    pub fn at(&self, point: Point3<FreeCoordinate>) -> Vector3<FreeCoordinate> {
+        match *self {
+            Gravity::Uniform(vector) => vector.map(NotNan::into_inner),
+            Gravity::Radial { center, strength } => {
+                let toward_center = center.map(NotNan::into_inner) - point;
+                if toward_center.is_zero() {
+                    Vector3::zero()
+                } else {
+                    toward_center.normalize() * strength.into_inner()
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Gravity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Gravity::Uniform(vector) => f
+                .debug_tuple("Uniform")
+                .field(&vector.map(NotNan::into_inner).custom_format(ConciseDebug))
+                .finish(),
+            Gravity::Radial { center, strength } => f
+                .debug_struct("Radial")
+                .field(
+                    "center",
+                    &center.map(NotNan::into_inner).custom_format(ConciseDebug),
+                )
+                .field("strength", &strength.into_inner())
+                .finish(),
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Gravity {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(if u.arbitrary()? {
+            Gravity::Uniform(Vector3::new(u.arbitrary()?, u.arbitrary()?, u.arbitrary()?))
+        } else {
+            Gravity::Radial {
+                center: Point3::new(u.arbitrary()?, u.arbitrary()?, u.arbitrary()?),
+                strength: u.arbitrary()?,
+            }
+        })
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        use arbitrary::{size_hint, Arbitrary};
+        size_hint::and(
+            <bool as Arbitrary>::size_hint(depth),
+            size_hint::or(
+                size_hint::and_all(&[<f64 as Arbitrary>::size_hint(depth); 3]),
+                size_hint::and_all(&[<f64 as Arbitrary>::size_hint(depth); 4]),
+            ),
+        )
+    }
+}
+
 /// Method used to compute the illumination of individual blocks in a [`Space`].
 #[non_exhaustive]
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, serde::Serialize, serde::Deserialize)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum LightPhysics {
     /// No light. All surface colors are taken exactly as displayed colors. The
-    /// [`SpacePhysics::sky_color`] is used solely as a background color.
+    /// [`SpacePhysics::sky`] is used solely as a background color.
     None,
     /// Raycast-based light propagation and diffuse reflections.
     ///
-    /// TODO: Need to provide a builder so that this can be constructed
-    /// even when more parameters are added.
-    // TODO: #[non_exhaustive]
+    /// To construct this variant, use [`LightPhysics::rays()`].
+    #[non_exhaustive]
     Rays {
         /// The maximum distance a simulated light ray will travel; blocks farther than
         /// that distance apart will never have direct influence on each other.
         maximum_distance: u16,
+        /// The number of directions in which rays are cast to compute the light falling
+        /// on each cube. Fewer rays are faster to compute but produce noisier results.
+        ///
+        /// Values above [`LightRaysBuilder::MAX_RAY_COUNT`] have the same effect as
+        /// that value.
+        ray_count: u16,
+        /// Fraction of the light falling on a surface which is reflected onward to
+        /// illuminate other surfaces (before the surface's color is taken into account).
+        /// Light emitted by blocks is not affected.
+        reflectance: NotNan<f32>,
+        /// A distant light source, such as the sun, which directly illuminates surfaces
+        /// facing it unless they are shadowed, in addition to the diffuse light from the
+        /// [`SpacePhysics::sky`].
+        #[serde(default)]
+        sun: Option<DirectionalLight>,
     },
 }
 
 impl LightPhysics {
-    pub(crate) const DEFAULT: Self = Self::Rays {
-        maximum_distance: 30,
-    };
+    pub(crate) const DEFAULT: Self = LightRaysBuilder::DEFAULT.build();
+
+    /// Returns a [`LightRaysBuilder`] with default parameters, which may be used to
+    /// construct a [`LightPhysics::Rays`] value.
+    ///
+    /// ```
+    /// use all_is_cubes::space::LightPhysics;
+    ///
+    /// let physics = LightPhysics::rays().maximum_distance(10).ray_count(100).build();
+    /// ```
+    pub const fn rays() -> LightRaysBuilder {
+        LightRaysBuilder::DEFAULT
+    }
 }
 
 impl Default for LightPhysics {
@@ -1046,6 +2152,102 @@ impl Default for LightPhysics {
     }
 }
 
+/// A distant light source casting parallel rays, such as the sun; part of
+/// [`LightPhysics::Rays`].
+///
+/// Unlike the diffuse light from the [`Sky`], which is averaged over many directions,
+/// directional light is traced along a single ray from each cube, so it casts sharp
+/// shadows.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(from = "DirectionalLightSer", into = "DirectionalLightSer")]
+#[non_exhaustive]
+pub struct DirectionalLight {
+    /// Direction pointing *toward* the light source. Need not be normalized.
+    pub direction: Vector3<NotNan<FreeCoordinate>>,
+    /// Light falling on a surface which faces the light source directly.
+    pub color: Rgb,
+}
+
+impl DirectionalLight {
+    /// Constructs a [`DirectionalLight`] shining from `direction` (that is, `direction`
+    /// points toward the light source).
+    ///
+    /// Panics if any of the coordinates are NaN.
+    pub fn new(direction: impl Into<Vector3<FreeCoordinate>>, color: Rgb) -> Self {
+        Self {
+            direction: direction.into().map(|c| NotNan::new(c).unwrap()),
+            color,
+        }
+    }
+}
+
+/// Serialized form of [`DirectionalLight`], since [`Vector3`] does not implement
+/// [`serde::Serialize`].
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+struct DirectionalLightSer {
+    direction: [NotNan<FreeCoordinate>; 3],
+    color: Rgb,
+}
+
+impl From<DirectionalLight> for DirectionalLightSer {
+    fn from(light: DirectionalLight) -> Self {
+        Self {
+            direction: light.direction.into(),
+            color: light.color,
+        }
+    }
+}
+
+impl From<DirectionalLightSer> for DirectionalLight {
+    fn from(light: DirectionalLightSer) -> Self {
+        Self {
+            direction: light.direction.into(),
+            color: light.color,
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for DirectionalLight {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(DirectionalLight {
+            direction: Vector3::new(u.arbitrary()?, u.arbitrary()?, u.arbitrary()?),
+            color: u.arbitrary()?,
+        })
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        use arbitrary::{size_hint, Arbitrary};
+        size_hint::and(
+            size_hint::and_all(&[<f64 as Arbitrary>::size_hint(depth); 3]),
+            <Rgb as Arbitrary>::size_hint(depth),
+        )
+    }
+}
+
+/// Which cubes are considered adjacent by [`Space::flood_fill`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Connectivity {
+    /// Cubes are adjacent if they share a face (6 neighbors).
+    Face,
+    /// Cubes are adjacent if they share a face, edge, or corner (26 neighbors).
+    Vertex,
+}
+
+impl Connectivity {
+    fn neighbor_offsets(self) -> impl Iterator<Item = GridVector> {
+        let vertex = self == Connectivity::Vertex;
+        Grid::new([-1, -1, -1], [3, 3, 3])
+            .interior_iter()
+            .map(|p| p.to_vec())
+            .filter(move |v| {
+                let nonzero = [v.x, v.y, v.z].iter().filter(|&&c| c != 0).count();
+                nonzero == 1 || vertex && nonzero > 1
+            })
+    }
+}
+
 /// Ways that [`Space::set`] can fail to make a change.
 ///
 /// Note that "already contained the given block" is considered a success.
@@ -1066,6 +2268,28 @@ pub enum SetCubeError {
     TooManyBlocks(),
 }
 
+/// Ways that [`Space::from_contents_runs`] can fail.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, thiserror::Error)]
+#[non_exhaustive]
+pub enum ContentsRunsError {
+    /// A run referred to a block index with no corresponding block.
+    #[error("block index {index} is out of range")]
+    IndexOutOfRange { index: BlockIndex },
+    /// The runs did not add up to the volume of the space.
+    #[error("runs cover {actual} cubes but the space contains {expected}")]
+    WrongLength { expected: usize, actual: usize },
+    /// The same block was given for more than one index in use.
+    #[error("block index {index} is a duplicate of an earlier block")]
+    DuplicateBlock { index: BlockIndex },
+    /// A block could not be added to the space.
+    #[error("block index {index} could not be used: {error}")]
+    Block {
+        index: BlockIndex,
+        #[source]
+        error: SetCubeError,
+    },
+}
+
 /// Description of a change to a [`Space`] for use in listeners.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 #[allow(clippy::exhaustive_enums)] // any change will probably be breaking anyway
@@ -1084,6 +2308,21 @@ pub enum SpaceChange {
     /// Equivalent to [`SpaceChange::Block`] for every cube and [`SpaceChange::Number`]
     /// for every index.
     EveryBlock,
+    /// The [`SpacePhysics`] were replaced with a different value by
+    /// [`Space::set_physics`].
+    ///
+    /// If [`SpacePhysics::light`] changed, the light of every cube may have changed
+    /// without any [`SpaceChange::Lighting`] notifications being sent.
+    Physics,
+    /// The [`Spawn`] was replaced with a different value by [`Space::set_spawn`].
+    Spawn,
+    /// A [`Modifier::Move`] in the block at the given
+    /// location finished its movement and removed itself, or the block became
+    /// [`AIR`](crate::block::AIR) because it moved entirely out of the cube.
+    ///
+    /// This is sent after the corresponding [`SpaceChange::Block`]. Note that a pair of
+    /// moves (as created by [`Modifier::paired_move()`]) produces one of these for each cube.
+    MoveCompleted(GridPoint),
 }
 
 /// Performance data returned by [`Space::step`]. The exact contents of this structure
@@ -1094,6 +2333,15 @@ pub enum SpaceChange {
 pub struct SpaceStepInfo {
     /// Number of spaces whose updates were aggregated into this value.
     pub spaces: usize,
+    /// Number of cubes whose [`BlockAttributes::tick_action`] was applied.
+    ///
+    /// [`BlockAttributes::tick_action`]: crate::block::BlockAttributes::tick_action
+    pub cube_ticks: usize,
+    /// Number of cubes whose [`BlockAttributes::tick_action`] conflicted with another
+    /// cube's and was postponed to the next step.
+    ///
+    /// [`BlockAttributes::tick_action`]: crate::block::BlockAttributes::tick_action
+    pub cube_ticks_deferred: usize,
     pub light: LightUpdatesInfo,
 }
 impl std::ops::AddAssign<SpaceStepInfo> for SpaceStepInfo {
@@ -1103,6 +2351,8 @@ impl std::ops::AddAssign<SpaceStepInfo> for SpaceStepInfo {
             return;
         }
         self.spaces += other.spaces;
+        self.cube_ticks += other.cube_ticks;
+        self.cube_ticks_deferred += other.cube_ticks_deferred;
         self.light += other.light;
     }
 }
@@ -1110,7 +2360,13 @@ impl CustomFormat<StatusText> for SpaceStepInfo {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>, _: StatusText) -> fmt::Result {
         write!(fmt, "{} spaces: ", self.spaces)?;
         if self.spaces > 0 {
-            write!(fmt, "Relighting: {}", self.light.custom_format(StatusText))?;
+            write!(
+                fmt,
+                "Cube ticks: {} ({} deferred) Relighting: {}",
+                self.cube_ticks,
+                self.cube_ticks_deferred,
+                self.light.custom_format(StatusText)
+            )?;
         }
         Ok(())
     }
@@ -1129,14 +2385,15 @@ struct SpaceTodo {
 #[derive(Clone, Debug)]
 struct SpaceBlockChangeListener {
     todo: Weak<Mutex<SpaceTodo>>,
-    index: BlockIndex,
+    /// Shared with [`SpaceBlockData::listener_index`] so that it can be renumbered.
+    index: Arc<AtomicU16>,
 }
 
 impl Listener<BlockChange> for SpaceBlockChangeListener {
     fn receive(&self, _: BlockChange) {
         if let Some(todo_mutex) = self.todo.upgrade() {
             if let Ok(mut todo) = todo_mutex.lock() {
-                todo.blocks.insert(self.index);
+                todo.blocks.insert(self.index.load(Ordering::Relaxed));
             }
             // If the mutex is poisoned, do nothing so we don't propagate failure to the notifier.
         }
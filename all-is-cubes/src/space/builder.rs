@@ -3,21 +3,33 @@
 
 use cgmath::{EuclideanSpace, InnerSpace, Point3};
 
+use crate::block::Block;
 use crate::character::Spawn;
-use crate::math::{FreeCoordinate, Rgb};
-use crate::space::{Grid, LightPhysics, Space, SpacePhysics};
+use crate::math::{FreeCoordinate, GridPoint, NotNan, Rgb};
+use crate::space::{
+    DirectionalLight, Grid, GridArray, LightPhysics, SetCubeError, Sky, Space, SpacePhysics,
+};
 
 /// Tool for constructing new [`Space`]s.
 ///
 /// To create one, call [`Space::builder(grid)`](Space::builder).
 ///
-/// TODO: Allow specifying behaviors and initial block contents.
+/// TODO: Allow specifying behaviors.
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[must_use]
 pub struct SpaceBuilder {
     pub(super) grid: Grid,
     pub(super) spawn: Option<Spawn>,
     pub(super) physics: SpacePhysics,
+    contents: Contents,
+}
+
+/// Initial block contents specified for a [`SpaceBuilder`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Contents {
+    Air,
+    Uniform(Block),
+    Array(GridArray<Block>),
 }
 
 impl SpaceBuilder {
@@ -26,6 +38,7 @@ impl SpaceBuilder {
             grid,
             spawn: None,
             physics: SpacePhysics::DEFAULT,
+            contents: Contents::Air,
         }
     }
 
@@ -36,12 +49,17 @@ impl SpaceBuilder {
         self
     }
 
-    /// Sets the value of [`SpacePhysics::sky_color`] for the space.
-    pub fn sky_color(mut self, color: Rgb) -> Self {
-        self.physics.sky_color = color;
+    /// Sets the value of [`SpacePhysics::sky`] for the space.
+    pub fn sky(mut self, sky: Sky) -> Self {
+        self.physics.sky = sky;
         self
     }
 
+    /// Sets the value of [`SpacePhysics::sky`] for the space to a single color.
+    pub fn sky_color(self, color: Rgb) -> Self {
+        self.sky(Sky::Uniform(color))
+    }
+
     /// Sets the value of [`SpacePhysics::light`] for the space, which determines the
     /// behavior of light within the space.
     pub fn light_physics(mut self, light_physics: LightPhysics) -> Self {
@@ -78,11 +96,138 @@ impl SpaceBuilder {
         self
     }
 
+    /// Sets the space to be entirely filled with `block` when built with
+    /// [`build()`](Self::build).
+    pub fn filled_with(mut self, block: Block) -> Self {
+        self.contents = Contents::Uniform(block);
+        self
+    }
+
+    /// Sets the space to be filled with the blocks returned by `function` for each cube
+    /// when built with [`build()`](Self::build).
+    ///
+    /// The function is called immediately, once for each cube in the bounds.
+    pub fn fill_fn(mut self, function: impl FnMut(GridPoint) -> Block) -> Self {
+        self.contents = Contents::Array(GridArray::from_fn(self.grid, function));
+        self
+    }
+
     /// Construct a new [`Space`] filled with [`AIR`](crate::block::AIR), with the bounds
     /// and settings of this builder.
+    ///
+    /// Panics if contents were specified by [`filled_with()`](Self::filled_with) or
+    /// [`fill_fn()`](Self::fill_fn), since they would be discarded; use
+    /// [`build()`](Self::build) instead.
+    #[track_caller]
     pub fn build_empty(self) -> Space {
+        assert!(
+            self.contents == Contents::Air,
+            "build_empty() used with contents; use build() instead"
+        );
         Space::new_from_builder(self)
     }
+
+    /// Construct a new [`Space`] with the bounds, settings, and contents of this builder.
+    ///
+    /// If no contents were specified, the space is filled with
+    /// [`AIR`](crate::block::AIR), as with [`build_empty()`](Self::build_empty).
+    ///
+    /// Returns an error if the blocks could not be placed, in the same way as
+    /// [`Space::fill()`].
+    ///
+    /// ```
+    /// use all_is_cubes::block::{Block, AIR};
+    /// use all_is_cubes::math::Rgba;
+    /// use all_is_cubes::space::{Grid, Space};
+    ///
+    /// let stone = Block::from(Rgba::new(0.5, 0.5, 0.5, 1.0));
+    /// let space = Space::builder(Grid::new([0, 0, 0], [4, 4, 4]))
+    ///     .fill_fn(|cube| if cube.y < 2 { stone.clone() } else { AIR })
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(space[[0, 1, 0]], stone);
+    /// assert_eq!(space[[0, 2, 0]], AIR);
+    /// ```
+    pub fn build(mut self) -> Result<Space, SetCubeError> {
+        let contents = std::mem::replace(&mut self.contents, Contents::Air);
+        let mut space = Space::new_from_builder(self);
+        let grid = space.grid();
+        match contents {
+            Contents::Air => {}
+            Contents::Uniform(block) => space.fill_uniform(grid, block)?,
+            Contents::Array(blocks) => space.fill(grid, |cube| Some(&blocks[cube]))?,
+        }
+        Ok(space)
+    }
+}
+
+/// Tool for constructing [`LightPhysics::Rays`] values.
+///
+/// To create one, call [`LightPhysics::rays()`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[must_use]
+pub struct LightRaysBuilder {
+    maximum_distance: u16,
+    ray_count: u16,
+    reflectance: NotNan<f32>,
+    sun: Option<DirectionalLight>,
+}
+
+impl LightRaysBuilder {
+    /// The number of distinct ray directions available; larger values of
+    /// [`ray_count`](Self::ray_count) have no further effect.
+    pub const MAX_RAY_COUNT: u16 = super::light::ALL_RAYS_COUNT as u16;
+
+    pub(super) const DEFAULT: Self = Self {
+        maximum_distance: 30,
+        ray_count: Self::MAX_RAY_COUNT,
+        reflectance: notnan!(1.0),
+        sun: None,
+    };
+
+    /// Sets the maximum distance a simulated light ray will travel.
+    /// The default is 30.
+    pub const fn maximum_distance(mut self, maximum_distance: u16) -> Self {
+        self.maximum_distance = maximum_distance;
+        self
+    }
+
+    /// Sets the number of rays cast per cube.
+    /// The default is [`Self::MAX_RAY_COUNT`].
+    pub const fn ray_count(mut self, ray_count: u16) -> Self {
+        self.ray_count = ray_count;
+        self
+    }
+
+    /// Sets the fraction of light reflected by surfaces.
+    /// The default is 1.0.
+    pub const fn reflectance(mut self, reflectance: NotNan<f32>) -> Self {
+        self.reflectance = reflectance;
+        self
+    }
+
+    /// Sets the directional light source, in addition to the sky, which casts sharp
+    /// shadows. The default is [`None`], meaning that all light is diffuse.
+    pub const fn sun(mut self, sun: Option<DirectionalLight>) -> Self {
+        self.sun = sun;
+        self
+    }
+
+    /// Returns the configured [`LightPhysics::Rays`].
+    pub const fn build(self) -> LightPhysics {
+        LightPhysics::Rays {
+            maximum_distance: self.maximum_distance,
+            ray_count: self.ray_count,
+            reflectance: self.reflectance,
+            sun: self.sun,
+        }
+    }
+}
+
+impl Default for LightRaysBuilder {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
 }
 
 #[cfg(feature = "arbitrary")]
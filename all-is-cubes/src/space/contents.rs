@@ -0,0 +1,100 @@
+// Copyright 2020-2022 Kevin Reid under the terms of the MIT License as detailed
+// in the accompanying file README.md or <https://opensource.org/licenses/MIT>.
+
+//! [`ContentsArray`], the block index storage of [`Space`](super::Space).
+
+use crate::space::BlockIndex;
+
+/// An array of [`BlockIndex`]es, one per cube of a [`Space`](super::Space), stored
+/// using the smallest integer type that can hold every index in use.
+///
+/// Starts out using [`u8`], and is rewritten to use [`u16`] when
+/// [`ContentsArray::reserve_index()`] is called with an index that does not fit.
+/// It never switches back.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum ContentsArray {
+    U8(Box<[u8]>),
+    U16(Box<[u16]>),
+}
+
+impl ContentsArray {
+    /// Constructs an array of `len` zeroes.
+    pub(crate) fn new(len: usize) -> Self {
+        Self::U8(vec![0; len].into_boxed_slice())
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, index: usize) -> BlockIndex {
+        match self {
+            Self::U8(array) => BlockIndex::from(array[index]),
+            Self::U16(array) => array[index],
+        }
+    }
+
+    /// Stores `value` at `index`.
+    ///
+    /// Panics if `value` is too large for the current storage; call
+    /// [`Self::reserve_index()`] first when allocating new indices.
+    #[inline]
+    #[track_caller]
+    pub(crate) fn set(&mut self, index: usize, value: BlockIndex) {
+        match self {
+            Self::U8(array) => {
+                array[index] = u8::try_from(value)
+                    .expect("ContentsArray::set: value too large; reserve_index() not called")
+            }
+            Self::U16(array) => array[index] = value,
+        }
+    }
+
+    /// Sets every element to `value`, which must be no larger than allowed by
+    /// [`Self::set()`].
+    pub(crate) fn fill(&mut self, value: BlockIndex) {
+        self.reserve_index(value);
+        match self {
+            Self::U8(array) => array.fill(value as u8),
+            Self::U16(array) => array.fill(value),
+        }
+    }
+
+    /// Ensures that `index` can subsequently be stored, converting to wider storage
+    /// if needed.
+    pub(crate) fn reserve_index(&mut self, index: BlockIndex) {
+        if let Self::U8(array) = self {
+            if u8::try_from(index).is_err() {
+                *self = Self::U16(array.iter().copied().map(BlockIndex::from).collect());
+            }
+        }
+    }
+
+    /// Replaces every element with the result of `f`, which must not return indices
+    /// larger than those already stored or reserved.
+    pub(crate) fn map_in_place(&mut self, mut f: impl FnMut(BlockIndex) -> BlockIndex) {
+        match self {
+            Self::U8(array) => {
+                for element in array.iter_mut() {
+                    *element = u8::try_from(f(BlockIndex::from(*element)))
+                        .expect("ContentsArray::map_in_place: value too large");
+                }
+            }
+            Self::U16(array) => {
+                for element in array.iter_mut() {
+                    *element = f(*element);
+                }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn iter(&self) -> impl Iterator<Item = BlockIndex> + '_ {
+        let (narrow, wide) = match self {
+            Self::U8(array) => (&array[..], &[][..]),
+            Self::U16(array) => (&[][..], &array[..]),
+        };
+        narrow
+            .iter()
+            .copied()
+            .map(BlockIndex::from)
+            .chain(wide.iter().copied())
+    }
+}
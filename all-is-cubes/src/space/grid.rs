@@ -12,7 +12,8 @@ use cgmath::{EuclideanSpace, Point3, Transform, Vector3};
 
 use crate::block::Resolution;
 use crate::math::{
-    Aab, Face6, Face7, FaceMap, FreeCoordinate, GridCoordinate, GridMatrix, GridPoint, GridVector,
+    Aab, Face6, Face7, FaceMap, FreeCoordinate, GridCoordinate, GridMatrix, GridPoint,
+    GridRotation, GridVector,
 };
 
 /// An axis-aligned box with integer coordinates, whose volume is no larger than [`usize::MAX`].
@@ -35,6 +36,15 @@ pub struct Grid {
 }
 
 impl Grid {
+    /// An arbitrarily chosen [`Grid`] with zero volume, located at the origin.
+    ///
+    /// This is the value returned by [`Grid::intersection_or_empty`] when there is no
+    /// intersection.
+    pub const EMPTY: Grid = Grid {
+        lower_bounds: GridPoint::new(0, 0, 0),
+        sizes: GridVector::new(0, 0, 0),
+    };
+
     /// Constructs a [`Grid`] from coordinate lower bounds and sizes.
     ///
     /// For example, if on one axis the lower bound is 5 and the size is 10,
@@ -300,6 +310,29 @@ impl Grid {
         self.lower_bounds.map(FreeCoordinate::from) + self.sizes.map(FreeCoordinate::from) / 2.0
     }
 
+    /// Converts this grid to an [`Aab`] occupying the same volume.
+    ///
+    /// Since a [`Grid`]'s bounds are half-open intervals of cubes, the resulting
+    /// [`Aab`] spans exactly from [`Grid::lower_bounds`] to [`Grid::upper_bounds`].
+    /// This conversion is exact and may be reversed with either
+    /// [`Aab::round_up_to_grid`] or [`Aab::round_down_to_grid`]:
+    ///
+    /// ```
+    /// use all_is_cubes::math::Aab;
+    /// use all_is_cubes::space::Grid;
+    ///
+    /// let grid = Grid::new((0, 0, -2), (10, 3, 4));
+    /// assert_eq!(grid.to_aab(), Aab::new(0.0, 10.0, 0.0, 3.0, -2.0, 2.0));
+    /// assert_eq!(grid.to_aab().round_up_to_grid(), grid);
+    /// assert_eq!(grid.to_aab().round_down_to_grid(), grid);
+    /// ```
+    pub fn to_aab(self) -> Aab {
+        Aab::from_lower_upper(
+            self.lower_bounds().map(FreeCoordinate::from),
+            self.upper_bounds().map(FreeCoordinate::from),
+        )
+    }
+
     /// Iterate over all cubes.
     ///
     /// ```
@@ -363,7 +396,8 @@ impl Grid {
 
     /// Returns the intersection of two grids, or None if they have no cubes in common.
     ///
-    /// TODO: Precisely define the behavior on zero volume grids.
+    /// In particular, the result is [`None`] if either grid has zero volume.
+    /// See also [`Grid::intersection_or_empty`].
     ///
     /// ```
     /// use all_is_cubes::space::Grid;
@@ -394,6 +428,31 @@ impl Grid {
         Some(Grid::from_lower_upper(lower, upper))
     }
 
+    /// Returns the intersection of two grids, or [`Grid::EMPTY`] if they have no cubes
+    /// in common.
+    ///
+    /// This is equivalent to [`Grid::intersection`], but convenient when the result is
+    /// to be used as a region for operations such as [`Space::fill`], which do nothing
+    /// when given an empty region.
+    ///
+    /// ```
+    /// use all_is_cubes::space::Grid;
+    ///
+    /// let g1 = Grid::new([0, 0, 0], [2, 2, 2]);
+    /// assert_eq!(
+    ///     g1.intersection_or_empty(Grid::new([1, 0, 0], [2, 1, 2])),
+    ///     Grid::new([1, 0, 0], [1, 1, 2]));
+    /// assert_eq!(
+    ///     g1.intersection_or_empty(Grid::new([2, 0, 0], [2, 1, 2])),
+    ///     Grid::EMPTY);
+    /// ```
+    ///
+    /// [`Space::fill`]: super::Space::fill
+    #[must_use]
+    pub fn intersection_or_empty(self, other: Grid) -> Grid {
+        self.intersection(other).unwrap_or(Grid::EMPTY)
+    }
+
     /// Returns the smallest [`Grid`] which fully encloses the two inputs,
     /// or [`GridOverflowError`] if the volume of the result exceeds [`usize::MAX`].
     ///
@@ -456,6 +515,51 @@ impl Grid {
         }
     }
 
+    /// Returns `count` distinct random cubes contained by the grid, in no particular
+    /// order, or every cube of the grid if it has fewer than `count` cubes.
+    ///
+    /// Each subset of the grid of the returned size is equally likely.
+    ///
+    /// ```
+    /// use all_is_cubes::space::Grid;
+    /// use rand::SeedableRng;
+    /// let mut rng = &mut rand_xoshiro::Xoshiro256Plus::seed_from_u64(0);
+    ///
+    /// let grid = Grid::new([4, 4, 4], [6, 6, 6]);
+    /// let cubes = grid.random_cubes(rng, 10);
+    /// assert_eq!(cubes.len(), 10);
+    /// assert!(cubes.iter().all(|&cube| grid.contains_cube(cube)));
+    ///
+    /// assert_eq!(Grid::new([0, 0, 0], [2, 1, 1]).random_cubes(rng, 10).len(), 2);
+    /// ```
+    pub fn random_cubes(&self, rng: &mut impl rand::Rng, count: usize) -> Vec<GridPoint> {
+        let volume = self.volume();
+        let count = count.min(volume);
+        // Floyd's algorithm: sample `count` distinct indices in `count` steps.
+        let mut chosen = std::collections::HashSet::with_capacity(count);
+        let mut cubes = Vec::with_capacity(count);
+        for upper in (volume - count)..volume {
+            let candidate = rng.gen_range(0..=upper);
+            let index = if chosen.insert(candidate) {
+                candidate
+            } else {
+                chosen.insert(upper);
+                upper
+            };
+            cubes.push(self.cube_at_index(index));
+        }
+        cubes
+    }
+
+    /// Inverse of [`Self::index()`]. `index` must be less than [`Self::volume()`].
+    fn cube_at_index(&self, index: usize) -> GridPoint {
+        let [sx, sy, sz]: [usize; 3] = self.unsigned_size().map(|s| s as usize).into();
+        debug_assert!(index < sx * sy * sz);
+        self.lower_bounds
+            + Vector3::new(index / (sy * sz), index / sz % sy, index % sz)
+                .map(|c| c as GridCoordinate)
+    }
+
     /// Moves the grid to another location with unchanged size and orientation.
     ///
     /// ```
@@ -592,6 +696,41 @@ impl Grid {
         )
     }
 
+    /// As [`Grid::expand`], but each bound of the result is clamped to lie within
+    /// `bounds`, so that the result may be used with, e.g., [`Space::fill`] without
+    /// going out of bounds.
+    ///
+    /// If `self` lies partly or wholly outside `bounds`, it is clamped too; in the latter
+    /// case the result is a zero-volume grid on the surface of `bounds`.
+    ///
+    /// ```
+    /// use all_is_cubes::space::Grid;
+    /// use all_is_cubes::math::FaceMap;
+    ///
+    /// let bounds = Grid::from_lower_upper([0, 0, 0], [10, 10, 10]);
+    /// assert_eq!(
+    ///     Grid::from_lower_upper([1, 2, 3], [8, 9, 10])
+    ///         .expand_clamped(FaceMap::repeat(2), bounds),
+    ///     Grid::from_lower_upper([0, 0, 1], [10, 10, 10]),
+    /// );
+    /// ```
+    ///
+    /// [`Space::fill`]: crate::space::Space::fill
+    #[inline]
+    #[track_caller]
+    #[must_use]
+    pub fn expand_clamped(self, deltas: FaceMap<GridCoordinate>, bounds: Grid) -> Self {
+        let expanded = self.expand(deltas);
+        let clamp = |p: GridPoint| {
+            p.zip(bounds.lower_bounds(), GridCoordinate::max)
+                .zip(bounds.upper_bounds(), GridCoordinate::min)
+        };
+        Self::from_lower_upper(
+            clamp(expanded.lower_bounds()),
+            clamp(expanded.upper_bounds()),
+        )
+    }
+
     /// Returns a [`Grid`] which includes the volume between the given `face` rectangle
     /// of `self` and the same rectangle translated `thickness` cubes outward from it
     /// (inward if negative).
@@ -638,7 +777,8 @@ impl Grid {
         let axis = face.axis_number();
 
         let mut size = self.size();
-        size[axis] = thickness.max(-size[axis]).abs();
+        let thickness = thickness.max(-size[axis]);
+        size[axis] = thickness.abs();
 
         // Coordinate on the axis that the two boxes share
         let abutting_coordinate = if face.is_positive() {
@@ -661,6 +801,164 @@ impl Grid {
 
         Grid::checked_new(lower_bounds, size)
     }
+
+    /// Returns the layer of cubes `thickness` cubes thick, inside `self`, which is
+    /// adjacent to the given `face` of `self`; that is, the part of `self` which
+    /// would be occupied by a wall of that thickness.
+    ///
+    /// If `thickness` is greater than the size of `self`, it is clamped so that the
+    /// result is all of `self`. Returns [`None`] if `thickness` is negative.
+    ///
+    /// ```
+    /// use all_is_cubes::math::Face6;
+    /// use all_is_cubes::space::Grid;
+    ///
+    /// let room = Grid::from_lower_upper([0, 0, 0], [10, 5, 10]);
+    /// assert_eq!(
+    ///     room.face_slab(Face6::NY, 1),
+    ///     Some(Grid::from_lower_upper([0, 0, 0], [10, 1, 10])),
+    /// );
+    /// ```
+    #[inline]
+    pub fn face_slab(self, face: Face6, thickness: GridCoordinate) -> Option<Self> {
+        if thickness < 0 {
+            return None;
+        }
+        // Inward abut() cannot overflow since the result is within self.
+        self.abut(face, -thickness).ok()
+    }
+
+    /// Iterates over all cubes of `self` which are on its boundary, i.e. which are
+    /// adjacent to any of its faces. Each cube is produced once, in the same order as
+    /// [`Self::interior_iter()`].
+    ///
+    /// This skips over the interior rather than visiting it, so it takes time
+    /// proportional to the surface area rather than the volume.
+    ///
+    /// ```
+    /// use all_is_cubes::space::Grid;
+    ///
+    /// let grid = Grid::from_lower_upper([0, 0, 0], [3, 3, 3]);
+    /// assert_eq!(grid.boundary_iter().count(), 26); // all but the center cube
+    /// ```
+    pub fn boundary_iter(self) -> impl Iterator<Item = GridPoint> {
+        let lower = self.lower_bounds();
+        let upper = self.upper_bounds() - GridVector::new(1, 1, 1);
+        self.x_range().flat_map(move |x| {
+            (lower.y..=upper.y).flat_map(move |y| {
+                let on_xy_boundary = x == lower.x || x == upper.x || y == lower.y || y == upper.y;
+                // Cubes not on the x or y boundary are on the boundary only at the two
+                // ends of the z range.
+                let z_step = if on_xy_boundary {
+                    1
+                } else {
+                    (upper.z - lower.z).max(1) as usize
+                };
+                (lower.z..=upper.z)
+                    .step_by(z_step)
+                    .map(move |z| GridPoint::new(x, y, z))
+            })
+        })
+    }
+
+    /// Iterates over the one-cube-thick layer of `self` adjacent to the given `face`;
+    /// this is equivalent to `self.face_slab(face, 1).unwrap().interior_iter()`.
+    ///
+    /// ```
+    /// use all_is_cubes::math::Face6;
+    /// use all_is_cubes::space::Grid;
+    ///
+    /// let grid = Grid::from_lower_upper([0, 0, 0], [4, 3, 2]);
+    /// assert_eq!(grid.face_iter(Face6::PY).count(), 4 * 2);
+    /// assert!(grid.face_iter(Face6::PY).all(|cube| cube.y == 2));
+    /// ```
+    pub fn face_iter(self, face: Face6) -> GridIter {
+        self.face_slab(face, 1)
+            .expect("face_slab() with positive thickness cannot fail")
+            .interior_iter()
+    }
+
+    /// Iterates over the chunks of `self`: the intersections of `self` with the cubical
+    /// regions of side length `chunk_size` whose lower corners are multiples of
+    /// `chunk_size`. Chunks at the edges of `self` are clipped to it, so the chunks
+    /// exactly cover `self` without overlapping.
+    ///
+    /// Panics if `chunk_size` is not positive.
+    ///
+    /// ```
+    /// use all_is_cubes::space::Grid;
+    ///
+    /// let grid = Grid::from_lower_upper([0, 0, 0], [20, 10, 10]);
+    /// assert_eq!(
+    ///     grid.chunks(10).collect::<Vec<_>>(),
+    ///     vec![
+    ///         Grid::from_lower_upper([0, 0, 0], [10, 10, 10]),
+    ///         Grid::from_lower_upper([10, 0, 0], [20, 10, 10]),
+    ///     ],
+    /// );
+    /// ```
+    #[track_caller]
+    pub fn chunks(self, chunk_size: GridCoordinate) -> impl Iterator<Item = Grid> {
+        assert!(
+            chunk_size > 0,
+            "Grid::chunks: chunk_size must be > 0, not {}",
+            chunk_size
+        );
+        let chunk_indices = if self.is_empty() {
+            Grid::new([0, 0, 0], [0, 0, 0])
+        } else {
+            self.divide(chunk_size)
+        };
+        chunk_indices
+            .interior_iter()
+            .map(move |chunk_index| self.clip_chunk(chunk_index, chunk_size))
+    }
+
+    /// Returns the chunk of `self`, as defined by [`Self::chunks()`], which contains
+    /// `cube`, or [`None`] if `cube` is not in `self`.
+    ///
+    /// Panics if `chunk_size` is not positive.
+    ///
+    /// ```
+    /// use all_is_cubes::space::Grid;
+    ///
+    /// let grid = Grid::from_lower_upper([0, 0, 0], [15, 15, 15]);
+    /// assert_eq!(
+    ///     grid.chunk_containing([12, 3, 0], 10),
+    ///     Some(Grid::from_lower_upper([10, 0, 0], [15, 10, 10])),
+    /// );
+    /// assert_eq!(grid.chunk_containing([15, 3, 0], 10), None);
+    /// ```
+    #[track_caller]
+    pub fn chunk_containing(
+        self,
+        cube: impl Into<GridPoint>,
+        chunk_size: GridCoordinate,
+    ) -> Option<Grid> {
+        assert!(
+            chunk_size > 0,
+            "Grid::chunk_containing: chunk_size must be > 0, not {}",
+            chunk_size
+        );
+        let cube = cube.into();
+        if !self.contains_cube(cube) {
+            return None;
+        }
+        Some(self.clip_chunk(cube.map(|c| c.div_euclid(chunk_size)), chunk_size))
+    }
+
+    /// Returns the intersection of `self` with the chunk at `chunk_index`, which must
+    /// be nonempty.
+    fn clip_chunk(self, chunk_index: GridPoint, chunk_size: GridCoordinate) -> Grid {
+        let lower = self.lower_bounds();
+        let upper = self.upper_bounds();
+        Grid::from_lower_upper(
+            chunk_index.zip(lower, |i, l| i.saturating_mul(chunk_size).max(l)),
+            chunk_index.zip(upper, |i, u| {
+                i.saturating_add(1).saturating_mul(chunk_size).min(u)
+            }),
+        )
+    }
 }
 
 impl fmt::Debug for Grid {
@@ -675,10 +973,7 @@ impl fmt::Debug for Grid {
 
 impl From<Grid> for Aab {
     fn from(grid: Grid) -> Self {
-        Aab::from_lower_upper(
-            grid.lower_bounds().map(FreeCoordinate::from),
-            grid.upper_bounds().map(FreeCoordinate::from),
-        )
+        grid.to_aab()
     }
 }
 
@@ -861,6 +1156,92 @@ impl<V> GridArray<V> {
         self
     }
 
+    /// Rotates the array about the origin: the element at each cube `c` is moved to
+    /// `rotation.to_rotation_matrix().transform_cube(c)`.
+    ///
+    /// This produces the same result as [`GridArray::from_fn`] with a transformed grid
+    /// and the inverse transform applied to each cube, but computes the source of each
+    /// element by stepping through `self` instead of transforming every cube, and moves
+    /// the elements within the existing storage rather than allocating new storage for
+    /// them.
+    ///
+    /// ```
+    /// use all_is_cubes::math::GridRotation;
+    /// use all_is_cubes::space::{Grid, GridArray};
+    ///
+    /// let array = GridArray::from_elements(Grid::new([0, 0, 0], [3, 1, 1]), *b"abc").unwrap();
+    /// assert_eq!(
+    ///     array.rotate(GridRotation::RyXZ),
+    ///     GridArray::from_elements(Grid::new([0, -3, 0], [1, 3, 1]), *b"cba").unwrap(),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn rotate(mut self, rotation: GridRotation) -> Self {
+        let source_grid = self.grid;
+        let grid = source_grid
+            .transform(rotation.to_rotation_matrix())
+            .expect("rotated grid overflowed");
+        if grid.volume() == 0 {
+            return GridArray {
+                grid,
+                contents: self.contents,
+            };
+        }
+
+        // Offsets in `self.contents` of one step along each axis of the destination.
+        let backward = rotation.inverse().to_rotation_matrix();
+        let source_size = source_grid.size();
+        let source_strides = Vector3::new(source_size.y * source_size.z, source_size.z, 1);
+        let stride_of = |v: GridVector| -> isize {
+            (v.x * source_strides.x + v.y * source_strides.y + v.z * source_strides.z) as isize
+        };
+        let (x_stride, y_stride, z_stride) = (
+            stride_of(backward.x),
+            stride_of(backward.y),
+            stride_of(backward.z),
+        );
+        let start = source_grid
+            .index(backward.transform_cube(grid.lower_bounds()))
+            .expect("rotation start out of bounds") as isize;
+
+        // For each index in the rotated array, the index of the element in `self`
+        // which belongs there.
+        let mut source_of: Vec<usize> = Vec::with_capacity(self.contents.len());
+        let size = grid.size();
+        let mut x_index = start;
+        for _ in 0..size.x {
+            let mut y_index = x_index;
+            for _ in 0..size.y {
+                let mut z_index = y_index;
+                for _ in 0..size.z {
+                    source_of.push(z_index as usize);
+                    z_index += z_stride;
+                }
+                y_index += y_stride;
+            }
+            x_index += x_stride;
+        }
+
+        // Apply the permutation by following each of its cycles, swapping each element
+        // into place. `usize::MAX` marks the indices which have already been filled.
+        for cycle_start in 0..source_of.len() {
+            let mut index = cycle_start;
+            while source_of[index] != usize::MAX {
+                let source = std::mem::replace(&mut source_of[index], usize::MAX);
+                if source == cycle_start {
+                    break;
+                }
+                self.contents.swap(index, source);
+                index = source;
+            }
+        }
+
+        GridArray {
+            grid,
+            contents: self.contents,
+        }
+    }
+
     /// Apply `f` to each element of the array, producing a new array of the results.
     pub fn map<T, F>(self, f: F) -> GridArray<T>
     where
@@ -871,6 +1252,41 @@ impl<V> GridArray<V> {
             contents: self.contents.into_vec().into_iter().map(f).collect(),
         }
     }
+
+    /// Apply `f` to each pair of corresponding elements of this array and `other`,
+    /// producing a new array of the results.
+    ///
+    /// Returns [`None`] if the two arrays do not have equal [`Grid`]s.
+    ///
+    /// ```
+    /// use all_is_cubes::space::{Grid, GridArray};
+    ///
+    /// let grid = Grid::new([0, 0, 0], [3, 1, 1]);
+    /// let a = GridArray::from_elements(grid, [1, 2, 3]).unwrap();
+    /// let b = GridArray::from_elements(grid, [10, 20, 30]).unwrap();
+    /// assert_eq!(
+    ///     a.zip(&b, |x, &y| x + y),
+    ///     GridArray::from_elements(grid, [11, 22, 33]),
+    /// );
+    /// ```
+    pub fn zip<U, R, F>(self, other: &GridArray<U>, mut f: F) -> Option<GridArray<R>>
+    where
+        F: FnMut(V, &U) -> R,
+    {
+        if self.grid != other.grid {
+            return None;
+        }
+        Some(GridArray {
+            grid: self.grid,
+            contents: self
+                .contents
+                .into_vec()
+                .into_iter()
+                .zip(other.contents.iter())
+                .map(|(v, u)| f(v, u))
+                .collect(),
+        })
+    }
 }
 
 impl<P: Into<GridPoint>, V> std::ops::Index<P> for GridArray<V> {
@@ -946,6 +1362,94 @@ mod tests {
     use super::*;
     use indoc::indoc;
 
+    #[test]
+    fn chunks_clipped_at_edges() {
+        let grid = Grid::from_lower_upper([-3, 0, 0], [12, 5, 1]);
+        let chunks: Vec<Grid> = grid.chunks(5).collect();
+        assert_eq!(
+            chunks,
+            vec![
+                Grid::from_lower_upper([-3, 0, 0], [0, 5, 1]),
+                Grid::from_lower_upper([0, 0, 0], [5, 5, 1]),
+                Grid::from_lower_upper([5, 0, 0], [10, 5, 1]),
+                Grid::from_lower_upper([10, 0, 0], [12, 5, 1]),
+            ]
+        );
+
+        // The chunks exactly cover the grid, and agree with chunk_containing().
+        assert_eq!(
+            chunks.iter().map(|chunk| chunk.volume()).sum::<usize>(),
+            grid.volume()
+        );
+        for cube in grid.interior_iter() {
+            let chunk = grid.chunk_containing(cube, 5).unwrap();
+            assert!(chunk.contains_cube(cube));
+            assert!(chunks.contains(&chunk), "{chunk:?} not in chunks");
+        }
+        assert_eq!(grid.chunk_containing([12, 0, 0], 5), None);
+    }
+
+    #[test]
+    fn chunks_of_empty_grid() {
+        assert_eq!(Grid::new([5, 5, 5], [0, 3, 3]).chunks(4).count(), 0);
+    }
+
+    #[test]
+    fn cube_at_index_inverts_index() {
+        let grid = Grid::new([-1, 2, 3], [2, 3, 4]);
+        for (i, cube) in grid.interior_iter().enumerate() {
+            assert_eq!(grid.index(cube), Some(i));
+            assert_eq!(grid.cube_at_index(i), cube);
+        }
+    }
+
+    /// Sampled cubes are in bounds, distinct, and each cube is chosen about equally often.
+    #[test]
+    fn random_cubes_uniform() {
+        use rand::SeedableRng as _;
+        let mut rng = rand_xoshiro::Xoshiro256Plus::seed_from_u64(0);
+        let grid = Grid::new([-1, 0, 1], [2, 2, 2]);
+        let trials: i32 = 4000;
+        let mut single_counts = GridArray::from_fn(grid, |_| 0);
+        let mut multiple_counts = GridArray::from_fn(grid, |_| 0);
+        for _ in 0..trials {
+            single_counts[grid.random_cube(&mut rng).unwrap()] += 1;
+
+            let cubes = grid.random_cubes(&mut rng, 3);
+            assert_eq!(cubes.len(), 3);
+            for &cube in &cubes {
+                assert!(grid.contains_cube(cube), "{cube:?}");
+                multiple_counts[cube] += 1;
+            }
+            assert!(cubes[0] != cubes[1] && cubes[1] != cubes[2] && cubes[0] != cubes[2]);
+        }
+
+        // Expected counts, with ±15% tolerance (several standard deviations).
+        let expected_single = trials / 8;
+        let expected_multiple = trials * 3 / 8;
+        for cube in grid.interior_iter() {
+            let (single, multiple) = (single_counts[cube], multiple_counts[cube]);
+            assert!(
+                (single - expected_single).abs() < expected_single * 15 / 100,
+                "{cube:?} {single}"
+            );
+            assert!(
+                (multiple - expected_multiple).abs() < expected_multiple * 15 / 100,
+                "{cube:?} {multiple}"
+            );
+        }
+    }
+
+    #[test]
+    fn random_cubes_of_empty_grid() {
+        use rand::SeedableRng as _;
+        let mut rng = rand_xoshiro::Xoshiro256Plus::seed_from_u64(0);
+        assert_eq!(
+            Grid::new([1, 2, 3], [0, 9, 9]).random_cubes(&mut rng, 5),
+            vec![]
+        );
+    }
+
     #[test]
     fn zero_is_valid() {
         assert_eq!(
@@ -956,6 +1460,22 @@ mod tests {
         assert_eq!(Grid::new([1, 2, 3], [0, 1, 1]).volume(), 0,);
     }
 
+    #[test]
+    fn to_aab_round_trip() {
+        for grid in [
+            Grid::new([0, 0, 0], [1, 1, 1]),
+            Grid::new([-5, 3, 100], [7, 1, 2]),
+            Grid::new([1, 2, 3], [0, 1, 1]),
+            Grid::new([GridCoordinate::MIN, 0, 0], [1, 1, 1]),
+            Grid::new([GridCoordinate::MAX - 1, 0, 0], [1, 1, 1]),
+        ] {
+            let aab = grid.to_aab();
+            assert_eq!(aab, Aab::from(grid));
+            assert_eq!(aab.round_up_to_grid(), grid, "round up {:?}", grid);
+            assert_eq!(aab.round_down_to_grid(), grid, "round down {:?}", grid);
+        }
+    }
+
     #[test]
     fn for_block() {
         assert_eq!(Grid::for_block(1), Grid::new((0, 0, 0), (1, 1, 1)));
@@ -1033,6 +1553,120 @@ mod tests {
 
     // TODO: test and improve transform() on matrices with skew / other non-axis-swaps
 
+    #[test]
+    fn face_slab() {
+        let grid = Grid::from_lower_upper([10, 20, 30], [15, 25, 35]);
+        let expected = [
+            (Face6::NX, [10, 20, 30], [12, 25, 35]),
+            (Face6::NY, [10, 20, 30], [15, 22, 35]),
+            (Face6::NZ, [10, 20, 30], [15, 25, 32]),
+            (Face6::PX, [13, 20, 30], [15, 25, 35]),
+            (Face6::PY, [10, 23, 30], [15, 25, 35]),
+            (Face6::PZ, [10, 20, 33], [15, 25, 35]),
+        ];
+        for (face, lower, upper) in expected {
+            assert_eq!(
+                grid.face_slab(face, 2),
+                Some(Grid::from_lower_upper(lower, upper)),
+                "{face:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn face_slab_edge_cases() {
+        let grid = Grid::from_lower_upper([10, 20, 30], [15, 25, 35]);
+        assert_eq!(
+            grid.face_slab(Face6::PY, 0),
+            Some(Grid::from_lower_upper([10, 25, 30], [15, 25, 35]))
+        );
+        assert_eq!(grid.face_slab(Face6::PY, 100), Some(grid));
+        assert_eq!(grid.face_slab(Face6::NY, 100), Some(grid));
+        assert_eq!(grid.face_slab(Face6::PY, -1), None);
+    }
+
+    #[test]
+    fn expand_clamped_each_face() {
+        let bounds = Grid::from_lower_upper([0, 0, 0], [10, 10, 10]);
+        let grid = Grid::from_lower_upper([1, 1, 1], [9, 9, 9]);
+        for (face, expected) in [
+            (Face6::NX, Grid::from_lower_upper([0, 1, 1], [9, 9, 9])),
+            (Face6::NY, Grid::from_lower_upper([1, 0, 1], [9, 9, 9])),
+            (Face6::NZ, Grid::from_lower_upper([1, 1, 0], [9, 9, 9])),
+            (Face6::PX, Grid::from_lower_upper([1, 1, 1], [10, 9, 9])),
+            (Face6::PY, Grid::from_lower_upper([1, 1, 1], [9, 10, 9])),
+            (Face6::PZ, Grid::from_lower_upper([1, 1, 1], [9, 9, 10])),
+        ] {
+            let mut deltas = FaceMap::repeat(0);
+            deltas[face] = 5;
+            assert_eq!(grid.expand_clamped(deltas, bounds), expected, "{face:?}");
+        }
+    }
+
+    #[test]
+    fn expand_clamped_with_room() {
+        let bounds = Grid::from_lower_upper([0, 0, 0], [10, 10, 10]);
+        let grid = Grid::from_lower_upper([2, 3, 4], [5, 6, 7]);
+        let deltas = FaceMap::repeat(1);
+        assert_eq!(grid.expand_clamped(deltas, bounds), grid.expand(deltas));
+    }
+
+    #[test]
+    fn expand_clamped_outside_bounds() {
+        let bounds = Grid::from_lower_upper([0, 0, 0], [10, 10, 10]);
+        let grid = Grid::from_lower_upper([20, 1, 1], [30, 2, 2]);
+        assert_eq!(
+            grid.expand_clamped(FaceMap::repeat(1), bounds),
+            Grid::from_lower_upper([10, 0, 0], [10, 3, 3])
+        );
+    }
+
+    #[test]
+    fn boundary_iter() {
+        let grid = Grid::from_lower_upper([0, 0, 0], [4, 3, 5]);
+        let interior = grid.expand(FaceMap::repeat(-1));
+        let shell: Vec<GridPoint> = grid.boundary_iter().collect();
+        assert_eq!(shell.len(), grid.volume() - interior.volume());
+        assert!(shell.iter().all(|&cube| !interior.contains_cube(cube)));
+        // Same order as interior_iter()
+        assert_eq!(
+            shell,
+            grid.interior_iter()
+                .filter(|&cube| !interior.contains_cube(cube))
+                .collect::<Vec<_>>()
+        );
+
+        // Thin grids are entirely shell.
+        let flat = Grid::from_lower_upper([0, 0, 0], [4, 1, 4]);
+        assert_eq!(flat.boundary_iter().count(), flat.volume());
+        let thin = Grid::from_lower_upper([0, 0, 0], [3, 3, 1]);
+        assert_eq!(thin.boundary_iter().count(), thin.volume());
+
+        assert_eq!(Grid::new([0, 0, 0], [0, 3, 3]).boundary_iter().count(), 0);
+    }
+
+    #[test]
+    fn face_iter() {
+        let grid = Grid::from_lower_upper([-1, 0, 2], [4, 3, 9]);
+        let interior = grid.expand(FaceMap::repeat(-1));
+        for face in Face6::ALL {
+            let cubes: Vec<GridPoint> = grid.face_iter(face).collect();
+            let size = grid.size();
+            let area = match face.axis_number() {
+                0 => size.y * size.z,
+                1 => size.x * size.z,
+                _ => size.x * size.y,
+            };
+            assert_eq!(cubes.len(), area as usize, "{face:?}");
+            assert!(
+                cubes
+                    .iter()
+                    .all(|&cube| grid.contains_cube(cube) && !interior.contains_cube(cube)),
+                "{face:?}"
+            );
+        }
+    }
+
     /// Test `Debug` formatting. Note this should be similar to the [`Aab`] formatting.
     #[test]
     fn debug() {
@@ -1106,6 +1740,43 @@ mod tests {
         assert_eq!(GridArray::from_elements(grid, vec![10i32, 11, 12]), None);
     }
 
+    #[test]
+    fn array_map() {
+        let grid = Grid::new([10, 0, 0], [2, 2, 1]);
+        assert_eq!(
+            GridArray::from_fn(grid, |p| p.x + p.y).map(|v| v * 10),
+            GridArray::from_elements(grid, vec![100, 110, 110, 120]).unwrap(),
+        );
+    }
+
+    #[test]
+    fn array_zip() {
+        let grid = Grid::new([10, 0, 0], [4, 1, 1]);
+        let a = GridArray::from_fn(grid, |p| p.x);
+        let b = GridArray::from_elements(grid, *b"abcd").unwrap();
+        assert_eq!(
+            a.clone().zip(&b, |x, &c| (x, c)),
+            GridArray::from_elements(grid, vec![(10, b'a'), (11, b'b'), (12, b'c'), (13, b'd')]),
+        );
+
+        let mismatched = GridArray::from_elements(grid.translate([1, 0, 0]), *b"abcd").unwrap();
+        assert_eq!(a.zip(&mismatched, |x, &c| (x, c)), None);
+    }
+
+    #[test]
+    fn array_rotate_matches_transform() {
+        let grid = Grid::new([-1, 2, 5], [2, 3, 4]);
+        let array = GridArray::from_fn(grid, |p| p);
+        for rotation in GridRotation::ALL {
+            let matrix = rotation.to_rotation_matrix();
+            let inverse = rotation.inverse().to_rotation_matrix();
+            let expected = GridArray::from_fn(grid.transform(matrix).unwrap(), |cube| {
+                array[inverse.transform_cube(cube)]
+            });
+            assert_eq!(array.clone().rotate(rotation), expected, "{rotation:?}");
+        }
+    }
+
     #[test]
     fn array_from_y_flipped() {
         let array = GridArray::from_y_flipped_array([
@@ -127,6 +127,10 @@ impl PackedLight {
         )
     }
 
+    /// Packs this light into RGBA texel form for use by shaders.
+    ///
+    /// The alpha component is the `LightStatus`, so shaders can distinguish a cube
+    /// which is inside an opaque block from one which is merely dark.
     #[inline]
     #[doc(hidden)] // TODO: used by all_is_cubes_gpu; but it should be doable equivalently using public functions
     pub fn as_texel(self) -> [u8; 4] {
@@ -308,6 +312,36 @@ impl LightUpdateQueue {
             .unwrap_or(0)
     }
 
+    /// Removes and returns all requests for cubes within `region`, in no particular
+    /// order.
+    ///
+    /// This takes time proportional to the smaller of the region's volume and the
+    /// length of the queue, rather than examining every entry.
+    pub fn take_in_region(&mut self, region: Grid) -> Vec<LightUpdateRequest> {
+        let mut taken = Vec::new();
+        if region.volume() < self.table.len() {
+            for cube in region.interior_iter() {
+                if let Some(priority) = self.table.remove(&cube) {
+                    taken.push(LightUpdateRequest { priority, cube });
+                }
+            }
+        } else {
+            self.table.retain(|&cube, &mut priority| {
+                if region.contains_cube(cube) {
+                    taken.push(LightUpdateRequest { priority, cube });
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+        for request in &taken {
+            let removed = self.queue.remove(request);
+            debug_assert!(removed);
+        }
+        taken
+    }
+
     pub fn clear(&mut self) {
         self.queue.clear();
         self.table.clear();
@@ -436,5 +470,37 @@ mod tests {
         assert_eq!(queue.pop(), None);
     }
 
+    #[test]
+    fn queue_take_in_region() {
+        fn r(cube: [GridCoordinate; 3], priority: PackedLightScalar) -> LightUpdateRequest {
+            LightUpdateRequest {
+                cube: GridPoint::from(cube),
+                priority,
+            }
+        }
+
+        // Check both the small-region and large-region strategies.
+        for region in [
+            Grid::new([0, 0, 0], [2, 1, 1]),
+            Grid::new([-10, -10, -10], [12, 11, 11]),
+        ] {
+            let mut queue = LightUpdateQueue::new();
+            queue.insert(r([0, 0, 0], 1));
+            queue.insert(r([1, 0, 0], 50));
+            queue.insert(r([5, 0, 0], 100));
+            queue.insert(r([0, 5, 0], 10));
+            queue.insert(r([0, 0, 5], 20));
+
+            let mut taken = queue.take_in_region(region);
+            taken.sort();
+            assert_eq!(taken, vec![r([0, 0, 0], 1), r([1, 0, 0], 50)]);
+            assert_eq!(queue.len(), 3);
+            assert_eq!(queue.pop(), Some(r([5, 0, 0], 100)));
+            assert_eq!(queue.pop(), Some(r([0, 0, 5], 20)));
+            assert_eq!(queue.pop(), Some(r([0, 5, 0], 10)));
+            assert_eq!(queue.pop(), None);
+        }
+    }
+
     // TODO: Test of queue priority updates
 }
@@ -8,8 +8,10 @@ use pretty_assertions::assert_eq;
 use super::{data::LightStatus, LightUpdatesInfo, PackedLight};
 use crate::block::{AnimationHint, Block, AIR};
 use crate::listen::{Listener, Sink};
-use crate::math::{FaceMap, GridPoint, Rgb, Rgba};
-use crate::space::{Grid, LightPhysics, Space, SpaceChange, SpacePhysics};
+use crate::math::{Face7, FaceMap, GridPoint, Rgb, Rgba};
+use crate::space::{
+    DirectionalLight, Grid, LightPhysics, LightRaysBuilder, Sky, Space, SpaceChange, SpacePhysics,
+};
 use crate::time::Tick;
 
 #[test]
@@ -22,7 +24,7 @@ fn initial_lighting_value() {
 fn out_of_bounds_lighting_value() {
     let space = Space::empty_positive(1, 1, 1);
     assert_eq!(
-        PackedLight::from(space.physics().sky_color),
+        PackedLight::from(space.physics().sky.overall_color()),
         space.get_lighting((-1, 0, 0))
     );
 }
@@ -31,10 +33,10 @@ fn out_of_bounds_lighting_value() {
 fn step() {
     let mut space = Space::empty_positive(3, 1, 1);
     space.set_physics(SpacePhysics {
-        sky_color: Rgb::new(1.0, 0.0, 0.0),
+        sky: Sky::Uniform(Rgb::new(1.0, 0.0, 0.0)),
         ..SpacePhysics::default()
     });
-    let sky_light = PackedLight::from(space.physics().sky_color);
+    let sky_light = PackedLight::from(space.physics().sky.overall_color());
 
     space.set((0, 0, 0), Rgb::ONE).unwrap();
     // Not changed yet... except for the now-opaque block
@@ -93,10 +95,24 @@ fn set_cube_opaque_notification() {
     );
 }
 
+/// The texel form of the light data, which is what shaders see, distinguishes
+/// opaque cubes from lit empty ones by its alpha component.
+#[test]
+fn texel_alpha_distinguishes_opaque() {
+    let mut space = Space::empty_positive(2, 1, 1);
+    space.set([0, 0, 0], Block::from(Rgb::ONE)).unwrap();
+    space.evaluate_light(0, |_| {});
+
+    let opaque_texel = space.get_lighting([0, 0, 0]).as_texel();
+    let empty_texel = space.get_lighting([1, 0, 0]).as_texel();
+    assert_eq!(opaque_texel[3], LightStatus::Opaque as u8);
+    assert_eq!(empty_texel[3], LightStatus::Visible as u8);
+}
+
 fn light_source_test_space(block: Block) -> Space {
     let mut space = Space::empty_positive(3, 3, 3);
     space.set_physics(SpacePhysics {
-        sky_color: Rgb::ZERO,
+        sky: Sky::Uniform(Rgb::ZERO),
         ..Default::default()
     });
     space.set([1, 1, 1], block).unwrap();
@@ -230,3 +246,229 @@ fn disabled_lighting_does_not_update() {
 // TODO: test sky lighting propagation onto blocks after quiescing
 
 // TODO: test a single semi-transparent block will receive and diffuse light
+
+#[test]
+fn directional_sky_lights_from_px() {
+    let warm = rgb_const!(1.0, 0.5, 0.0);
+    let cool = rgb_const!(0.0, 0.0, 1.0);
+    let mut space = Space::builder(Grid::new([0, 0, 0], [4, 3, 3]))
+        .sky(Sky::Faces(FaceMap::repeat(cool).with(Face7::PX, warm)))
+        .build_empty();
+    // Fill with opaque blocks except for a tunnel open only to the +X side.
+    space
+        .fill_uniform(space.grid(), Block::from(Rgb::ONE))
+        .unwrap();
+    space
+        .fill_uniform(Grid::new([1, 1, 1], [3, 1, 1]), &AIR)
+        .unwrap();
+    space.evaluate_light(0, |_| {});
+
+    // The surface facing PX at the dead end of the tunnel, and the exit,
+    // both see only the PX sky color.
+    for cube in [GridPoint::new(1, 1, 1), GridPoint::new(4, 1, 1)] {
+        let light = space.get_lighting(cube).value();
+        let [red, green, blue] = [light.red(), light.green(), light.blue()].map(f32::from);
+        assert_eq!(blue, 0.0, "{cube:?} {light:?}");
+        assert!(red > 0.0, "{cube:?} {light:?}");
+        assert!((green / red - 0.5).abs() < 0.05, "{cube:?} {light:?}");
+    }
+    assert_eq!(space.get_lighting([4, 1, 1]), PackedLight::from(warm));
+    assert_eq!(space.get_lighting([-1, 1, 1]), PackedLight::from(cool));
+}
+
+/// A floor shadowed from the sun by a block above it receives less light than the
+/// unshadowed floor beside it.
+#[test]
+fn sun_casts_shadow() {
+    let mut space = Space::builder(Grid::new([0, 0, 0], [5, 5, 5]))
+        .sky_color(Rgb::ZERO)
+        .light_physics(
+            LightPhysics::rays()
+                .sun(Some(DirectionalLight::new([0.0, 1.0, 0.0], Rgb::ONE)))
+                .build(),
+        )
+        .build_empty();
+    let block = Block::from(Rgb::ONE);
+    space
+        .fill_uniform(Grid::new([0, 0, 0], [5, 1, 5]), &block)
+        .unwrap();
+    space.set([1, 3, 2], &block).unwrap();
+    space.evaluate_light(0, |_| {});
+
+    let shadowed = space.get_lighting([1, 1, 2]).value();
+    let unshadowed = space.get_lighting([3, 1, 2]).value();
+    assert!(
+        shadowed.luminance() < unshadowed.luminance(),
+        "{shadowed:?} should be less than {unshadowed:?}"
+    );
+    // With a black sky, the unshadowed floor is lit only by the sun.
+    assert!(unshadowed.luminance() > 0.9, "{unshadowed:?}");
+}
+
+/// Placing a block between a lit floor and the sun darkens the floor, even if the
+/// block is not adjacent to it.
+#[test]
+fn sun_shadow_updates_when_block_placed() {
+    let mut space = Space::builder(Grid::new([0, 0, 0], [5, 7, 5]))
+        .sky_color(Rgb::ZERO)
+        .light_physics(
+            LightPhysics::rays()
+                .sun(Some(DirectionalLight::new([0.0, 1.0, 0.0], Rgb::ONE)))
+                .build(),
+        )
+        .build_empty();
+    let block = Block::from(Rgb::ONE);
+    space
+        .fill_uniform(Grid::new([0, 0, 0], [5, 1, 5]), &block)
+        .unwrap();
+    space.evaluate_light(0, |_| {});
+    let before = space.get_lighting([2, 1, 2]).value();
+
+    space.set([2, 5, 2], &block).unwrap();
+    space.evaluate_light(0, |_| {});
+    let after = space.get_lighting([2, 1, 2]).value();
+    assert!(
+        after.luminance() < before.luminance(),
+        "{after:?} should be less than {before:?}"
+    );
+}
+
+/// As [`sun_shadow_updates_when_block_placed`], but with the sun at an angle, so that
+/// the shadow falls on cubes which are not in line with the block along any axis.
+/// A single diffuse ray keeps the update from reaching the floor indirectly, through
+/// the dependencies of the block's neighbors.
+#[test]
+fn oblique_sun_shadow_updates_when_block_placed() {
+    let mut space = Space::builder(Grid::new([0, 0, 0], [10, 8, 3]))
+        .sky_color(Rgb::ZERO)
+        .light_physics(
+            LightPhysics::rays()
+                .ray_count(1)
+                .sun(Some(DirectionalLight::new([1.0, 2.0, 0.0], Rgb::ONE)))
+                .build(),
+        )
+        .build_empty();
+    let block = Block::from(Rgb::ONE);
+    space
+        .fill_uniform(Grid::new([0, 0, 0], [10, 1, 3]), &block)
+        .unwrap();
+    space.evaluate_light(0, |_| {});
+    let before = space.get_lighting([2, 1, 1]).value();
+
+    // The ray from the center of [2, 1, 1] toward the sun passes through the center
+    // of this cube.
+    space.set([4, 5, 1], &block).unwrap();
+    space.evaluate_light(0, |_| {});
+    let after = space.get_lighting([2, 1, 1]).value();
+    assert!(
+        after.luminance() < before.luminance(),
+        "{after:?} should be less than {before:?}"
+    );
+}
+
+#[test]
+fn fewer_rays_approximate_more_rays() {
+    fn light_with_ray_count(ray_count: u16) -> (PackedLight, usize) {
+        let mut space = Space::empty_positive(3, 2, 1);
+        space.set_physics(SpacePhysics {
+            light: LightPhysics::rays().ray_count(ray_count).build(),
+            ..SpacePhysics::default()
+        });
+        space.set([0, 0, 0], Rgb::ONE).unwrap();
+        space.set([2, 0, 0], Rgb::ONE).unwrap();
+        let (light, _, cost, ()) = space.compute_lighting::<()>(GridPoint::new(1, 0, 0));
+        (light, cost)
+    }
+
+    let (few_light, few_cost) = light_with_ray_count(4);
+    let (many_light, many_cost) = light_with_ray_count(LightRaysBuilder::MAX_RAY_COUNT);
+    assert!(few_cost < many_cost, "{few_cost} < {many_cost}");
+    // The fewer-rays result is noisier, but should still be in the same ballpark.
+    let few = few_light.value().luminance();
+    let many = many_light.value().luminance();
+    assert!(many > 0.0);
+    assert!(few > many * 0.5 && few < many * 2.0, "{few} vs. {many}");
+}
+
+/// [`Space::fill`] should not queue light updates for the interior of a solid fill.
+#[test]
+fn fill_queues_fewer_light_updates() {
+    let outer = Grid::new([0, 0, 0], [18, 18, 18]);
+    let region = Grid::new([1, 1, 1], [16, 16, 16]);
+    let block = Block::from(Rgb::ONE);
+
+    let mut per_cube = Space::empty(outer);
+    for cube in region.interior_iter() {
+        per_cube.set(cube, &block).unwrap();
+    }
+    let mut filled = Space::empty(outer);
+    filled.fill_uniform(region, &block).unwrap();
+
+    // The only cubes needing updates are the air cubes adjacent to the fill.
+    let shell_count = 6 * 16 * 16;
+    assert_eq!(filled.light_update_queue.len(), shell_count);
+    assert!(
+        per_cube.light_update_queue.len() > shell_count + 3000,
+        "{}",
+        per_cube.light_update_queue.len()
+    );
+
+    // Both must produce the same final lighting.
+    per_cube.evaluate_light(0, |_| {});
+    filled.evaluate_light(0, |_| {});
+    for cube in outer.interior_iter() {
+        assert_eq!(
+            per_cube.get_lighting(cube),
+            filled.get_lighting(cube),
+            "{cube:?}"
+        );
+    }
+}
+
+/// Relighting only an enclosed room should give the same result there as relighting
+/// everything, and leave the rest of the queue intact for later.
+#[test]
+fn evaluate_light_region_matches_full_evaluation() {
+    let room = Grid::new([1, 1, 1], [4, 4, 4]);
+    let lamp = Block::builder()
+        .light_emission(Rgb::new(1.0, 0.5, 0.25))
+        .color(Rgba::new(1.0, 1.0, 1.0, 0.5))
+        .build();
+    let make_space = || {
+        let mut space = Space::empty(Grid::new([0, 0, 0], [10, 6, 6]));
+        let wall = Block::from(Rgb::ONE);
+        for cube in room.expand(FaceMap::repeat(1)).boundary_iter() {
+            space.set(cube, &wall).unwrap();
+        }
+        space.evaluate_light(0, |_| {});
+        // Edits both inside and outside the room.
+        space.set([2, 2, 2], &lamp).unwrap();
+        space.set([8, 1, 1], &wall).unwrap();
+        space
+    };
+
+    let mut full = make_space();
+    full.evaluate_light(0, |_| {});
+
+    let mut partial = make_space();
+    assert!(partial.evaluate_light_region(room, 0, |_| {}) > 0);
+    for cube in room.interior_iter() {
+        assert_eq!(
+            partial.get_lighting(cube),
+            full.get_lighting(cube),
+            "{cube:?}"
+        );
+    }
+    // The update outside the room is still pending.
+    assert!(partial.light_update_queue.len() > 0);
+    assert_eq!(partial.evaluate_light_region(room, 0, |_| {}), 0);
+
+    partial.evaluate_light(0, |_| {});
+    for cube in full.grid().interior_iter() {
+        assert_eq!(
+            partial.get_lighting(cube),
+            full.get_lighting(cube),
+            "{cube:?}"
+        );
+    }
+}
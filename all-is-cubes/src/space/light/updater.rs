@@ -6,17 +6,23 @@
 
 use std::cmp::Ordering;
 use std::fmt;
+use std::mem;
 
-use cgmath::{EuclideanSpace as _, InnerSpace as _, Point3, Vector3};
+use cgmath::{EuclideanSpace as _, InnerSpace as _, Point3, Vector3, Zero as _};
 use once_cell::sync::Lazy;
 
 use super::debug::LightComputeOutput;
-use super::LightUpdateRequest;
+use super::{LightUpdateQueue, LightUpdateRequest};
 use crate::block::EvaluatedBlock;
-use crate::math::{Face6, Face7, FaceMap, FreeCoordinate, Geometry, GridPoint, NotNan, Rgb};
+use crate::math::{
+    cube_to_midpoint, Face6, Face7, FaceMap, FreeCoordinate, Geometry, GridPoint, NotNan, Rgb,
+};
 use crate::raycast::{Ray, RaycastStep};
 use crate::space::light::LightUpdateRayInfo;
-use crate::space::{Grid, LightPhysics, PackedLight, PackedLightScalar, Space, SpaceChange};
+use crate::space::{
+    DirectionalLight, Grid, GridArray, LightPhysics, PackedLight, PackedLightScalar, Space,
+    SpaceChange,
+};
 use crate::util::{CustomFormat, StatusText};
 
 /// This parameter determines to what degree absorption of light due to a block surface's
@@ -28,7 +34,7 @@ const SURFACE_ABSORPTION: f32 = 0.75;
 
 const RAY_DIRECTION_STEP: isize = 5;
 const RAY_CUBE_EDGE: usize = (RAY_DIRECTION_STEP as usize) * 2 + 1;
-const ALL_RAYS_COUNT: usize = RAY_CUBE_EDGE.pow(3) - (RAY_CUBE_EDGE - 2).pow(3);
+pub(crate) const ALL_RAYS_COUNT: usize = RAY_CUBE_EDGE.pow(3) - (RAY_CUBE_EDGE - 2).pow(3);
 
 /// Limit on light computation per one [`Space::update_lighting_from_queue`] call.
 ///
@@ -46,6 +52,9 @@ struct LightRayData {
     face_cosines: FaceMap<f32>,
 }
 
+/// All the rays we might cast, ordered so that any prefix of the list is spread out
+/// over the sphere of directions, so that [`LightPhysics::Rays::ray_count`] may select
+/// a prefix.
 // TODO: Make multiple ray patterns that suit the maximum_distance parameter.
 static LIGHT_RAYS: Lazy<[LightRayData; ALL_RAYS_COUNT]> = Lazy::new(|| {
     let mut rays: Vec<LightRayData> = Vec::new();
@@ -75,11 +84,129 @@ static LIGHT_RAYS: Lazy<[LightRayData; ALL_RAYS_COUNT]> = Lazy::new(|| {
             }
         }
     }
-    rays.try_into().unwrap()
+
+    // Greedily reorder the rays so that each one is the one farthest from
+    // (having the least maximum cosine with) all the previous ones, starting from
+    // straight up, the most likely direction of the sky.
+    let mut ordered: Vec<LightRayData> = Vec::with_capacity(rays.len());
+    let first = rays
+        .iter()
+        .position(|r| r.ray.direction == Vector3::unit_y())
+        .unwrap();
+    ordered.push(rays.swap_remove(first));
+    let mut max_cosines: Vec<FreeCoordinate> = rays
+        .iter()
+        .map(|r| r.ray.direction.dot(Vector3::unit_y()))
+        .collect();
+    while !rays.is_empty() {
+        let (next, _) = max_cosines
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .unwrap();
+        let chosen = rays.swap_remove(next);
+        max_cosines.swap_remove(next);
+        for (r, max_cosine) in rays.iter().zip(max_cosines.iter_mut()) {
+            *max_cosine = max_cosine.max(r.ray.direction.dot(chosen.ray.direction));
+        }
+        ordered.push(chosen);
+    }
+
+    ordered.try_into().unwrap()
 });
 
 /// Methods on Space that specifically implement the lighting algorithm.
 impl Space {
+    /// Update lighting state for a block having been placed at `position`.
+    ///
+    /// Neighbors for which `skip_neighbor` returns true are not queued for updates.
+    pub(in crate::space) fn light_side_effects_of_set(
+        &mut self,
+        position: GridPoint,
+        contents_index: usize,
+        skip_neighbor: impl Fn(GridPoint) -> bool,
+    ) {
+        if self.physics.light == LightPhysics::None {
+            return;
+        }
+
+        let evaluated = &self.block_data[self.contents.get(contents_index) as usize].evaluated;
+        if opaque_for_light_computation(evaluated) {
+            // Since we already have the information, immediately update light value
+            // to zero rather than putting it in the queue.
+            // (It would be mostly okay to skip doing this entirely, but doing it gives
+            // more determinism, and the old value could be temporarily revealed when
+            // the block is removed.)
+            self.lighting[contents_index] = PackedLight::OPAQUE;
+            self.notifier.notify(SpaceChange::Lighting(position));
+        } else {
+            self.light_needs_update(position, PackedLightScalar::MAX);
+        }
+        for face in Face6::ALL {
+            let neighbor = position + face.normal_vector();
+            // Skip neighbor light updates in the definitely-black-inside case.
+            if !skip_neighbor(neighbor) && !self.get_evaluated(neighbor).opaque {
+                self.light_needs_update(neighbor, PackedLightScalar::MAX);
+            }
+        }
+
+        // The cubes in this cube's shadow from the sun need updating too, however far
+        // away they are (within the distance the sun rays are traced).
+        if let LightPhysics::Rays {
+            maximum_distance,
+            sun: Some(sun),
+            ..
+        } = self.physics.light
+        {
+            let direction = sun.direction.map(NotNan::into_inner);
+            if direction != Vector3::zero() {
+                let ray = Ray {
+                    origin: cube_to_midpoint(position),
+                    direction: -direction.normalize(),
+                };
+                for hit in ray.cast().within_grid(self.grid()) {
+                    if hit.t_distance() > FreeCoordinate::from(maximum_distance) {
+                        break;
+                    }
+                    let shadowed = hit.cube_ahead();
+                    if shadowed == position {
+                        continue;
+                    }
+                    if self.get_evaluated(shadowed).opaque {
+                        // Cubes beyond this one are shadowed by it regardless.
+                        break;
+                    }
+                    self.light_needs_update(shadowed, PackedLightScalar::MAX);
+                }
+            }
+        }
+    }
+
+    /// Equivalent to [`Space::light_side_effects_of_set`] for each of `changed_cubes`,
+    /// which must all be within `region`, except that neighbors which are themselves
+    /// in `changed_cubes` are not queued, since they are handled on their own.
+    /// Thus, a large fill only queues its boundary and its non-opaque interior.
+    pub(in crate::space) fn light_side_effects_of_fill(
+        &mut self,
+        region: Grid,
+        changed_cubes: &[GridPoint],
+    ) {
+        if self.physics.light == LightPhysics::None || changed_cubes.is_empty() {
+            return;
+        }
+
+        let mut is_changed = GridArray::from_fn(region, |_| false);
+        for &cube in changed_cubes {
+            is_changed[cube] = true;
+        }
+        for &cube in changed_cubes {
+            let contents_index = self.grid().index(cube).unwrap();
+            self.light_side_effects_of_set(cube, contents_index, |neighbor| {
+                is_changed.get(neighbor).copied().unwrap_or(false)
+            });
+        }
+    }
+
     pub(crate) fn light_needs_update(&mut self, cube: GridPoint, priority: PackedLightScalar) {
         if self.physics.light == LightPhysics::None {
             return;
@@ -122,6 +249,73 @@ impl Space {
         }
     }
 
+    /// Do some lighting updates, like [`Space::update_lighting_from_queue`], but only
+    /// for queued cubes within `region` whose priority exceeds `epsilon`.
+    ///
+    /// Requests for cubes outside `region` are left in the queue, as are any updates
+    /// to cubes outside `region` which become necessary due to changes inside it.
+    /// Returns whether there is no more work to do within `region`.
+    pub(crate) fn update_lighting_in_region(
+        &mut self,
+        region: Grid,
+        epsilon: PackedLightScalar,
+    ) -> (LightUpdatesInfo, bool) {
+        let mut light_update_count: usize = 0;
+        self.last_light_updates.clear();
+        let mut max_difference: PackedLightScalar = 0;
+        let mut cost = 0;
+        let mut finished = true;
+
+        if self.physics.light != LightPhysics::None {
+            // Work from a queue holding only the requests within the region, so that
+            // those outside it need not be examined. Updates add further requests to
+            // this queue; any of those outside the region are moved aside as they come
+            // up, which keeps the in-region updates in the same order as they would be
+            // done by update_lighting_from_queue().
+            let mut outside = mem::replace(&mut self.light_update_queue, LightUpdateQueue::new());
+            for request in outside.take_in_region(region) {
+                self.light_update_queue.insert(request);
+            }
+
+            while let Some(request) = self.light_update_queue.pop() {
+                if !region.contains_cube(request.cube) {
+                    outside.insert(request);
+                    continue;
+                }
+                if request.priority <= epsilon {
+                    // Everything remaining in the region is of equal or lower priority.
+                    outside.insert(request);
+                    break;
+                }
+                light_update_count += 1;
+                let (difference, cube_cost) = self.update_lighting_now_on(request.cube);
+                max_difference = max_difference.max(difference);
+                cost += cube_cost;
+                if cost >= MAXIMUM_LIGHT_COMPUTATION_COST {
+                    finished = false;
+                    break;
+                }
+            }
+
+            // Put back what we did not get to. If a cube was queued in both, the queue
+            // keeps whichever priority is higher.
+            let mut remaining = mem::replace(&mut self.light_update_queue, outside);
+            while let Some(request) = remaining.pop() {
+                self.light_update_queue.insert(request);
+            }
+        }
+
+        (
+            LightUpdatesInfo {
+                update_count: light_update_count,
+                max_update_difference: max_difference,
+                queue_count: self.light_update_queue.len(),
+                max_queue_priority: self.light_update_queue.peek_priority(),
+            },
+            finished,
+        )
+    }
+
     #[inline]
     fn update_lighting_now_on(&mut self, cube: GridPoint) -> (PackedLightScalar, usize) {
         let (new_light_value, dependencies, mut cost, ()) = self.compute_lighting(cube);
@@ -161,11 +355,21 @@ impl Space {
     where
         D: LightComputeOutput,
     {
-        let maximum_distance = match self.physics.light {
+        let (maximum_distance, rays, reflectance, sun) = match self.physics.light {
             LightPhysics::None => {
                 panic!("Light is disabled; should not reach here");
             }
-            LightPhysics::Rays { maximum_distance } => FreeCoordinate::from(maximum_distance),
+            LightPhysics::Rays {
+                maximum_distance,
+                ray_count,
+                reflectance,
+                sun,
+            } => (
+                FreeCoordinate::from(maximum_distance),
+                &LIGHT_RAYS[..usize::from(ray_count).min(ALL_RAYS_COUNT)],
+                reflectance.into_inner(),
+                sun,
+            ),
         };
 
         let mut cube_buffer = LightBuffer::new();
@@ -188,7 +392,7 @@ impl Space {
             let direction_weights = directions_to_seek_light(ev_neighbors);
 
             // TODO: Choose a ray pattern that suits the maximum_distance.
-            for &LightRayData { ray, face_cosines } in &LIGHT_RAYS[..] {
+            for &LightRayData { ray, face_cosines } in rays {
                 // TODO: Theoretically we should weight light rays by the cosine but that has caused poor behavior in the past.
                 let ray_weight_by_faces = face_cosines
                     .zip(direction_weights, |_face, ray_cosine, reflects| {
@@ -210,13 +414,29 @@ impl Space {
                         // indoor spaces.
                         break 'raycast;
                     }
-                    cube_buffer.traverse::<D>(&mut ray_state, &mut info_rays, self, hit);
+                    cube_buffer.traverse::<D>(
+                        &mut ray_state,
+                        &mut info_rays,
+                        self,
+                        hit,
+                        reflectance,
+                    );
                     if ray_state.alpha.partial_cmp(&0.0) != Some(Ordering::Greater) {
                         break;
                     }
                 }
                 cube_buffer.end_of_ray(&mut ray_state, self);
             }
+
+            if let Some(sun) = sun {
+                cube_buffer.add_directional_light(
+                    self,
+                    cube,
+                    sun,
+                    direction_weights,
+                    maximum_distance,
+                );
+            }
         }
 
         let new_light_value = cube_buffer.finish(ev_origin.opaque);
@@ -249,7 +469,7 @@ impl Space {
                     let index = grid.index(cube).unwrap();
 
                     let this_cube_evaluated =
-                        &self.block_data[self.contents[index] as usize].evaluated;
+                        &self.block_data[self.contents.get(index) as usize].evaluated;
                     self.lighting[index] = if opaque_for_light_computation(this_cube_evaluated) {
                         covered = true;
                         PackedLight::OPAQUE
@@ -271,7 +491,7 @@ impl Space {
                             if covered {
                                 PackedLight::ZERO
                             } else {
-                                self.packed_sky_color
+                                self.packed_sky_color.within
                             }
                         } else {
                             PackedLight::NO_RAYS
@@ -331,6 +551,9 @@ struct LightBuffer {
     total_ray_weight: f32,
     /// Cubes whose lighting value contributed to the incoming_light value.
     dependencies: Vec<GridPoint>,
+    /// Light from a [`DirectionalLight`], which is added to the average of the rays
+    /// rather than being counted as one of them.
+    direct_light: Rgb,
     /// Approximation of CPU cost of doing the calculation, with one unit defined as
     /// one raycast step.
     cost: usize,
@@ -347,7 +570,7 @@ struct LightRayState {
     ray_weight_by_faces: f32,
     /// The cube we're lighting; remembered to check for loopbacks
     origin_cube: GridPoint,
-    /// The ray we're casting; remembered for debugging only. (TODO: avoid this?)
+    /// The ray we're casting; remembered for sampling the sky and for debugging.
     translated_ray: Ray,
 }
 
@@ -375,6 +598,7 @@ impl LightBuffer {
             total_rays: 0,
             total_ray_weight: 0.0,
             dependencies: Vec::new(),
+            direct_light: Rgb::ZERO,
             cost: 0,
         }
     }
@@ -390,6 +614,7 @@ impl LightBuffer {
         info: &mut D::RayInfoBuffer,
         space: &Space,
         hit: RaycastStep,
+        reflectance: f32,
     ) where
         D: LightComputeOutput,
     {
@@ -416,8 +641,9 @@ impl LightBuffer {
             }
             let stored_light = space.get_lighting(light_cube);
 
-            let surface_color = ev_hit.color.clamp().to_rgb() * SURFACE_ABSORPTION
-                + Rgb::ONE * (1. - SURFACE_ABSORPTION);
+            let surface_color = (ev_hit.color.clamp().to_rgb() * SURFACE_ABSORPTION
+                + Rgb::ONE * (1. - SURFACE_ABSORPTION))
+                * reflectance;
             let light_from_struck_face =
                 ev_hit.attributes.light_emission + stored_light.value() * surface_color;
             self.incoming_light +=
@@ -483,12 +709,68 @@ impl LightBuffer {
         if ray_state.ray_weight_by_faces > 0. {
             // Note that if ray_state.alpha has reached zero, the sky color has no effect.
             self.add_weighted_light(
-                space.physics.sky_color * ray_state.alpha,
+                space.physics.sky.sample(ray_state.translated_ray.direction) * ray_state.alpha,
                 ray_state.ray_weight_by_faces,
             );
         }
     }
 
+    /// Trace a ray from `cube` toward the directional light source `sun`, and add the
+    /// light which reaches the cube's surfaces (weighted as by `direction_weights`)
+    /// without being blocked.
+    fn add_directional_light(
+        &mut self,
+        space: &Space,
+        cube: GridPoint,
+        sun: DirectionalLight,
+        direction_weights: FaceMap<f32>,
+        maximum_distance: FreeCoordinate,
+    ) {
+        let direction = sun.direction.map(NotNan::into_inner);
+        if direction == Vector3::zero() {
+            return;
+        }
+        let direction = direction.normalize();
+
+        // Light falls on the surface which most directly faces it.
+        let facing = FaceMap::from_fn(|face| face.dot(direction.map(|s| s as f32)).max(0.0))
+            .zip(direction_weights, |_face, cosine, weight| cosine * weight)
+            .into_values_iter()
+            .fold(0.0f32, f32::max);
+        if facing <= 0.0 {
+            return;
+        }
+
+        let ray = Ray {
+            origin: cube.map(FreeCoordinate::from) + Vector3::new(0.5, 0.5, 0.5),
+            direction,
+        };
+        let mut transmittance = 1.0f32;
+        for hit in ray.cast().within_grid(space.grid()) {
+            self.cost += 1;
+            if hit.t_distance() > maximum_distance {
+                // As with the sky, treat distant unobstructed rays as reaching the source.
+                break;
+            }
+            if hit.cube_ahead() == cube {
+                continue;
+            }
+            let ev_hit = space.get_evaluated(hit.cube_ahead());
+            if !ev_hit.visible_or_animated() {
+                continue;
+            }
+            if ev_hit.opaque {
+                return;
+            }
+            transmittance *= 1.0 - ev_hit.color.alpha().into_inner().clamp(0.0, 1.0);
+            if transmittance <= 0.0 {
+                return;
+            }
+        }
+
+        self.direct_light += sun.color * (facing * transmittance);
+    }
+
     /// Add the given color to the sum counting it as having the given weight,
     /// as if it was an entire ray's contribution
     /// (that is, incrementing total_rays).
@@ -504,7 +786,7 @@ impl LightBuffer {
         // We just need to avoid dividing by zero.
         let scale = NotNan::new(1.0 / self.total_ray_weight.max(1.0)).unwrap();
         let new_light_value: PackedLight = if self.total_rays > 0 {
-            PackedLight::some(self.incoming_light * scale)
+            PackedLight::some(self.incoming_light * scale + self.direct_light)
         } else if origin_is_opaque {
             PackedLight::OPAQUE
         } else {
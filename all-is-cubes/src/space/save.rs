@@ -0,0 +1,728 @@
+// Copyright 2020-2022 Kevin Reid under the terms of the MIT License as detailed
+// in the accompanying file README.md or <https://opensource.org/licenses/MIT>.
+
+//! Serialization of [`Space`].
+//!
+//! The types in this module are a private schema which the public types are converted
+//! to and from, so that the serialized form does not depend on internal details such
+//! as the storage of block indices, and so that [`URef`]s can
+//! be stored by name and resolved against a [`Universe`] when loading.
+
+use std::fmt;
+use std::num::NonZeroU16;
+
+use cgmath::{Point3, Vector3};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::block::{
+    AnimationHint, Block, BlockAttributes, BlockCollision, BlockDef, CompositeMode, Modifier,
+    MonitorView, Primitive, Resolution, RotationPlacementRule, TickAction,
+};
+use crate::character::Spawn;
+use crate::drawing::VoxelBrush;
+use crate::inv::{Slot, Tool};
+use crate::math::{Face6, FreeCoordinate, GridCoordinate, GridRotation, NotNan, Rgb, Rgba};
+use crate::space::{
+    BlockIndex, ContentsRunsError, Gravity, Grid, GridOverflowError, LightPhysics, Sky, Space,
+    SpacePhysics,
+};
+use crate::universe::{Name, RefError, URef, Universe, UniverseIndex, UniverseMember};
+
+impl Space {
+    /// Loads a [`Space`] previously written by its [`Serialize`] implementation.
+    ///
+    /// References to other universe members (such as [`Primitive::Indirect`] blocks)
+    /// are saved by name, and are looked up in `universe`; it is an error if they do
+    /// not exist there.
+    ///
+    /// The saved data does not include lighting, which is recomputed approximately and
+    /// queued for update as if by [`Space::fast_evaluate_light()`], or behaviors, which
+    /// are not saved at all.
+    pub fn deserialize_in<'de, D>(universe: &Universe, deserializer: D) -> Result<Space, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        SpaceSer::deserialize(deserializer)?
+            .into_space(universe)
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Saves the space's bounds, physics, spawn, block definitions, and contents.
+///
+/// Light data and behaviors are not saved. Use [`Space::deserialize_in()`] to load the
+/// result.
+///
+/// Saving fails if the space refers to a universe member that has no name (such as the
+/// space of a [`Primitive::Recur`] block inserted with
+/// [`Universe::insert_anonymous()`]), since it could not be found again when loading.
+impl Serialize for Space {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SpaceSer::from_space(self)
+            .map_err(ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+/// The [`BlockDef`]s and [`Space`]s of a [`Universe`], converted to their serialized
+/// form at the moment [`UniverseSnapshot::new()`] was called.
+///
+/// This is not a complete saved game (it omits characters, lighting, and behaviors), but
+/// it records enough of the world to examine or reproduce a problem.
+#[derive(Clone, Serialize)]
+pub struct UniverseSnapshot {
+    /// Block definitions by name.
+    block_defs: Vec<(String, BlockSer)>,
+    /// Spaces by name.
+    spaces: Vec<(String, SpaceSer)>,
+}
+
+impl UniverseSnapshot {
+    /// Converts the current contents of `universe`.
+    ///
+    /// Members which are currently in use, or which cannot be saved (for example,
+    /// because they contain a [`Tool::ExternalAction`]), are logged and omitted.
+    pub fn new(universe: &Universe) -> Self {
+        let block_defs = UniverseIndex::<BlockDef>::iter_by_type(universe)
+            .filter_map(|(name, def)| {
+                match def
+                    .try_borrow()
+                    .map_err(FormatError::from)
+                    .and_then(|block| BlockSer::from_block(&block))
+                {
+                    Ok(ser) => Some((name.to_string(), ser)),
+                    Err(e) => {
+                        log::warn!("Omitting block definition {name} from snapshot: {e}");
+                        None
+                    }
+                }
+            })
+            .collect();
+        let spaces = UniverseIndex::<Space>::iter_by_type(universe)
+            .filter_map(|(name, space)| {
+                match space
+                    .try_borrow()
+                    .map_err(FormatError::from)
+                    .and_then(|space| SpaceSer::from_space(&space))
+                {
+                    Ok(ser) => Some((name.to_string(), ser)),
+                    Err(e) => {
+                        log::warn!("Omitting space {name} from snapshot: {e}");
+                        None
+                    }
+                }
+            })
+            .collect();
+        Self { block_defs, spaces }
+    }
+}
+
+impl fmt::Debug for UniverseSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn names<T>(members: &[(String, T)]) -> Vec<&str> {
+            members.iter().map(|(name, _)| &**name).collect()
+        }
+        f.debug_struct("UniverseSnapshot")
+            .field("block_defs", &names(&self.block_defs))
+            .field("spaces", &names(&self.spaces))
+            .finish()
+    }
+}
+
+/// Errors which may occur while converting to or from the serialized form.
+#[derive(Debug, thiserror::Error)]
+enum FormatError {
+    #[error("Tool::ExternalAction cannot be saved")]
+    ExternalAction,
+    #[error("invalid bounds: {0}")]
+    Bounds(#[from] GridOverflowError),
+    #[error("cannot save a reference to {0}, which has no name")]
+    AnonymousRef(Name),
+    #[error("the universe has no {kind} named {name}")]
+    MissingMember { kind: &'static str, name: Name },
+    #[error("invalid contents: {0}")]
+    Contents(#[from] ContentsRunsError),
+    #[error("{0}")]
+    Ref(#[from] RefError),
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct SpaceSer {
+    bounds: GridSer,
+    physics: PhysicsSer,
+    spawn: SpawnSer,
+    /// Every block present in the space, without duplicates.
+    blocks: Vec<BlockSer>,
+    /// Run-length encoded indices into `blocks`, in the linear order of
+    /// [`Grid::index()`].
+    contents: Vec<(BlockIndex, usize)>,
+}
+
+impl SpaceSer {
+    fn from_space(space: &Space) -> Result<Self, FormatError> {
+        // Renumber blocks to skip unused indices.
+        let mut renumbering: Vec<Option<BlockIndex>> = vec![None; space.block_data.len()];
+        let mut blocks = Vec::new();
+        for (old_index, data) in space.block_data.iter().enumerate() {
+            if data.count > 0 {
+                renumbering[old_index] = Some(blocks.len() as BlockIndex);
+                blocks.push(BlockSer::from_block(&data.block)?);
+            }
+        }
+
+        Ok(Self {
+            bounds: GridSer::from(space.grid),
+            physics: PhysicsSer::from(&space.physics),
+            spawn: SpawnSer::from_spawn(&space.spawn)?,
+            blocks,
+            contents: space
+                .contents_runs()
+                .map(|(index, length)| {
+                    let index = renumbering[usize::from(index)]
+                        .expect("space contains block with zero count");
+                    (index, length)
+                })
+                .collect(),
+        })
+    }
+
+    fn into_space(self, universe: &Universe) -> Result<Space, FormatError> {
+        let builder = Space::builder(self.bounds.to_grid()?)
+            .physics(self.physics.into())
+            .spawn(self.spawn.into_spawn(universe)?);
+        let blocks = self
+            .blocks
+            .into_iter()
+            .map(|block| block.into_block(universe))
+            .collect::<Result<Vec<Block>, FormatError>>()?;
+        Ok(Space::from_builder_and_contents_runs(
+            builder,
+            &blocks,
+            self.contents,
+        )?)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct GridSer {
+    lower_bounds: [GridCoordinate; 3],
+    size: [GridCoordinate; 3],
+}
+
+impl From<Grid> for GridSer {
+    fn from(grid: Grid) -> Self {
+        Self {
+            lower_bounds: grid.lower_bounds().into(),
+            size: grid.size().into(),
+        }
+    }
+}
+
+impl GridSer {
+    fn to_grid(&self) -> Result<Grid, GridOverflowError> {
+        Grid::checked_new(self.lower_bounds, self.size)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct PhysicsSer {
+    gravity: GravitySer,
+    sky: Sky,
+    light: LightPhysics,
+}
+
+impl From<&SpacePhysics> for PhysicsSer {
+    fn from(physics: &SpacePhysics) -> Self {
+        let SpacePhysics {
+            gravity,
+            sky,
+            light,
+        } = physics;
+        Self {
+            gravity: match *gravity {
+                Gravity::Uniform(vector) => GravitySer::Uniform(vector.into()),
+                Gravity::Radial { center, strength } => GravitySer::Radial {
+                    center: center.into(),
+                    strength,
+                },
+            },
+            sky: sky.clone(),
+            light: light.clone(),
+        }
+    }
+}
+
+impl From<PhysicsSer> for SpacePhysics {
+    fn from(physics: PhysicsSer) -> Self {
+        Self {
+            gravity: match physics.gravity {
+                GravitySer::Uniform(vector) => Gravity::Uniform(Vector3::from(vector)),
+                GravitySer::Radial { center, strength } => Gravity::Radial {
+                    center: Point3::from(center),
+                    strength,
+                },
+            },
+            sky: physics.sky,
+            light: physics.light,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+enum GravitySer {
+    Uniform([NotNan<FreeCoordinate>; 3]),
+    Radial {
+        center: [NotNan<FreeCoordinate>; 3],
+        strength: NotNan<FreeCoordinate>,
+    },
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct SpawnSer {
+    bounds: GridSer,
+    eye_position: Option<[FreeCoordinate; 3]>,
+    look_direction: [FreeCoordinate; 3],
+    inventory: Vec<SlotSer>,
+}
+
+impl SpawnSer {
+    fn from_spawn(spawn: &Spawn) -> Result<Self, FormatError> {
+        Ok(Self {
+            bounds: GridSer::from(spawn.bounds()),
+            eye_position: spawn.eye_position().map(Into::into),
+            look_direction: spawn.look_direction().into(),
+            inventory: spawn
+                .inventory()
+                .iter()
+                .map(SlotSer::from_slot)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn into_spawn(self, universe: &Universe) -> Result<Spawn, FormatError> {
+        let mut builder = Spawn::builder(self.bounds.to_grid()?)
+            .look_direction(self.look_direction)
+            .inventory(
+                self.inventory
+                    .into_iter()
+                    .map(|slot| slot.into_slot(universe))
+                    .collect::<Result<_, _>>()?,
+            );
+        if let Some(position) = self.eye_position {
+            builder = builder.eye_position(position);
+        }
+        Ok(builder.build())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+enum SlotSer {
+    Empty,
+    Stack(NonZeroU16, Box<ToolSer>),
+}
+
+impl SlotSer {
+    fn from_slot(slot: &Slot) -> Result<Self, FormatError> {
+        Ok(match slot {
+            Slot::Empty => SlotSer::Empty,
+            Slot::Stack(count, tool) => SlotSer::Stack(*count, Box::new(ToolSer::from_tool(tool)?)),
+        })
+    }
+
+    fn into_slot(self, universe: &Universe) -> Result<Slot, FormatError> {
+        Ok(match self {
+            SlotSer::Empty => Slot::Empty,
+            SlotSer::Stack(count, tool) => Slot::Stack(count, tool.into_tool(universe)?),
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+enum ToolSer {
+    Activate,
+    RemoveBlock { keep: bool },
+    Block(BlockSer),
+    InfiniteBlocks(BlockSer),
+    CopyFromSpace,
+    EditBlock,
+    PushPull,
+    Jetpack { active: bool },
+}
+
+impl ToolSer {
+    fn from_tool(tool: &Tool) -> Result<Self, FormatError> {
+        Ok(match *tool {
+            Tool::Activate => ToolSer::Activate,
+            Tool::RemoveBlock { keep } => ToolSer::RemoveBlock { keep },
+            Tool::Block(ref block) => ToolSer::Block(BlockSer::from_block(block)?),
+            Tool::InfiniteBlocks(ref block) => {
+                ToolSer::InfiniteBlocks(BlockSer::from_block(block)?)
+            }
+            Tool::CopyFromSpace => ToolSer::CopyFromSpace,
+            Tool::EditBlock => ToolSer::EditBlock,
+            Tool::PushPull => ToolSer::PushPull,
+            Tool::Jetpack { active } => ToolSer::Jetpack { active },
+            Tool::ExternalAction { .. } => return Err(FormatError::ExternalAction),
+        })
+    }
+
+    fn into_tool(self, universe: &Universe) -> Result<Tool, FormatError> {
+        Ok(match self {
+            ToolSer::Activate => Tool::Activate,
+            ToolSer::RemoveBlock { keep } => Tool::RemoveBlock { keep },
+            ToolSer::Block(block) => Tool::Block(block.into_block(universe)?),
+            ToolSer::InfiniteBlocks(block) => Tool::InfiniteBlocks(block.into_block(universe)?),
+            ToolSer::CopyFromSpace => Tool::CopyFromSpace,
+            ToolSer::EditBlock => Tool::EditBlock,
+            ToolSer::PushPull => Tool::PushPull,
+            ToolSer::Jetpack { active } => Tool::Jetpack { active },
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct BlockSer {
+    primitive: PrimitiveSer,
+    modifiers: Vec<ModifierSer>,
+}
+
+impl BlockSer {
+    fn from_block(block: &Block) -> Result<Self, FormatError> {
+        Ok(Self {
+            primitive: match *block.primitive() {
+                Primitive::Indirect(ref def) => PrimitiveSer::Indirect(NameSer::from_uref(def)?),
+                Primitive::Atom(ref attributes, color) => PrimitiveSer::Atom {
+                    attributes: AttributesSer::from_attributes(attributes)?,
+                    color,
+                },
+                Primitive::Recur {
+                    ref attributes,
+                    offset,
+                    resolution,
+                    ref space,
+                } => PrimitiveSer::Recur {
+                    attributes: AttributesSer::from_attributes(attributes)?,
+                    offset: offset.into(),
+                    resolution,
+                    space: NameSer::from_uref(space)?,
+                },
+            },
+            modifiers: block
+                .modifiers()
+                .iter()
+                .map(ModifierSer::from_modifier)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn into_block(self, universe: &Universe) -> Result<Block, FormatError> {
+        let primitive = match self.primitive {
+            PrimitiveSer::Indirect(name) => {
+                Primitive::Indirect(name.resolve::<BlockDef>(universe, "block definition")?)
+            }
+            PrimitiveSer::Atom { attributes, color } => {
+                Primitive::Atom(attributes.into_attributes(universe)?, color)
+            }
+            PrimitiveSer::Recur {
+                attributes,
+                offset,
+                resolution,
+                space,
+            } => Primitive::Recur {
+                attributes: attributes.into_attributes(universe)?,
+                offset: offset.into(),
+                resolution,
+                space: space.resolve::<Space>(universe, "space")?,
+            },
+        };
+        let mut block = Block::from_primitive(primitive);
+        *block.modifiers_mut() = self
+            .modifiers
+            .into_iter()
+            .map(|modifier| modifier.into_modifier(universe))
+            .collect::<Result<_, _>>()?;
+        Ok(block)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+enum ModifierSer {
+    Quote {
+        ambient: bool,
+    },
+    Rotate(GridRotation),
+    Mirror(Face6),
+    Move {
+        direction: Face6,
+        distance: u16,
+        velocity: i16,
+    },
+    Scale {
+        resolution: Resolution,
+    },
+    Tint {
+        multiply: Rgba,
+    },
+    Composite {
+        source: Box<BlockSer>,
+        mode: CompositeMode,
+    },
+}
+
+impl ModifierSer {
+    fn from_modifier(modifier: &Modifier) -> Result<Self, FormatError> {
+        Ok(match *modifier {
+            Modifier::Quote { ambient } => ModifierSer::Quote { ambient },
+            Modifier::Rotate(rotation) => ModifierSer::Rotate(rotation),
+            Modifier::Mirror(face) => ModifierSer::Mirror(face),
+            Modifier::Move {
+                direction,
+                distance,
+                velocity,
+            } => ModifierSer::Move {
+                direction,
+                distance,
+                velocity,
+            },
+            Modifier::Scale { resolution } => ModifierSer::Scale { resolution },
+            Modifier::Tint { multiply } => ModifierSer::Tint { multiply },
+            Modifier::Composite { ref source, mode } => ModifierSer::Composite {
+                source: Box::new(BlockSer::from_block(source)?),
+                mode,
+            },
+        })
+    }
+
+    fn into_modifier(self, universe: &Universe) -> Result<Modifier, FormatError> {
+        Ok(match self {
+            ModifierSer::Quote { ambient } => Modifier::quote(ambient),
+            ModifierSer::Rotate(rotation) => Modifier::Rotate(rotation),
+            ModifierSer::Mirror(face) => Modifier::Mirror(face),
+            ModifierSer::Move {
+                direction,
+                distance,
+                velocity,
+            } => Modifier::moving(direction, distance, velocity),
+            ModifierSer::Scale { resolution } => Modifier::Scale { resolution },
+            ModifierSer::Tint { multiply } => Modifier::Tint { multiply },
+            ModifierSer::Composite { source, mode } => Modifier::Composite {
+                source: source.into_block(universe)?,
+                mode,
+            },
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+enum PrimitiveSer {
+    Indirect(NameSer),
+    Atom {
+        attributes: AttributesSer,
+        color: Rgba,
+    },
+    Recur {
+        attributes: AttributesSer,
+        offset: [GridCoordinate; 3],
+        resolution: u8,
+        space: NameSer,
+    },
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct AttributesSer {
+    display_name: String,
+    selectable: bool,
+    collision: BlockCollision,
+    voxel_collision: Option<BlockCollision>,
+    rotation_rule: RotationPlacementRule,
+    light_emission: Rgb,
+    tick_action: Option<TickActionSer>,
+    activation_action: Option<TickActionSer>,
+    animation_hint: AnimationHint,
+    disable_culling: bool,
+    monitor: Option<MonitorViewSer>,
+}
+
+impl AttributesSer {
+    fn from_attributes(attributes: &BlockAttributes) -> Result<Self, FormatError> {
+        let BlockAttributes {
+            display_name,
+            selectable,
+            collision,
+            voxel_collision,
+            rotation_rule,
+            light_emission,
+            tick_action,
+            activation_action,
+            animation_hint,
+            disable_culling,
+            monitor,
+        } = attributes;
+        Ok(Self {
+            display_name: display_name.to_string(),
+            selectable: *selectable,
+            collision: *collision,
+            voxel_collision: *voxel_collision,
+            rotation_rule: *rotation_rule,
+            light_emission: *light_emission,
+            tick_action: tick_action
+                .as_ref()
+                .map(TickActionSer::from_tick_action)
+                .transpose()?,
+            activation_action: activation_action
+                .as_ref()
+                .map(TickActionSer::from_tick_action)
+                .transpose()?,
+            animation_hint: *animation_hint,
+            disable_culling: *disable_culling,
+            monitor: monitor
+                .as_ref()
+                .map(MonitorViewSer::from_monitor_view)
+                .transpose()?,
+        })
+    }
+
+    fn into_attributes(self, universe: &Universe) -> Result<BlockAttributes, FormatError> {
+        Ok(BlockAttributes {
+            display_name: self.display_name.into(),
+            selectable: self.selectable,
+            collision: self.collision,
+            voxel_collision: self.voxel_collision,
+            rotation_rule: self.rotation_rule,
+            light_emission: self.light_emission,
+            tick_action: match self.tick_action {
+                Some(action) => Some(action.into_tick_action(universe)?),
+                None => None,
+            },
+            activation_action: match self.activation_action {
+                Some(action) => Some(action.into_tick_action(universe)?),
+                None => None,
+            },
+            animation_hint: self.animation_hint,
+            disable_culling: self.disable_culling,
+            monitor: match self.monitor {
+                Some(monitor) => Some(monitor.into_monitor_view(universe)?),
+                None => None,
+            },
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+enum TickActionSer {
+    Paint(Vec<([GridCoordinate; 3], BlockSer)>),
+    Replace(Box<BlockSer>),
+    UnlessSolid {
+        offset: [GridCoordinate; 3],
+        action: Box<TickActionSer>,
+    },
+}
+
+impl TickActionSer {
+    fn from_tick_action(action: &TickAction) -> Result<Self, FormatError> {
+        Ok(match action {
+            TickAction::Paint(brush) => TickActionSer::Paint(
+                brush
+                    .entries()
+                    .iter()
+                    .map(|(offset, block)| Ok(((*offset).into(), BlockSer::from_block(block)?)))
+                    .collect::<Result<_, FormatError>>()?,
+            ),
+            TickAction::Replace(block) => {
+                TickActionSer::Replace(Box::new(BlockSer::from_block(block)?))
+            }
+            TickAction::UnlessSolid { offset, action } => TickActionSer::UnlessSolid {
+                offset: (*offset).into(),
+                action: Box::new(TickActionSer::from_tick_action(action)?),
+            },
+        })
+    }
+
+    fn into_tick_action(self, universe: &Universe) -> Result<TickAction, FormatError> {
+        Ok(match self {
+            TickActionSer::Paint(entries) => TickAction::Paint(VoxelBrush::new(
+                entries
+                    .into_iter()
+                    .map(|(offset, block)| Ok((offset, block.into_block(universe)?)))
+                    .collect::<Result<Vec<_>, FormatError>>()?,
+            )),
+            TickActionSer::Replace(block) => TickAction::Replace(block.into_block(universe)?),
+            TickActionSer::UnlessSolid { offset, action } => TickAction::UnlessSolid {
+                offset: offset.into(),
+                action: Box::new(action.into_tick_action(universe)?),
+            },
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct MonitorViewSer {
+    space: NameSer,
+    face: Face6,
+    eye: [NotNan<FreeCoordinate>; 3],
+    look_at: [NotNan<FreeCoordinate>; 3],
+    fov_y: NotNan<FreeCoordinate>,
+    resolution: Resolution,
+}
+
+impl MonitorViewSer {
+    fn from_monitor_view(view: &MonitorView) -> Result<Self, FormatError> {
+        let MonitorView {
+            space,
+            face,
+            eye,
+            look_at,
+            fov_y,
+            resolution,
+        } = view;
+        Ok(Self {
+            space: NameSer::from_uref(space)?,
+            face: *face,
+            eye: (*eye).into(),
+            look_at: (*look_at).into(),
+            fov_y: *fov_y,
+            resolution: *resolution,
+        })
+    }
+
+    fn into_monitor_view(self, universe: &Universe) -> Result<MonitorView, FormatError> {
+        Ok(MonitorView {
+            space: self.space.resolve::<Space>(universe, "space")?,
+            face: self.face,
+            eye: Point3::from(self.eye),
+            look_at: Point3::from(self.look_at),
+            fov_y: self.fov_y,
+            resolution: self.resolution,
+        })
+    }
+}
+
+/// A [`Name`] used as a reference to a universe member.
+///
+/// Anonymous members cannot be referred to, since their numbers are not meaningful in
+/// any other universe.
+#[derive(Clone, Serialize, Deserialize)]
+enum NameSer {
+    Specific(String),
+}
+
+impl NameSer {
+    fn from_uref<T: 'static>(uref: &URef<T>) -> Result<Self, FormatError> {
+        match uref.name() {
+            Name::Specific(name) => Ok(NameSer::Specific(name.to_string())),
+            name @ Name::Anonym(_) => Err(FormatError::AnonymousRef(name.clone())),
+        }
+    }
+
+    fn resolve<T>(self, universe: &Universe, kind: &'static str) -> Result<URef<T>, FormatError>
+    where
+        T: UniverseMember,
+        Universe: UniverseIndex<T>,
+    {
+        let NameSer::Specific(name) = self;
+        let name = Name::from(&*name);
+        universe
+            .get(&name)
+            .ok_or(FormatError::MissingMember { kind, name })
+    }
+}
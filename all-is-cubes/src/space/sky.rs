@@ -0,0 +1,173 @@
+// Copyright 2020-2022 Kevin Reid under the terms of the MIT License as detailed
+// in the accompanying file README.md or <https://opensource.org/licenses/MIT>.
+
+use cgmath::Vector3;
+
+use crate::math::{Face7, FaceMap, FreeCoordinate, GridPoint, Rgb};
+use crate::space::Grid;
+
+/// Color of the light arriving from outside a [`Space`](super::Space), which may vary
+/// with direction; part of [`SpacePhysics`](super::SpacePhysics).
+///
+/// This is used both as the source of light for the space and as the background color
+/// seen when looking out of the space.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub enum Sky {
+    /// The same color in every direction.
+    Uniform(Rgb),
+    /// A separate color for each of the six axis-aligned directions, such as a warm
+    /// [`px`](FaceMap::px) and cool [`nx`](FaceMap::nx) for a sunset.
+    ///
+    /// Any other direction takes the color of the face its largest component points
+    /// towards (as in a cube map without filtering).
+    /// The [`within`](FaceMap::within) entry is used where no direction applies;
+    /// see [`Sky::overall_color()`].
+    Faces(FaceMap<Rgb>),
+}
+
+impl Sky {
+    /// Returns the color of the sky as seen looking in `direction`.
+    ///
+    /// ```
+    /// use all_is_cubes::cgmath::Vector3;
+    /// use all_is_cubes::math::{Face7, FaceMap, Rgb};
+    /// use all_is_cubes::space::Sky;
+    ///
+    /// let sky = Sky::Faces(FaceMap::repeat(Rgb::ZERO).with(Face7::PX, Rgb::ONE));
+    /// assert_eq!(sky.sample(Vector3::new(1.0, 0.5, -0.5)), Rgb::ONE);
+    /// assert_eq!(sky.sample(Vector3::new(-1.0, 0.5, -0.5)), Rgb::ZERO);
+    /// ```
+    pub fn sample(&self, direction: Vector3<FreeCoordinate>) -> Rgb {
+        self.face_color(nearest_face(direction))
+    }
+
+    /// Returns a single color which stands for the sky as a whole, for use where
+    /// there is no particular direction to consider (such as renderers' fog).
+    ///
+    /// For [`Sky::Uniform`] this is the color; for [`Sky::Faces`] it is the
+    /// [`within`](FaceMap::within) entry.
+    pub fn overall_color(&self) -> Rgb {
+        self.face_color(Face7::Within)
+    }
+
+    /// Returns the color in the direction `face`, or the overall color if `face` is
+    /// [`Face7::Within`].
+    pub(crate) fn face_color(&self, face: Face7) -> Rgb {
+        match *self {
+            Sky::Uniform(color) => color,
+            Sky::Faces(ref faces) => faces[face],
+        }
+    }
+
+    /// Returns the colors for all directions; the `within` element is the
+    /// [overall color](Self::overall_color).
+    pub(crate) fn to_face_map(&self) -> FaceMap<Rgb> {
+        FaceMap::from_fn(|face| self.face_color(face))
+    }
+}
+
+impl From<Rgb> for Sky {
+    fn from(color: Rgb) -> Self {
+        Sky::Uniform(color)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Sky {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(if u.arbitrary()? {
+            Sky::Uniform(u.arbitrary()?)
+        } else {
+            Sky::Faces(FaceMap {
+                within: u.arbitrary()?,
+                nx: u.arbitrary()?,
+                ny: u.arbitrary()?,
+                nz: u.arbitrary()?,
+                px: u.arbitrary()?,
+                py: u.arbitrary()?,
+                pz: u.arbitrary()?,
+            })
+        })
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        use arbitrary::{size_hint, Arbitrary};
+        size_hint::and(
+            <bool as Arbitrary>::size_hint(depth),
+            size_hint::or(
+                <Rgb as Arbitrary>::size_hint(depth),
+                size_hint::and_all(&[<Rgb as Arbitrary>::size_hint(depth); 7]),
+            ),
+        )
+    }
+}
+
+/// Returns the face whose normal is closest to `direction`, or [`Face7::Within`] if
+/// it is zero or NaN.
+fn nearest_face(direction: Vector3<FreeCoordinate>) -> Face7 {
+    let Vector3 { x, y, z } = direction.map(FreeCoordinate::abs);
+    if x >= y && x >= z && x > 0.0 {
+        if direction.x > 0.0 {
+            Face7::PX
+        } else {
+            Face7::NX
+        }
+    } else if y >= z && y > 0.0 {
+        if direction.y > 0.0 {
+            Face7::PY
+        } else {
+            Face7::NY
+        }
+    } else if z > 0.0 {
+        if direction.z > 0.0 {
+            Face7::PZ
+        } else {
+            Face7::NZ
+        }
+    } else {
+        Face7::Within
+    }
+}
+
+/// Chooses which direction's sky light should be considered to fill `cube`, which is
+/// outside of `grid`: the side of `grid` it is farthest outside of, relative to the
+/// size of `grid`.
+pub(crate) fn sky_face_for_cube(grid: Grid, cube: GridPoint) -> Face7 {
+    let cube_center = cube.map(FreeCoordinate::from) + Vector3::new(0.5, 0.5, 0.5);
+    let half_size = grid.size().map(|s| FreeCoordinate::from(s.max(1)) / 2.0);
+    let offset = cube_center - grid.center();
+    nearest_face(Vector3::new(
+        offset.x / half_size.x,
+        offset.y / half_size.y,
+        offset.z / half_size.z,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_face_examples() {
+        assert_eq!(nearest_face(Vector3::new(1.0, 0.0, 0.0)), Face7::PX);
+        assert_eq!(nearest_face(Vector3::new(-1.0, 0.9, 0.0)), Face7::NX);
+        assert_eq!(nearest_face(Vector3::new(0.1, -2.0, 1.0)), Face7::NY);
+        assert_eq!(nearest_face(Vector3::new(0.1, 0.0, 0.5)), Face7::PZ);
+        assert_eq!(nearest_face(Vector3::new(0.0, 0.0, 0.0)), Face7::Within);
+        assert_eq!(
+            nearest_face(Vector3::new(FreeCoordinate::NAN, 0.0, 0.0)),
+            Face7::Within
+        );
+    }
+
+    #[test]
+    fn sky_face_for_cube_sides() {
+        let grid = Grid::new([0, 0, 0], [4, 2, 2]);
+        assert_eq!(sky_face_for_cube(grid, GridPoint::new(4, 1, 1)), Face7::PX);
+        assert_eq!(sky_face_for_cube(grid, GridPoint::new(-1, 1, 1)), Face7::NX);
+        assert_eq!(sky_face_for_cube(grid, GridPoint::new(2, 2, 1)), Face7::PY);
+        assert_eq!(sky_face_for_cube(grid, GridPoint::new(4, 2, 1)), Face7::PY);
+        assert_eq!(sky_face_for_cube(grid, GridPoint::new(5, 2, 1)), Face7::PX);
+    }
+}
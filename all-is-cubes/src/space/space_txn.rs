@@ -1,10 +1,10 @@
 // Copyright 2020-2022 Kevin Reid under the terms of the MIT License as detailed
 // in the accompanying file README.md or <https://opensource.org/licenses/MIT>.
 
-//! TODO: Maybe this file is too small
+//! [`SpaceTransaction`] and [`SpaceHistory`].
 
 use std::collections::btree_map::Entry::*;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
 use std::{fmt, mem};
 
 use crate::behavior::{BehaviorSet, BehaviorSetTransaction};
@@ -13,7 +13,8 @@ use crate::drawing::DrawingPlane;
 use crate::math::{GridCoordinate, GridMatrix, GridPoint};
 use crate::space::{Grid, SetCubeError, Space};
 use crate::transaction::{
-    CommitError, Merge, PreconditionFailed, Transaction, TransactionConflict, Transactional,
+    CommitError, ExecuteError, Merge, PreconditionFailed, Transaction, TransactionConflict,
+    Transactional,
 };
 use crate::util::{ConciseDebug, CustomFormat as _};
 use crate::vui::ActivatableRegion;
@@ -149,6 +150,45 @@ impl SpaceTransaction {
         Self::single(cube, CubeTransaction::ACTIVATE)
     }
 
+    /// Returns a transaction which will undo the effects of this transaction on blocks,
+    /// if executed immediately after it.
+    ///
+    /// This must be called *before* this transaction is executed, since it captures
+    /// the blocks currently in `space` which this transaction would replace.
+    /// The inverse transaction has preconditions that the blocks this transaction
+    /// places are still present, so it will fail rather than overwrite later changes.
+    ///
+    /// Returns [`None`] if this transaction cannot be inverted, which is currently
+    /// the case if it modifies behaviors. Cube activations are not inverted, since
+    /// they do not themselves change the space.
+    pub fn inverse(&self, space: &Space) -> Option<Self> {
+        // Destructuring to statically check that we consider all fields.
+        let Self { cubes, behaviors } = self;
+        if !behaviors.is_empty() {
+            return None;
+        }
+
+        let mut inverse = Self::default();
+        for (&cube, cube_txn) in cubes {
+            if let Some(new) = &cube_txn.new {
+                if space.grid().contains_cube(cube) {
+                    inverse.cubes.insert(
+                        cube,
+                        CubeTransaction {
+                            old: Some(new.clone()),
+                            new: Some(space[cube].clone()),
+                            conserved: true,
+                            activate: false,
+                        },
+                    );
+                }
+                // Otherwise, the cube is out of bounds and the transaction must be
+                // nonconserved, so it has no effect there and needs no inverse.
+            }
+        }
+        Some(inverse)
+    }
+
     /// Computes the region affected by this transaction.
     ///
     /// TODO: This does not currently report behaviors but it should, once they have
@@ -196,7 +236,7 @@ impl Transaction<Space> for SpaceTransaction {
                 if let Some(old) = old {
                     // Raw lookup because we already computed the index for a bounds check
                     // (TODO: Put this in a function, like get_block_index)
-                    if space.block_data[space.contents[cube_index] as usize].block != *old {
+                    if space.block_data[space.contents.get(cube_index) as usize].block != *old {
                         return Err(PreconditionFailed {
                             location: "Space",
                             problem: "existing block not as expected",
@@ -260,6 +300,20 @@ impl Transaction<Space> for SpaceTransaction {
                     }
                 }
             }
+            for cube in to_activate {
+                let cube = GridPoint::from(cube);
+                let action_txn = space
+                    .get_evaluated(cube)
+                    .attributes
+                    .activation_action
+                    .as_ref()
+                    .and_then(|action| action.transaction(space, cube));
+                if let Some(action_txn) = action_txn {
+                    // TODO: error report? A failing action is not a failure of this
+                    // transaction, which has already been committed.
+                    let _ = action_txn.nonconserved().execute(space);
+                }
+            }
         }
         Ok(())
     }
@@ -363,10 +417,16 @@ impl Merge for CubeTransaction {
             // Incompatible preconditions will always fail.
             return Err(TransactionConflict {});
         }
-        if self.new.is_some() && other.new.is_some() && self.conserved {
-            // Replacing the same cube twice is not allowed -- even if they're
-            // equal, doing so could violate an intended conservation law.
-            return Err(TransactionConflict {});
+        if let (Some(a), Some(b)) = (&self.new, &other.new) {
+            if self.conserved || other.conserved {
+                // Replacing the same cube twice is not allowed -- even if they're
+                // equal, doing so could violate an intended conservation law.
+                return Err(TransactionConflict {});
+            }
+            if a != b {
+                // Nonconserved replacements may coincide, but must agree on the result.
+                return Err(TransactionConflict {});
+            }
         }
         Ok(CubeMergeCheck {})
     }
@@ -391,6 +451,108 @@ struct CubeMergeCheck {
     // and getting clippy::let_unit_value warnings
 }
 
+/// Undo and redo history for edits to a [`Space`].
+///
+/// Edits are made by passing [`SpaceTransaction`]s to [`SpaceHistory::execute`], which
+/// records their [inverses](SpaceTransaction::inverse). The history does not own the
+/// space; the same space should be passed to every method, and if the space is
+/// modified by other means, undoing may fail due to the inverse transactions'
+/// preconditions.
+#[derive(Clone, Debug)]
+pub struct SpaceHistory {
+    /// Inverses of executed transactions, most recent last.
+    undo: VecDeque<SpaceTransaction>,
+    /// Inverses of undone transactions, most recent last.
+    redo: VecDeque<SpaceTransaction>,
+    /// Maximum length of `undo`.
+    limit: usize,
+}
+
+impl SpaceHistory {
+    /// Constructs an empty history which will remember at most `limit` edits;
+    /// older edits are forgotten and can no longer be undone.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: VecDeque::new(),
+            limit,
+        }
+    }
+
+    /// Executes `transaction` on `space` and records it so that it may be undone.
+    /// This discards any edits that were undone and could have been redone.
+    ///
+    /// Fails without making any changes if the transaction's preconditions are not met
+    /// or if it cannot be [inverted](SpaceTransaction::inverse).
+    pub fn execute(
+        &mut self,
+        space: &mut Space,
+        transaction: &SpaceTransaction,
+    ) -> Result<(), ExecuteError> {
+        let inverse =
+            transaction
+                .inverse(space)
+                .ok_or(ExecuteError::Check(PreconditionFailed {
+                    location: "SpaceHistory",
+                    problem: "transaction cannot be undone",
+                }))?;
+        transaction.execute(space)?;
+        self.redo.clear();
+        if self.limit > 0 {
+            if self.undo.len() >= self.limit {
+                self.undo.pop_front();
+            }
+            self.undo.push_back(inverse);
+        }
+        Ok(())
+    }
+
+    /// Undoes the most recent edit which has not been undone.
+    ///
+    /// Returns `Ok(false)` if there is nothing to undo. If undoing fails, the history
+    /// is unchanged.
+    pub fn undo(&mut self, space: &mut Space) -> Result<bool, ExecuteError> {
+        Self::transfer(&mut self.undo, &mut self.redo, space)
+    }
+
+    /// Redoes the most recently undone edit.
+    ///
+    /// Returns `Ok(false)` if there is nothing to redo. If redoing fails, the history
+    /// is unchanged.
+    pub fn redo(&mut self, space: &mut Space) -> Result<bool, ExecuteError> {
+        Self::transfer(&mut self.redo, &mut self.undo, space)
+    }
+
+    /// Returns whether [`Self::undo`] has an edit to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Returns whether [`Self::redo`] has an edit to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Executes the last transaction in `from` and moves its inverse to `to`.
+    fn transfer(
+        from: &mut VecDeque<SpaceTransaction>,
+        to: &mut VecDeque<SpaceTransaction>,
+        space: &mut Space,
+    ) -> Result<bool, ExecuteError> {
+        let transaction = match from.back() {
+            Some(t) => t,
+            None => return Ok(false),
+        };
+        let inverse = transaction
+            .inverse(space)
+            .expect("inverse transactions should be invertible");
+        transaction.execute(space)?;
+        from.pop_back();
+        to.push_back(inverse);
+        Ok(true)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::atomic::{AtomicU32, Ordering};
@@ -398,9 +560,10 @@ mod tests {
 
     use pretty_assertions::assert_eq;
 
-    use crate::block::AIR;
+    use crate::block::{TickAction, AIR};
     use crate::content::make_some_blocks;
     use crate::inv::EphemeralOpaque;
+    use crate::math::Rgba;
     use crate::space::Grid;
     use crate::transaction::TransactionTester;
 
@@ -530,6 +693,14 @@ mod tests {
         t1.merge(t2).unwrap_err();
     }
 
+    #[test]
+    fn merge_rejects_different_new_nonconserved() {
+        let [b1, b2] = make_some_blocks();
+        let t1 = SpaceTransaction::set_cube([0, 0, 0], None, Some(b1)).nonconserved();
+        let t2 = SpaceTransaction::set_cube([0, 0, 0], None, Some(b2)).nonconserved();
+        t1.merge(t2).unwrap_err();
+    }
+
     #[test]
     fn merge_rejects_different_old() {
         let [b1, b2] = make_some_blocks();
@@ -569,6 +740,23 @@ mod tests {
         assert_eq!(signal.load(Ordering::Relaxed), 1);
     }
 
+    #[test]
+    fn activate_block_with_activation_action() {
+        let [block2] = make_some_blocks();
+        let block1 = Block::builder()
+            .color(Rgba::WHITE)
+            .activation_action(Some(TickAction::Replace(block2.clone())))
+            .build();
+        let mut space = Space::empty_positive(1, 1, 1);
+        let cube = GridPoint::new(0, 0, 0);
+        space.set(cube, &block1).unwrap();
+
+        SpaceTransaction::activate_block(cube)
+            .execute(&mut space)
+            .unwrap();
+        assert_eq!(space[cube], block2);
+    }
+
     #[test]
     fn systematic() {
         let [b1, b2, b3] = make_some_blocks();
@@ -660,4 +848,117 @@ mod tests {
             Some(Grid::from_lower_upper([-7, 3, 5], [11, 4, 6]))
         );
     }
+
+    /// Returns the block and block index of every cube, and the blocks in the
+    /// block index table, for comparing the exact state of a space.
+    fn snapshot(space: &Space) -> (Vec<Option<u16>>, Vec<Block>) {
+        (
+            space
+                .grid()
+                .interior_iter()
+                .map(|cube| space.get_block_index(cube))
+                .collect(),
+            space
+                .block_data()
+                .iter()
+                .map(|data| data.block().clone())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn history_undo_restores_blocks_and_indices() {
+        let [b1, b2, b3] = make_some_blocks();
+        let mut space = Space::empty_positive(3, 1, 1);
+        space.set([0, 0, 0], &b1).unwrap();
+        space.set([1, 0, 0], &b2).unwrap();
+        let before = snapshot(&space);
+
+        let mut history = SpaceHistory::new(10);
+        assert!(!history.can_undo());
+        history
+            .execute(
+                &mut space,
+                &SpaceTransaction::set_cube([0, 0, 0], None, Some(b3.clone())),
+            )
+            .unwrap();
+        assert_eq!(space[[0, 0, 0]], b3);
+        let after = snapshot(&space);
+
+        assert!(history.undo(&mut space).unwrap());
+        assert_eq!(space[[0, 0, 0]], b1);
+        assert_eq!(snapshot(&space), before);
+        assert!(!history.can_undo());
+
+        assert!(history.redo(&mut space).unwrap());
+        assert_eq!(snapshot(&space), after);
+        assert!(!history.redo(&mut space).unwrap());
+    }
+
+    #[test]
+    fn history_new_edit_discards_redo() {
+        let [b1, b2] = make_some_blocks();
+        let mut space = Space::empty_positive(1, 1, 1);
+        let mut history = SpaceHistory::new(10);
+        history
+            .execute(
+                &mut space,
+                &SpaceTransaction::set_cube([0, 0, 0], None, Some(b1)),
+            )
+            .unwrap();
+        history.undo(&mut space).unwrap();
+        assert!(history.can_redo());
+
+        history
+            .execute(
+                &mut space,
+                &SpaceTransaction::set_cube([0, 0, 0], None, Some(b2.clone())),
+            )
+            .unwrap();
+        assert!(!history.can_redo());
+        assert!(!history.redo(&mut space).unwrap());
+        assert_eq!(space[[0, 0, 0]], b2);
+    }
+
+    #[test]
+    fn history_limit() {
+        let blocks = make_some_blocks::<4>();
+        let mut space = Space::empty_positive(1, 1, 1);
+        let mut history = SpaceHistory::new(2);
+        for block in &blocks {
+            history
+                .execute(
+                    &mut space,
+                    &SpaceTransaction::set_cube([0, 0, 0], None, Some(block.clone())),
+                )
+                .unwrap();
+        }
+
+        assert!(history.undo(&mut space).unwrap());
+        assert!(history.undo(&mut space).unwrap());
+        assert!(!history.undo(&mut space).unwrap());
+        // The two oldest edits were forgotten.
+        assert_eq!(space[[0, 0, 0]], blocks[1]);
+    }
+
+    #[test]
+    fn history_undo_fails_if_space_changed() {
+        let [b1, b2] = make_some_blocks();
+        let mut space = Space::empty_positive(1, 1, 1);
+        let mut history = SpaceHistory::new(10);
+        history
+            .execute(
+                &mut space,
+                &SpaceTransaction::set_cube([0, 0, 0], None, Some(b1)),
+            )
+            .unwrap();
+        space.set([0, 0, 0], &b2).unwrap();
+
+        assert!(matches!(
+            history.undo(&mut space),
+            Err(ExecuteError::Check(_))
+        ));
+        assert_eq!(space[[0, 0, 0]], b2);
+        assert!(history.can_undo());
+    }
 }
@@ -5,16 +5,23 @@
 //!
 //! Note that some sub-modules have their own test modules.
 
-use cgmath::EuclideanSpace as _;
+use std::borrow::Cow;
+
+use cgmath::{EuclideanSpace as _, InnerSpace as _, Point3, Vector3};
 use indoc::indoc;
 
-use crate::block::{Block, BlockDef, BlockDefTransaction, EvalBlockError, Primitive, AIR};
+use crate::block::{
+    Block, BlockDef, BlockDefTransaction, EvalBlockError, EvaluatedBlock, Modifier, Primitive,
+    TickAction, AIR,
+};
 use crate::content::make_some_blocks;
 use crate::drawing::VoxelBrush;
 use crate::listen::Sink;
-use crate::math::{GridPoint, Rgba};
+use crate::math::{Face6, GridCoordinate, GridPoint, GridRotation, GridVector, Rgb, Rgba};
+use crate::space::contents::ContentsArray;
 use crate::space::{
-    Grid, LightPhysics, PackedLight, SetCubeError, Space, SpaceChange, SpacePhysics,
+    BlockIndex, Connectivity, ContentsRunsError, Gravity, Grid, GridArray, LightPhysics,
+    PackedLight, SetCubeError, Sky, Space, SpaceChange, SpacePhysics,
 };
 use crate::time::Tick;
 use crate::universe::{RefError, Universe, UniverseIndex as _, UniverseTransaction};
@@ -38,6 +45,62 @@ fn initial_state_consistency() {
         .consistency_check();
 }
 
+#[test]
+fn builder_filled_with() {
+    let [block] = make_some_blocks();
+    let grid = Grid::new([1, 2, 3], [4, 5, 6]);
+    let space = Space::builder(grid)
+        .filled_with(block.clone())
+        .build()
+        .unwrap();
+    space.consistency_check();
+    assert_eq!(space[[1, 2, 3]], block);
+    assert_eq!(space[[4, 6, 8]], block);
+    let counts: Vec<(&Block, usize)> = space
+        .block_data()
+        .iter()
+        .map(|data| (data.block(), data.count))
+        .collect();
+    assert_eq!(counts, vec![(&block, grid.volume())]);
+}
+
+#[test]
+fn builder_fill_fn() {
+    let [block] = make_some_blocks();
+    let grid = Grid::new([0, 0, 0], [3, 3, 3]);
+    let space = Space::builder(grid)
+        .fill_fn(|cube| if cube.y == 0 { block.clone() } else { AIR })
+        .build()
+        .unwrap();
+    space.consistency_check();
+    assert_eq!(space[[0, 0, 0]], block);
+    assert_eq!(space[[2, 0, 1]], block);
+    assert_eq!(space[[1, 1, 1]], AIR);
+    assert_eq!(space[[2, 2, 2]], AIR);
+    assert_eq!(space.count_blocks(grid, |ev| ev.visible), 9);
+    assert_eq!(space.count_blocks(grid, |ev| !ev.visible), 18);
+}
+
+#[test]
+#[should_panic(expected = "build_empty() used with contents; use build() instead")]
+fn builder_build_empty_with_contents() {
+    let [block] = make_some_blocks();
+    Space::builder(Grid::new([0, 0, 0], [1, 1, 1]))
+        .filled_with(block)
+        .build_empty();
+}
+
+/// A builder with no contents specified builds the same as `build_empty()`.
+#[test]
+fn builder_build_without_contents() {
+    let grid = Grid::new([0, 0, 0], [2, 2, 2]);
+    let space = Space::builder(grid).build().unwrap();
+    space.consistency_check();
+    assert_eq!(space.block_data().len(), 1);
+    assert_eq!(space.block_data()[0].block(), &AIR);
+    assert_eq!(space.block_data()[0].count, grid.volume());
+}
+
 /// set() returns Ok when the cube was changed or already equal.
 #[test]
 fn set_success() {
@@ -72,6 +135,140 @@ fn set_failure_out_of_bounds() {
     space.consistency_check(); // bonus testing
 }
 
+#[test]
+fn set_if_matching() {
+    let [first, second] = make_some_blocks();
+    let mut space = Space::empty_positive(1, 1, 1);
+    let pt = GridPoint::origin();
+    assert_eq!(Ok(true), space.set_if(pt, &AIR, &first));
+    assert_eq!(&space[pt], &first);
+    assert_eq!(Ok(true), space.set_if(pt, &first, &second));
+    assert_eq!(&space[pt], &second);
+    // Writing the same block still counts as a match.
+    assert_eq!(Ok(true), space.set_if(pt, &second, &second));
+    assert_eq!(&space[pt], &second);
+
+    space.consistency_check(); // bonus testing
+}
+
+#[test]
+fn set_if_mismatch() {
+    let [first, second] = make_some_blocks();
+    let mut space = Space::empty_positive(1, 1, 1);
+    let pt = GridPoint::origin();
+    let sink = Sink::new();
+    space.listen(sink.listener());
+
+    assert_eq!(Ok(false), space.set_if(pt, &first, &second));
+    assert_eq!(&space[pt], &AIR);
+    assert_eq!(sink.drain(), vec![]);
+
+    space.consistency_check(); // bonus testing
+}
+
+#[test]
+fn set_if_out_of_bounds() {
+    let [block] = make_some_blocks();
+    let pt = GridPoint::new(1, 0, 0);
+    let space_bounds = Grid::new([0, 0, 0], [1, 1, 1]);
+    let mut space = Space::empty(space_bounds);
+
+    assert_eq!(
+        space.set_if(pt, &AIR, &block),
+        Err(SetCubeError::OutOfBounds {
+            modification: Grid::single_cube(pt),
+            space_bounds,
+        })
+    );
+}
+
+#[test]
+fn replace_block_definition_with_new_block() {
+    let [old, new, other] = make_some_blocks();
+    let mut space = Space::empty_positive(3, 1, 1);
+    space.set([0, 0, 0], &old).unwrap();
+    space.set([1, 0, 0], &other).unwrap();
+    space.set([2, 0, 0], &old).unwrap();
+    let old_index = space.get_block_index([0, 0, 0]).unwrap();
+    let sink = Sink::new();
+    space.listen(sink.listener());
+
+    assert_eq!(space.replace_block_definition(&old, new.clone()), Ok(true));
+    assert_eq!(&space[[0, 0, 0]], &new);
+    assert_eq!(&space[[1, 0, 0]], &other);
+    assert_eq!(&space[[2, 0, 0]], &new);
+    // The index was reused rather than each cube being individually set.
+    assert_eq!(space.get_block_index([0, 0, 0]), Some(old_index));
+    assert!(sink.drain().contains(&SpaceChange::Number(old_index)));
+    assert_eq!(
+        space.count_blocks(space.grid(), |ev| ev.color == old.evaluate().unwrap().color),
+        0
+    );
+    assert_eq!(
+        space.count_blocks(space.grid(), |ev| ev.color == new.evaluate().unwrap().color),
+        2
+    );
+
+    space.consistency_check(); // bonus testing
+}
+
+/// Changing only the color of a block still updates the light around it, since
+/// light is reflected off of blocks.
+#[test]
+fn replace_block_definition_updates_light_for_color() {
+    let old = Block::from(Rgba::new(1.0, 0.0, 0.0, 1.0));
+    let new = Block::from(Rgba::new(0.0, 0.0, 1.0, 1.0));
+    let mut space = Space::empty_positive(3, 1, 1);
+    space.set([1, 0, 0], &old).unwrap();
+    space.evaluate_light(0, |_| {});
+    assert_eq!(space.light_update_queue.len(), 0);
+
+    assert_eq!(space.replace_block_definition(&old, new), Ok(true));
+    assert!(space.light_update_queue.len() > 0);
+}
+
+/// If the new block has a tick action and the old one did not, its cubes are ticked.
+#[test]
+fn replace_block_definition_schedules_ticks() {
+    let [old, after_tick] = make_some_blocks();
+    let new = Block::builder()
+        .color(Rgba::WHITE)
+        .tick_action(Some(TickAction::Replace(after_tick.clone())))
+        .build();
+    let mut space = Space::empty_positive(2, 1, 1);
+    space.fill_uniform(space.grid(), &old).unwrap();
+
+    assert_eq!(space.replace_block_definition(&old, new), Ok(true));
+    let (info, _) = space.step(None, Tick::arbitrary());
+    assert_eq!(info.cube_ticks, 2);
+    assert_eq!(space[[0, 0, 0]], after_tick);
+    assert_eq!(space[[1, 0, 0]], after_tick);
+}
+
+#[test]
+fn replace_block_definition_merges_with_existing_block() {
+    let [old, new] = make_some_blocks();
+    let mut space = Space::empty_positive(3, 1, 1);
+    space.set([0, 0, 0], &old).unwrap();
+    space.set([1, 0, 0], &new).unwrap();
+    space.set([2, 0, 0], &old).unwrap();
+    let new_index = space.get_block_index([1, 0, 0]).unwrap();
+
+    assert_eq!(space.replace_block_definition(&old, new.clone()), Ok(true));
+    for x in 0..3 {
+        assert_eq!(&space[[x, 0, 0]], &new);
+        assert_eq!(space.get_block_index([x, 0, 0]), Some(new_index));
+    }
+    assert_eq!(
+        space.count_blocks(space.grid(), |ev| ev.color == new.evaluate().unwrap().color),
+        3
+    );
+    // The old block is entirely gone, so replacing it again does nothing.
+    assert_eq!(space.replace_block_definition(&old, AIR), Ok(false));
+
+    space.consistency_check(); // bonus testing
+}
+
 /// This test case should also cover `RefError::Gone`.
 #[test]
 fn set_failure_borrow() {
@@ -112,6 +309,30 @@ fn set_failure_too_many() {
     space.consistency_check(); // bonus testing
 }
 
+/// Using more than 256 block indices switches to wider storage without disturbing
+/// the existing contents.
+#[test]
+fn set_many_blocks_widens_contents() {
+    const N: usize = 300;
+    let blocks = make_some_blocks::<N>();
+    let mut space = Space::empty_positive(N as GridCoordinate, 1, 1);
+    for (i, block) in blocks.iter().enumerate() {
+        // Index 0 is air, so the index of block i is i + 1.
+        assert_eq!(
+            matches!(space.contents, ContentsArray::U8(_)),
+            i < 256,
+            "wrong storage before setting block {i}"
+        );
+        space.set([i as GridCoordinate, 0, 0], block).unwrap();
+    }
+    assert!(matches!(space.contents, ContentsArray::U16(_)));
+
+    for (i, block) in blocks.iter().enumerate() {
+        assert_eq!(&space[[i as GridCoordinate, 0, 0]], block);
+    }
+    space.consistency_check(); // bonus testing
+}
+
 #[test]
 fn set_error_format() {
     assert_eq!(
@@ -224,6 +445,28 @@ fn change_listener() {
     assert_eq!(sink.drain(), vec![]);
 }
 
+#[test]
+fn change_listener_region() {
+    let [block] = make_some_blocks();
+    let mut space = Space::empty_positive(3, 1, 1);
+    let sink = Sink::new();
+    space.listen_region(Grid::new((0, 0, 0), (1, 1, 1)), sink.listener());
+
+    // Changes outside the region are filtered out, but not block index changes.
+    assert_eq!(Ok(true), space.set((2, 0, 0), &block));
+    assert_eq!(sink.drain(), vec![SpaceChange::Number(1)]);
+
+    // Changes inside the region are delivered.
+    assert_eq!(Ok(true), space.set((0, 0, 0), &block));
+    assert_eq!(
+        sink.drain(),
+        vec![
+            SpaceChange::Lighting(GridPoint::new(0, 0, 0)),
+            SpaceChange::Block(GridPoint::new(0, 0, 0)),
+        ],
+    );
+}
+
 #[test]
 fn extract_out_of_bounds() {
     let [block_0, block_1] = make_some_blocks();
@@ -258,6 +501,159 @@ fn fill_out_of_bounds() {
     );
 }
 
+/// Filling a zero-volume region succeeds and has no effect, even out of bounds.
+#[test]
+fn fill_empty_region() {
+    let [block] = make_some_blocks();
+    let mut space = Space::empty_positive(2, 1, 1);
+    let sink = Sink::new();
+    space.listen(sink.listener());
+
+    for region in [
+        Grid::EMPTY,
+        Grid::new([1, 0, 0], [0, 1, 1]),
+        Grid::new([100, 0, 0], [0, 5, 5]),
+    ] {
+        space.fill(region, |_| Some(&block)).unwrap();
+        space.fill_uniform(region, &block).unwrap();
+        space
+            .fill_replace(region, |_, _| Some(Cow::Borrowed(&block)))
+            .unwrap();
+    }
+
+    assert_eq!(sink.drain(), vec![]);
+    space.consistency_check();
+    assert_eq!(space.count_blocks(space.grid(), |ev| ev.visible), 0);
+}
+
+/// A zero-volume space may be filled as a whole, which does nothing.
+#[test]
+fn fill_uniform_zero_volume_space() {
+    let [block] = make_some_blocks();
+    let mut space = Space::empty(Grid::new([0, 0, 0], [4, 0, 4]));
+
+    space.fill_uniform(space.grid(), &block).unwrap();
+
+    space.consistency_check();
+    assert!(space.block_data().is_empty());
+}
+
+#[test]
+fn resized_grow() {
+    let [b0, b1] = make_some_blocks();
+    let mut space = Space::builder(Grid::new([0, 0, 0], [2, 2, 1]))
+        .sky_color(Rgb::new(0.5, 0.5, 1.0))
+        .spawn_position(Point3::new(1.0, 1.5, 0.5))
+        .build_empty();
+    space.set([0, 0, 0], &b0).unwrap();
+    space.set([1, 1, 0], &b1).unwrap();
+
+    let new_grid = Grid::new([-1, 0, 0], [4, 3, 2]);
+    let resized = space.resized(new_grid).unwrap();
+
+    resized.consistency_check();
+    assert_eq!(resized.grid(), new_grid);
+    assert_eq!(resized.physics(), space.physics());
+    assert_eq!(resized.spawn(), space.spawn());
+    for cube in new_grid.interior_iter() {
+        let expected = if space.grid().contains_cube(cube) {
+            &space[cube]
+        } else {
+            &AIR
+        };
+        assert_eq!(&resized[cube], expected, "{cube:?}");
+    }
+    assert_eq!(resized[[1, 1, 0]], b1);
+}
+
+#[test]
+fn resized_crop() {
+    let [b0, b1] = make_some_blocks();
+    let mut space = Space::empty_positive(3, 1, 1);
+    space.set([0, 0, 0], &b0).unwrap();
+    space.set([2, 0, 0], &b1).unwrap();
+
+    let resized = space.resized(Grid::new([1, 0, 0], [2, 1, 1])).unwrap();
+
+    resized.consistency_check();
+    assert_eq!(resized[[1, 0, 0]], AIR);
+    assert_eq!(resized[[2, 0, 0]], b1);
+    assert_eq!(resized.distinct_blocks().len(), 2);
+    assert!(!resized.distinct_blocks().contains(&b0));
+
+    // Entirely outside the original bounds
+    let disjoint = space.resized(Grid::new([10, 0, 0], [1, 1, 1])).unwrap();
+    disjoint.consistency_check();
+    assert_eq!(disjoint[[10, 0, 0]], AIR);
+}
+
+#[test]
+fn fill_replace_receives_previous_block() {
+    let [b0, b1, b2] = make_some_blocks();
+    let grid = Grid::new([0, 0, 0], [3, 2, 1]);
+    let mut space = Space::empty(grid);
+    space.set([0, 0, 0], &b0).unwrap();
+    space.set([1, 0, 0], &b1).unwrap();
+    space.set([2, 1, 0], &b0).unwrap();
+    let before: Vec<(GridPoint, Block)> = grid
+        .interior_iter()
+        .map(|cube| (cube, space[cube].clone()))
+        .collect();
+
+    let mut seen = Vec::new();
+    space
+        .fill_replace(grid, |cube, old_block| {
+            seen.push((cube, old_block.clone()));
+            if *old_block == b0 {
+                Some(Cow::Borrowed(&b2))
+            } else {
+                None
+            }
+        })
+        .unwrap();
+
+    space.consistency_check();
+    assert_eq!(seen, before);
+    for (cube, old_block) in before {
+        let expected = if old_block == b0 { &b2 } else { &old_block };
+        assert_eq!(&space[cube], expected, "{:?}", cube);
+    }
+}
+
+#[test]
+fn fill_replace_none_leaves_unchanged() {
+    let [block] = make_some_blocks();
+    let mut space = Space::empty_positive(2, 2, 2);
+    space.set([1, 1, 1], &block).unwrap();
+    let sink = Sink::new();
+    space.listen(sink.listener());
+
+    space.fill_replace(space.grid(), |_, _| None).unwrap();
+
+    assert_eq!(sink.drain(), vec![]);
+    assert_eq!(space[(1, 1, 1)], block);
+    assert_eq!(space[(0, 0, 0)], AIR);
+}
+
+#[test]
+fn fill_replace_out_of_bounds() {
+    let mut space = Space::empty_positive(2, 1, 1);
+    let fill_grid = Grid::new((1, 0, 0), (1, 2, 1));
+    let mut called = false;
+    let result = space.fill_replace(fill_grid, |_, _| {
+        called = true;
+        None
+    });
+    assert_eq!(
+        result,
+        Err(SetCubeError::OutOfBounds {
+            modification: fill_grid,
+            space_bounds: Grid::new([0, 0, 0], [2, 1, 1])
+        })
+    );
+    assert!(!called);
+}
+
 /// Test filling an entire space with one block using [`Space::fill`].
 #[test]
 fn fill_entire_space() {
@@ -290,6 +686,210 @@ fn fill_uniform_entire_space() {
     }
 }
 
+#[test]
+fn flood_fill_rectangle() {
+    let [outside, inside, paint] = make_some_blocks();
+    let mut space = Space::empty_positive(6, 5, 1);
+    space.fill_uniform(space.grid(), &outside).unwrap();
+    let rectangle = Grid::from_lower_upper([1, 1, 0], [5, 4, 1]);
+    space.fill_uniform(rectangle, &inside).unwrap();
+
+    assert_eq!(
+        space.flood_fill([2, 2, 0], &paint, Connectivity::Face),
+        Ok(rectangle.volume())
+    );
+
+    space.consistency_check();
+    for cube in space.grid().interior_iter() {
+        let expected = if rectangle.contains_cube(cube) {
+            &paint
+        } else {
+            &outside
+        };
+        assert_eq!(&space[cube], expected, "{cube:?}");
+    }
+
+    // Filling with the same block is a no-op.
+    assert_eq!(
+        space.flood_fill([2, 2, 0], &paint, Connectivity::Face),
+        Ok(0)
+    );
+    assert!(matches!(
+        space.flood_fill([6, 0, 0], &paint, Connectivity::Face),
+        Err(SetCubeError::OutOfBounds { .. })
+    ));
+}
+
+#[test]
+fn flood_fill_diagonal_gap() {
+    let [line, paint] = make_some_blocks();
+    // Two cubes which touch only at an edge:
+    //   .#
+    //   #.
+    let make_space = || {
+        let mut space = Space::empty_positive(2, 2, 1);
+        space.set([0, 0, 0], &line).unwrap();
+        space.set([1, 1, 0], &line).unwrap();
+        space
+    };
+
+    let mut space = make_space();
+    assert_eq!(
+        space.flood_fill([0, 0, 0], &paint, Connectivity::Face),
+        Ok(1)
+    );
+    assert_eq!(space[[1, 1, 0]], line);
+
+    let mut space = make_space();
+    assert_eq!(
+        space.flood_fill([0, 0, 0], &paint, Connectivity::Vertex),
+        Ok(2)
+    );
+    assert_eq!(space[[1, 1, 0]], paint);
+    assert_eq!(space[[1, 0, 0]], AIR);
+}
+
+#[test]
+fn copy_from_rotated_non_cubical() {
+    let [b0, b1, b2] = make_some_blocks();
+    let mut src = Space::empty_positive(3, 1, 2);
+    src.set([0, 0, 0], &b0).unwrap();
+    src.set([1, 0, 0], &b1).unwrap();
+    src.set([2, 0, 0], &b2).unwrap();
+    src.set([0, 0, 1], &b2).unwrap();
+    let mut dst = Space::empty_positive(5, 5, 5);
+
+    dst.copy_from(
+        GridPoint::new(1, 1, 1),
+        &src,
+        src.grid(),
+        GridRotation::CLOCKWISE,
+    )
+    .unwrap();
+
+    dst.consistency_check();
+    // The +X axis of the source now points along +Z, and +Z along -X.
+    assert_eq!(dst[(2, 1, 1)], b0);
+    assert_eq!(dst[(2, 1, 2)], b1);
+    assert_eq!(dst[(2, 1, 3)], b2);
+    assert_eq!(dst[(1, 1, 1)], b2);
+    assert_eq!(dst[(1, 1, 2)], AIR);
+    assert_eq!(
+        dst.grid()
+            .interior_iter()
+            .filter(|&cube| dst[cube] != AIR)
+            .count(),
+        4
+    );
+}
+
+#[test]
+fn copy_from_overwrites_and_shares_indices() {
+    let [b0, b1] = make_some_blocks();
+    let mut src = Space::empty_positive(2, 1, 1);
+    src.fill_uniform(src.grid(), &b0).unwrap();
+    let mut dst = Space::empty_positive(4, 1, 1);
+    dst.fill_uniform(dst.grid(), &b1).unwrap();
+
+    dst.copy_from(
+        GridPoint::new(1, 0, 0),
+        &src,
+        src.grid(),
+        GridRotation::IDENTITY,
+    )
+    .unwrap();
+
+    dst.consistency_check();
+    assert_eq!(dst[(0, 0, 0)], b1);
+    assert_eq!(dst[(1, 0, 0)], b0);
+    assert_eq!(dst[(2, 0, 0)], b0);
+    assert_eq!(dst[(3, 0, 0)], b1);
+    assert_eq!(
+        dst.get_block_index([1, 0, 0]),
+        dst.get_block_index([2, 0, 0])
+    );
+}
+
+#[test]
+fn copy_from_out_of_bounds() {
+    let [block] = make_some_blocks();
+    let mut src = Space::empty_positive(3, 1, 1);
+    src.fill_uniform(src.grid(), &block).unwrap();
+    let mut dst = Space::empty_positive(2, 2, 2);
+    let sink = Sink::new();
+    dst.listen(sink.listener());
+
+    // The region is too long to fit.
+    let result = dst.copy_from(
+        GridPoint::new(0, 0, 0),
+        &src,
+        src.grid(),
+        GridRotation::IDENTITY,
+    );
+
+    assert_eq!(
+        result,
+        Err(SetCubeError::OutOfBounds {
+            modification: Grid::new([0, 0, 0], [3, 1, 1]),
+            space_bounds: dst.grid(),
+        })
+    );
+    // Nothing was changed.
+    assert_eq!(sink.drain(), vec![]);
+    assert_eq!(dst.distinct_blocks(), vec![AIR]);
+
+    // Source region out of bounds is also rejected.
+    assert_eq!(
+        dst.copy_from(
+            GridPoint::new(0, 0, 0),
+            &src,
+            Grid::new([-1, 0, 0], [1, 1, 1]),
+            GridRotation::IDENTITY,
+        ),
+        Err(SetCubeError::OutOfBounds {
+            modification: Grid::new([-1, 0, 0], [1, 1, 1]),
+            space_bounds: src.grid(),
+        })
+    );
+}
+
+#[test]
+fn extract_then_paste_array_round_trip() {
+    let blocks = make_some_blocks::<3>();
+    let mut space = Space::empty_positive(6, 3, 3);
+    let src_region = Grid::new([0, 0, 0], [2, 3, 1]);
+    space
+        .fill(src_region, |p| Some(&blocks[p.y as usize]))
+        .unwrap();
+
+    let array = space.extract(src_region, |_, data, _| data.block().clone());
+    space.paste_array(GridPoint::new(3, 0, 2), &array).unwrap();
+
+    for cube in src_region.interior_iter() {
+        assert_eq!(space[cube + Vector3::new(3, 0, 2)], space[cube], "{cube:?}");
+    }
+    // Nothing else was touched.
+    assert_eq!(space[(2, 0, 2)], AIR);
+    assert_eq!(space[(5, 0, 2)], AIR);
+}
+
+#[test]
+fn paste_array_out_of_bounds() {
+    let [block] = make_some_blocks();
+    let array = GridArray::from_fn(Grid::new([0, 0, 0], [3, 1, 1]), |_| block.clone());
+    let mut space = Space::empty_positive(2, 2, 2);
+
+    assert_eq!(
+        space.paste_array(GridPoint::new(0, 1, 0), &array),
+        Err(SetCubeError::OutOfBounds {
+            modification: Grid::new([0, 1, 0], [3, 1, 1]),
+            space_bounds: space.grid(),
+        })
+    );
+    // Nothing was changed.
+    assert_eq!(space.distinct_blocks(), vec![AIR]);
+}
+
 /// There was a bug triggered when the last instance of a block was replaced with
 /// a block already in the space. This specifically runs a consistency check in that
 /// case.
@@ -334,6 +934,160 @@ fn listens_to_block_changes() {
     assert_eq!(space.get_evaluated((0, 0, 0)), &new_evaluated);
 }
 
+#[test]
+fn gc_block_indices_compacts() {
+    let [block_0, block_1, block_2] = make_some_blocks();
+    let mut space = Space::empty_positive(4, 1, 1);
+    space.set([0, 0, 0], &block_0).unwrap();
+    space.set([1, 0, 0], &block_1).unwrap();
+    space.set([2, 0, 0], &block_2).unwrap();
+    // Free index 1 (block_0), then index 0 (AIR).
+    space.set([0, 0, 0], &AIR).unwrap();
+    space.set([0, 0, 0], &block_2).unwrap();
+    space.set([3, 0, 0], &block_2).unwrap();
+    assert_eq!(space.block_data()[0].block(), &AIR);
+    assert_eq!(space.get_block_index([1, 0, 0]), Some(2));
+    assert_eq!(space.get_block_index([2, 0, 0]), Some(3));
+    let sink = Sink::new();
+    space.listen(sink.listener());
+
+    let remapping = space.gc_block_indices();
+
+    space.consistency_check();
+    assert!(!remapping.is_identity());
+    // Index 0 is AIR and stays put even though it is unused.
+    assert_eq!(remapping.get(0), Some(0));
+    assert_eq!(remapping.get(1), None);
+    assert_eq!(remapping.get(2), Some(1));
+    assert_eq!(remapping.get(3), Some(2));
+    assert_eq!(remapping.get(4), None);
+    assert_eq!(space.block_data().len(), 3);
+    assert_eq!(space[(0, 0, 0)], block_2);
+    assert_eq!(space[(1, 0, 0)], block_1);
+    assert_eq!(space[(2, 0, 0)], block_2);
+    assert_eq!(space[(3, 0, 0)], block_2);
+    assert_eq!(space.get_block_index([1, 0, 0]), Some(1));
+    assert_eq!(sink.drain(), vec![SpaceChange::EveryBlock]);
+}
+
+#[test]
+fn gc_block_indices_no_op() {
+    let [block_0] = make_some_blocks();
+    let mut space = Space::empty_positive(2, 1, 1);
+    space.set([0, 0, 0], &block_0).unwrap();
+    let sink = Sink::new();
+    space.listen(sink.listener());
+
+    let remapping = space.gc_block_indices();
+
+    space.consistency_check();
+    assert!(remapping.is_identity());
+    assert_eq!(remapping.get(0), Some(0));
+    assert_eq!(remapping.get(1), Some(1));
+    assert_eq!(sink.drain(), vec![]);
+}
+
+/// Block change notifications must use the new index after GC.
+#[test]
+fn gc_block_indices_then_block_changes() {
+    let mut universe = Universe::new();
+    let block_def_ref = universe.insert_anonymous(BlockDef::new(Block::from(Rgba::WHITE)));
+    let indirect = Block::from_primitive(Primitive::Indirect(block_def_ref.clone()));
+    let [other] = make_some_blocks();
+
+    // Light disabled so that the only notifications are from the block change.
+    let mut space = Space::builder(Grid::new([0, 0, 0], [3, 1, 1]))
+        .physics(SpacePhysics::DEFAULT_FOR_BLOCK)
+        .build_empty();
+    space.set((0, 0, 0), &other).unwrap();
+    space.set((1, 0, 0), indirect).unwrap();
+    space.set((0, 0, 0), AIR).unwrap();
+    assert_eq!(space.get_block_index([1, 0, 0]), Some(2));
+    space.gc_block_indices();
+    space.consistency_check();
+    assert_eq!(space.get_block_index([1, 0, 0]), Some(1));
+    let sink = Sink::new();
+    space.listen(sink.listener());
+
+    let new_block = Block::from(Rgba::BLACK);
+    let new_evaluated = new_block.evaluate().unwrap();
+    block_def_ref
+        .execute(&BlockDefTransaction::overwrite(new_block))
+        .unwrap();
+    let (_, _) = space.step(None, Tick::arbitrary());
+    assert_eq!(sink.drain(), vec![SpaceChange::BlockValue(1)]);
+    assert_eq!(space.get_evaluated((1, 0, 0)), &new_evaluated);
+}
+
+/// A 4×4×4 space with its lower half filled with two kinds of blocks.
+fn half_filled_space() -> (Space, [Block; 2]) {
+    let blocks = make_some_blocks();
+    let mut space = Space::empty_positive(4, 4, 4);
+    space
+        .fill_uniform(Grid::new([0, 0, 0], [4, 2, 4]), &blocks[0])
+        .unwrap();
+    space
+        .fill_uniform(Grid::new([0, 0, 0], [1, 1, 4]), &blocks[1])
+        .unwrap();
+    (space, blocks)
+}
+
+#[test]
+fn region_is_empty() {
+    let (mut space, _) = half_filled_space();
+    assert!(!space.region_is_empty(space.grid()));
+    assert!(!space.region_is_empty(Grid::new([3, 1, 3], [1, 1, 1])));
+    assert!(space.region_is_empty(Grid::new([0, 2, 0], [4, 2, 4])));
+    assert!(space.region_is_empty(Grid::new([-10, 2, -10], [20, 20, 20])));
+    assert!(space.region_is_empty(Grid::new([10, 10, 10], [1, 1, 1])));
+    assert!(!space.region_is_empty(Grid::new([-10, -10, -10], [20, 20, 20])));
+
+    // Space with no AIR at all
+    space
+        .fill_uniform(space.grid(), &make_some_blocks::<1>()[0])
+        .unwrap();
+    assert!(!space.region_is_empty(Grid::new([3, 3, 3], [1, 1, 1])));
+    assert!(space.region_is_empty(Grid::new([4, 3, 3], [1, 1, 1])));
+}
+
+#[test]
+fn count_blocks() {
+    let (space, [_, block1]) = half_filled_space();
+    let color1 = block1.evaluate().unwrap().color;
+    assert_eq!(space.count_blocks(space.grid(), |ev| ev.visible), 32);
+    assert_eq!(space.count_blocks(space.grid(), |ev| !ev.visible), 32);
+    assert_eq!(space.count_blocks(space.grid(), |ev| ev.color == color1), 4);
+    // Outside the bounds counts as AIR
+    let region = Grid::new([0, 0, 0], [4, 6, 4]);
+    assert_eq!(space.count_blocks(region, |ev| ev.visible), 32);
+    assert_eq!(space.count_blocks(region, |ev| !ev.visible), 64);
+    assert_eq!(
+        space.count_blocks(Grid::new([10, 0, 0], [2, 2, 2]), |ev| !ev.visible),
+        8
+    );
+}
+
+#[test]
+fn iter_region_matches_individual_lookups() {
+    let (mut space, _) = half_filled_space();
+    space.evaluate_light(0, |_| {});
+    let region = Grid::new([-1, 1, 2], [3, 6, 3]);
+
+    let actual: Vec<(GridPoint, &Block, PackedLight)> = space.iter_region(region).collect();
+    let expected: Vec<(GridPoint, &Block, PackedLight)> = region
+        .interior_iter()
+        .filter(|&cube| space.grid().contains_cube(cube))
+        .map(|cube| (cube, &space[cube], space.get_lighting(cube)))
+        .collect();
+    assert_eq!(actual.len(), 2 * 3 * 2);
+    assert_eq!(actual, expected);
+
+    assert_eq!(
+        space.iter_region(Grid::new([10, 0, 0], [2, 2, 2])).count(),
+        0
+    );
+}
+
 #[test]
 fn space_debug() {
     let mut space = Space::empty_positive(1, 1, 1);
@@ -368,8 +1122,12 @@ fn space_debug() {
                     },
                 ],
                 physics: SpacePhysics {
-                    gravity: (+0.000, -20.000, +0.000),
-                    sky_color: Rgb(0.8962694, 0.8962694, 1.0),
+                    gravity: Uniform(
+                        (+0.000, -20.000, +0.000),
+                    ),
+                    sky: Uniform(
+                        Rgb(0.8962694, 0.8962694, 1.0),
+                    ),
                     light: None,
                 },
                 behaviors: BehaviorSet([]),
@@ -413,24 +1171,95 @@ fn set_physics_light_rays() {
 
     // This is the set_physics we're actually testing
     space.set_physics(SpacePhysics {
-        light: LightPhysics::Rays {
-            maximum_distance: 10,
-        },
+        light: LightPhysics::rays().maximum_distance(10).build(),
         ..SpacePhysics::default()
     });
 
     assert_eq!(space.lighting.len(), 2);
-    assert_eq!(space.get_lighting([0, 0, 0]), space.packed_sky_color);
+    assert_eq!(space.get_lighting([0, 0, 0]), space.packed_sky_color.within);
     assert_eq!(space.get_lighting([1, 0, 0]), PackedLight::OPAQUE);
     assert_eq!(space.light_update_queue.len(), 1);
     // TODO: test what change notifications are sent
 }
 
+#[test]
+fn set_physics_notification() {
+    let mut space = Space::empty_positive(1, 1, 1);
+    let sink = Sink::new();
+    space.listen(sink.listener());
+
+    // No-op change
+    space.set_physics(space.physics().clone());
+    assert_eq!(sink.drain(), vec![]);
+
+    // Sky change only
+    space.set_physics(SpacePhysics {
+        sky: Sky::Uniform(Rgb::new(1.0, 0.5, 0.0)),
+        ..space.physics().clone()
+    });
+    assert_eq!(sink.drain(), vec![SpaceChange::Physics]);
+}
+
+#[test]
+fn set_spawn_notification() {
+    let mut space = Space::empty_positive(1, 1, 1);
+    let sink = Sink::new();
+    space.listen(sink.listener());
+
+    // No-op change
+    space.set_spawn(space.spawn().clone());
+    assert_eq!(sink.drain(), vec![]);
+
+    let mut spawn = space.spawn().clone();
+    spawn.set_eye_position([0.5, 0.5, 0.5]);
+    space.set_spawn(spawn.clone());
+    assert_eq!(sink.drain(), vec![SpaceChange::Spawn]);
+    assert_eq!(space.spawn(), &spawn);
+}
+
+#[test]
+fn gravity_radial_points_toward_center() {
+    let physics = SpacePhysics {
+        gravity: Gravity::Radial {
+            center: Point3::new(notnan!(1.0), notnan!(2.0), notnan!(3.0)),
+            strength: notnan!(10.0),
+        },
+        ..SpacePhysics::default()
+    };
+    let center = Point3::new(1.0, 2.0, 3.0);
+    for point in [
+        Point3::new(1.0, 12.0, 3.0),
+        Point3::new(-4.0, 2.0, 3.0),
+        Point3::new(5.0, -1.0, 3.0),
+        Point3::new(0.0, 0.0, 0.0),
+    ] {
+        let gravity = physics.gravity_at(point);
+        let expected_direction = (center - point).normalize();
+        assert!(
+            (gravity.magnitude() - 10.0).abs() < 1e-9,
+            "{point:?} -> {gravity:?}"
+        );
+        assert!(
+            gravity.normalize().dot(expected_direction) > 1.0 - 1e-9,
+            "{point:?} -> {gravity:?}"
+        );
+    }
+    assert_eq!(physics.gravity_at(center), Vector3::new(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn gravity_default_is_uniform() {
+    let physics = SpacePhysics::default();
+    for point in [Point3::new(0.0, 0.0, 0.0), Point3::new(100.0, -50.0, 7.0)] {
+        assert_eq!(physics.gravity_at(point), Vector3::new(0.0, -20.0, 0.0));
+    }
+}
+
 #[test]
 fn block_tick_action() {
     let [mut block1, block2] = make_some_blocks();
     if let Primitive::Atom(attributes, _) = block1.primitive_mut() {
-        attributes.tick_action = Some(VoxelBrush::single(block2.clone()));
+        attributes.tick_action = Some(VoxelBrush::single(block2.clone()).into());
     } else {
         panic!();
     }
@@ -444,3 +1273,303 @@ fn block_tick_action() {
 
     assert_eq!(&space[[0, 0, 0]], &block2);
 }
+
+/// Two cubes whose tick actions write different blocks to the same cube conflict.
+/// One of them (deterministically, the lower-coordinate one) should win, and the
+/// other should be retried on the next step.
+#[test]
+fn block_tick_action_conflict() {
+    let [mut left, mut right, left_moved, right_moved] = make_some_blocks();
+    for (block, brush) in [
+        (
+            &mut left,
+            VoxelBrush::new(vec![([0, 0, 0], AIR), ([1, 0, 0], left_moved.clone())]),
+        ),
+        (
+            &mut right,
+            VoxelBrush::new(vec![([0, 0, 0], AIR), ([-1, 0, 0], right_moved.clone())]),
+        ),
+    ] {
+        if let Primitive::Atom(attributes, _) = block.primitive_mut() {
+            attributes.tick_action = Some(brush.into());
+        } else {
+            panic!();
+        }
+    }
+
+    let mut space = Space::empty_positive(3, 1, 1);
+    space.set([0, 0, 0], &left).unwrap();
+    space.set([2, 0, 0], &right).unwrap();
+
+    let (info, _) = space.step(None, Tick::arbitrary());
+    assert_eq!((info.cube_ticks, info.cube_ticks_deferred), (1, 1));
+    assert_eq!(&space[[0, 0, 0]], &AIR);
+    assert_eq!(&space[[1, 0, 0]], &left_moved);
+    assert_eq!(&space[[2, 0, 0]], &right);
+
+    let (info, _) = space.step(None, Tick::arbitrary());
+    assert_eq!((info.cube_ticks, info.cube_ticks_deferred), (1, 0));
+    assert_eq!(&space[[1, 0, 0]], &right_moved);
+    assert_eq!(&space[[2, 0, 0]], &AIR);
+
+    let (info, _) = space.step(None, Tick::arbitrary());
+    assert_eq!((info.cube_ticks, info.cube_ticks_deferred), (0, 0));
+}
+
+/// A block which copies itself upward, but only into non-solid cubes, fills the space
+/// up to the first solid block and then stops.
+#[test]
+fn block_tick_action_grow_until_solid() {
+    let mut universe = Universe::new();
+    let [stone] = make_some_blocks();
+    let block_def_ref = universe.insert_anonymous(BlockDef::new(AIR));
+    let grower = Block::from_primitive(Primitive::Indirect(block_def_ref.clone()));
+    block_def_ref
+        .execute(&BlockDefTransaction::overwrite(
+            Block::builder()
+                .color(Rgba::WHITE)
+                .tick_action(Some(TickAction::UnlessSolid {
+                    offset: GridVector::unit_y(),
+                    action: Box::new(VoxelBrush::new(vec![([0, 1, 0], grower.clone())]).into()),
+                }))
+                .build(),
+        ))
+        .unwrap();
+
+    let mut space = Space::empty_positive(1, 6, 1);
+    space.set([0, 0, 0], &grower).unwrap();
+    space.set([0, 4, 0], &stone).unwrap();
+
+    for _ in 0..3 {
+        let (info, _) = space.step(None, Tick::arbitrary());
+        assert_eq!(info.cube_ticks, 1);
+    }
+    let (info, _) = space.step(None, Tick::arbitrary());
+    assert_eq!(info.cube_ticks, 0);
+
+    for y in 0..4 {
+        assert_eq!(space[[0, y, 0]], grower, "y = {y}");
+    }
+    assert_eq!(space[[0, 4, 0]], stone);
+    assert_eq!(space[[0, 5, 0]], AIR);
+
+    // Once the obstacle is gone, growth resumes, until it reaches the top of the space.
+    space.set([0, 4, 0], AIR).unwrap();
+    for _ in 0..2 {
+        let (info, _) = space.step(None, Tick::arbitrary());
+        assert_eq!(info.cube_ticks, 1);
+    }
+    let (info, _) = space.step(None, Tick::arbitrary());
+    assert_eq!(info.cube_ticks, 0);
+
+    for y in 0..6 {
+        assert_eq!(space[[0, y, 0]], grower, "y = {y}");
+    }
+}
+
+/// Collects only the [`SpaceChange::MoveCompleted`] messages from a sink.
+fn drain_move_completions(sink: &Sink<SpaceChange>) -> Vec<SpaceChange> {
+    sink.drain()
+        .into_iter()
+        .filter(|change| matches!(change, SpaceChange::MoveCompleted(_)))
+        .collect()
+}
+
+#[test]
+fn move_completion_notification() {
+    let [block] = make_some_blocks();
+    // Small enough a distance to finish in one tick, then remove itself on the next.
+    let moving = Modifier::moving(Face6::PX, 10, -10).attach(block.clone());
+    let mut space = Space::empty_positive(2, 1, 1);
+    space.set([1, 0, 0], &moving).unwrap();
+    let sink = Sink::new();
+    space.listen(sink.listener());
+
+    let mut completions = Vec::new();
+    for _ in 0..4 {
+        let (_, _) = space.step(None, Tick::arbitrary());
+        completions.extend(drain_move_completions(&sink));
+    }
+    assert_eq!(
+        completions,
+        vec![SpaceChange::MoveCompleted(GridPoint::new(1, 0, 0))]
+    );
+    assert_eq!(&space[[1, 0, 0]], &block);
+}
+
+#[test]
+fn move_completion_notification_when_moved_out() {
+    let [block] = make_some_blocks();
+    let moving = Modifier::moving(Face6::PX, 256, 0).attach(block);
+    let mut space = Space::empty_positive(2, 1, 1);
+    space.set([0, 0, 0], &moving).unwrap();
+    let sink = Sink::new();
+    space.listen(sink.listener());
+
+    let (_, _) = space.step(None, Tick::arbitrary());
+    assert_eq!(
+        drain_move_completions(&sink),
+        vec![SpaceChange::MoveCompleted(GridPoint::new(0, 0, 0))]
+    );
+    assert_eq!(&space[[0, 0, 0]], &AIR);
+    let (_, _) = space.step(None, Tick::arbitrary());
+    assert_eq!(drain_move_completions(&sink), vec![]);
+}
+
+/// If a block fails to reevaluate, it is replaced with a placeholder instead of
+/// panicking.
+#[test]
+fn block_reevaluation_failure_uses_placeholder() {
+    let mut universe = Universe::new();
+    let block_def_ref = universe.insert_anonymous(BlockDef::new(Block::from(Rgba::WHITE)));
+    let indirect = Block::from_primitive(Primitive::Indirect(block_def_ref.clone()));
+    let mut space = Space::empty_positive(1, 1, 1);
+    space.set([0, 0, 0], &indirect).unwrap();
+
+    // Make the definition refer to itself, so it cannot be evaluated.
+    block_def_ref
+        .execute(&BlockDefTransaction::overwrite(indirect.clone()))
+        .unwrap();
+    let (_, _) = space.step(None, Tick::arbitrary());
+
+    let evaluated = space.get_evaluated([0, 0, 0]);
+    assert_eq!(evaluated, &EvalBlockError::StackOverflow.to_placeholder());
+    assert_eq!(evaluated.voxels, EvaluatedBlock::broken().voxels);
+    assert_eq!(&space[[0, 0, 0]], &indirect);
+
+    // The space is still usable.
+    let (_, _) = space.step(None, Tick::arbitrary());
+    space.set([0, 0, 0], AIR).unwrap();
+    space.consistency_check();
+}
+
+#[test]
+fn serialize_round_trip() {
+    let mut universe = Universe::new();
+    let [block_0, block_1] = make_some_blocks();
+    let def_ref = universe
+        .insert("def".into(), BlockDef::new(block_1))
+        .unwrap();
+    let indirect = Block::from_primitive(Primitive::Indirect(def_ref));
+    let rotated = block_0.clone().rotate(GridRotation::RXyZ);
+
+    let mut space = Space::builder(Grid::new([-1, 0, 0], [3, 2, 2]))
+        .sky_color(Rgb::new(0.25, 0.5, 1.0))
+        .build_empty();
+    space.set([-1, 0, 0], &block_0).unwrap();
+    space.set([0, 0, 0], &indirect).unwrap();
+    space.set([1, 1, 1], &rotated).unwrap();
+    // Leave behind a tombstone, which should not be saved.
+    space.set([0, 0, 0], AIR).unwrap();
+    space.set([0, 1, 0], &indirect).unwrap();
+
+    let json = serde_json::to_string(&space).unwrap();
+    let loaded =
+        Space::deserialize_in(&universe, &mut serde_json::Deserializer::from_str(&json)).unwrap();
+    loaded.consistency_check();
+
+    assert_eq!(loaded.grid(), space.grid());
+    assert_eq!(loaded.physics(), space.physics());
+    for cube in space.grid().interior_iter() {
+        assert_eq!(loaded[cube], space[cube], "{cube:?}");
+    }
+    assert_eq!(
+        serde_json::to_string(&loaded).unwrap(),
+        json,
+        "second serialization differs"
+    );
+
+    // References are resolved by name, so loading fails if the name is missing.
+    let error = Space::deserialize_in(
+        &Universe::new(),
+        &mut serde_json::Deserializer::from_str(&json),
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "the universe has no block definition named 'def'"
+    );
+}
+
+/// A reference to an anonymous member is an error, since its number would not
+/// identify anything when loaded.
+#[test]
+fn serialize_anonymous_ref_is_error() {
+    let mut universe = Universe::new();
+    let [block] = make_some_blocks();
+    let def_ref = universe.insert_anonymous(BlockDef::new(block));
+    let mut space = Space::empty_positive(1, 1, 1);
+    space
+        .set(
+            [0, 0, 0],
+            Block::from_primitive(Primitive::Indirect(def_ref)),
+        )
+        .unwrap();
+
+    let error = serde_json::to_string(&space).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "cannot save a reference to [anonymous #0], which has no name"
+    );
+}
+
+#[test]
+fn contents_runs_round_trip() {
+    let [block_0, block_1] = make_some_blocks();
+    let grid = Grid::new([0, 0, 0], [40, 30, 20]);
+    let mut space = Space::empty(grid);
+    // Fill complete y-z layers, so that each fill is contiguous in storage order.
+    space
+        .fill_uniform(Grid::new([0, 0, 0], [5, 30, 20]), &block_0)
+        .unwrap();
+    space
+        .fill_uniform(Grid::new([10, 0, 0], [5, 30, 20]), &block_1)
+        .unwrap();
+    space.set([3, 4, 5], &block_1).unwrap();
+
+    let runs: Vec<(BlockIndex, usize)> = space.contents_runs().collect();
+    assert!(runs.len() <= 10, "too many runs: {runs:?}");
+    assert_eq!(
+        runs.iter().map(|&(_, length)| length).sum::<usize>(),
+        grid.volume()
+    );
+
+    let blocks: Vec<Block> = space
+        .block_data()
+        .iter()
+        .map(|data| data.block().clone())
+        .collect();
+    let copy = Space::from_contents_runs(grid, &blocks, runs.iter().copied()).unwrap();
+    copy.consistency_check();
+    assert_eq!(copy.contents_runs().collect::<Vec<_>>(), runs);
+    for cube in grid.interior_iter() {
+        assert_eq!(copy[cube], space[cube], "{cube:?}");
+    }
+}
+
+#[test]
+fn from_contents_runs_errors() {
+    let grid = Grid::new([0, 0, 0], [2, 2, 2]);
+    assert_eq!(
+        Space::from_contents_runs(grid, &[AIR], [(0, 7)]).unwrap_err(),
+        ContentsRunsError::WrongLength {
+            expected: 8,
+            actual: 7
+        }
+    );
+    assert_eq!(
+        Space::from_contents_runs(grid, &[AIR], [(0, usize::MAX), (0, 1)]).unwrap_err(),
+        ContentsRunsError::WrongLength {
+            expected: 8,
+            actual: usize::MAX
+        }
+    );
+    assert_eq!(
+        Space::from_contents_runs(grid, &[AIR], [(0, 4), (1, 4)]).unwrap_err(),
+        ContentsRunsError::IndexOutOfRange { index: 1 }
+    );
+    assert_eq!(
+        Space::from_contents_runs(grid, &[AIR, AIR], [(0, 4), (1, 4)]).unwrap_err(),
+        ContentsRunsError::DuplicateBlock { index: 1 }
+    );
+}
@@ -44,6 +44,8 @@ pub use uref::*;
 mod visit;
 pub use visit::*;
 
+pub use crate::space::save::UniverseSnapshot;
+
 #[cfg(test)]
 mod tests;
 
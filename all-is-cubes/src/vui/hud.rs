@@ -19,7 +19,7 @@ use crate::drawing::VoxelBrush;
 use crate::linking::BlockProvider;
 use crate::listen::ListenableSource;
 use crate::math::{Face6, GridCoordinate, GridMatrix, GridPoint, GridRotation, Rgba};
-use crate::space::{Grid, Space, SpacePhysics};
+use crate::space::{Grid, Sky, Space, SpacePhysics};
 use crate::universe::{URef, Universe};
 use crate::util::YieldProgress;
 use crate::vui::widgets::{FrameWidget, ToggleButtonVisualState};
@@ -75,7 +75,7 @@ impl HudLayout {
         let grid = self.grid();
         let mut space = Space::builder(grid)
             .physics(SpacePhysics {
-                sky_color: palette::HUD_SKY,
+                sky: Sky::Uniform(palette::HUD_SKY),
                 ..SpacePhysics::default()
             })
             .build_empty();
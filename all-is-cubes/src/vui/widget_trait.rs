@@ -205,6 +205,10 @@ impl Behavior<Space> for ActivatableRegion {
     fn ephemeral(&self) -> bool {
         true
     }
+
+    fn bounds(&self) -> Option<Grid> {
+        Some(self.region)
+    }
 }
 
 impl VisitRefs for ActivatableRegion {
@@ -12,7 +12,7 @@ use all_is_cubes::vui::Icons;
 use futures::future::BoxFuture;
 use futures::FutureExt;
 
-use all_is_cubes::apps::StandardCameras;
+use all_is_cubes::apps::{Layers, StandardCameras};
 use all_is_cubes::block::Block;
 use all_is_cubes::camera::{
     ExposureOption, FogOption, GraphicsOptions, LightingOption, ToneMappingOperator,
@@ -204,6 +204,7 @@ async fn follow_character_change(context: RenderTestContext) {
         ListenableSource::constant(COMMON_VIEWPORT),
         character_cell.as_source(),
         ListenableSource::constant(None),
+        Layers::default(),
     )
     .unwrap();
     let mut renderer = context.renderer(cameras);
@@ -253,6 +254,7 @@ async fn follow_options_change(mut context: RenderTestContext) {
         ListenableSource::constant(COMMON_VIEWPORT),
         ListenableSource::constant(universe.get_default_character()),
         ListenableSource::constant(None),
+        Layers::default(),
     )
     .unwrap();
 
@@ -342,6 +344,7 @@ async fn layers_all_show_ui(mut context: RenderTestContext, show_ui: bool) {
         ListenableSource::constant(COMMON_VIEWPORT),
         ListenableSource::constant(universe.get_default_character()),
         ListenableSource::constant(Some(ui_space(&mut universe))),
+        Layers::default(),
     )
     .unwrap();
 
@@ -386,6 +389,7 @@ async fn layers_ui_only(mut context: RenderTestContext) {
         ListenableSource::constant(COMMON_VIEWPORT),
         ListenableSource::constant(None),
         ListenableSource::constant(Some(ui_space(&mut universe))),
+        Layers::default(),
     )
     .unwrap();
 
@@ -505,6 +509,7 @@ async fn zero_viewport(mut context: RenderTestContext) {
         viewport_cell.as_source(),
         ListenableSource::constant(universe.get_default_character()),
         ListenableSource::constant(None),
+        Layers::default(),
     )
     .unwrap();
     let overlays = Overlays {